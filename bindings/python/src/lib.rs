@@ -0,0 +1,126 @@
+//! Python bindings for the isaw engine, built with PyO3 into the `isaw`
+//! extension module (`pip install bindings/python`, or `maturin develop`).
+//!
+//! Generators come back as lazy iterators, so `next(isaw.permutations(...))`
+//! never builds the whole keyspace. The dictionary functions run the same
+//! anagram and spelling checks as `isaw words -d`.
+//!
+//! ```python
+//! >>> import isaw
+//! >>> list(isaw.permutations("abc", min=3))
+//! ['abc', 'acb', 'bac', 'bca', 'cab', 'cba']
+//! >>> list(isaw.anagrams("tab", "/usr/share/dict/words"))
+//! ['bat', 'tab']
+//! ```
+
+use isaw_core::dictionary::Dictionary;
+use isaw_core::rack::{self, Rack};
+use isaw_core::{generate, Matcher};
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+create_exception!(isaw, IsawError, PyException, "Raised when the engine rejects its input (e.g. an invalid regex).");
+
+/// A lazy iterator over engine results.
+#[pyclass(unsendable, module = "isaw")]
+struct Results {
+    inner: Box<dyn Iterator<Item = String>>,
+}
+
+#[pymethods]
+impl Results {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>) -> Option<String> {
+        slf.inner.next()
+    }
+}
+
+fn results(inner: impl Iterator<Item = String> + 'static) -> Results {
+    Results { inner: Box::new(inner) }
+}
+
+/// Every ordered arrangement of `min..=max` letters, shortest first.
+#[pyfunction]
+#[pyo3(signature = (letters, min = 1, max = None))]
+fn permutations(letters: &str, min: usize, max: Option<usize>) -> Results {
+    let chars: Vec<char> = letters.chars().collect();
+    let max = max.unwrap_or(chars.len());
+    results(generate::permutations(chars, min, max))
+}
+
+/// Every selection of `length` letters where order doesn't matter.
+#[pyfunction]
+#[pyo3(signature = (letters, length = 2))]
+fn combinations(letters: &str, length: usize) -> Results {
+    results(generate::combinations(letters.chars().collect(), length))
+}
+
+/// Words formable from the letters, like the `isaw words` subcommand.
+#[pyfunction]
+#[pyo3(signature = (letters, min = 2, max = None, unique = false))]
+fn words(letters: &str, min: usize, max: Option<usize>, unique: bool) -> Results {
+    let chars: Vec<char> = letters.chars().collect();
+    let max = max.unwrap_or(chars.len());
+    results(generate::words(chars, min, max, unique))
+}
+
+/// `length`-letter strings over `letters` matching `pattern`.
+#[pyfunction]
+#[pyo3(signature = (pattern, letters = "abcdefghijklmnopqrstuvwxyz", length = 3, ignore_case = false, regex = false))]
+fn search(pattern: &str, letters: &str, length: usize, ignore_case: bool, regex: bool) -> PyResult<Results> {
+    let matcher = match regex {
+        true => Matcher::regexes(&[pattern.to_string()], ignore_case).map_err(|e| IsawError::new_err(format!("Invalid regex: {}", e)))?,
+        false => Matcher::substring(pattern, ignore_case),
+    };
+    let chars: Vec<char> = letters.chars().collect();
+    Ok(results(generate::Product::new(&chars, length, 0).filter(move |word| matcher.is_match(word))))
+}
+
+/// Loads a wordlist, one word per line.
+fn load_dictionary(path: PathBuf) -> PyResult<Dictionary> {
+    let bytes = std::fs::read(&path).map_err(|e| IsawError::new_err(format!("Cannot read {}: {}", path.display(), e)))?;
+    Dictionary::new(bytes).map_err(|e| IsawError::new_err(format!("Cannot use {}: {}", path.display(), e)))
+}
+
+/// The words in `dictionary` matching `keep`, each once, in file order.
+fn dictionary_matches(dictionary: &Dictionary, mut keep: impl FnMut(&str) -> bool) -> Results {
+    let mut seen = HashSet::new();
+    let words: Vec<String> = dictionary.words().filter(|word| keep(word) && seen.insert(word.to_lowercase())).map(str::to_string).collect();
+    results(words.into_iter())
+}
+
+/// Words in the `dictionary` wordlist file using every letter exactly once.
+#[pyfunction]
+fn anagrams(letters: &str, dictionary: PathBuf) -> PyResult<Results> {
+    let dictionary = load_dictionary(dictionary)?;
+    let signature = rack::signature(letters);
+    Ok(dictionary_matches(&dictionary, |word| rack::signature(word) == signature))
+}
+
+/// Words of at least `min` letters in the `dictionary` file that the letters can spell.
+#[pyfunction]
+#[pyo3(signature = (letters, dictionary, min = 2))]
+fn dictionary_words(letters: &str, dictionary: PathBuf, min: usize) -> PyResult<Results> {
+    let dictionary = load_dictionary(dictionary)?;
+    let rack = Rack::new(letters);
+    Ok(dictionary_matches(&dictionary, |word| word.chars().count() >= min && rack.can_spell(word)))
+}
+
+#[pymodule]
+fn isaw(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("IsawError", m.py().get_type::<IsawError>())?;
+    m.add_class::<Results>()?;
+    m.add_function(wrap_pyfunction!(permutations, m)?)?;
+    m.add_function(wrap_pyfunction!(combinations, m)?)?;
+    m.add_function(wrap_pyfunction!(words, m)?)?;
+    m.add_function(wrap_pyfunction!(search, m)?)?;
+    m.add_function(wrap_pyfunction!(anagrams, m)?)?;
+    m.add_function(wrap_pyfunction!(dictionary_words, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,52 @@
+"""Tests for the `isaw` extension module; run after `pip install bindings/python`
+with `python -m unittest discover bindings/python/tests`."""
+
+import os
+import tempfile
+import unittest
+
+import isaw
+
+
+class GeneratorTests(unittest.TestCase):
+    def test_permutations(self):
+        self.assertEqual(list(isaw.permutations("abc", min=3)), ["abc", "acb", "bac", "bca", "cab", "cba"])
+
+    def test_combinations(self):
+        self.assertEqual(list(isaw.combinations("abc", length=2)), ["ab", "ac", "bc"])
+
+    def test_results_are_lazy(self):
+        # 26! arrangements: only iterating lazily makes this instant.
+        self.assertEqual(next(isaw.permutations("abcdefghijklmnopqrstuvwxyz", min=26)), "abcdefghijklmnopqrstuvwxyz")
+
+    def test_search(self):
+        self.assertEqual(list(isaw.search("^c.t$", letters="act", regex=True)), ["cat", "cct", "ctt"])
+        self.assertEqual(list(isaw.search("AA", letters="ab", length=2, ignore_case=True)), ["aa"])
+
+    def test_invalid_regex_raises(self):
+        with self.assertRaises(isaw.IsawError):
+            isaw.search("(", regex=True)
+
+
+class DictionaryTests(unittest.TestCase):
+    def setUp(self):
+        handle, self.path = tempfile.mkstemp(suffix=".txt")
+        with os.fdopen(handle, "w") as f:
+            f.write("bat\ntab\nTab\nbath\nat\na\n")
+
+    def tearDown(self):
+        os.remove(self.path)
+
+    def test_anagrams(self):
+        self.assertEqual(list(isaw.anagrams("tab", self.path)), ["bat", "tab"])
+
+    def test_dictionary_words(self):
+        self.assertEqual(list(isaw.dictionary_words("tab", self.path, min=2)), ["bat", "tab", "at"])
+
+    def test_missing_dictionary_raises(self):
+        with self.assertRaises(isaw.IsawError):
+            isaw.anagrams("tab", self.path + ".missing")
+
+
+if __name__ == "__main__":
+    unittest.main()
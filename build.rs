@@ -0,0 +1,11 @@
+//! Generates the gRPC service for `isaw daemon --grpc` from
+//! `proto/isaw.proto`, parsing it in-process so no `protoc` is needed.
+
+fn main() {
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/isaw.proto");
+        let descriptors = protox::compile(["proto/isaw.proto"], ["proto"]).expect("proto/isaw.proto compiles");
+        tonic_prost_build::configure().compile_fds(descriptors).expect("the gRPC service generates");
+    }
+}
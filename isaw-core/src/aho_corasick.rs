@@ -0,0 +1,214 @@
+//! Aho-Corasick automaton for matching many substrings in one pass.
+//!
+//! The trie keeps sparse transitions so large pattern files stay small;
+//! only the root, which every failed lookup falls back to, is a dense table.
+//! With `fold` set, ASCII letters in patterns and haystacks compare
+//! case-insensitively.
+
+use std::ops::Range;
+
+const ROOT: u32 = 0;
+
+#[derive(Clone, Debug, Default)]
+struct State {
+    /// Trie edges, sorted by byte.
+    next: Vec<(u8, u32)>,
+    fail: u32,
+    /// Patterns ending here, including those reached via failure links.
+    out: Vec<u32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct AhoCorasick {
+    states: Vec<State>,
+    root: Box<[u32; 256]>,
+    lengths: Vec<usize>,
+    fold: bool,
+}
+
+impl AhoCorasick {
+    pub fn new<P: AsRef<[u8]>>(patterns: &[P], fold: bool) -> Self {
+        let mut states = vec![State::default()];
+        let mut lengths = Vec::with_capacity(patterns.len());
+
+        for (id, pattern) in patterns.iter().enumerate() {
+            let pattern = pattern.as_ref();
+            let mut state = ROOT;
+            for &b in pattern {
+                let b = if fold { b.to_ascii_lowercase() } else { b };
+                let edges = &states[state as usize].next;
+                state = match edges.binary_search_by_key(&b, |&(b, _)| b) {
+                    Ok(i) => edges[i].1,
+                    Err(i) => {
+                        let child = states.len() as u32;
+                        states[state as usize].next.insert(i, (b, child));
+                        states.push(State::default());
+                        child
+                    }
+                };
+            }
+            states[state as usize].out.push(id as u32);
+            lengths.push(pattern.len());
+        }
+
+        let mut root = Box::new([ROOT; 256]);
+        for &(b, child) in &states[ROOT as usize].next {
+            root[b as usize] = child;
+        }
+
+        // Breadth-first, so every failure target is finished before it is used.
+        let mut queue: std::collections::VecDeque<u32> = states[ROOT as usize].next.iter().map(|&(_, s)| s).collect();
+        while let Some(state) = queue.pop_front() {
+            for (b, child) in states[state as usize].next.clone() {
+                let mut fail = states[state as usize].fail;
+                let target = loop {
+                    if let Some(t) = lookup(&states, &root, fail, b) {
+                        break t;
+                    }
+                    fail = states[fail as usize].fail;
+                };
+                let inherited = states[target as usize].out.clone();
+                let child_state = &mut states[child as usize];
+                child_state.fail = target;
+                child_state.out.extend(inherited);
+                queue.push_back(child);
+            }
+        }
+
+        AhoCorasick { states, root, lengths, fold }
+    }
+
+    /// Number of patterns the automaton was built from.
+    pub fn len(&self) -> usize {
+        self.lengths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lengths.is_empty()
+    }
+
+    pub fn is_match(&self, haystack: &[u8]) -> bool {
+        self.scan(haystack, |_, _| true)
+    }
+
+    /// The first match to end in `haystack` as `(pattern, byte range)`;
+    /// the longest pattern wins when several end at the same byte.
+    pub fn find(&self, haystack: &[u8]) -> Option<(usize, Range<usize>)> {
+        let mut found = None;
+        self.scan(haystack, |end, ids| {
+            let &id = ids.iter().max_by_key(|&&id| self.lengths[id as usize]).expect("non-empty output set");
+            found = Some((id as usize, end - self.lengths[id as usize]..end));
+            true
+        });
+        found
+    }
+
+    /// Every pattern that occurs in `haystack`, in pattern order.
+    pub fn hits(&self, haystack: &[u8]) -> Vec<usize> {
+        let mut seen = vec![false; self.len()];
+        self.scan(haystack, |_, ids| {
+            for &id in ids {
+                seen[id as usize] = true;
+            }
+            false
+        });
+        (0..seen.len()).filter(|&id| seen[id]).collect()
+    }
+
+    /// Calls `visit(end, patterns)` at every position where patterns end
+    /// until it returns true; returns whether it did.
+    fn scan(&self, haystack: &[u8], mut visit: impl FnMut(usize, &[u32]) -> bool) -> bool {
+        let mut state = ROOT;
+        let out = &self.states[ROOT as usize].out;
+        if !out.is_empty() && visit(0, out) {
+            return true;
+        }
+        for (i, &b) in haystack.iter().enumerate() {
+            let b = if self.fold { b.to_ascii_lowercase() } else { b };
+            state = self.step(state, b);
+            let out = &self.states[state as usize].out;
+            if !out.is_empty() && visit(i + 1, out) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn step(&self, mut state: u32, b: u8) -> u32 {
+        loop {
+            if let Some(next) = lookup(&self.states, &self.root, state, b) {
+                return next;
+            }
+            state = self.states[state as usize].fail;
+        }
+    }
+}
+
+/// The edge for `b` out of `state`; the root never fails.
+fn lookup(states: &[State], root: &[u32; 256], state: u32, b: u8) -> Option<u32> {
+    if state == ROOT {
+        return Some(root[b as usize]);
+    }
+    let edges = &states[state as usize].next;
+    edges.binary_search_by_key(&b, |&(b, _)| b).ok().map(|i| edges[i].1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(seed: u64, count: usize, max_len: usize) -> Vec<Vec<u8>> {
+        let mut state = seed;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        (0..count).map(|_| (1..=1 + next() as usize % max_len).map(|_| b"abAB"[next() as usize % 4]).collect()).collect()
+    }
+
+    fn occurs(haystack: &[u8], pattern: &[u8], fold: bool) -> Vec<Range<usize>> {
+        let eq = |a: &[u8], b: &[u8]| if fold { a.eq_ignore_ascii_case(b) } else { a == b };
+        (0..(haystack.len() + 1).saturating_sub(pattern.len())).filter(|&i| eq(&haystack[i..i + pattern.len()], pattern)).map(|i| i..i + pattern.len()).collect()
+    }
+
+    #[test]
+    fn agrees_with_a_naive_search() {
+        let haystacks = strings(0x9e37_79b9_7f4a_7c15, 200, 30);
+        for seed in 1..20u64 {
+            let patterns = strings(seed.wrapping_mul(0x2545_f491_4f6c_dd1d), 1 + seed as usize % 6, 4);
+            for fold in [false, true] {
+                let automaton = AhoCorasick::new(&patterns, fold);
+                assert_eq!(automaton.len(), patterns.len());
+                for haystack in &haystacks {
+                    let found: Vec<Vec<Range<usize>>> = patterns.iter().map(|p| occurs(haystack, p, fold)).collect();
+                    let hits: Vec<usize> = (0..patterns.len()).filter(|&id| !found[id].is_empty()).collect();
+                    assert_eq!(automaton.hits(haystack), hits, "{:?} in {:?}", patterns, haystack);
+                    assert_eq!(automaton.is_match(haystack), !hits.is_empty());
+
+                    // The earliest end wins, then the longest pattern ending there.
+                    let first = found.iter().flatten().map(|range| range.end).min();
+                    match automaton.find(haystack) {
+                        Some((id, range)) => {
+                            assert_eq!(Some(range.end), first);
+                            assert!(found[id].contains(&range));
+                            let longest = found.iter().flatten().filter(|r| r.end == range.end).map(|r| r.len()).max();
+                            assert_eq!(Some(range.len()), longest);
+                        }
+                        None => assert_eq!(first, None),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn overlapping_and_nested_patterns() {
+        let automaton = AhoCorasick::new(&["he", "she", "his", "hers"], false);
+        assert_eq!(automaton.hits(b"ushers"), [0, 1, 3]);
+        assert_eq!(automaton.find(b"ushers"), Some((1, 1..4)));
+        assert!(!automaton.is_match(b"hi"));
+        assert!(AhoCorasick::new::<&str>(&[], false).is_empty());
+    }
+}
@@ -0,0 +1,30 @@
+//! Named character sets, so common alphabets don't have to be typed out.
+
+/// Every preset as `(name, characters)`.
+pub const PRESETS: [(&str, &str); 9] = [
+    ("lower", "abcdefghijklmnopqrstuvwxyz"),
+    ("upper", "ABCDEFGHIJKLMNOPQRSTUVWXYZ"),
+    ("digits", "0123456789"),
+    ("hex", "0123456789abcdef"),
+    ("base64", "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/"),
+    ("greek", "αβγδεζηθικλμνξοπρστυφχψω"),
+    ("cyrillic", "абвгдеёжзийклмнопрстуфхцчшщъыьэюя"),
+    ("dna", crate::dna::DNA),
+    ("rna", crate::dna::RNA),
+];
+
+/// The preset names, for argument parsers.
+pub const NAMES: [&str; 9] = {
+    let mut names = [""; 9];
+    let mut i = 0;
+    while i < PRESETS.len() {
+        names[i] = PRESETS[i].0;
+        i += 1;
+    }
+    names
+};
+
+/// The characters of the preset called `name`.
+pub fn preset(name: &str) -> Option<&'static str> {
+    PRESETS.iter().find(|(n, _)| *n == name).map(|(_, chars)| *chars)
+}
@@ -0,0 +1,113 @@
+//! Bloom filter for approximate deduplication.
+//!
+//! Remembers which words were seen in a fixed bit array sized up front from
+//! the expected number of words and the acceptable false-positive rate. A
+//! false positive makes a new word look seen, so it is dropped; words are
+//! never reported twice.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+pub struct Bloom {
+    bits: Vec<u64>,
+    /// Bit count, always a whole number of `u64`s.
+    len: u64,
+    hashes: u32,
+}
+
+impl Bloom {
+    /// A filter for up to `items` words that wrongly drops at most about
+    /// `fp_rate` of them. Returns `None` if it would not fit in memory.
+    pub fn new(items: u128, fp_rate: f64) -> Option<Self> {
+        let items = items.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let bits = (-items * fp_rate.ln() / (ln2 * ln2)).ceil().max(64.0);
+        let words = (bits / 64.0).ceil();
+        if words > (isize::MAX as usize / 8) as f64 {
+            return None;
+        }
+        let words = words as usize;
+        let len = words as u64 * 64;
+        let hashes = ((len as f64 / items) * ln2).round().clamp(1.0, 16.0) as u32;
+        Some(Bloom { bits: vec![0; words], len, hashes })
+    }
+
+    /// Size of the bit array in bytes.
+    pub fn bytes(&self) -> usize {
+        self.bits.len() * 8
+    }
+
+    /// Records `word`; returns false if it was (probably) recorded before.
+    pub fn insert(&mut self, word: &str) -> bool {
+        let mut new = false;
+        for (word, mask) in probes(self.hashes, self.len, word) {
+            new |= self.bits[word] & mask == 0;
+            self.bits[word] |= mask;
+        }
+        new
+    }
+
+    /// Whether `word` was (probably) recorded, without recording it.
+    pub fn contains(&self, word: &str) -> bool {
+        probes(self.hashes, self.len, word).all(|(word, mask)| self.bits[word] & mask != 0)
+    }
+}
+
+/// The `(u64 index, bit mask)` of each of the `hashes` bits `word` sets
+/// in a filter of `len` bits.
+fn probes(hashes: u32, len: u64, word: &str) -> impl Iterator<Item = (usize, u64)> {
+    let mut hasher = DefaultHasher::new();
+    word.hash(&mut hasher);
+    let h1 = hasher.finish();
+    // Derive the remaining probes from two hashes (Kirsch-Mitzenmacher).
+    let h2 = mix(h1) | 1;
+    (0..hashes as u64).map(move |i| {
+        let bit = h1.wrapping_add(i.wrapping_mul(h2)) % len;
+        ((bit / 64) as usize, 1u64 << (bit % 64))
+    })
+}
+
+/// SplitMix64 finalizer.
+fn mix(mut x: u64) -> u64 {
+    x ^= x >> 30;
+    x = x.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x ^= x >> 27;
+    x = x.wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remembers_every_inserted_word() {
+        let mut bloom = Bloom::new(1000, 0.01).unwrap();
+        assert!(!bloom.contains("word0"));
+        for i in 0..1000 {
+            bloom.insert(&format!("word{}", i));
+        }
+        assert!((0..1000).all(|i| bloom.contains(&format!("word{}", i))));
+        assert!(!bloom.insert("word7"));
+    }
+
+    #[test]
+    fn false_positives_stay_near_the_target_rate() {
+        let mut bloom = Bloom::new(10_000, 0.01).unwrap();
+        for i in 0..10_000 {
+            bloom.insert(&format!("seen{}", i));
+        }
+        let false_positives = (0..10_000).filter(|i| bloom.contains(&format!("unseen{}", i))).count();
+        assert!(false_positives < 300, "{} false positives", false_positives);
+    }
+
+    #[test]
+    fn sizing() {
+        // About 9.6 bits per item at 1%, rounded up to whole u64s.
+        let bloom = Bloom::new(1000, 0.01).unwrap();
+        assert_eq!(bloom.bytes() % 8, 0);
+        assert!((1100..1300).contains(&bloom.bytes()));
+        assert_eq!(Bloom::new(0, 0.5).unwrap().bytes(), 8);
+        assert!(Bloom::new(u128::MAX, 1e-9).is_none());
+    }
+}
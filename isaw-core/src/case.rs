@@ -0,0 +1,32 @@
+//! Case variants: every way of writing a word's letters in either case.
+//!
+//! Each letter is kept as given first and then swapped to its other case, so
+//! a word with k cased letters has 2^k variants, the word itself first.
+//! Characters without a single-character counterpart (digits, `ß`) stay put.
+
+use crate::leet::Variants;
+
+/// `word` and all of its case variants.
+pub fn variants(word: &str) -> Variants {
+    Variants::new(word.chars().map(|c| std::iter::once(c).chain(swapped(c)).collect()).collect())
+}
+
+/// `chars` followed by the other case of each, where that is new.
+pub fn expand(chars: &[char]) -> Vec<char> {
+    let mut expanded = chars.to_vec();
+    for c in chars.iter().filter_map(|&c| swapped(c)) {
+        if !expanded.contains(&c) {
+            expanded.push(c);
+        }
+    }
+    expanded
+}
+
+/// The other case of `c`, if it has exactly one.
+fn swapped(c: char) -> Option<char> {
+    let other: Vec<char> = if c.is_lowercase() { c.to_uppercase().collect() } else { c.to_lowercase().collect() };
+    match other[..] {
+        [other] if other != c => Some(other),
+        _ => None,
+    }
+}
@@ -0,0 +1,143 @@
+//! Classical ciphers over the Latin alphabet. Case is kept and anything that
+//! isn't an ASCII letter passes through unchanged.
+
+/// Rotates every letter `shift` places forward (ROT-N).
+pub fn shift(text: &str, shift: u8) -> String {
+    text.chars().map(|c| rotate(c, shift % 26)).collect()
+}
+
+/// Replaces every letter `c` with `key[c]`, letters counting from 0 = A.
+pub fn substitute(text: &str, key: &[u8; 26]) -> String {
+    text.chars()
+        .map(|c| match c {
+            'a'..='z' => (key[(c as u8 - b'a') as usize] + b'a') as char,
+            'A'..='Z' => (key[(c as u8 - b'A') as usize] + b'A') as char,
+            _ => c,
+        })
+        .collect()
+}
+
+/// Like [`substitute`] with a partial key: letters without a mapping
+/// become `?`.
+pub fn substitute_partial(text: &str, key: &[Option<u8>; 26]) -> String {
+    text.chars()
+        .map(|c| match c {
+            'a'..='z' => key[(c as u8 - b'a') as usize].map_or('?', |p| (p + b'a') as char),
+            'A'..='Z' => key[(c as u8 - b'A') as usize].map_or('?', |p| (p + b'A') as char),
+            _ => c,
+        })
+        .collect()
+}
+
+/// A word's letter pattern: each letter replaced by the order it first
+/// appears in, so `that` and `high` both have the shape ABCA, `[0, 1, 2, 0]`.
+pub fn shape(word: &[u8]) -> Vec<u8> {
+    let mut seen: Vec<u8> = Vec::new();
+    word.iter()
+        .map(|c| match seen.iter().position(|s| s == c) {
+            Some(i) => i as u8,
+            None => {
+                seen.push(*c);
+                (seen.len() - 1) as u8
+            }
+        })
+        .collect()
+}
+
+/// Keys under which every ciphertext word, but at most `misses` of them,
+/// deciphers to one of `words` of the same shape: the cryptoquote solver's
+/// search. Words are letters 0 = A; keys map cipher to plain letters one to
+/// one, and leave letters no matched word uses unmapped. Stops after
+/// `limit` keys.
+pub fn pattern_keys(cipher_words: &[Vec<u8>], words: &[Vec<u8>], misses: usize, limit: usize) -> Vec<[Option<u8>; 26]> {
+    let mut unique: Vec<&Vec<u8>> = cipher_words.iter().filter(|word| !word.is_empty()).collect();
+    unique.sort_unstable();
+    unique.dedup();
+    let mut candidates: Vec<(&[u8], Vec<&[u8]>)> = unique
+        .into_iter()
+        .map(|cipher| {
+            let wanted = shape(cipher);
+            (cipher.as_slice(), words.iter().filter(|word| word.len() == cipher.len() && shape(word) == wanted).map(Vec::as_slice).collect())
+        })
+        .collect();
+    // Tightest words first, longest among equals: they fix the most letters
+    // for the fewest choices.
+    candidates.sort_by(|a, b| a.1.len().cmp(&b.1.len()).then(b.0.len().cmp(&a.0.len())));
+    let mut keys = Vec::new();
+    let mut key = [None; 26];
+    let mut used = [false; 26];
+    extend(&candidates, misses, limit, &mut key, &mut used, &mut keys);
+    keys
+}
+
+/// Tries every consistent plain word for the first cipher word, then the
+/// rest; returns `false` once `limit` keys are found.
+fn extend(candidates: &[(&[u8], Vec<&[u8]>)], misses: usize, limit: usize, key: &mut [Option<u8>; 26], used: &mut [bool; 26], keys: &mut Vec<[Option<u8>; 26]>) -> bool {
+    let Some(((cipher, plains), rest)) = candidates.split_first() else {
+        keys.push(*key);
+        return keys.len() < limit;
+    };
+    for plain in plains {
+        // The letters this word maps for the first time, to undo after.
+        let mut fresh = Vec::new();
+        let fits = cipher.iter().zip(plain.iter()).all(|(&c, &p)| match key[c as usize] {
+            Some(mapped) => mapped == p,
+            None if used[p as usize] => false,
+            None => {
+                key[c as usize] = Some(p);
+                used[p as usize] = true;
+                fresh.push(c);
+                true
+            }
+        });
+        let go_on = !fits || extend(rest, misses, limit, key, used, keys);
+        for c in fresh {
+            used[key[c as usize].take().expect("mapped above") as usize] = false;
+        }
+        if !go_on {
+            return false;
+        }
+    }
+    // Words outside the wordlist, such as names, may be left to the others.
+    misses == 0 || extend(rest, misses - 1, limit, key, used, keys)
+}
+
+/// Vigenère decryption: the n-th letter is shifted back by
+/// `key[n % key.len()]`; other characters don't advance the key.
+pub fn vigenere_decrypt(text: &str, key: &[u8]) -> String {
+    if key.is_empty() {
+        return text.to_string();
+    }
+    let mut n = 0;
+    text.chars()
+        .map(|c| {
+            if !c.is_ascii_alphabetic() {
+                return c;
+            }
+            let shift = 26 - key[n % key.len()] % 26;
+            n += 1;
+            rotate(c, shift)
+        })
+        .collect()
+}
+
+/// The key stream that turns the start of `plaintext` into `ciphertext`,
+/// one shift per letter the two have in common.
+pub fn key_stream(ciphertext: &str, plaintext: &str) -> Vec<u8> {
+    let letters = |text: &str| text.bytes().filter(u8::is_ascii_alphabetic).map(|b| b.to_ascii_lowercase() - b'a').collect::<Vec<u8>>();
+    letters(ciphertext).into_iter().zip(letters(plaintext)).map(|(c, p)| (c + 26 - p) % 26).collect()
+}
+
+/// The shortest period that `stream` repeats with.
+pub fn period(stream: &[u8]) -> usize {
+    (1..=stream.len()).find(|&p| stream.iter().enumerate().all(|(i, &k)| k == stream[i % p])).unwrap_or(0)
+}
+
+fn rotate(c: char, by: u8) -> char {
+    let base = match c {
+        'a'..='z' => b'a',
+        'A'..='Z' => b'A',
+        _ => return c,
+    };
+    ((c as u8 - base + by) % 26 + base) as char
+}
@@ -0,0 +1,35 @@
+//! Closed-form keyspace sizes.
+
+/// n^k, the size of the Search keyspace, or `None` if it doesn't fit.
+pub fn power(n: u128, k: u128) -> Option<u128> {
+    n.checked_pow(u32::try_from(k).ok()?)
+}
+
+/// P(n, k) = n! / (n-k)! as a falling product, or `None` if it overflows.
+/// n! itself is never computed.
+pub fn checked_permutation(n: u128, k: u128) -> Option<u128> {
+    if k > n {
+        return Some(0);
+    }
+    (n - k + 1..=n).try_fold(1u128, |acc, x| acc.checked_mul(x))
+}
+
+/// C(n, k) = n! / (k! * (n-k)!) built up one factor at a time, or `None` if it overflows.
+pub fn checked_binomial(n: u128, k: u128) -> Option<u128> {
+    if k > n {
+        return Some(0);
+    }
+    let k = k.min(n - k);
+    // After step i the accumulator is C(n, i + 1), so each division is exact.
+    (0..k).try_fold(1u128, |acc, i| Some(acc.checked_mul(n - i)? / (i + 1)))
+}
+
+/// Distinct arrangements of letters occurring `counts` times each, the
+/// multinomial (Σc)! / Πc!, or `None` if it overflows.
+pub fn checked_multinomial(counts: &[u128]) -> Option<u128> {
+    let mut placed = 0;
+    counts.iter().try_fold(1u128, |acc, &c| {
+        placed += c;
+        acc.checked_mul(checked_binomial(placed, c)?)
+    })
+}
@@ -0,0 +1,61 @@
+//! De Bruijn sequences: the shortest cyclic string that contains every
+//! length-`n` string over `k` letters exactly once as a substring, which is
+//! the whole Search keyspace in `k^n` characters instead of `n·k^n`.
+//!
+//! Built by concatenating, in lexicographic order, the Lyndon words whose
+//! length divides `n` (Fredricksen, Kessler and Maiorana); generation is
+//! iterative, so sequences far larger than memory can be streamed.
+
+/// Calls `visit` with successive pieces of the lexicographically smallest
+/// cyclic sequence, as indices into the `k` letters.
+pub fn sequence(k: usize, n: usize, mut visit: impl FnMut(&[usize])) {
+    if k == 0 || n == 0 {
+        return;
+    }
+    // Duval's algorithm; `word` holds the current Lyndon word (or prefix).
+    let mut word: Vec<usize> = vec![0];
+    loop {
+        let m = word.len();
+        if n.is_multiple_of(m) {
+            visit(&word);
+        }
+        while word.len() < n {
+            word.push(word[word.len() - m]);
+        }
+        while word.last() == Some(&(k - 1)) {
+            word.pop();
+        }
+        match word.last_mut() {
+            Some(last) => *last += 1,
+            None => return,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn cyclic(k: usize, n: usize) -> Vec<usize> {
+        let mut out = Vec::new();
+        sequence(k, n, |piece| out.extend_from_slice(piece));
+        out
+    }
+
+    #[test]
+    fn every_window_appears_exactly_once() {
+        for (k, n) in [(2, 3), (3, 2), (2, 5), (4, 3)] {
+            let seq = cyclic(k, n);
+            assert_eq!(seq.len(), k.pow(n as u32));
+            let windows: HashSet<Vec<usize>> = (0..seq.len()).map(|i| (0..n).map(|j| seq[(i + j) % seq.len()]).collect()).collect();
+            assert_eq!(windows.len(), seq.len(), "B({}, {})", k, n);
+        }
+    }
+
+    #[test]
+    fn binary_order_three_is_the_textbook_sequence() {
+        assert_eq!(cyclic(2, 3), [0, 0, 0, 1, 0, 1, 1, 1]);
+    }
+}
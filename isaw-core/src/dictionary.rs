@@ -0,0 +1,142 @@
+//! Wordlists for keeping only real words.
+//!
+//! A [`Dictionary`] works directly on the file's bytes (typically a memory
+//! map supplied by the caller) instead of copying every line into its own
+//! `String`. The first lookup builds a compact index: one `u32` offset per
+//! line plus an open-addressing hash table of line numbers. Lookups ignore
+//! ASCII case and trailing `\r`.
+//!
+//! A line may carry a tab-separated definition after the word, so the same
+//! type also serves as an offline glossary.
+
+use std::sync::OnceLock;
+
+pub struct Dictionary {
+    data: Box<dyn AsRef<[u8]> + Send + Sync>,
+    index: OnceLock<Index>,
+}
+
+struct Index {
+    /// Start offset of every non-empty line, in file order.
+    starts: Vec<u32>,
+    /// Line number + 1 per slot, 0 when empty; the length is a power of two.
+    table: Vec<u32>,
+}
+
+impl Dictionary {
+    /// Wraps wordlist bytes, one word per line. Fails for files over 4 GiB,
+    /// which the 32-bit index cannot address.
+    pub fn new(data: impl AsRef<[u8]> + Send + Sync + 'static) -> Result<Self, &'static str> {
+        if data.as_ref().len() > u32::MAX as usize {
+            return Err("dictionaries are limited to 4 GiB");
+        }
+        Ok(Dictionary { data: Box::new(data), index: OnceLock::new() })
+    }
+
+    fn bytes(&self) -> &[u8] {
+        (*self.data).as_ref()
+    }
+
+    fn index(&self) -> &Index {
+        self.index.get_or_init(|| Index::build(self.bytes()))
+    }
+
+    /// Number of words (non-empty lines).
+    pub fn len(&self) -> usize {
+        self.index().starts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.line(word).is_some()
+    }
+
+    /// The definition following `word`'s tab, if its line has one.
+    pub fn definition(&self, word: &str) -> Option<&str> {
+        let line = self.line(word)?;
+        let (_, gloss) = line.split_once('\t')?;
+        Some(gloss.trim()).filter(|gloss| !gloss.is_empty())
+    }
+
+    /// The whole line holding `word`.
+    fn line(&self, word: &str) -> Option<&str> {
+        let index = self.index();
+        let word = word.as_bytes();
+        let mask = index.table.len() - 1;
+        let mut slot = hash(word) as usize & mask;
+        loop {
+            match index.table[slot] {
+                0 => return None,
+                line => {
+                    let line = line_at(self.bytes(), index.starts[line as usize - 1]);
+                    if key(line).eq_ignore_ascii_case(word) {
+                        return std::str::from_utf8(line).ok();
+                    }
+                    slot = (slot + 1) & mask;
+                }
+            }
+        }
+    }
+
+    /// Every word in file order, skipping lines that are not UTF-8.
+    pub fn words(&self) -> impl Iterator<Item = &str> {
+        let bytes = self.bytes();
+        self.index().starts.iter().filter_map(move |&start| std::str::from_utf8(key(line_at(bytes, start))).ok())
+    }
+}
+
+impl Index {
+    fn build(bytes: &[u8]) -> Self {
+        let mut starts = Vec::new();
+        let mut start = 0;
+        for line in bytes.split(|&b| b == b'\n') {
+            if !key(trim(line)).is_empty() {
+                starts.push(start as u32);
+            }
+            start += line.len() + 1;
+        }
+
+        let mut table = vec![0u32; (starts.len() * 2).next_power_of_two().max(2)];
+        let mask = table.len() - 1;
+        for (line, &start) in starts.iter().enumerate() {
+            let word = key(line_at(bytes, start));
+            let mut slot = hash(word) as usize & mask;
+            loop {
+                match table[slot] {
+                    0 => {
+                        table[slot] = line as u32 + 1;
+                        break;
+                    }
+                    // Duplicate entries only need one slot.
+                    other if key(line_at(bytes, starts[other as usize - 1])).eq_ignore_ascii_case(word) => break,
+                    _ => slot = (slot + 1) & mask,
+                }
+            }
+        }
+        Index { starts, table }
+    }
+}
+
+/// The line beginning at `start`, without its terminator.
+fn line_at(bytes: &[u8], start: u32) -> &[u8] {
+    let rest = &bytes[start as usize..];
+    let end = rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+    trim(&rest[..end])
+}
+
+/// The word a line defines: everything before the first tab.
+fn key(line: &[u8]) -> &[u8] {
+    line.split(|&b| b == b'\t').next().unwrap_or(line)
+}
+
+fn trim(line: &[u8]) -> &[u8] {
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+/// FNV-1a over the ASCII-lowercased bytes.
+fn hash(word: &[u8]) -> u64 {
+    word.iter().fold(0xcbf2_9ce4_8422_2325, |h, &b| (h ^ b.to_ascii_lowercase() as u64).wrapping_mul(0x1000_0000_01b3))
+}
@@ -0,0 +1,76 @@
+//! Nucleotide sequences: IUPAC motifs and strand complements.
+
+pub const DNA: &str = "ACGT";
+pub const RNA: &str = "ACGU";
+
+/// The bases an IUPAC code stands for, written with T (see [`motif_regex`]
+/// for RNA).
+fn expand(code: char) -> Option<&'static str> {
+    Some(match code {
+        'A' => "A",
+        'C' => "C",
+        'G' => "G",
+        'T' | 'U' => "T",
+        'R' => "AG",
+        'Y' => "CT",
+        'S' => "CG",
+        'W' => "AT",
+        'K' => "GT",
+        'M' => "AC",
+        'B' => "CGT",
+        'D' => "AGT",
+        'H' => "ACT",
+        'V' => "ACG",
+        'N' => "ACGT",
+        _ => return None,
+    })
+}
+
+/// An unanchored regex matching `motif`, whose IUPAC ambiguity codes
+/// (N, R, Y, ...) become character classes. Fails with the first character
+/// that is not an IUPAC code.
+pub fn motif_regex(motif: &str, rna: bool) -> Result<String, char> {
+    let mut regex = String::new();
+    for code in motif.chars() {
+        let bases = expand(code.to_ascii_uppercase()).ok_or(code)?;
+        let bases = if rna { bases.replace('T', "U") } else { bases.to_string() };
+        if bases.len() == 1 {
+            regex.push_str(&bases);
+        } else {
+            regex.push('[');
+            regex.push_str(&bases);
+            regex.push(']');
+        }
+    }
+    Ok(regex)
+}
+
+/// The pairing base (or IUPAC code) of every base; anything else is kept.
+pub fn complement(seq: &str, rna: bool) -> String {
+    seq.chars().map(|base| complement_base(base, rna)).collect()
+}
+
+/// The other strand, read in its own 5'→3' direction.
+pub fn reverse_complement(seq: &str, rna: bool) -> String {
+    seq.chars().rev().map(|base| complement_base(base, rna)).collect()
+}
+
+fn complement_base(base: char, rna: bool) -> char {
+    let paired = match base.to_ascii_uppercase() {
+        'A' if rna => 'U',
+        'A' => 'T',
+        'T' | 'U' => 'A',
+        'C' => 'G',
+        'G' => 'C',
+        'R' => 'Y',
+        'Y' => 'R',
+        'K' => 'M',
+        'M' => 'K',
+        'B' => 'V',
+        'V' => 'B',
+        'D' => 'H',
+        'H' => 'D',
+        other => other,
+    };
+    if base.is_ascii_lowercase() { paired.to_ascii_lowercase() } else { paired }
+}
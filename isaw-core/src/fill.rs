@@ -0,0 +1,243 @@
+//! Crossword fill: completing a grid of blocks and letters so that every
+//! across and down run of two or more cells is a dictionary word.
+//!
+//! Each open cell keeps the set of letters still possible there. The words
+//! that fit a slot are found by walking the trie along those sets; the
+//! letters they use at each position narrow the crossing cells, over and
+//! over until nothing changes. The search then tries every word for the
+//! slot with the fewest left and repeats, so dead ends show up as soon as
+//! some slot runs out of words rather than when the grid is nearly full.
+
+use crate::trie::{Trie, ROOT};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cell {
+    Block,
+    Open,
+    Letter(char),
+}
+
+#[derive(Clone, Debug)]
+pub struct Grid {
+    pub width: usize,
+    pub height: usize,
+    /// Row by row.
+    pub cells: Vec<Cell>,
+}
+
+/// One across or down run of cells, with its clue number.
+#[derive(Clone, Debug)]
+pub struct Slot {
+    pub number: usize,
+    pub across: bool,
+    /// Indices into [`Grid::cells`].
+    pub cells: Vec<usize>,
+}
+
+impl Grid {
+    /// One line per row: `#` a block, `.` (or `_`, `?`) an open cell, and
+    /// a letter a prefilled one. Blank lines are skipped; short rows are
+    /// padded with blocks.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let rows: Vec<Vec<char>> = spec.lines().map(|line| line.trim().chars().filter(|c| !c.is_whitespace()).collect()).filter(|row: &Vec<char>| !row.is_empty()).collect();
+        let width = rows.iter().map(Vec::len).max().ok_or("the grid is empty")?;
+        let mut cells = Vec::with_capacity(width * rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            for x in 0..width {
+                cells.push(match row.get(x) {
+                    None | Some('#') => Cell::Block,
+                    Some('.' | '_' | '?') => Cell::Open,
+                    Some(&c) if c.is_alphabetic() => Cell::Letter(c.to_ascii_lowercase()),
+                    Some(c) => return Err(format!("'{}' at row {}, column {} is not a letter, '#' or '.'", c, y + 1, x + 1)),
+                });
+            }
+        }
+        Ok(Grid { width, height: rows.len(), cells })
+    }
+
+    /// Every slot, numbered the usual way: left to right, top to bottom,
+    /// one number per cell that starts an across or down slot.
+    pub fn slots(&self) -> Vec<Slot> {
+        let open = |x: usize, y: usize| x < self.width && y < self.height && self.cells[y * self.width + x] != Cell::Block;
+        let mut slots = Vec::new();
+        let mut number = 0;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if !open(x, y) {
+                    continue;
+                }
+                let across = (x == 0 || !open(x - 1, y)) && open(x + 1, y);
+                let down = (y == 0 || !open(x, y - 1)) && open(x, y + 1);
+                if across || down {
+                    number += 1;
+                }
+                if across {
+                    let cells = (x..self.width).take_while(|&x| open(x, y)).map(|x| y * self.width + x).collect();
+                    slots.push(Slot { number, across: true, cells });
+                }
+                if down {
+                    let cells = (y..self.height).take_while(|&y| open(x, y)).map(|y| y * self.width + x).collect();
+                    slots.push(Slot { number, across: false, cells });
+                }
+            }
+        }
+        slots
+    }
+
+    /// The word in `slot`, with `.` for open cells.
+    pub fn word(&self, slot: &Slot) -> String {
+        slot.cells
+            .iter()
+            .map(|&i| match self.cells[i] {
+                Cell::Letter(c) => c,
+                _ => '.',
+            })
+            .collect()
+    }
+}
+
+/// Letters still possible in one cell, sorted; `None` for any.
+type Domain = Option<Vec<char>>;
+
+struct Solver<'a> {
+    trie: &'a Trie,
+    grid: &'a Grid,
+    slots: Vec<Slot>,
+}
+
+/// Calls `found` with every fill of `grid` from the words in `trie`, no
+/// word used twice, until it returns `false` or `stop` returns `true`.
+/// Returns how many search steps it took.
+pub fn fill(grid: &Grid, trie: &Trie, stop: &dyn Fn() -> bool, found: &mut dyn FnMut(&Grid) -> bool) -> u64 {
+    let slots = grid.slots();
+    let domains = grid
+        .cells
+        .iter()
+        .map(|cell| match cell {
+            Cell::Letter(c) => Some(vec![*c]),
+            _ => None,
+        })
+        .collect();
+    let solver = Solver { trie, grid, slots };
+    let mut steps = 0;
+    solver.search(domains, stop, found, &mut steps);
+    steps
+}
+
+impl Solver<'_> {
+    /// Returns `false` once the search should end.
+    fn search(&self, mut domains: Vec<Domain>, stop: &dyn Fn() -> bool, found: &mut dyn FnMut(&Grid) -> bool, steps: &mut u64) -> bool {
+        *steps += 1;
+        if stop() {
+            return false;
+        }
+        let Some(words) = self.propagate(&mut domains) else { return true };
+        // The slot with the fewest words that isn't settled yet.
+        let Some(slot) = (0..self.slots.len()).filter(|&s| words[s].len() > 1).min_by_key(|&s| words[s].len()) else {
+            return self.settled(&domains, &words, found);
+        };
+        // Words already settled elsewhere.
+        let taken: Vec<&str> = words.iter().filter(|w| w.len() == 1).map(|w| w[0].as_str()).collect();
+        for word in &words[slot] {
+            if taken.contains(&word.as_str()) {
+                continue;
+            }
+            let mut next = domains.clone();
+            for (&cell, c) in self.slots[slot].cells.iter().zip(word.chars()) {
+                next[cell] = Some(vec![c]);
+            }
+            if !self.search(next, stop, found, steps) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Every slot fits exactly one word; reports the grid unless a word
+    /// repeats.
+    fn settled(&self, domains: &[Domain], words: &[Vec<String>], found: &mut dyn FnMut(&Grid) -> bool) -> bool {
+        let mut all: Vec<&str> = words.iter().map(|w| w[0].as_str()).collect();
+        all.sort_unstable();
+        if all.windows(2).any(|pair| pair[0] == pair[1]) {
+            return true;
+        }
+        let mut grid = self.grid.clone();
+        for (cell, domain) in grid.cells.iter_mut().zip(domains) {
+            if let (Cell::Open, Some(letters)) = (*cell, domain) {
+                *cell = Cell::Letter(letters[0]);
+            }
+        }
+        found(&grid)
+    }
+
+    /// Narrows `domains` until every slot's words agree with them; the
+    /// words per slot, or `None` if some slot has none left.
+    fn propagate(&self, domains: &mut [Domain]) -> Option<Vec<Vec<String>>> {
+        loop {
+            let mut changed = false;
+            let mut words = Vec::with_capacity(self.slots.len());
+            for slot in &self.slots {
+                let fits = self.words(slot, domains);
+                if fits.is_empty() {
+                    return None;
+                }
+                for (i, &cell) in slot.cells.iter().enumerate() {
+                    let mut letters: Vec<char> = fits.iter().filter_map(|word| word.chars().nth(i)).collect();
+                    letters.sort_unstable();
+                    letters.dedup();
+                    let narrowed = match &domains[cell] {
+                        Some(current) => current.iter().copied().filter(|c| letters.binary_search(c).is_ok()).collect(),
+                        None => letters,
+                    };
+                    if domains[cell].as_ref() != Some(&narrowed) {
+                        if narrowed.is_empty() {
+                            return None;
+                        }
+                        domains[cell] = Some(narrowed);
+                        changed = true;
+                    }
+                }
+                words.push(fits);
+            }
+            if !changed {
+                return Some(words);
+            }
+        }
+    }
+
+    /// The words that fit `slot` given `domains`.
+    fn words(&self, slot: &Slot, domains: &[Domain]) -> Vec<String> {
+        let mut words = Vec::new();
+        let mut prefix = String::new();
+        self.walk(ROOT, &slot.cells, domains, &mut prefix, &mut words);
+        words
+    }
+
+    fn walk(&self, node: u32, cells: &[usize], domains: &[Domain], prefix: &mut String, words: &mut Vec<String>) {
+        let Some((&cell, rest)) = cells.split_first() else {
+            if self.trie.is_word(node) {
+                words.push(prefix.clone());
+            }
+            return;
+        };
+        let mut visit = |c: char, child: u32| {
+            prefix.push(c);
+            self.walk(child, rest, domains, prefix, words);
+            prefix.pop();
+        };
+        match &domains[cell] {
+            Some(letters) => {
+                for &c in letters {
+                    if let Some(child) = self.trie.child(node, c) {
+                        visit(c, child);
+                    }
+                }
+            }
+            None => {
+                for (c, child) in self.trie.children(node) {
+                    visit(c, child);
+                }
+            }
+        }
+    }
+}
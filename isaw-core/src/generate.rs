@@ -0,0 +1,733 @@
+//! Candidate generators.
+
+use crate::bloom::Bloom;
+use crate::trie::{self, Trie};
+use crate::{count, rank};
+use itertools::{Either, Itertools};
+use std::collections::HashSet;
+use std::ops::{Range, RangeInclusive};
+use std::rc::Rc;
+
+/// Every ordered arrangement of `min..=max` letters, shortest first.
+pub fn permutations(chars: Vec<char>, min: usize, max: usize) -> impl Iterator<Item = String> {
+    (min..=max).flat_map(move |len| {
+        chars.clone().into_iter().permutations(len).map(|perm| perm.into_iter().collect())
+    })
+}
+
+/// Like [`permutations`], but each selection of letters is arranged in
+/// Steinhaus–Johnson–Trotter order: successive arrangements differ by one
+/// swap of neighbouring letters.
+pub fn sjt(chars: Vec<char>, min: usize, max: usize) -> impl Iterator<Item = String> {
+    (min..=max).flat_map(move |len| chars.clone().into_iter().combinations(len).flat_map(Sjt::new))
+}
+
+/// Like [`permutations`], but each selection of letters is arranged in the
+/// order of Heap's algorithm: successive arrangements differ by one swap.
+pub fn heap(chars: Vec<char>, min: usize, max: usize) -> impl Iterator<Item = String> {
+    (min..=max).flat_map(move |len| chars.clone().into_iter().combinations(len).flat_map(Heap::new))
+}
+
+/// Arrangements of all of `chars` by adjacent swaps, with the directed
+/// "largest mobile element" rule.
+struct Sjt {
+    chars: Vec<char>,
+    /// Positions hold element numbers; element `e` is `chars[e]`.
+    perm: Vec<usize>,
+    /// Per element: whether it moves left.
+    left: Vec<bool>,
+    started: bool,
+}
+
+impl Sjt {
+    fn new(chars: Vec<char>) -> Self {
+        let n = chars.len();
+        Sjt { chars, perm: (0..n).collect(), left: vec![true; n], started: false }
+    }
+}
+
+impl Iterator for Sjt {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        if self.started {
+            let n = self.perm.len();
+            let target = |i: usize, left: &[bool], perm: &[usize]| if left[perm[i]] { i.checked_sub(1) } else { Some(i + 1).filter(|&j| j < n) };
+            // The largest element whose neighbour in its direction is smaller.
+            let mobile = (0..n)
+                .filter(|&i| target(i, &self.left, &self.perm).is_some_and(|j| self.perm[j] < self.perm[i]))
+                .max_by_key(|&i| self.perm[i])?;
+            let element = self.perm[mobile];
+            let to = target(mobile, &self.left, &self.perm)?;
+            self.perm.swap(mobile, to);
+            for e in element + 1..n {
+                self.left[e] = !self.left[e];
+            }
+        }
+        self.started = true;
+        Some(self.perm.iter().map(|&e| self.chars[e]).collect())
+    }
+}
+
+/// Arrangements of all of `chars` by Heap's algorithm, iteratively.
+struct Heap {
+    chars: Vec<char>,
+    /// The stack of loop counters the recursive version would keep.
+    counters: Vec<usize>,
+    i: usize,
+    started: bool,
+}
+
+impl Heap {
+    fn new(chars: Vec<char>) -> Self {
+        Heap { counters: vec![0; chars.len()], chars, i: 1, started: false }
+    }
+}
+
+impl Iterator for Heap {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        if self.started {
+            loop {
+                if self.i >= self.chars.len() {
+                    return None;
+                }
+                let i = self.i;
+                if self.counters[i] < i {
+                    let j = if i.is_multiple_of(2) { 0 } else { self.counters[i] };
+                    self.chars.swap(j, i);
+                    self.counters[i] += 1;
+                    self.i = 1;
+                    break;
+                }
+                self.counters[i] = 0;
+                self.i += 1;
+            }
+        }
+        self.started = true;
+        Some(self.chars.iter().collect())
+    }
+}
+
+/// Every selection of `length` letters, in input order.
+pub fn combinations(chars: Vec<char>, length: usize) -> impl Iterator<Item = String> {
+    chars.into_iter().combinations(length).map(|combo| combo.into_iter().collect())
+}
+
+/// Every selection of `length` letters in revolving-door order: each one
+/// differs from the one before by swapping a single letter for another.
+pub fn revolving_door(chars: Vec<char>, length: usize) -> RevolvingDoor {
+    // c[1..=length] are the chosen indices, c[length + 1] a sentinel.
+    let mut c: Vec<usize> = (0..=length).map(|j| j.saturating_sub(1)).collect();
+    c.push(chars.len());
+    RevolvingDoor { done: length > chars.len(), chars, c, started: false }
+}
+
+/// Knuth's Algorithm R (TAOCP 7.2.1.3), one selection per `next`.
+pub struct RevolvingDoor {
+    chars: Vec<char>,
+    c: Vec<usize>,
+    started: bool,
+    done: bool,
+}
+
+impl RevolvingDoor {
+    /// Moves `c` to the next selection; false after the last one.
+    fn advance(&mut self) -> bool {
+        let c = &mut self.c;
+        let t = c.len() - 2;
+        if t == 0 {
+            return false;
+        }
+        // R3: the easy moves of c[1]; otherwise R4 (odd t) or R5 (even t).
+        let mut decrease = if t % 2 == 1 {
+            if c[1] + 1 < c[2] {
+                c[1] += 1;
+                return true;
+            }
+            true
+        } else {
+            if c[1] > 0 {
+                c[1] -= 1;
+                return true;
+            }
+            false
+        };
+        let mut j = 2;
+        while j <= t {
+            if decrease {
+                // R4: try to decrease c[j].
+                if c[j] >= j {
+                    c[j] = c[j - 1];
+                    c[j - 1] = j - 2;
+                    return true;
+                }
+            } else if c[j] + 1 < c[j + 1] {
+                // R5: try to increase c[j].
+                c[j - 1] = c[j];
+                c[j] += 1;
+                return true;
+            }
+            j += 1;
+            decrease = !decrease;
+        }
+        false
+    }
+}
+
+impl Iterator for RevolvingDoor {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        if self.done {
+            return None;
+        }
+        if self.started && !self.advance() {
+            self.done = true;
+            return None;
+        }
+        self.started = true;
+        let t = self.c.len() - 2;
+        Some(self.c[1..=t].iter().map(|&i| self.chars[i]).collect())
+    }
+}
+
+/// Like [`permutations`], optionally without the repeats that repeated
+/// input letters otherwise produce.
+pub fn words(chars: Vec<char>, min: usize, max: usize, unique: bool) -> impl Iterator<Item = String> {
+    if unique {
+        Either::Left((min..=max).flat_map(move |len| distinct_permutations(chars.clone(), len)))
+    } else {
+        Either::Right(permutations(chars, min, max))
+    }
+}
+
+/// The arrangements [`words`] would produce, in the same order, that are
+/// words in `trie`. Letters are only added while some word still starts
+/// with the candidate, so dead branches are never generated at all.
+pub fn dictionary_words(chars: Vec<char>, min: usize, max: usize, unique: bool, trie: &Trie) -> impl Iterator<Item = String> + '_ {
+    (min..=max.min(chars.len())).flat_map(move |length| {
+        let mut walk = Walk { chars: &chars, unique, trie, length, used: vec![false; chars.len()], path: Vec::new(), found: Vec::new() };
+        walk.extend(trie::ROOT);
+        walk.found
+    })
+}
+
+/// Depth-first search state of [`dictionary_words`] for one length.
+struct Walk<'a> {
+    chars: &'a [char],
+    unique: bool,
+    trie: &'a Trie,
+    length: usize,
+    used: Vec<bool>,
+    path: Vec<char>,
+    found: Vec<String>,
+}
+
+impl Walk<'_> {
+    /// Tries every usable next letter from the trie `node` the path reached.
+    fn extend(&mut self, node: u32) {
+        if self.path.len() == self.length {
+            if self.trie.is_word(node) {
+                self.found.push(self.path.iter().collect());
+            }
+            return;
+        }
+        for i in 0..self.chars.len() {
+            // As in `distinct_permutations`: only the earliest unused copy of a letter.
+            let repeat = self.unique && self.chars[..i].iter().rposition(|&c| c == self.chars[i]).is_some_and(|prev| !self.used[prev]);
+            if self.used[i] || repeat {
+                continue;
+            }
+            let Some(child) = self.trie.child(node, self.chars[i]) else { continue };
+            self.used[i] = true;
+            self.path.push(self.chars[i]);
+            self.extend(child);
+            self.path.pop();
+            self.used[i] = false;
+        }
+    }
+}
+
+/// Every distinct arrangement of `length` letters from `chars`, each
+/// exactly once, in the order [`permutations`] first produces them.
+///
+/// Among copies of a letter only the earliest unused one is ever picked, so
+/// equal siblings are skipped instead of generated and filtered out.
+pub fn distinct_permutations(chars: Vec<char>, length: usize) -> Distinct {
+    let prev_same = (0..chars.len()).map(|i| chars[..i].iter().rposition(|&c| c == chars[i])).collect();
+    Distinct { used: vec![false; chars.len()], chars, prev_same, path: Vec::with_capacity(length), length, started: false, done: false }
+}
+
+pub struct Distinct {
+    chars: Vec<char>,
+    /// Index of the previous copy of each letter, if any.
+    prev_same: Vec<Option<usize>>,
+    used: Vec<bool>,
+    /// Indices of the letters in the current word.
+    path: Vec<usize>,
+    length: usize,
+    started: bool,
+    done: bool,
+}
+
+impl Distinct {
+    fn usable(&self, i: usize) -> bool {
+        !self.used[i] && self.prev_same[i].is_none_or(|prev| self.used[prev])
+    }
+    
+    fn take(&mut self, from: usize) -> bool {
+        match (from..self.chars.len()).find(|&i| self.usable(i)) {
+            Some(i) => {
+                self.used[i] = true;
+                self.path.push(i);
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Completes the path with the smallest usable letters.
+    fn fill(&mut self) -> bool {
+        while self.path.len() < self.length {
+            if !self.take(0) {
+                return false;
+            }
+        }
+        true
+    }
+    
+    /// Moves to the next word: bumps the deepest letter that can move right.
+    fn advance(&mut self) -> bool {
+        while let Some(i) = self.path.pop() {
+            self.used[i] = false;
+            if self.take(i + 1) {
+                return self.fill();
+            }
+        }
+        false
+    }
+}
+
+impl Iterator for Distinct {
+    type Item = String;
+    
+    fn next(&mut self) -> Option<String> {
+        if self.done {
+            return None;
+        }
+        let ok = if self.started { self.advance() } else { self.fill() };
+        self.started = true;
+        if !ok {
+            self.done = true;
+            return None;
+        }
+        Some(self.path.iter().map(|&i| self.chars[i]).collect())
+    }
+}
+
+/// Every distinct palindrome of `min..=max` letters from `chars`, each used
+/// at most once.
+///
+/// Palindromes are built rather than filtered: the first half is a distinct
+/// arrangement of the letters that come in pairs, mirrored around an
+/// optional middle letter left over after taking the pairs.
+pub fn palindromes(chars: &[char], min: usize, max: usize) -> impl Iterator<Item = String> {
+    let mut letters: Vec<(char, usize)> = Vec::new();
+    for &c in chars {
+        match letters.iter_mut().find(|(l, _)| *l == c) {
+            Some((_, n)) => *n += 1,
+            None => letters.push((c, 1)),
+        }
+    }
+    let pairs: Vec<char> = letters.iter().flat_map(|&(c, n)| std::iter::repeat_n(c, n / 2)).collect();
+    let letters: Rc<[(char, usize)]> = letters.into();
+    
+    let max = max.min(2 * pairs.len() + 1);
+    (min..=max).flat_map(move |length| {
+        let letters = Rc::clone(&letters);
+        distinct_permutations(pairs.clone(), length / 2).flat_map(move |half| {
+            let middles: Vec<Option<char>> = if length % 2 == 0 {
+                vec![None]
+            } else {
+                letters.iter().filter(|&&(c, n)| n > 2 * half.chars().filter(|&h| h == c).count()).map(|&(c, _)| Some(c)).collect()
+            };
+            let mirrored: String = half.chars().rev().collect();
+            middles.into_iter().map(move |middle| format!("{}{}{}", half, middle.map(String::from).unwrap_or_default(), mirrored))
+        })
+    })
+}
+
+/// An independently generatable slice of [`permutations`]: every arrangement
+/// of `length` letters that starts with `chars[first]`.
+///
+/// Blocks from [`blocks`] concatenate, in order, to exactly what
+/// [`permutations`] yields, which is what lets callers split the work
+/// between threads without changing the output.
+#[derive(Clone, Copy, Debug)]
+pub struct Block {
+    pub length: usize,
+    pub first: usize,
+}
+
+impl Block {
+    pub fn permutations(self, chars: &[char]) -> impl Iterator<Item = String> {
+        let lead = if self.length == 0 { None } else { Some(chars[self.first]) };
+        let mut rest = chars.to_vec();
+        if lead.is_some() {
+            rest.remove(self.first);
+        }
+        let tail = self.length.saturating_sub(1);
+        rest.into_iter().permutations(tail).map(move |perm| lead.into_iter().chain(perm).collect())
+    }
+    
+    /// Like [`Block::permutations`] without repeated words; only meaningful
+    /// for blocks from [`blocks`] with `distinct_first`.
+    pub fn distinct_permutations(self, chars: &[char]) -> impl Iterator<Item = String> {
+        let lead = if self.length == 0 { None } else { Some(chars[self.first]) };
+        let mut rest = chars.to_vec();
+        if lead.is_some() {
+            rest.remove(self.first);
+        }
+        let tail = self.length.saturating_sub(1);
+        distinct_permutations(rest, tail).map(move |perm| lead.into_iter().chain(perm.chars()).collect())
+    }
+}
+
+/// Splits lengths `min..=max` into blocks by first letter.
+///
+/// With `distinct_first`, only the first occurrence of each repeated letter
+/// gets a block: later copies would only produce the same words again.
+pub fn blocks(chars: &[char], min: usize, max: usize, distinct_first: bool) -> Vec<Block> {
+    let firsts: Vec<usize> = (0..chars.len())
+        .filter(|&i| !distinct_first || !chars[..i].contains(&chars[i]))
+        .collect();
+    
+    let mut blocks = Vec::new();
+    for length in min..=max.min(chars.len()) {
+        if length == 0 {
+            blocks.push(Block { length, first: 0 });
+            continue;
+        }
+        blocks.extend(firsts.iter().map(|&first| Block { length, first }));
+    }
+    blocks
+}
+
+/// Odometer over every string of exactly `length` letters drawn from
+/// `chars` with repetition, the last position turning fastest, without
+/// materializing them. `start` skips straight to the candidate with that
+/// index, so the keyspace can be processed in chunks.
+///
+/// [`Product::next_str`] walks it without allocating: the current string
+/// lives in one buffer, and each step only rewrites the positions whose
+/// digits changed.
+pub struct Product {
+    chars: Vec<char>,
+    digits: Vec<usize>,
+    remaining: u128,
+    /// The current string.
+    word: String,
+    /// Byte offset of each position in `word`.
+    offsets: Vec<usize>,
+    started: bool,
+}
+
+impl Product {
+    pub fn new(chars: &[char], length: usize, start: u128) -> Self {
+        let total = count::power(chars.len() as u128, length as u128).unwrap_or(u128::MAX);
+        let digits = rank::unrank_product(chars.len(), length, start);
+        let remaining = total.saturating_sub(start);
+        let mut product = Product { chars: chars.to_vec(), digits, remaining, word: String::new(), offsets: vec![0; length], started: false };
+        if remaining > 0 {
+            product.word.reserve(length * chars.iter().map(|c| c.len_utf8()).max().unwrap_or(1));
+            product.spell(0);
+        }
+        product
+    }
+
+    /// The next string, borrowed from a buffer the following call reuses.
+    pub fn next_str(&mut self) -> Option<&str> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        if self.started {
+            self.advance();
+        }
+        self.started = true;
+        Some(&self.word)
+    }
+
+    fn advance(&mut self) {
+        let mut i = self.digits.len();
+        while i > 0 {
+            i -= 1;
+            self.digits[i] += 1;
+            if self.digits[i] < self.chars.len() {
+                break;
+            }
+            self.digits[i] = 0;
+        }
+        self.spell(i);
+    }
+
+    /// Rewrites `word` from position `from` on, after its digits changed.
+    fn spell(&mut self, from: usize) {
+        if from >= self.digits.len() {
+            return;
+        }
+        self.word.truncate(self.offsets[from]);
+        for i in from..self.digits.len() {
+            self.offsets[i] = self.word.len();
+            self.word.push(self.chars[self.digits[i]]);
+        }
+    }
+}
+
+impl Iterator for Product {
+    type Item = String;
+    
+    fn next(&mut self) -> Option<String> {
+        self.next_str().map(str::to_string)
+    }
+}
+
+/// Candidates `range` of a mask's keyspace, every string taking one of
+/// `positions[i]` at position `i` (see [`crate::mask`]), in odometer order
+/// or from the top down with `reverse`.
+pub fn masked(positions: Vec<Vec<char>>, range: Range<u128>, reverse: bool) -> Masked {
+    let mut rank = if reverse { range.end.saturating_sub(1) } else { range.start };
+    let mut digits = vec![0; positions.len()];
+    for (digit, chars) in digits.iter_mut().zip(&positions).rev() {
+        if !chars.is_empty() {
+            *digit = (rank % chars.len() as u128) as usize;
+            rank /= chars.len() as u128;
+        }
+    }
+    let remaining = if positions.iter().any(Vec::is_empty) { 0 } else { range.end.saturating_sub(range.start) };
+    Masked { positions, digits, remaining, backward: reverse }
+}
+
+/// Mixed-radix odometer, see [`masked`].
+pub struct Masked {
+    positions: Vec<Vec<char>>,
+    digits: Vec<usize>,
+    remaining: u128,
+    backward: bool,
+}
+
+impl Iterator for Masked {
+    type Item = String;
+    
+    fn next(&mut self) -> Option<String> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let word = self.digits.iter().zip(&self.positions).map(|(&d, chars)| chars[d]).collect();
+        
+        for (digit, chars) in self.digits.iter_mut().zip(&self.positions).rev() {
+            match self.backward {
+                true if *digit > 0 => {
+                    *digit -= 1;
+                    break;
+                }
+                true => *digit = chars.len() - 1,
+                false if *digit + 1 < chars.len() => {
+                    *digit += 1;
+                    break;
+                }
+                false => *digit = 0,
+            }
+        }
+        Some(word)
+    }
+}
+
+/// The three keyspaces isaw enumerates, addressable by rank.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Keyspace {
+    /// Ordered arrangements without repetition ([`permutations`]).
+    Permutations,
+    /// Unordered selections ([`combinations`]).
+    Combinations,
+    /// Strings with repetition ([`Product`]).
+    Product,
+}
+
+impl Keyspace {
+    /// Candidates of length `k` over `n` letters, or `None` on overflow.
+    pub fn size(self, n: usize, k: usize) -> Option<u128> {
+        let (n, k) = (n as u128, k as u128);
+        match self {
+            Keyspace::Permutations => count::checked_permutation(n, k),
+            Keyspace::Combinations => count::checked_binomial(n, k),
+            Keyspace::Product => count::power(n, k),
+        }
+    }
+    
+    /// Candidates across all `lengths`, or `None` on overflow.
+    pub fn total(self, n: usize, lengths: RangeInclusive<usize>) -> Option<u128> {
+        lengths.map(|k| self.size(n, k)).try_fold(0u128, |acc, size| acc.checked_add(size?))
+    }
+    
+    /// The candidates with ranks in `range`, numbering all `lengths` one
+    /// after another in generation order.
+    pub fn candidates(self, chars: Vec<char>, lengths: RangeInclusive<usize>, range: Range<u128>) -> impl Iterator<Item = String> {
+        let mut parts = Vec::new();
+        let mut offset: u128 = 0;
+        for k in lengths {
+            let size = self.size(chars.len(), k).unwrap_or(u128::MAX);
+            let (start, end) = (range.start.max(offset), range.end.min(offset.saturating_add(size)));
+            if start < end {
+                parts.push((k, start - offset, end - start));
+            }
+            offset = offset.saturating_add(size);
+        }
+        parts.into_iter().flat_map(move |(k, start, count)| Ranked::new(self, chars.clone(), k, start, count))
+    }
+    
+    /// Exactly the candidates of [`Keyspace::candidates`], last rank first.
+    pub fn candidates_rev(self, chars: Vec<char>, lengths: RangeInclusive<usize>, range: Range<u128>) -> impl Iterator<Item = String> {
+        let mut parts = Vec::new();
+        let mut offset: u128 = 0;
+        for k in lengths {
+            let size = self.size(chars.len(), k).unwrap_or(u128::MAX);
+            let (start, end) = (range.start.max(offset), range.end.min(offset.saturating_add(size)));
+            if start < end {
+                parts.push((k, end - 1 - offset, end - start));
+            }
+            offset = offset.saturating_add(size);
+        }
+        parts.into_iter().rev().flat_map(move |(k, last, count)| Ranked { backward: true, ..Ranked::new(self, chars.clone(), k, last, count) })
+    }
+}
+
+/// Walks `count` candidates of one length in lexicographic index order,
+/// starting from an arbitrary rank, or in reverse order down from it.
+struct Ranked {
+    keyspace: Keyspace,
+    chars: Vec<char>,
+    indices: Vec<usize>,
+    remaining: u128,
+    backward: bool,
+}
+
+impl Ranked {
+    fn new(keyspace: Keyspace, chars: Vec<char>, k: usize, start: u128, count: u128) -> Self {
+        let n = chars.len();
+        let indices = match keyspace {
+            Keyspace::Permutations => rank::unrank_permutation(n, k, start),
+            Keyspace::Combinations => rank::unrank_combination(n, k, start),
+            Keyspace::Product => rank::unrank_product(n, k, start),
+        };
+        Ranked { keyspace, chars, indices, remaining: count, backward: false }
+    }
+    
+    fn advance(&mut self) {
+        let n = self.chars.len();
+        let a = &mut self.indices;
+        let k = a.len();
+        match self.keyspace {
+            Keyspace::Permutations => {
+                // Bump the rightmost position that can take a larger unused
+                // index, then refill the tail with the smallest unused ones.
+                for j in (0..k).rev() {
+                    let used = &a[..j];
+                    if let Some(next) = (a[j] + 1..n).find(|i| !used.contains(i)) {
+                        a[j] = next;
+                        let fill: Vec<usize> = (0..n).filter(|i| !a[..=j].contains(i)).take(k - j - 1).collect();
+                        a[j + 1..].copy_from_slice(&fill);
+                        return;
+                    }
+                }
+            }
+            Keyspace::Combinations => {
+                if let Some(j) = (0..k).rev().find(|&j| a[j] < n - k + j) {
+                    a[j] += 1;
+                    for slot in j + 1..k {
+                        a[slot] = a[slot - 1] + 1;
+                    }
+                }
+            }
+            Keyspace::Product => {
+                for digit in a.iter_mut().rev() {
+                    *digit += 1;
+                    if *digit < n {
+                        break;
+                    }
+                    *digit = 0;
+                }
+            }
+        }
+    }
+    
+    /// The mirror image of [`Ranked::advance`]: steps to the previous rank.
+    fn retreat(&mut self) {
+        let n = self.chars.len();
+        let a = &mut self.indices;
+        let k = a.len();
+        match self.keyspace {
+            Keyspace::Permutations => {
+                // Lower the rightmost position that can take a smaller unused
+                // index, then refill the tail with the largest unused ones.
+                for j in (0..k).rev() {
+                    let used = &a[..j];
+                    if let Some(prev) = (0..a[j]).rev().find(|i| !used.contains(i)) {
+                        a[j] = prev;
+                        let fill: Vec<usize> = (0..n).rev().filter(|i| !a[..=j].contains(i)).take(k - j - 1).collect();
+                        a[j + 1..].copy_from_slice(&fill);
+                        return;
+                    }
+                }
+            }
+            Keyspace::Combinations => {
+                if let Some(j) = (0..k).rev().find(|&j| a[j] > if j == 0 { 0 } else { a[j - 1] + 1 }) {
+                    a[j] -= 1;
+                    for (slot, index) in a.iter_mut().enumerate().skip(j + 1) {
+                        *index = n - k + slot;
+                    }
+                }
+            }
+            Keyspace::Product => {
+                for digit in a.iter_mut().rev() {
+                    if *digit > 0 {
+                        *digit -= 1;
+                        break;
+                    }
+                    *digit = n - 1;
+                }
+            }
+        }
+    }
+}
+
+impl Iterator for Ranked {
+    type Item = String;
+    
+    fn next(&mut self) -> Option<String> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let word = self.indices.iter().map(|&i| self.chars[i]).collect();
+        if self.remaining > 0 {
+            if self.backward { self.retreat() } else { self.advance() }
+        }
+        Some(word)
+    }
+}
+
+/// Drops candidates that were already produced earlier in `words`.
+pub fn unique(words: impl Iterator<Item = String>) -> impl Iterator<Item = String> {
+    let mut seen: HashSet<String> = HashSet::new();
+    words.filter(move |word| seen.insert(word.clone()))
+}
+
+/// Like [`unique`], but remembers words in `bloom` instead of a set, so
+/// memory stays fixed at the cost of occasionally dropping a new word.
+pub fn unique_approx(words: impl Iterator<Item = String>, mut bloom: Bloom) -> impl Iterator<Item = String> {
+    words.filter(move |word| bloom.insert(word))
+}
@@ -0,0 +1,60 @@
+//! Shell-style globs over whole candidates: `?` is any one character, `*`
+//! any run of them (even none) and `\` makes the next character literal.
+
+/// Whether `word` matches `pattern` from end to end.
+pub fn matches(pattern: &str, word: &str) -> bool {
+    let pattern = tokens(pattern);
+    let word: Vec<char> = word.chars().collect();
+    // Greedy with one backtrack point: the last `*` and where it resumed.
+    let (mut p, mut w) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while w < word.len() {
+        match pattern.get(p) {
+            Some(Token::Any) => {
+                p += 1;
+                w += 1;
+            }
+            Some(Token::Char(c)) if *c == word[w] => {
+                p += 1;
+                w += 1;
+            }
+            Some(Token::Run) => {
+                star = Some((p, w));
+                p += 1;
+            }
+            _ => match star {
+                // Let the last `*` take one more character and retry.
+                Some((at, from)) => {
+                    star = Some((at, from + 1));
+                    p = at + 1;
+                    w = from + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|token| *token == Token::Run)
+}
+
+#[derive(PartialEq)]
+enum Token {
+    Char(char),
+    Any,
+    Run,
+}
+
+fn tokens(pattern: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        tokens.push(match c {
+            '?' => Token::Any,
+            // Runs of stars mean the same as one.
+            '*' if tokens.last() == Some(&Token::Run) => continue,
+            '*' => Token::Run,
+            '\\' => Token::Char(chars.next().unwrap_or('\\')),
+            c => Token::Char(c),
+        });
+    }
+    tokens
+}
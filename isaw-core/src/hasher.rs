@@ -0,0 +1,101 @@
+//! Backends that hash candidates for `--hash` matching.
+//!
+//! [`Matcher::Sha256`](crate::Matcher::Sha256) hashes through a
+//! [`HashBackend`] instead of calling [`sha256::digest`] directly, and hands
+//! it whole blocks of candidates where the caller has them, so a backend
+//! that offloads to an accelerator only has to implement the batch calls.
+//! With the `gpu` feature, [`Gpu`] hashes large batches on a graphics card
+//! through wgpu; [`best`] falls back to [`Cpu`] whenever there is no GPU.
+//! Candidates are still generated on the CPU and shipped to the device in
+//! batches.
+
+use crate::{sha1, sha256};
+use std::sync::Arc;
+
+#[cfg(feature = "gpu")]
+mod gpu;
+
+#[cfg(feature = "gpu")]
+pub use gpu::Gpu;
+
+pub trait HashBackend: Send + Sync {
+    /// Short name, for logs.
+    fn name(&self) -> &'static str;
+
+    /// Whether the backend can run here, e.g. that its device is present.
+    fn available(&self) -> bool {
+        true
+    }
+
+    /// Appends the SHA-256 digest of each input to `out`, in order.
+    fn sha256_batch(&self, inputs: &[&[u8]], out: &mut Vec<[u8; 32]>);
+
+    /// Appends the SHA-1 digest of each input to `out`, in order.
+    fn sha1_batch(&self, inputs: &[&[u8]], out: &mut Vec<[u8; 20]>);
+
+    /// SHA-256 of a single input, for callers matching one word at a time.
+    fn sha256(&self, input: &[u8]) -> [u8; 32] {
+        let mut out = Vec::with_capacity(1);
+        self.sha256_batch(&[input], &mut out);
+        out[0]
+    }
+}
+
+/// Hashes on the calling thread with the crate's own SHA code; callers
+/// spread batches across cores themselves.
+pub struct Cpu;
+
+impl HashBackend for Cpu {
+    fn name(&self) -> &'static str {
+        "cpu"
+    }
+
+    fn sha256_batch(&self, inputs: &[&[u8]], out: &mut Vec<[u8; 32]>) {
+        out.extend(inputs.iter().map(|input| sha256::digest(input)));
+    }
+
+    fn sha1_batch(&self, inputs: &[&[u8]], out: &mut Vec<[u8; 20]>) {
+        out.extend(inputs.iter().map(|input| sha1::digest(input)));
+    }
+
+    fn sha256(&self, input: &[u8]) -> [u8; 32] {
+        sha256::digest(input)
+    }
+}
+
+/// The first available backend, in order of preference. Accelerated
+/// backends go ahead of [`Cpu`], which always works.
+pub fn best() -> Arc<dyn HashBackend> {
+    let backends: Vec<Arc<dyn HashBackend>> = vec![Arc::new(Cpu)];
+    #[cfg(feature = "gpu")]
+    let backends: Vec<Arc<dyn HashBackend>> = gpu().into_iter().chain(backends).collect();
+    backends.into_iter().find(|backend| backend.available()).expect("the CPU backend is always available")
+}
+
+/// The hardware GPU backend, opened once per process since finding and
+/// opening the device is slow.
+#[cfg(feature = "gpu")]
+fn gpu() -> Option<Arc<dyn HashBackend>> {
+    static GPU: std::sync::OnceLock<Option<Arc<Gpu>>> = std::sync::OnceLock::new();
+    GPU.get_or_init(|| Gpu::new(false).map(Arc::new)).clone().map(|gpu| gpu as Arc<dyn HashBackend>)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batches_match_single_digests() {
+        let inputs: [&[u8]; 3] = [b"", b"abc", b"isaw"];
+        let mut sha256s = Vec::new();
+        let mut sha1s = Vec::new();
+        Cpu.sha256_batch(&inputs, &mut sha256s);
+        Cpu.sha1_batch(&inputs, &mut sha1s);
+        for (i, input) in inputs.iter().enumerate() {
+            assert_eq!(sha256s[i], sha256::digest(input));
+            assert_eq!(sha1s[i], sha1::digest(input));
+        }
+        #[cfg(not(feature = "gpu"))]
+        assert_eq!(best().name(), "cpu");
+    }
+}
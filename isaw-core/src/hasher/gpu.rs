@@ -0,0 +1,183 @@
+//! Hashing on the GPU through wgpu (Vulkan, Metal, DirectX 12 or OpenGL),
+//! behind the `gpu` feature.
+//!
+//! A batch is packed into one buffer with an offset table, and
+//! `hash.wgsl` runs one invocation per candidate. Batches too small to be
+//! worth the round trip stay on the CPU.
+
+use super::{Cpu, HashBackend};
+use std::sync::mpsc;
+use wgpu::util::DeviceExt;
+
+/// Smallest batch sent to the device.
+const MIN_BATCH: usize = 1024;
+
+/// Invocations per workgroup, as declared in the shader.
+const WORKGROUP: usize = 64;
+
+pub struct Gpu {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    sha256: wgpu::ComputePipeline,
+    sha1: wgpu::ComputePipeline,
+    /// Most candidates in one dispatch.
+    max_inputs: usize,
+    /// Most candidate bytes in one dispatch.
+    max_bytes: usize,
+}
+
+impl Gpu {
+    /// Opens the first hardware adapter, or with `software` set any
+    /// adapter, including CPU emulations like llvmpipe that are slower
+    /// than [`Cpu`] but run the same shader.
+    pub fn new(software: bool) -> Option<Gpu> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor { backends: wgpu::Backends::all(), ..wgpu::InstanceDescriptor::new_without_display_handle() });
+        let adapters = pollster::block_on(instance.enumerate_adapters(wgpu::Backends::all()));
+        let adapter = adapters.into_iter().find(|adapter| software || adapter.get_info().device_type != wgpu::DeviceType::Cpu)?;
+        let limits = adapter.limits();
+        let descriptor = wgpu::DeviceDescriptor { label: Some("isaw hasher"), required_limits: limits.clone(), ..Default::default() };
+        let (device, queue) = pollster::block_on(adapter.request_device(&descriptor)).ok()?;
+        let module = device.create_shader_module(wgpu::include_wgsl!("hash.wgsl"));
+        let pipeline = |entry_point: &str| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(entry_point),
+                layout: None,
+                module: &module,
+                entry_point: Some(entry_point),
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+        let (sha256, sha1) = (pipeline("sha256"), pipeline("sha1"));
+        let max_inputs = (limits.max_compute_workgroups_per_dimension as usize * WORKGROUP).min(limits.max_storage_buffer_binding_size as usize / 32 - 1);
+        let max_bytes = (limits.max_storage_buffer_binding_size as usize).min(u32::MAX as usize);
+        Some(Gpu { device, queue, sha256, sha1, max_inputs, max_bytes })
+    }
+
+    /// Runs `pipeline` over `inputs`, `words` state words per digest, in
+    /// as few dispatches as the device limits allow.
+    fn run(&self, pipeline: &wgpu::ComputePipeline, words: usize, inputs: &[&[u8]]) -> Vec<u32> {
+        let mut digests = Vec::with_capacity(inputs.len() * words);
+        let mut rest = inputs;
+        while !rest.is_empty() {
+            let mut bytes = 0;
+            let take = rest.iter().take(self.max_inputs).take_while(|input| {
+                bytes += input.len();
+                bytes <= self.max_bytes
+            });
+            let take = take.count().max(1);
+            digests.extend(self.dispatch(pipeline, words, &rest[..take]));
+            rest = &rest[take..];
+        }
+        digests
+    }
+
+    fn dispatch(&self, pipeline: &wgpu::ComputePipeline, words: usize, inputs: &[&[u8]]) -> Vec<u32> {
+        let mut data: Vec<u8> = inputs.concat();
+        data.resize(data.len().next_multiple_of(4).max(4), 0);
+        let mut offsets = Vec::with_capacity(inputs.len() + 2);
+        offsets.push(inputs.len() as u32);
+        offsets.push(0);
+        for input in inputs {
+            offsets.push(offsets[offsets.len() - 1] + input.len() as u32);
+        }
+        let size = (inputs.len() * words * 4) as u64;
+
+        let storage = |label: &str, contents: &[u8]| {
+            self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor { label: Some(label), contents, usage: wgpu::BufferUsages::STORAGE })
+        };
+        let data = storage("candidates", &data);
+        let offsets = storage("offsets", &offsets.iter().flat_map(|offset| offset.to_le_bytes()).collect::<Vec<u8>>());
+        let output = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("digests"),
+            size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: data.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: offsets.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: output.as_entire_binding() },
+            ],
+        });
+
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&Default::default());
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(inputs.len().div_ceil(WORKGROUP) as u32, 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&output, 0, &readback, 0, size);
+        self.queue.submit([encoder.finish()]);
+
+        let (done, mapped) = mpsc::channel();
+        readback.slice(..).map_async(wgpu::MapMode::Read, move |result| done.send(result).unwrap_or_default());
+        self.device.poll(wgpu::PollType::wait_indefinitely()).expect("the GPU finishes hashing");
+        mapped.recv().expect("the readback is mapped").expect("the readback maps");
+        let view = readback.slice(..).get_mapped_range().expect("the mapped digests");
+        view.chunks_exact(4).map(|word| u32::from_le_bytes(word.try_into().unwrap())).collect()
+    }
+}
+
+impl HashBackend for Gpu {
+    fn name(&self) -> &'static str {
+        "gpu"
+    }
+
+    fn sha256_batch(&self, inputs: &[&[u8]], out: &mut Vec<[u8; 32]>) {
+        if inputs.len() < MIN_BATCH {
+            return Cpu.sha256_batch(inputs, out);
+        }
+        let words = self.run(&self.sha256, 8, inputs);
+        out.extend(words.chunks_exact(8).map(|state| std::array::from_fn(|i| state[i / 4].to_be_bytes()[i % 4])));
+    }
+
+    fn sha1_batch(&self, inputs: &[&[u8]], out: &mut Vec<[u8; 20]>) {
+        if inputs.len() < MIN_BATCH {
+            return Cpu.sha1_batch(inputs, out);
+        }
+        let words = self.run(&self.sha1, 5, inputs);
+        out.extend(words.chunks_exact(5).map(|state| std::array::from_fn(|i| state[i / 4].to_be_bytes()[i % 4])));
+    }
+
+    fn sha256(&self, input: &[u8]) -> [u8; 32] {
+        Cpu.sha256(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{sha1, sha256};
+
+    #[test]
+    fn digests_match_the_cpu() {
+        // Any adapter will do; without one there is nothing to check.
+        let Some(gpu) = Gpu::new(true) else { return };
+        let long = "x".repeat(200);
+        let mut inputs: Vec<Vec<u8>> = (0..MIN_BATCH * 2).map(|i| format!("candidate{}", i).into_bytes()).collect();
+        // Empty, and either side of the one- and two-block padding edges.
+        inputs.extend([0, 55, 56, 63, 64, 119, 120].map(|len| vec![b'a'; len]));
+        inputs.push(long.into_bytes());
+        let inputs: Vec<&[u8]> = inputs.iter().map(Vec::as_slice).collect();
+
+        let (mut sha256s, mut sha1s) = (Vec::new(), Vec::new());
+        gpu.sha256_batch(&inputs, &mut sha256s);
+        gpu.sha1_batch(&inputs, &mut sha1s);
+        assert_eq!(sha256s.len(), inputs.len());
+        for (i, input) in inputs.iter().enumerate() {
+            assert_eq!(sha256s[i], sha256::digest(input), "sha256 of {:?}", input);
+            assert_eq!(sha1s[i], sha1::digest(input), "sha1 of {:?}", input);
+        }
+    }
+}
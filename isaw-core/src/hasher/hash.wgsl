@@ -0,0 +1,139 @@
+// SHA-256 and SHA-1 of many short messages at once, one per invocation.
+//
+// `data` holds every message back to back, four bytes to a word in
+// little-endian order. `offsets` starts with the number of messages, then
+// message `i` is bytes `offsets[i + 1]..offsets[i + 2]` of `data`.
+// Padding is worked out on the fly rather than stored. Each digest is
+// written as its big-endian state words: 8 per message for SHA-256, 5 for
+// SHA-1.
+
+@group(0) @binding(0) var<storage, read> data: array<u32>;
+@group(0) @binding(1) var<storage, read> offsets: array<u32>;
+@group(0) @binding(2) var<storage, read_write> digests: array<u32>;
+
+const K256 = array<u32, 64>(
+    0x428a2f98u, 0x71374491u, 0xb5c0fbcfu, 0xe9b5dba5u, 0x3956c25bu, 0x59f111f1u, 0x923f82a4u, 0xab1c5ed5u,
+    0xd807aa98u, 0x12835b01u, 0x243185beu, 0x550c7dc3u, 0x72be5d74u, 0x80deb1feu, 0x9bdc06a7u, 0xc19bf174u,
+    0xe49b69c1u, 0xefbe4786u, 0x0fc19dc6u, 0x240ca1ccu, 0x2de92c6fu, 0x4a7484aau, 0x5cb0a9dcu, 0x76f988dau,
+    0x983e5152u, 0xa831c66du, 0xb00327c8u, 0xbf597fc7u, 0xc6e00bf3u, 0xd5a79147u, 0x06ca6351u, 0x14292967u,
+    0x27b70a85u, 0x2e1b2138u, 0x4d2c6dfcu, 0x53380d13u, 0x650a7354u, 0x766a0abbu, 0x81c2c92eu, 0x92722c85u,
+    0xa2bfe8a1u, 0xa81a664bu, 0xc24b8b70u, 0xc76c51a3u, 0xd192e819u, 0xd6990624u, 0xf40e3585u, 0x106aa070u,
+    0x19a4c116u, 0x1e376c08u, 0x2748774cu, 0x34b0bcb5u, 0x391c0cb3u, 0x4ed8aa4au, 0x5b9cca4fu, 0x682e6ff3u,
+    0x748f82eeu, 0x78a5636fu, 0x84c87814u, 0x8cc70208u, 0x90befffau, 0xa4506cebu, 0xbef9a3f7u, 0xc67178f2u,
+);
+
+fn byte_at(at: u32) -> u32 {
+    return (data[at >> 2u] >> ((at & 3u) * 8u)) & 0xffu;
+}
+
+// Big-endian word `w` of padded block `block` of the message at `start`
+// that is `len` bytes long and `blocks` blocks once padded.
+fn message_word(start: u32, len: u32, blocks: u32, block: u32, w: u32) -> u32 {
+    if block == blocks - 1u && w == 14u {
+        return len >> 29u;
+    }
+    if block == blocks - 1u && w == 15u {
+        return len << 3u;
+    }
+    var word = 0u;
+    for (var k = 0u; k < 4u; k++) {
+        let at = block * 64u + w * 4u + k;
+        var byte = 0u;
+        if at < len {
+            byte = byte_at(start + at);
+        } else if at == len {
+            byte = 0x80u;
+        }
+        word = (word << 8u) | byte;
+    }
+    return word;
+}
+
+fn rotr(x: u32, n: u32) -> u32 {
+    return (x >> n) | (x << (32u - n));
+}
+
+fn rotl(x: u32, n: u32) -> u32 {
+    return (x << n) | (x >> (32u - n));
+}
+
+@compute @workgroup_size(64)
+fn sha256(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if i >= offsets[0] {
+        return;
+    }
+    let start = offsets[i + 1u];
+    let len = offsets[i + 2u] - start;
+    let blocks = (len + 8u) / 64u + 1u;
+    var h = array<u32, 8>(0x6a09e667u, 0xbb67ae85u, 0x3c6ef372u, 0xa54ff53au, 0x510e527fu, 0x9b05688cu, 0x1f83d9abu, 0x5be0cd19u);
+    var w: array<u32, 64>;
+    for (var block = 0u; block < blocks; block++) {
+        for (var t = 0u; t < 16u; t++) {
+            w[t] = message_word(start, len, blocks, block, t);
+        }
+        for (var t = 16u; t < 64u; t++) {
+            let s0 = rotr(w[t - 15u], 7u) ^ rotr(w[t - 15u], 18u) ^ (w[t - 15u] >> 3u);
+            let s1 = rotr(w[t - 2u], 17u) ^ rotr(w[t - 2u], 19u) ^ (w[t - 2u] >> 10u);
+            w[t] = w[t - 16u] + s0 + w[t - 7u] + s1;
+        }
+        var a = h[0]; var b = h[1]; var c = h[2]; var d = h[3];
+        var e = h[4]; var f = h[5]; var g = h[6]; var hh = h[7];
+        for (var t = 0u; t < 64u; t++) {
+            let t1 = hh + (rotr(e, 6u) ^ rotr(e, 11u) ^ rotr(e, 25u)) + ((e & f) ^ (~e & g)) + K256[t] + w[t];
+            let t2 = (rotr(a, 2u) ^ rotr(a, 13u) ^ rotr(a, 22u)) + ((a & b) ^ (a & c) ^ (b & c));
+            hh = g; g = f; f = e; e = d + t1;
+            d = c; c = b; b = a; a = t1 + t2;
+        }
+        h[0] += a; h[1] += b; h[2] += c; h[3] += d;
+        h[4] += e; h[5] += f; h[6] += g; h[7] += hh;
+    }
+    for (var k = 0u; k < 8u; k++) {
+        digests[i * 8u + k] = h[k];
+    }
+}
+
+@compute @workgroup_size(64)
+fn sha1(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if i >= offsets[0] {
+        return;
+    }
+    let start = offsets[i + 1u];
+    let len = offsets[i + 2u] - start;
+    let blocks = (len + 8u) / 64u + 1u;
+    var h = array<u32, 5>(0x67452301u, 0xefcdab89u, 0x98badcfeu, 0x10325476u, 0xc3d2e1f0u);
+    var w: array<u32, 80>;
+    for (var block = 0u; block < blocks; block++) {
+        for (var t = 0u; t < 16u; t++) {
+            w[t] = message_word(start, len, blocks, block, t);
+        }
+        for (var t = 16u; t < 80u; t++) {
+            w[t] = rotl(w[t - 3u] ^ w[t - 8u] ^ w[t - 14u] ^ w[t - 16u], 1u);
+        }
+        var a = h[0]; var b = h[1]; var c = h[2]; var d = h[3]; var e = h[4];
+        for (var t = 0u; t < 80u; t++) {
+            var f: u32;
+            var k: u32;
+            if t < 20u {
+                f = (b & c) | (~b & d);
+                k = 0x5a827999u;
+            } else if t < 40u {
+                f = b ^ c ^ d;
+                k = 0x6ed9eba1u;
+            } else if t < 60u {
+                f = (b & c) | (b & d) | (c & d);
+                k = 0x8f1bbcdcu;
+            } else {
+                f = b ^ c ^ d;
+                k = 0xca62c1d6u;
+            }
+            let temp = rotl(a, 5u) + f + e + k + w[t];
+            e = d; d = c; c = rotl(b, 30u); b = a; a = temp;
+        }
+        h[0] += a; h[1] += b; h[2] += c; h[3] += d; h[4] += e;
+    }
+    for (var k = 0u; k < 5u; k++) {
+        digests[i * 5u + k] = h[k];
+    }
+}
@@ -0,0 +1,62 @@
+//! Word ladders: turning one word into another a letter at a time.
+
+use std::collections::{HashMap, VecDeque};
+
+/// The shortest chain from `start` to `end` in which each step changes one
+/// letter and every word is in `words` (the ends needn't be). Comparison
+/// ignores case; words of other lengths are skipped.
+pub fn shortest<'a>(start: &str, end: &str, words: impl IntoIterator<Item = &'a str>) -> Option<Vec<String>> {
+    let start: Vec<char> = start.chars().flat_map(char::to_lowercase).collect();
+    let end: Vec<char> = end.chars().flat_map(char::to_lowercase).collect();
+    if start.len() != end.len() {
+        return None;
+    }
+
+    let mut nodes: Vec<Vec<char>> = vec![start.clone(), end.clone()];
+    let mut ids: HashMap<Vec<char>, usize> = HashMap::from([(start, 0)]);
+    ids.entry(end).or_insert(1);
+    for word in words {
+        let word: Vec<char> = word.chars().flat_map(char::to_lowercase).collect();
+        if word.len() == nodes[0].len() && !ids.contains_key(&word) {
+            ids.insert(word.clone(), nodes.len());
+            nodes.push(word);
+        }
+    }
+    let target = ids[&nodes[1]];
+
+    // Words sharing a pattern with one letter blanked are one step apart.
+    let mut buckets: HashMap<(usize, Vec<char>), Vec<usize>> = HashMap::new();
+    for (id, word) in nodes.iter().enumerate() {
+        for i in 0..word.len() {
+            let mut pattern = word.clone();
+            pattern.remove(i);
+            buckets.entry((i, pattern)).or_default().push(id);
+        }
+    }
+
+    let mut previous: Vec<Option<usize>> = vec![None; nodes.len()];
+    let mut seen = vec![false; nodes.len()];
+    let mut queue = VecDeque::from([0]);
+    seen[0] = true;
+    while let Some(id) = queue.pop_front() {
+        if id == target {
+            let mut chain = vec![id];
+            while let Some(prev) = previous[*chain.last().expect("chain is never empty")] {
+                chain.push(prev);
+            }
+            return Some(chain.into_iter().rev().map(|id| nodes[id].iter().collect()).collect());
+        }
+        for i in 0..nodes[id].len() {
+            let mut pattern = nodes[id].clone();
+            pattern.remove(i);
+            for &next in &buckets[&(i, pattern)] {
+                if !seen[next] {
+                    seen[next] = true;
+                    previous[next] = Some(id);
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+    None
+}
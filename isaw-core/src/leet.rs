@@ -0,0 +1,98 @@
+//! Leetspeak variants: every way of swapping letters for look-alikes.
+//!
+//! A [`Leet`] table maps letters to their substitutes (`a` to `4` and `@`,
+//! ...). A word's variants are all combinations of keeping each letter or
+//! replacing it, so a word with k substitutable letters of two options each
+//! has 3^k variants, the word itself first. Letters match either case.
+
+use std::str::FromStr;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Leet {
+    /// Lowercase letter and its substitutes, sorted by letter.
+    table: Vec<(char, Vec<char>)>,
+}
+
+impl Default for Leet {
+    fn default() -> Self {
+        "a=4@,b=8,e=3,g=9,i=1!,l=1,o=0,s=5$,t=7".parse().expect("valid built-in table")
+    }
+}
+
+/// Parses `a=4@,e=3,...`: each letter, `=`, then the characters that may
+/// replace it.
+impl FromStr for Leet {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut table: Vec<(char, Vec<char>)> = Vec::new();
+        for entry in s.split(',').filter(|entry| !entry.is_empty()) {
+            let (letter, substitutes) = entry.split_once('=').ok_or_else(|| format!("expected letter=substitutes, got '{}'", entry))?;
+            let mut letters = letter.chars();
+            let letter = match (letters.next(), letters.next()) {
+                (Some(letter), None) => letter.to_lowercase().next().unwrap_or(letter),
+                _ => return Err(format!("'{}' is not a single letter", letter)),
+            };
+            match table.iter_mut().find(|(l, _)| *l == letter) {
+                Some((_, existing)) => existing.extend(substitutes.chars()),
+                None => table.push((letter, substitutes.chars().collect())),
+            }
+        }
+        table.sort_by_key(|(letter, _)| *letter);
+        Ok(Leet { table })
+    }
+}
+
+impl Leet {
+    fn substitutes(&self, c: char) -> &[char] {
+        let lower = c.to_lowercase().next().unwrap_or(c);
+        match self.table.binary_search_by_key(&lower, |(letter, _)| *letter) {
+            Ok(i) => &self.table[i].1,
+            Err(_) => &[],
+        }
+    }
+
+    /// `word` and all of its variants.
+    pub fn variants(&self, word: &str) -> Variants {
+        let options: Vec<Vec<char>> = word
+            .chars()
+            .map(|c| std::iter::once(c).chain(self.substitutes(c).iter().copied()).collect())
+            .collect();
+        Variants::new(options)
+    }
+}
+
+/// Odometer over one option per position, see [`Leet::variants`].
+pub struct Variants {
+    options: Vec<Vec<char>>,
+    digits: Vec<usize>,
+    done: bool,
+}
+
+impl Variants {
+    /// Every word taking one of `options[i]` at position `i`, first options first.
+    pub(crate) fn new(options: Vec<Vec<char>>) -> Self {
+        Variants { digits: vec![0; options.len()], options, done: false }
+    }
+}
+
+impl Iterator for Variants {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        if self.done {
+            return None;
+        }
+        let word = self.digits.iter().zip(&self.options).map(|(&d, options)| options[d]).collect();
+        self.done = true;
+        for (digit, options) in self.digits.iter_mut().zip(&self.options).rev() {
+            *digit += 1;
+            if *digit < options.len() {
+                self.done = false;
+                break;
+            }
+            *digit = 0;
+        }
+        Some(word)
+    }
+}
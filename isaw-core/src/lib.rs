@@ -0,0 +1,43 @@
+//! Generation and matching engine behind the `isaw` CLI.
+//!
+//! Everything here is plain `std`, so the crate builds for
+//! `wasm32-unknown-unknown` and can be embedded by other front ends.
+//! Threading and terminal output are left to the callers.
+
+pub mod aho_corasick;
+pub mod alphabet;
+pub mod bloom;
+pub mod case;
+pub mod cipher;
+pub mod count;
+pub mod debruijn;
+pub mod dictionary;
+pub mod dna;
+pub mod fill;
+pub mod generate;
+pub mod glob;
+pub mod hasher;
+pub mod ladder;
+pub mod leet;
+pub mod major;
+pub mod mask;
+pub mod matcher;
+pub mod memmem;
+pub mod morse;
+pub mod necklace;
+pub mod ngram;
+pub mod perm;
+pub mod plate;
+pub mod rack;
+pub mod rank;
+pub mod script;
+pub mod script_blocks;
+pub mod sha1;
+pub mod sha256;
+pub mod transform;
+pub mod trie;
+pub mod typo;
+pub mod vanity;
+pub mod wordsearch;
+
+pub use matcher::Matcher;
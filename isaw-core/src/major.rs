@@ -0,0 +1,50 @@
+//! The Major memory system: digits stand for consonant sounds, so a number
+//! is remembered as words whose consonants spell it.
+//!
+//! | 0 | 1 | 2 | 3 | 4 | 5 | 6 | 7 | 8 | 9 |
+//! |---|---|---|---|---|---|---|---|---|---|
+//! | s z | t d | n | m | r | l | j sh ch | k q hard c/g | f v | p b |
+//!
+//! Words are encoded from their spelling, which only approximates the
+//! sounds: doubled letters count once, `c` and `g` are soft before e, i and
+//! y, and vowels plus `h`, `w` and `y` carry no digit.
+
+/// The digits `word` encodes.
+pub fn encode(word: &str) -> String {
+    let letters: Vec<char> = word.chars().filter(char::is_ascii_alphabetic).map(|c| c.to_ascii_lowercase()).collect();
+    let mut digits = String::new();
+    let mut i = 0;
+    while i < letters.len() {
+        let c = letters[i];
+        let next = letters.get(i + 1).copied();
+        let soft = matches!(next, Some('e' | 'i' | 'y'));
+        let (code, width) = match (c, next) {
+            ('s' | 'c', Some('h')) => ("6", 2),
+            ('p', Some('h')) => ("8", 2),
+            ('t', Some('h')) => ("1", 2),
+            ('c', Some('k')) => ("7", 2),
+            ('g', Some('h')) => ("", 2),
+            ('s' | 'z', _) => ("0", 1),
+            ('c', _) if soft => ("0", 1),
+            ('t' | 'd', _) => ("1", 1),
+            ('n', _) => ("2", 1),
+            ('m', _) => ("3", 1),
+            ('r', _) => ("4", 1),
+            ('l', _) => ("5", 1),
+            ('j', _) => ("6", 1),
+            ('g', _) if soft => ("6", 1),
+            ('k' | 'q' | 'c' | 'g', _) => ("7", 1),
+            ('f' | 'v', _) => ("8", 1),
+            ('p' | 'b', _) => ("9", 1),
+            ('x', _) => ("70", 1),
+            _ => ("", 1),
+        };
+        digits.push_str(code);
+        i += width;
+        // A doubled consonant is one sound.
+        while i < letters.len() && letters[i] == letters[i - 1] && !code.is_empty() {
+            i += 1;
+        }
+    }
+    digits
+}
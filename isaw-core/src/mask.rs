@@ -0,0 +1,59 @@
+//! Hashcat-style masks: one character class per position.
+//!
+//! `?l` lowercase, `?u` uppercase, `?d` digits, `?s` symbols, `?a` all of
+//! those, `?h`/`?H` lower/upper hex digits, `??` a literal `?`; any other
+//! character stands for itself.
+//!
+//! [`template`] reads crunch-style templates instead, where the slots are
+//! single characters: `pass@@##` is `pass` followed by two lowercase letters
+//! and two digits.
+
+pub const LOWER: &str = "abcdefghijklmnopqrstuvwxyz";
+pub const UPPER: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+pub const DIGITS: &str = "0123456789";
+pub const SYMBOLS: &str = " !\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+/// The characters allowed at each position of `mask`.
+pub fn parse(mask: &str) -> Result<Vec<Vec<char>>, String> {
+    let mut positions = Vec::new();
+    let mut chars = mask.chars();
+    while let Some(c) = chars.next() {
+        if c != '?' {
+            positions.push(vec![c]);
+            continue;
+        }
+        let class: String = match chars.next() {
+            Some('l') => LOWER.into(),
+            Some('u') => UPPER.into(),
+            Some('d') => DIGITS.into(),
+            Some('s') => SYMBOLS.into(),
+            Some('a') => [LOWER, UPPER, DIGITS, SYMBOLS].concat(),
+            Some('h') => "0123456789abcdef".into(),
+            Some('H') => "0123456789ABCDEF".into(),
+            Some('?') => "?".into(),
+            Some(other) => return Err(format!("unknown mask class '?{}'", other)),
+            None => return Err("mask ends in a lone '?'".to_string()),
+        };
+        positions.push(class.chars().collect());
+    }
+    Ok(positions)
+}
+
+/// The characters allowed at each position of a crunch-style template:
+/// `@` lowercase, `,` uppercase, `#` digits, `^` symbols, and `\` makes the
+/// next character literal; anything else stands for itself.
+pub fn template(template: &str) -> Result<Vec<Vec<char>>, String> {
+    let mut positions = Vec::new();
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        positions.push(match c {
+            '@' => LOWER.chars().collect(),
+            ',' => UPPER.chars().collect(),
+            '#' => DIGITS.chars().collect(),
+            '^' => SYMBOLS.chars().collect(),
+            '\\' => vec![chars.next().ok_or("template ends in a lone '\\'")?],
+            literal => vec![literal],
+        });
+    }
+    Ok(positions)
+}
@@ -0,0 +1,344 @@
+//! Pattern matching applied to generated candidates.
+
+use crate::aho_corasick::AhoCorasick;
+use crate::dictionary::Dictionary;
+use crate::glob;
+use crate::hasher::{self, HashBackend};
+use crate::memmem::Finder;
+use crate::ngram::Model;
+use crate::script::Script;
+#[cfg(not(feature = "full-regex"))]
+use crate::script_blocks;
+use crate::vanity::Vanity;
+use std::ops::Range;
+
+/// The regex engine: regex-lite, or with the `full-regex` feature the
+/// regex crate with full Unicode classes, properties and case folding.
+#[cfg(not(feature = "full-regex"))]
+type Regex = regex_lite::Regex;
+#[cfg(feature = "full-regex")]
+type Regex = regex::Regex;
+use std::sync::Arc;
+
+pub enum Matcher {
+    /// Plain substring containment.
+    Substring {
+        pattern: String,
+        /// Byte searcher for the pattern; folds ASCII case itself when the
+        /// search is case-insensitive and the pattern is ASCII.
+        finder: Finder,
+        /// Lowercased pattern, compared against lowercased candidates when
+        /// only Unicode lowercasing can fold them.
+        folded: Option<String>,
+    },
+    /// Any of several substrings, found in one pass.
+    Multi {
+        /// Lowercased when the search is case-insensitive.
+        patterns: Vec<String>,
+        automaton: AhoCorasick,
+        ignore_case: bool,
+    },
+    /// Any of several patterns whose letters appear in order, not
+    /// necessarily next to each other: `cat` matches `crate`.
+    Subsequence {
+        patterns: Vec<String>,
+        ignore_case: bool,
+    },
+    Regex(Regex),
+    /// Any of several globs, each matching the whole candidate.
+    Glob(Vec<String>),
+    /// Any of several regexes, tried as one alternation; each is kept on
+    /// its own as well to tell which of them a result matches.
+    Regexes {
+        any: Regex,
+        each: Vec<(String, Regex)>,
+    },
+    /// Only words in at least one of several named dictionaries.
+    Dictionary(Vec<(String, Arc<Dictionary>)>),
+    /// Candidates whose English-likeness, [`Model::average`], is within
+    /// `min..=max`.
+    Score { model: Model, min: f64, max: f64 },
+    /// Candidates whose SHA-256 digest is one of `digests`, kept sorted,
+    /// hashed by `backend`.
+    Sha256 { digests: Vec<[u8; 32]>, backend: Arc<dyn HashBackend> },
+    /// Candidates a `--filter-script` expression keeps.
+    Script(Box<Script>),
+    /// Candidates completing a vanity hash.
+    Vanity(Vanity),
+    /// Every one of several matchers.
+    All(Vec<Matcher>),
+    /// Candidates the inner matcher rejects.
+    Not(Box<Matcher>),
+}
+
+impl Matcher {
+    pub fn substring(pattern: &str, ignore_case: bool) -> Self {
+        Matcher::Substring {
+            pattern: pattern.to_string(),
+            finder: Finder::new(pattern.as_bytes(), ignore_case && pattern.is_ascii()),
+            folded: ignore_case.then(|| pattern.to_lowercase()),
+        }
+    }
+    
+    /// Matches candidates containing any of `patterns`.
+    pub fn substrings(patterns: &[String], ignore_case: bool) -> Self {
+        if let [pattern] = patterns {
+            return Matcher::substring(pattern, ignore_case);
+        }
+        let patterns: Vec<String> = if ignore_case {
+            patterns.iter().map(|p| p.to_lowercase()).collect()
+        } else {
+            patterns.to_vec()
+        };
+        let automaton = AhoCorasick::new(&patterns, ignore_case);
+        Matcher::Multi { patterns, automaton, ignore_case }
+    }
+    
+    /// Matches candidates containing any of `patterns` as a subsequence.
+    pub fn subsequences(patterns: &[String], ignore_case: bool) -> Self {
+        let patterns = if ignore_case { patterns.iter().map(|p| p.to_lowercase()).collect() } else { patterns.to_vec() };
+        Matcher::Subsequence { patterns, ignore_case }
+    }
+    
+    /// Compiles `pattern`; without `full-regex`, `\p{Script}` classes are
+    /// expanded into block ranges beforehand.
+    pub fn regex(pattern: &str) -> Result<Self, String> {
+        compile(pattern, false).map(Matcher::Regex)
+    }
+    
+    /// Matches candidates matching any of `patterns`, with a single regex
+    /// pass per candidate.
+    pub fn regexes(patterns: &[String], ignore_case: bool) -> Result<Self, String> {
+        if let [pattern] = patterns {
+            return compile(pattern, ignore_case).map(Matcher::Regex);
+        }
+        let each = patterns.iter().map(|p| Ok((p.clone(), compile(p, ignore_case)?))).collect::<Result<Vec<_>, String>>()?;
+        let any = patterns.iter().map(|p| format!("(?:{})", p)).collect::<Vec<_>>().join("|");
+        Ok(Matcher::Regexes { any: compile(&any, ignore_case)?, each })
+    }
+    
+    /// Matches candidates hashing to any of `digests`, given in hex, on the
+    /// [`hasher::best`] backend; fails with the first one that isn't a
+    /// 64-digit hex SHA-256 digest.
+    pub fn sha256<S: AsRef<str>>(digests: &[S]) -> Result<Self, String> {
+        let mut parsed = Vec::with_capacity(digests.len());
+        for digest in digests {
+            let digest = digest.as_ref();
+            let bytes = (digest.len() == 64 && digest.is_ascii())
+                .then(|| (0..32).map(|i| u8::from_str_radix(&digest[2 * i..2 * i + 2], 16).ok()).collect::<Option<Vec<u8>>>())
+                .flatten()
+                .ok_or_else(|| digest.to_string())?;
+            parsed.push(bytes.try_into().expect("32 bytes"));
+        }
+        parsed.sort_unstable();
+        Ok(Matcher::Sha256 { digests: parsed, backend: hasher::best() })
+    }
+    
+    /// Requires `other` to match as well.
+    pub fn and(self, other: Matcher) -> Self {
+        match self {
+            Matcher::All(mut all) => {
+                all.push(other);
+                Matcher::All(all)
+            }
+            first => Matcher::All(vec![first, other]),
+        }
+    }
+    
+    /// Matches exactly what `self` doesn't.
+    pub fn negate(self) -> Self {
+        Matcher::Not(Box::new(self))
+    }
+    
+    pub fn is_match(&self, word: &str) -> bool {
+        match self {
+            Matcher::Substring { finder, folded: Some(folded), .. } if !in_place(finder, word) => {
+                word.to_lowercase().contains(folded.as_str())
+            }
+            Matcher::Substring { finder, .. } => finder.is_match(word.as_bytes()),
+            Matcher::Multi { automaton, ignore_case: true, .. } if !word.is_ascii() => {
+                automaton.is_match(word.to_lowercase().as_bytes())
+            }
+            Matcher::Multi { automaton, .. } => automaton.is_match(word.as_bytes()),
+            Matcher::Subsequence { patterns, ignore_case } => patterns.iter().any(|p| span(word, p, *ignore_case).is_some()),
+            Matcher::Regex(re) | Matcher::Regexes { any: re, .. } => re.is_match(word),
+            Matcher::Dictionary(dictionaries) => dictionaries.iter().any(|(_, d)| d.contains(word)),
+            Matcher::Glob(patterns) => patterns.iter().any(|p| glob::matches(p, word)),
+            Matcher::Score { model, min, max } => model.average(word).is_some_and(|score| (*min..=*max).contains(&score)),
+            Matcher::Sha256 { digests, backend } => digests.binary_search(&backend.sha256(word.as_bytes())).is_ok(),
+            Matcher::All(all) => all.iter().all(|m| m.is_match(word)),
+            Matcher::Vanity(vanity) => vanity.is_match(word),
+            Matcher::Script(script) => script.is_match(word),
+            Matcher::Not(inner) => !inner.is_match(word),
+        }
+    }
+    
+    /// Whether [`Matcher::retain`] does better than matching word by word,
+    /// because candidates are hashed in batches.
+    pub fn batches(&self) -> bool {
+        match self {
+            Matcher::Sha256 { .. } => true,
+            Matcher::All(all) => all.iter().any(Matcher::batches),
+            _ => false,
+        }
+    }
+    
+    /// Keeps the matching `words`, handing all of them to the hash backend
+    /// in one batch.
+    pub fn retain(&self, words: &mut Vec<String>) {
+        match self {
+            Matcher::Sha256 { digests, backend } => {
+                let mut hashed = Vec::with_capacity(words.len());
+                backend.sha256_batch(&words.iter().map(|word| word.as_bytes()).collect::<Vec<_>>(), &mut hashed);
+                let mut hashed = hashed.into_iter();
+                words.retain(|_| hashed.next().is_some_and(|digest| digests.binary_search(&digest).is_ok()));
+            }
+            Matcher::All(all) => all.iter().for_each(|m| m.retain(words)),
+            _ => words.retain(|word| self.is_match(word)),
+        }
+    }
+    
+    /// Byte range of the first match in `word`, for highlighting.
+    pub fn find(&self, word: &str) -> Option<Range<usize>> {
+        match self {
+            Matcher::Substring { pattern, finder, folded: Some(folded) } if !in_place(finder, word) => {
+                // Positions in the lowercased word only line up with the
+                // original when lowercasing kept the byte lengths.
+                let pos = word.to_lowercase().find(folded.as_str())?;
+                let range = pos..pos + pattern.len();
+                word.get(range.clone()).map(|_| range)
+            }
+            Matcher::Substring { pattern, finder, .. } => {
+                finder.find(word.as_bytes()).map(|pos| pos..pos + pattern.len())
+            }
+            Matcher::Multi { automaton, ignore_case: true, .. } if !word.is_ascii() => {
+                let (_, range) = automaton.find(word.to_lowercase().as_bytes())?;
+                word.get(range.clone()).map(|_| range)
+            }
+            Matcher::Multi { automaton, .. } => automaton.find(word.as_bytes()).map(|(_, range)| range),
+            // From the first letter used to the last.
+            Matcher::Subsequence { patterns, ignore_case } => patterns.iter().find_map(|p| span(word, p, *ignore_case)),
+            Matcher::Regex(re) | Matcher::Regexes { any: re, .. } => re.find(word).map(|m| m.range()),
+            // The whole word matches; nothing worth highlighting.
+            Matcher::Dictionary(_) | Matcher::Glob(_) | Matcher::Score { .. } | Matcher::Sha256 { .. } | Matcher::Vanity(_) | Matcher::Script(_) | Matcher::Not(_) => None,
+            Matcher::All(all) => all.iter().find_map(|m| m.find(word)),
+        }
+    }
+    
+    /// The patterns occurring in `word`, when there are several to tell apart.
+    pub fn hits(&self, word: &str) -> Option<Vec<&str>> {
+        if let Matcher::All(all) = self {
+            return all.iter().find_map(|m| m.hits(word));
+        }
+        if let Matcher::Regexes { each, .. } = self {
+            return Some(each.iter().filter(|(_, re)| re.is_match(word)).map(|(pattern, _)| pattern.as_str()).collect());
+        }
+        if let Matcher::Glob(patterns) = self {
+            return (patterns.len() > 1).then(|| patterns.iter().filter(|p| glob::matches(p, word)).map(String::as_str).collect());
+        }
+        if let Matcher::Subsequence { patterns, ignore_case } = self {
+            return (patterns.len() > 1).then(|| patterns.iter().filter(|p| span(word, p, *ignore_case).is_some()).map(String::as_str).collect());
+        }
+        let Matcher::Multi { patterns, automaton, ignore_case } = self else { return None };
+        let ids = if *ignore_case && !word.is_ascii() {
+            automaton.hits(word.to_lowercase().as_bytes())
+        } else {
+            automaton.hits(word.as_bytes())
+        };
+        Some(ids.into_iter().map(|id| patterns[id].as_str()).collect())
+    }
+    
+    /// Every pattern [`Matcher::hits`] tells apart, in order.
+    pub fn terms(&self) -> Option<Vec<&str>> {
+        match self {
+            Matcher::Multi { patterns, .. } => Some(patterns.iter().map(String::as_str).collect()),
+            Matcher::Subsequence { patterns, .. } | Matcher::Glob(patterns) if patterns.len() > 1 => Some(patterns.iter().map(String::as_str).collect()),
+            Matcher::Regexes { each, .. } => Some(each.iter().map(|(pattern, _)| pattern.as_str()).collect()),
+            Matcher::All(all) => all.iter().find_map(Matcher::terms),
+            _ => None,
+        }
+    }
+    
+    /// The hex digest a vanity search gives `word`.
+    pub fn digest(&self, word: &str) -> Option<String> {
+        match self {
+            Matcher::Vanity(vanity) => Some(vanity.hex_digest(word)),
+            Matcher::All(all) => all.iter().find_map(|m| m.digest(word)),
+            _ => None,
+        }
+    }
+    
+    /// The dictionaries containing `word`, when there are several to tell apart.
+    pub fn sources(&self, word: &str) -> Option<Vec<&str>> {
+        match self {
+            Matcher::Dictionary(dictionaries) if dictionaries.len() > 1 => {
+                Some(dictionaries.iter().filter(|(_, d)| d.contains(word)).map(|(name, _)| name.as_str()).collect())
+            }
+            Matcher::All(all) => all.iter().find_map(|m| m.sources(word)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(not(feature = "full-regex"))]
+fn compile(pattern: &str, ignore_case: bool) -> Result<Regex, String> {
+    regex_lite::RegexBuilder::new(&script_blocks::expand(pattern)?).case_insensitive(ignore_case).build().map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "full-regex")]
+fn compile(pattern: &str, ignore_case: bool) -> Result<Regex, String> {
+    regex::RegexBuilder::new(pattern).case_insensitive(ignore_case).build().map_err(|e| e.to_string())
+}
+
+/// Whether a case-insensitive search can fold `word` without lowercasing
+/// it into a new string.
+fn in_place(finder: &Finder, word: &str) -> bool {
+    finder.folds() && word.is_ascii()
+}
+
+/// The bytes of `word` from the first to the last letter of the earliest
+/// in-order occurrence of `pattern`'s letters (lowercase when `fold`).
+fn span(word: &str, pattern: &str, fold: bool) -> Option<Range<usize>> {
+    let mut wanted = pattern.chars().peekable();
+    let mut start = None;
+    for (i, c) in word.char_indices() {
+        let Some(&next) = wanted.peek() else { break };
+        let matches = if fold { c.to_lowercase().eq(std::iter::once(next)) } else { c == next };
+        if matches {
+            start.get_or_insert(i);
+            wanted.next();
+            if wanted.peek().is_none() {
+                return Some(start.unwrap_or(i)..i + c.len_utf8());
+            }
+        }
+    }
+    wanted.peek().is_none().then_some(0..0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script_classes_match_under_either_engine() {
+        let greek = Matcher::regex(r"^\p{Greek}+$").unwrap();
+        assert!(greek.is_match("αβγ"));
+        assert!(!greek.is_match("abc"));
+    }
+
+    #[cfg(feature = "full-regex")]
+    #[test]
+    fn full_regex_knows_every_property_and_folds_unicode_case() {
+        let letters = Matcher::regex(r"^\p{L}+$").unwrap();
+        assert!(letters.is_match("żółw"));
+        assert!(!letters.is_match("r2d2"));
+        assert!(Matcher::regex(r"^\p{Ethiopic}+$").unwrap().is_match("ሰላም"));
+        assert!(Matcher::regexes(&["^ŻÓŁW$".to_string()], true).unwrap().is_match("żółw"));
+    }
+
+    #[cfg(not(feature = "full-regex"))]
+    #[test]
+    fn lite_engine_rejects_properties_it_cannot_expand() {
+        assert!(Matcher::regex(r"^\p{L}+$").is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//! Substring search tuned for the short candidates isaw generates.
+//!
+//! `str::contains` sets up a Two-Way searcher on every call, which dominates
+//! when haystacks are a handful of bytes. [`Finder`] is built once per
+//! pattern and scans eight positions at a time with SWAR (SIMD within a
+//! register): a position is only verified when both the first and the last
+//! needle byte line up, which rules out nearly everything in one step.
+//!
+//! With `fold` set, ASCII letters are lowercased in-register while
+//! scanning, so case-insensitive matching needs no allocation. Callers must
+//! only use folding on ASCII haystacks.
+
+const LO: u64 = 0x0101_0101_0101_0101;
+const HI: u64 = 0x8080_8080_8080_8080;
+
+#[derive(Clone, Debug)]
+pub struct Finder {
+    needle: Vec<u8>,
+    fold: bool,
+}
+
+impl Finder {
+    /// With `fold`, the needle is matched ASCII-case-insensitively.
+    pub fn new(needle: &[u8], fold: bool) -> Self {
+        let needle = if fold { needle.to_ascii_lowercase() } else { needle.to_vec() };
+        Finder { needle, fold }
+    }
+
+    /// Whether ASCII case is folded while matching.
+    pub fn folds(&self) -> bool {
+        self.fold
+    }
+
+    pub fn is_match(&self, haystack: &[u8]) -> bool {
+        self.find(haystack).is_some()
+    }
+
+    /// Offset of the first occurrence of the needle.
+    pub fn find(&self, haystack: &[u8]) -> Option<usize> {
+        let m = self.needle.len();
+        if m == 0 {
+            return Some(0);
+        }
+        if m > haystack.len() {
+            return None;
+        }
+        let last = haystack.len() - m;
+        let (first_byte, last_byte) = (self.needle[0], self.needle[m - 1]);
+
+        let mut i = 0;
+        // Both 8-byte loads (at i and at i + m - 1) must stay in bounds.
+        while i + 8 <= last + 1 {
+            let a = self.load(&haystack[i..i + 8]);
+            let b = self.load(&haystack[i + m - 1..i + m + 7]);
+            let mut candidates = zero_bytes(a ^ splat(first_byte)) & zero_bytes(b ^ splat(last_byte));
+            while candidates != 0 {
+                let pos = i + (candidates.trailing_zeros() / 8) as usize;
+                if self.verify(&haystack[pos..pos + m]) {
+                    return Some(pos);
+                }
+                candidates &= candidates - 1;
+            }
+            i += 8;
+        }
+
+        (i..=last).find(|&pos| {
+            self.byte(haystack[pos]) == first_byte
+                && self.byte(haystack[pos + m - 1]) == last_byte
+                && self.verify(&haystack[pos..pos + m])
+        })
+    }
+
+    fn verify(&self, window: &[u8]) -> bool {
+        if self.fold {
+            window.eq_ignore_ascii_case(&self.needle)
+        } else {
+            window == self.needle.as_slice()
+        }
+    }
+
+    fn byte(&self, b: u8) -> u8 {
+        if self.fold { b.to_ascii_lowercase() } else { b }
+    }
+
+    fn load(&self, bytes: &[u8]) -> u64 {
+        let word = u64::from_le_bytes(bytes.try_into().expect("8-byte window"));
+        if self.fold { ascii_lowercase(word) } else { word }
+    }
+}
+
+fn splat(b: u8) -> u64 {
+    LO * b as u64
+}
+
+/// High bit set in every byte of `x` that is zero. Bytes above a true zero
+/// may also be flagged, which is harmless because hits are verified.
+fn zero_bytes(x: u64) -> u64 {
+    x.wrapping_sub(LO) & !x & HI
+}
+
+/// Lowercases the ASCII letters in all eight bytes at once.
+fn ascii_lowercase(x: u64) -> u64 {
+    let heptets = x & !HI;
+    let above_z = heptets + splat(0x80 - b'Z' - 1);
+    let from_a = heptets + splat(0x80 - b'A');
+    let upper = !x & (from_a ^ above_z) & HI;
+    x | (upper >> 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic strings over a few letters, so matches are common.
+    fn strings(seed: u64, count: usize, max_len: usize) -> Vec<Vec<u8>> {
+        let mut state = seed;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        (0..count).map(|_| (0..next() as usize % (max_len + 1)).map(|_| b"abAB"[next() as usize % 4]).collect()).collect()
+    }
+
+    fn naive(haystack: &[u8], needle: &[u8], fold: bool) -> Option<usize> {
+        let eq = |a: &[u8], b: &[u8]| if fold { a.eq_ignore_ascii_case(b) } else { a == b };
+        (0..=haystack.len().checked_sub(needle.len())?).find(|&i| eq(&haystack[i..i + needle.len()], needle))
+    }
+
+    #[test]
+    fn agrees_with_a_naive_search() {
+        let haystacks = strings(0x9e37_79b9_7f4a_7c15, 300, 40);
+        let needles = strings(0x2545_f491_4f6c_dd1d, 60, 6);
+        for fold in [false, true] {
+            for needle in &needles {
+                let finder = Finder::new(needle, fold);
+                for haystack in &haystacks {
+                    assert_eq!(finder.find(haystack), naive(haystack, needle, fold), "{:?} in {:?}", needle, haystack);
+                    assert_eq!(finder.is_match(haystack), naive(haystack, needle, fold).is_some());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn edges() {
+        assert_eq!(Finder::new(b"", false).find(b""), Some(0));
+        assert_eq!(Finder::new(b"abc", false).find(b"ab"), None);
+        // Matches at the very end, past the last full eight-byte window.
+        assert_eq!(Finder::new(b"xyz", false).find(b"aaaaaaaaaaxyz"), Some(10));
+        assert_eq!(Finder::new(b"XyZ", true).find(b"aaaaaaaaaaxYz"), Some(10));
+        assert!(Finder::new(b"q", true).folds());
+    }
+}
@@ -0,0 +1,73 @@
+//! International Morse code.
+//!
+//! Letters are separated by spaces and words by ` / `. Without the spaces a
+//! dot/dash string is ambiguous: `...` is `s`, `ie`, `ei` or `eee`.
+//! [`decodings`] enumerates every letter sequence that fits.
+
+/// Letters and digits with their codes.
+pub const CODES: [(char, &str); 36] = [
+    ('a', ".-"), ('b', "-..."), ('c', "-.-."), ('d', "-.."), ('e', "."), ('f', "..-."), ('g', "--."), ('h', "...."),
+    ('i', ".."), ('j', ".---"), ('k', "-.-"), ('l', ".-.."), ('m', "--"), ('n', "-."), ('o', "---"), ('p', ".--."),
+    ('q', "--.-"), ('r', ".-."), ('s', "..."), ('t', "-"), ('u', "..-"), ('v', "...-"), ('w', ".--"), ('x', "-..-"),
+    ('y', "-.--"), ('z', "--.."), ('0', "-----"), ('1', ".----"), ('2', "..---"), ('3', "...--"), ('4', "....-"),
+    ('5', "....."), ('6', "-...."), ('7', "--..."), ('8', "---.."), ('9', "----."),
+];
+
+/// Whether `text` looks like Morse rather than plain text.
+pub fn is_code(text: &str) -> bool {
+    text.chars().any(|c| c == '.' || c == '-') && text.chars().all(|c| matches!(c, '.' | '-' | ' ' | '/'))
+}
+
+/// Encodes letters and digits; fails with the first character that has no
+/// code.
+pub fn encode(text: &str) -> Result<String, char> {
+    let mut words = Vec::new();
+    for word in text.split_whitespace() {
+        let letters = word
+            .chars()
+            .map(|c| {
+                let lower = c.to_ascii_lowercase();
+                CODES.iter().find(|(letter, _)| *letter == lower).map(|(_, code)| *code).ok_or(c)
+            })
+            .collect::<Result<Vec<&str>, char>>()?;
+        words.push(letters.join(" "));
+    }
+    Ok(words.join(" / "))
+}
+
+/// Decodes spaced Morse; fails with the first code that isn't one.
+pub fn decode(code: &str) -> Result<String, String> {
+    let mut words = Vec::new();
+    for word in code.split('/') {
+        let letters = word
+            .split_whitespace()
+            .map(|symbol| CODES.iter().find(|(_, c)| *c == symbol).map(|(letter, _)| *letter).ok_or_else(|| symbol.to_string()))
+            .collect::<Result<String, String>>()?;
+        if !letters.is_empty() {
+            words.push(letters);
+        }
+    }
+    Ok(words.join(" "))
+}
+
+/// Calls `visit` with every string of letters (a-z) whose codes, run
+/// together, spell `code`, until it returns false.
+pub fn decodings(code: &str, mut visit: impl FnMut(&str) -> bool) {
+    fn walk(rest: &str, decoded: &mut String, visit: &mut dyn FnMut(&str) -> bool) -> bool {
+        if rest.is_empty() {
+            return visit(decoded);
+        }
+        for &(letter, symbol) in CODES.iter().filter(|(letter, _)| letter.is_ascii_lowercase()) {
+            if let Some(next) = rest.strip_prefix(symbol) {
+                decoded.push(letter);
+                let more = walk(next, decoded, visit);
+                decoded.pop();
+                if !more {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+    walk(code, &mut String::new(), &mut visit);
+}
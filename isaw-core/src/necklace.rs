@@ -0,0 +1,241 @@
+//! Necklaces and bracelets: one representative per class of candidates
+//! that are the same up to rotation (and, for bracelets, reflection).
+//!
+//! An arrangement is a string of beads read around a circle, so `abc`,
+//! `bca` and `cab` are one necklace. A selection of `k` out of `n` letters
+//! is a set of positions on an `n`-bead circle, a binary necklace with `k`
+//! black beads; that is how constant-weight cyclic codes are enumerated.
+//! The representative is the smallest member of its class, so filtering a
+//! lexicographic run keeps the first member of each class it meets.
+//!
+//! The counts use Burnside's lemma. Arrangements may repeat letters;
+//! selections are sets of positions, so their letters must be distinct.
+
+use crate::count;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Symmetry {
+    /// Necklaces: equal up to rotation.
+    Rotation,
+    /// Bracelets: equal up to rotation or reflection.
+    Dihedral,
+}
+
+/// Accepts `rotation` and `rotation+reflection`.
+impl FromStr for Symmetry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "rotation" => Ok(Symmetry::Rotation),
+            "rotation+reflection" => Ok(Symmetry::Dihedral),
+            _ => Err(format!("unknown symmetry '{}' (expected rotation or rotation+reflection)", s)),
+        }
+    }
+}
+
+/// Whether `word` is the smallest of its rotations (and their reversals).
+pub fn is_canonical<T: Ord>(word: &[T], symmetry: Symmetry) -> bool {
+    let k = word.len();
+    let rotated = |r: usize, reflect: bool| {
+        (0..k).map(move |i| {
+            let j = (i + r) % k;
+            &word[if reflect { k - 1 - j } else { j }]
+        })
+    };
+    (0..k).all(|r| word.iter().le(rotated(r, false)) && (symmetry == Symmetry::Rotation || word.iter().le(rotated(r, true))))
+}
+
+/// Whether the ascending `positions` out of `n` are the smallest of their
+/// images under rotating (and reflecting) the circle.
+pub fn is_canonical_subset(positions: &[usize], n: usize, symmetry: Symmetry) -> bool {
+    let mut image = Vec::with_capacity(positions.len());
+    for r in 0..n {
+        for reflect in [false, true] {
+            if reflect && symmetry == Symmetry::Rotation {
+                continue;
+            }
+            image.clear();
+            image.extend(positions.iter().map(|&i| if reflect { (r + n - i) % n } else { (i + r) % n }));
+            image.sort_unstable();
+            if image < positions.to_vec() {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// Necklaces (or bracelets) of length `k` over letters that may each be
+/// used `multiplicities[i]` times (all 1 for distinct letters), or `None`
+/// on overflow. A string fixed by a rotation repeats a block, and one fixed
+/// by a reflection mirrors pairs of beads, so each count only has to share
+/// out what the multiplicities allow among the free beads.
+pub fn arrangements(multiplicities: &[usize], k: usize, symmetry: Symmetry) -> Option<u128> {
+    if k == 0 {
+        return Some(1);
+    }
+    let shared = |share: &dyn Fn(usize, usize) -> usize, len: usize| capped_strings(multiplicities.iter().enumerate().map(|(i, &m)| share(i, m)), len);
+    // Rotation by r fixes the strings made of k / gcd(k, r) copies of a block.
+    let mut fixed = 0u128;
+    for r in 0..k {
+        let block = gcd(k, r);
+        let copies = k / block;
+        fixed = fixed.checked_add(shared(&|_, m| m / copies, block)?)?;
+    }
+    if symmetry == Symmetry::Rotation {
+        return Some(fixed / k as u128);
+    }
+    let letters = multiplicities.len();
+    let fixed_by_reflections = if k % 2 == 1 {
+        // Every axis passes through one bead, which takes any letter.
+        let mut onaxis = 0u128;
+        for c in (0..letters).filter(|&c| multiplicities[c] >= 1) {
+            onaxis = onaxis.checked_add(shared(&|i, m| (m - (i == c) as usize) / 2, k / 2)?)?;
+        }
+        (k as u128).checked_mul(onaxis)?
+    } else {
+        // Through two beads, each taking any letter still available.
+        let mut vertices = 0u128;
+        for a in 0..letters {
+            for b in 0..letters {
+                let used = |i: usize| (i == a) as usize + (i == b) as usize;
+                if used(a) > multiplicities[a] || used(b) > multiplicities[b] {
+                    continue;
+                }
+                vertices = vertices.checked_add(shared(&|i, m| (m - used(i)) / 2, k / 2 - 1)?)?;
+            }
+        }
+        // Between beads: only pairs.
+        let edges = shared(&|_, m| m / 2, k / 2)?;
+        (k as u128 / 2).checked_mul(vertices.checked_add(edges)?)?
+    };
+    Some(fixed.checked_add(fixed_by_reflections)? / (2 * k as u128))
+}
+
+/// Strings of length `len` using letter `i` at most `caps[i]` times.
+fn capped_strings(caps: impl Iterator<Item = usize>, len: usize) -> Option<u128> {
+    // ways[j]: strings of length j over the letters seen so far.
+    let mut ways = vec![0u128; len + 1];
+    ways[0] = 1;
+    for cap in caps {
+        for j in (1..=len).rev() {
+            let mut total = ways[j];
+            for t in 1..=cap.min(j) {
+                let placed = count::checked_binomial(j as u128, t as u128)?.checked_mul(ways[j - t])?;
+                total = total.checked_add(placed)?;
+            }
+            ways[j] = total;
+        }
+    }
+    Some(ways[len])
+}
+
+/// Binary necklaces (or bracelets) of `n` beads with `k` black, or `None`
+/// on overflow.
+pub fn subsets(n: usize, k: usize, symmetry: Symmetry) -> Option<u128> {
+    if k > n {
+        return Some(0);
+    }
+    if n == 0 {
+        return Some(1);
+    }
+    let binomial = |n: usize, k: usize| count::checked_binomial(n as u128, k as u128);
+    // Rotation by r fixes a set iff it repeats every gcd(n, r) beads.
+    let mut fixed_by_rotations = 0u128;
+    for r in 0..n {
+        let period = gcd(n, r);
+        if (k * period).is_multiple_of(n) {
+            fixed_by_rotations = fixed_by_rotations.checked_add(binomial(period, k * period / n)?)?;
+        }
+    }
+    if symmetry == Symmetry::Rotation {
+        return Some(fixed_by_rotations / n as u128);
+    }
+    // Sets fixed by each kind of reflection: beads on the axis are free,
+    // the others come in mirrored pairs.
+    let half = |pairs: usize, k: usize| if k.is_multiple_of(2) { binomial(pairs, k / 2) } else { Some(0) };
+    let fixed_by_reflections = if n % 2 == 1 {
+        // Every axis passes through one bead.
+        (n as u128).checked_mul(binomial((n - 1) / 2, k / 2)?)?
+    } else {
+        let pairs = (n - 2) / 2;
+        // Through two beads: none, one or both of them black.
+        let vertices = half(pairs, k)? + if k >= 1 { 2 * half(pairs, k - 1)? } else { 0 } + if k >= 2 { half(pairs, k - 2)? } else { 0 };
+        // Between beads: only pairs.
+        let edges = half(n / 2, k)?;
+        (n as u128 / 2).checked_mul(vertices.checked_add(edges)?)?
+    };
+    Some(fixed_by_rotations.checked_add(fixed_by_reflections)? / (2 * n as u128))
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    /// Counts canonical strings by brute force over every distinct arrangement.
+    fn brute_force(letters: &str, k: usize, symmetry: Symmetry) -> u128 {
+        let chars: Vec<char> = letters.chars().collect();
+        let mut seen = HashSet::new();
+        let mut stack = vec![(Vec::new(), vec![false; chars.len()])];
+        while let Some((word, used)) = stack.pop() {
+            if word.len() == k {
+                if is_canonical(&word, symmetry) {
+                    seen.insert(word);
+                }
+                continue;
+            }
+            for i in (0..chars.len()).filter(|&i| !used[i]) {
+                let (mut word, mut used) = (word.clone(), used.clone());
+                word.push(chars[i]);
+                used[i] = true;
+                stack.push((word, used));
+            }
+        }
+        seen.len() as u128
+    }
+
+    fn multiplicities(letters: &str) -> Vec<usize> {
+        let mut counts: Vec<(char, usize)> = Vec::new();
+        for c in letters.chars() {
+            match counts.iter_mut().find(|(seen, _)| *seen == c) {
+                Some((_, n)) => *n += 1,
+                None => counts.push((c, 1)),
+            }
+        }
+        counts.into_iter().map(|(_, n)| n).collect()
+    }
+
+    #[test]
+    fn repeated_letters_are_counted_once_per_class() {
+        assert_eq!(arrangements(&[2, 2], 4, Symmetry::Rotation), Some(2));
+        assert_eq!(arrangements(&[2, 2], 4, Symmetry::Dihedral), Some(2));
+        for letters in ["aabb", "aaab", "aabbc", "abcde", "aaabbc", "aabbcc"] {
+            for k in 0..=letters.len() {
+                for symmetry in [Symmetry::Rotation, Symmetry::Dihedral] {
+                    assert_eq!(arrangements(&multiplicities(letters), k, symmetry), Some(brute_force(letters, k, symmetry)), "{} k={} {:?}", letters, k, symmetry);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn distinct_letters_match_the_closed_form() {
+        // P(n, k) / k necklaces, and half as many bracelets from k = 3.
+        assert_eq!(arrangements(&[1; 6], 4, Symmetry::Rotation), Some(360 / 4));
+        assert_eq!(arrangements(&[1; 6], 4, Symmetry::Dihedral), Some(360 / 8));
+        assert_eq!(arrangements(&[1; 26], 26, Symmetry::Rotation), count::checked_permutation(26, 26).map(|p| p / 26));
+    }
+
+    #[test]
+    fn binary_necklaces_with_k_black_beads() {
+        assert_eq!(subsets(6, 3, Symmetry::Rotation), Some(4));
+        assert_eq!(subsets(6, 3, Symmetry::Dihedral), Some(3));
+    }
+}
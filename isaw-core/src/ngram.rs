@@ -0,0 +1,236 @@
+//! Letter n-gram statistics for telling plaintext from gibberish.
+//!
+//! A [`Model`] scores text by the summed log10 probability of its n-grams,
+//! counting ASCII letters only and ignoring case. Higher (closer to zero)
+//! is more English-like. Models load from the common `GRAM COUNT` format,
+//! one n-gram per line. [`Model::english`] is single-letter frequencies;
+//! [`Model::english_quadgrams`] is a built-in quadgram table for telling
+//! real text from shuffles of it, which letter frequencies alone cannot.
+//!
+//! [`Tables`] hold the counts of every n-gram size at once, built from a
+//! corpus by `isaw analyze` and saved in a small binary format, so one file
+//! can supply letter weights and models of any order.
+
+/// Relative frequency of A..Z in English text, in percent.
+pub const ENGLISH: [f64; 26] = [
+    8.167, 1.492, 2.782, 4.253, 12.702, 2.228, 2.015, 6.094, 6.966, 0.153, 0.772, 4.025, 2.406, 6.749, 7.507, 1.929, 0.095, 5.987,
+    6.327, 9.056, 2.758, 0.978, 2.360, 0.150, 1.974, 0.074,
+];
+
+/// Dense tables stop being small past this.
+const MAX_N: usize = 4;
+
+#[derive(Clone, Debug)]
+pub struct Model {
+    n: usize,
+    /// log10 probability per n-gram, indexed in base 26.
+    log_probs: Vec<f64>,
+}
+
+impl Model {
+    /// Single-letter English frequencies.
+    pub fn english() -> Self {
+        let total: f64 = ENGLISH.iter().sum();
+        Model { n: 1, log_probs: ENGLISH.iter().map(|f| (f / total).log10()).collect() }
+    }
+
+    /// Quadgrams from about 190,000 letters of public-domain English prose
+    /// and verse (`data/english-quadgrams.txt`).
+    pub fn english_quadgrams() -> Self {
+        Model::parse(include_str!("../data/english-quadgrams.txt")).expect("the built-in quadgrams parse")
+    }
+
+    /// Parses `GRAM COUNT` lines; every gram must have the same length.
+    /// N-grams that never appear score as a tenth of a single occurrence.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut n = 0;
+        let mut counts = Vec::new();
+        let mut total = 0.0;
+        for (line_no, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fail = || format!("line {}: expected `GRAM COUNT`", line_no + 1);
+            let (gram, count) = line.split_once(char::is_whitespace).ok_or_else(fail)?;
+            let count: f64 = count.trim().parse().map_err(|_| fail())?;
+            if n == 0 {
+                if gram.is_empty() || gram.len() > MAX_N {
+                    return Err(format!("line {}: n-grams must be 1 to {} letters", line_no + 1, MAX_N));
+                }
+                n = gram.len();
+                counts = vec![0.0; 26usize.pow(n as u32)];
+            }
+            let index = if gram.len() == n { index(gram.bytes()) } else { None };
+            let index = index.ok_or_else(|| format!("line {}: '{}' is not a {}-letter n-gram", line_no + 1, gram, n))?;
+            counts[index] += count;
+            total += count;
+        }
+        Model::from_table(n, counts, total)
+    }
+
+    /// A model from `n`-letter counts, skipping grams that aren't all
+    /// ASCII letters.
+    pub fn from_counts(n: usize, grams: &[(String, u64)]) -> Result<Self, String> {
+        if n == 0 || n > MAX_N {
+            return Err(format!("n-grams must be 1 to {} letters", MAX_N));
+        }
+        let mut counts = vec![0.0; 26usize.pow(n as u32)];
+        let mut total = 0.0;
+        for (gram, count) in grams {
+            if let Some(index) = index(gram.bytes()).filter(|_| gram.len() == n) {
+                counts[index] += *count as f64;
+                total += *count as f64;
+            }
+        }
+        Model::from_table(n, counts, total)
+    }
+
+    fn from_table(n: usize, counts: Vec<f64>, total: f64) -> Result<Self, String> {
+        if total <= 0.0 {
+            return Err("no n-grams found".to_string());
+        }
+        let floor = (0.1 / total).log10();
+        let log_probs = counts.into_iter().map(|count| if count > 0.0 { (count / total).log10() } else { floor }).collect();
+        Ok(Model { n, log_probs })
+    }
+
+    /// Letters per n-gram.
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    pub fn score(&self, text: &str) -> f64 {
+        let letters: Vec<u8> = text.bytes().filter(u8::is_ascii_alphabetic).map(|b| b.to_ascii_uppercase() - b'A').collect();
+        self.score_letters(&letters)
+    }
+
+    /// [`Model::score`] per n-gram, so texts of different lengths compare;
+    /// `None` when there are too few letters to judge.
+    pub fn average(&self, text: &str) -> Option<f64> {
+        let grams = (text.bytes().filter(u8::is_ascii_alphabetic).count() + 1).saturating_sub(self.n);
+        (grams > 0).then(|| self.score(text) / grams as f64)
+    }
+
+    /// Scores letters already reduced to 0..26, for callers rescoring the
+    /// same text under many keys.
+    pub fn score_letters(&self, letters: &[u8]) -> f64 {
+        letters
+            .windows(self.n)
+            .map(|gram| self.log_probs[gram.iter().fold(0, |i, &b| i * 26 + b as usize)])
+            .sum()
+    }
+}
+
+const TABLES_MAGIC: &[u8] = b"ISAWNGR1";
+
+/// Counts of every n-gram of 1 to 4 letters in a corpus.
+pub struct Tables {
+    /// Per size, most frequent first, as [`counts`] returns them.
+    grams: Vec<Vec<(String, u64)>>,
+}
+
+impl Tables {
+    pub fn from_text(text: &str) -> Self {
+        Tables { grams: (1..=MAX_N).map(|n| counts(text, n)).collect() }
+    }
+
+    /// Whether `bytes` look like [`Tables::to_bytes`] output.
+    pub fn recognize(bytes: &[u8]) -> bool {
+        bytes.starts_with(TABLES_MAGIC)
+    }
+
+    /// The counts of `n`-letter grams; empty beyond 4.
+    pub fn counts(&self, n: usize) -> &[(String, u64)] {
+        n.checked_sub(1).and_then(|i| self.grams.get(i)).map_or(&[], Vec::as_slice)
+    }
+
+    /// The highest-order model the tables support.
+    pub fn model(&self) -> Result<Model, String> {
+        (1..=MAX_N).rev().find_map(|n| Model::from_counts(n, self.counts(n)).ok()).ok_or_else(|| "no letter n-grams in the tables".to_string())
+    }
+
+    /// A magic header, then per size a `u32` entry count and entries of a
+    /// length byte, the gram's UTF-8 and a `u64` count, all little-endian.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = TABLES_MAGIC.to_vec();
+        for grams in &self.grams {
+            bytes.extend_from_slice(&(grams.len() as u32).to_le_bytes());
+            for (gram, count) in grams {
+                bytes.push(gram.len() as u8);
+                bytes.extend_from_slice(gram.as_bytes());
+                bytes.extend_from_slice(&count.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut rest = bytes.strip_prefix(TABLES_MAGIC).ok_or("not an n-gram tables file")?;
+        let mut take = |len: usize| -> Result<&[u8], String> {
+            let (taken, left) = rest.split_at_checked(len).ok_or("truncated n-gram tables")?;
+            rest = left;
+            Ok(taken)
+        };
+        let mut grams = Vec::with_capacity(MAX_N);
+        for _ in 0..MAX_N {
+            let entries = u32::from_le_bytes(take(4)?.try_into().expect("4 bytes"));
+            let mut table = Vec::with_capacity(entries.min(1 << 20) as usize);
+            for _ in 0..entries {
+                let len = take(1)?[0] as usize;
+                let gram = std::str::from_utf8(take(len)?).map_err(|_| "n-gram tables hold invalid UTF-8")?.to_string();
+                let count = u64::from_le_bytes(take(8)?.try_into().expect("8 bytes"));
+                table.push((gram, count));
+            }
+            grams.push(table);
+        }
+        Ok(Tables { grams })
+    }
+}
+
+/// How often each n-gram of letters occurs in `text`, most frequent first
+/// (ties alphabetically). Letters are lowercased and everything else is
+/// skipped, as when scoring.
+pub fn counts(text: &str, n: usize) -> Vec<(String, u64)> {
+    let letters: Vec<char> = text.chars().filter(|c| c.is_alphabetic()).flat_map(char::to_lowercase).collect();
+    let mut counts: std::collections::HashMap<&[char], u64> = std::collections::HashMap::new();
+    if n > 0 {
+        for gram in letters.windows(n) {
+            *counts.entry(gram).or_default() += 1;
+        }
+    }
+    let mut counts: Vec<(String, u64)> = counts.into_iter().map(|(gram, count)| (gram.iter().collect(), count)).collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+/// Pearson's chi-squared distance of a letter distribution (letters as
+/// 0..26) from [`ENGLISH`]; lower is closer.
+pub fn chi_squared(letters: &[u8]) -> f64 {
+    let mut counts = [0u32; 26];
+    for &l in letters {
+        counts[l as usize] += 1;
+    }
+    let total = letters.len() as f64;
+    let sum: f64 = ENGLISH.iter().sum();
+    counts
+        .iter()
+        .zip(ENGLISH)
+        .map(|(&count, frequency)| {
+            let expected = total * frequency / sum;
+            (count as f64 - expected).powi(2) / expected
+        })
+        .sum()
+}
+
+/// The base-26 table index of an all-letter n-gram.
+fn index(gram: impl Iterator<Item = u8>) -> Option<usize> {
+    let mut i = 0;
+    for b in gram {
+        if !b.is_ascii_alphabetic() {
+            return None;
+        }
+        i = i * 26 + (b.to_ascii_uppercase() - b'A') as usize;
+    }
+    Some(i)
+}
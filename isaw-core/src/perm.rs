@@ -0,0 +1,164 @@
+//! Permutation algebra over letters.
+//!
+//! A word is read as a permutation of a reference ordering of the same
+//! letters: `bca` against `abc` sends a→b, b→c and c→a, which is the cycle
+//! `(a b c)`. The reference defaults to the word's letters sorted.
+
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Perm {
+    /// The reference ordering.
+    letters: Vec<char>,
+    /// `image[i] = j` when `letters[i]` is sent to `letters[j]`.
+    image: Vec<usize>,
+}
+
+impl Perm {
+    /// `word` as a permutation of `reference`, or of its own sorted letters.
+    pub fn new(word: &str, reference: Option<&str>) -> Result<Self, String> {
+        let letters = reference_letters(word, reference)?;
+        let mut image = Vec::with_capacity(letters.len());
+        for c in word.chars() {
+            let j = letters.iter().position(|&l| l == c).ok_or_else(|| format!("'{}' is not in the reference '{}'", c, String::from_iter(&letters)))?;
+            image.push(j);
+        }
+        if image.len() != letters.len() {
+            return Err(format!("'{}' must use each of '{}' exactly once", word, String::from_iter(&letters)));
+        }
+        let mut seen = vec![false; letters.len()];
+        for &j in &image {
+            if std::mem::replace(&mut seen[j], true) {
+                return Err(format!("'{}' uses '{}' twice", word, letters[j]));
+            }
+        }
+        Ok(Perm { letters, image })
+    }
+
+    /// Parses cycle notation such as `(a c)(b d e)`; letters of the
+    /// reference that appear in no cycle stay fixed.
+    pub fn from_cycles(cycles: &str, reference: Option<&str>) -> Result<Self, String> {
+        let written: String = cycles.chars().filter(|c| !matches!(c, '(' | ')' | ',') && !c.is_whitespace()).collect();
+        let letters = reference_letters(&written, reference)?;
+        let mut image: Vec<usize> = (0..letters.len()).collect();
+        let mut seen = vec![false; letters.len()];
+        let mut rest = cycles.trim();
+        while !rest.is_empty() {
+            let body = rest.strip_prefix('(').and_then(|r| r.split_once(')')).ok_or_else(|| format!("expected '(...)' cycles, got '{}'", rest))?;
+            let (cycle, after) = body;
+            let members = cycle
+                .chars()
+                .filter(|c| *c != ',' && !c.is_whitespace())
+                .map(|c| {
+                    let i = letters.iter().position(|&l| l == c).ok_or_else(|| format!("'{}' is not in the reference '{}'", c, String::from_iter(&letters)))?;
+                    if std::mem::replace(&mut seen[i], true) {
+                        return Err(format!("'{}' appears in more than one place", c));
+                    }
+                    Ok(i)
+                })
+                .collect::<Result<Vec<usize>, String>>()?;
+            for (k, &i) in members.iter().enumerate() {
+                image[i] = members[(k + 1) % members.len()];
+            }
+            rest = after.trim_start();
+        }
+        Ok(Perm { letters, image })
+    }
+
+    /// The reference letters rearranged by the permutation.
+    pub fn word(&self) -> String {
+        self.image.iter().map(|&j| self.letters[j]).collect()
+    }
+
+    pub fn reference(&self) -> String {
+        self.letters.iter().collect()
+    }
+
+    pub fn inverse(&self) -> Self {
+        let mut image = vec![0; self.image.len()];
+        for (i, &j) in self.image.iter().enumerate() {
+            image[j] = i;
+        }
+        Perm { letters: self.letters.clone(), image }
+    }
+
+    /// `self` followed by `other`; both must share a reference.
+    pub fn then(&self, other: &Perm) -> Result<Self, String> {
+        if self.letters != other.letters {
+            return Err(format!("references differ: '{}' vs '{}'", self.reference(), other.reference()));
+        }
+        let image = self.image.iter().map(|&j| other.image[j]).collect();
+        Ok(Perm { letters: self.letters.clone(), image })
+    }
+
+    /// Disjoint cycles of two or more letters, each starting from its
+    /// earliest reference letter.
+    pub fn cycles(&self) -> Vec<Vec<char>> {
+        let mut seen = vec![false; self.image.len()];
+        let mut cycles = Vec::new();
+        for start in 0..self.image.len() {
+            let mut cycle = Vec::new();
+            let mut i = start;
+            while !seen[i] {
+                seen[i] = true;
+                cycle.push(self.letters[i]);
+                i = self.image[i];
+            }
+            if cycle.len() > 1 {
+                cycles.push(cycle);
+            }
+        }
+        cycles
+    }
+
+    /// How many times the permutation must be applied to get back to the
+    /// start, or `None` if that overflows.
+    pub fn order(&self) -> Option<u128> {
+        self.cycles().iter().try_fold(1u128, |order, cycle| {
+            let n = cycle.len() as u128;
+            (order / gcd(order, n)).checked_mul(n)
+        })
+    }
+
+    /// Whether the permutation is a product of an even number of swaps.
+    pub fn is_even(&self) -> bool {
+        self.cycles().iter().map(|cycle| cycle.len() - 1).sum::<usize>() % 2 == 0
+    }
+}
+
+/// Cycle notation; the identity is `()`.
+impl fmt::Display for Perm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cycles = self.cycles();
+        if cycles.is_empty() {
+            return write!(f, "()");
+        }
+        for cycle in cycles {
+            let letters: Vec<String> = cycle.iter().map(char::to_string).collect();
+            write!(f, "({})", letters.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+fn reference_letters(word: &str, reference: Option<&str>) -> Result<Vec<char>, String> {
+    let letters: Vec<char> = match reference {
+        Some(reference) => reference.chars().collect(),
+        None => {
+            let mut letters: Vec<char> = word.chars().collect();
+            letters.sort_unstable();
+            letters.dedup();
+            letters
+        }
+    };
+    let mut sorted = letters.clone();
+    sorted.sort_unstable();
+    if let Some(pair) = sorted.windows(2).find(|pair| pair[0] == pair[1]) {
+        return Err(format!("the reference repeats '{}'", pair[0]));
+    }
+    Ok(letters)
+}
+
+fn gcd(a: u128, b: u128) -> u128 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
@@ -0,0 +1,59 @@
+//! Licence-plate style formats: `L` a letter, `D` a digit, `A` either, `\`
+//! makes the next character literal, and anything else (dashes, spaces,
+//! fixed letters after `\`) stands for itself.
+//!
+//! The regional presets give the layout and the letters a country issues.
+//! Finer issuing rules, such as reserved prefixes or age identifiers, are
+//! not modelled, so a preset covers every real plate and some that will
+//! never be issued.
+
+use crate::mask::{DIGITS, UPPER};
+
+/// Every region as `(name, format, letters)`.
+pub const REGIONS: [(&str, &str, &str); 5] = [
+    // No I, Q or Z.
+    ("uk", "LLDD LLL", "ABCDEFGHJKLMNOPRSTUVWXY"),
+    // No I, O or U.
+    ("fr", "LL-DDD-LL", "ABCDEFGHJKLMNPQRSTVWXYZ"),
+    // No I, O, Q or U.
+    ("it", "LL DDDLL", "ABCDEFGHJKLMNPRSTVWXYZ"),
+    // Consonants only, without Ñ or Q.
+    ("es", "DDDD LLL", "BCDFGHJKLMNPRSTVWXYZ"),
+    ("us-ca", "DLLLDDD", UPPER),
+];
+
+/// The region names, for argument parsers.
+pub const NAMES: [&str; 5] = {
+    let mut names = [""; 5];
+    let mut i = 0;
+    while i < REGIONS.len() {
+        names[i] = REGIONS[i].0;
+        i += 1;
+    }
+    names
+};
+
+/// The format and letters of the region called `name`.
+pub fn region(name: &str) -> Option<(&'static str, &'static str)> {
+    REGIONS.iter().find(|(n, _, _)| *n == name).map(|&(_, format, letters)| (format, letters))
+}
+
+/// The characters allowed at each position of `format`, with `L` and `A`
+/// drawing on `letters`.
+pub fn positions(format: &str, letters: &str) -> Result<Vec<Vec<char>>, String> {
+    let mut positions = Vec::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        positions.push(match c {
+            'L' => letters.chars().collect(),
+            'D' => DIGITS.chars().collect(),
+            'A' => letters.chars().chain(DIGITS.chars()).collect(),
+            '\\' => vec![chars.next().ok_or("format ends in a lone '\\'")?],
+            literal => vec![literal],
+        });
+    }
+    if positions.iter().all(|chars| chars.len() < 2) {
+        return Err(format!("'{}' has no L, D or A positions", format));
+    }
+    Ok(positions)
+}
@@ -0,0 +1,87 @@
+//! A rack of letters, as in Scrabble: which words can be spelled from it.
+
+/// A multiset of letters; case is ignored.
+#[derive(Clone, Debug)]
+pub struct Rack {
+    letters: Vec<char>,
+    counts: Vec<u32>,
+    size: usize,
+}
+
+impl Rack {
+    pub fn new(letters: &str) -> Self {
+        let mut rack = Rack { letters: Vec::new(), counts: Vec::new(), size: 0 };
+        for c in letters.chars().flat_map(char::to_lowercase) {
+            match rack.letters.iter().position(|&l| l == c) {
+                Some(i) => rack.counts[i] += 1,
+                None => {
+                    rack.letters.push(c);
+                    rack.counts.push(1);
+                }
+            }
+            rack.size += 1;
+        }
+        rack
+    }
+
+    /// Number of letters on the rack.
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Whether `word` uses only letters on the rack, each at most as often
+    /// as it appears there.
+    pub fn can_spell(&self, word: &str) -> bool {
+        if word.len() > self.size * 4 {
+            return false;
+        }
+        let mut left = self.counts.clone();
+        word.chars().flat_map(char::to_lowercase).all(|c| match self.letters.iter().position(|&l| l == c) {
+            Some(i) if left[i] > 0 => {
+                left[i] -= 1;
+                true
+            }
+            _ => false,
+        })
+    }
+
+    /// The rack left after spelling `word`, if it can be spelled.
+    pub fn without(&self, word: &str) -> Option<Rack> {
+        let mut rest = self.clone();
+        for c in word.chars().flat_map(char::to_lowercase) {
+            let i = rest.letters.iter().position(|&l| l == c)?;
+            rest.counts[i] = rest.counts[i].checked_sub(1)?;
+            rest.size -= 1;
+        }
+        Some(rest)
+    }
+
+    /// How often `c` is on the rack.
+    pub fn count(&self, c: char) -> u32 {
+        self.letters.iter().position(|&l| l == c).map_or(0, |i| self.counts[i])
+    }
+
+    /// Every letter whose count differs between the racks, in order, with
+    /// its count on `self` and on `other`. Empty when they are anagrams.
+    pub fn difference(&self, other: &Rack) -> Vec<(char, u32, u32)> {
+        let mut letters: Vec<char> = self.letters.iter().chain(&other.letters).copied().collect();
+        letters.sort_unstable();
+        letters.dedup();
+        letters
+            .into_iter()
+            .map(|c| (c, self.count(c), other.count(c)))
+            .filter(|(_, mine, theirs)| mine != theirs)
+            .collect()
+    }
+}
+
+/// The sorted lowercase letters of `word`, shared by all of its anagrams.
+pub fn signature(word: &str) -> String {
+    let mut letters: Vec<char> = word.chars().flat_map(char::to_lowercase).collect();
+    letters.sort_unstable();
+    letters.into_iter().collect()
+}
@@ -0,0 +1,273 @@
+//! Rank arithmetic: jumping straight to the candidate at a given position
+//! of a keyspace, and cutting keyspaces into equal slices.
+//!
+//! Ranks follow generation order, which for every keyspace here is
+//! lexicographic in the indices of the input letters.
+
+use crate::count;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+/// Indices of the `rank`-th arrangement of `k` out of `n` letters.
+pub fn unrank_permutation(n: usize, k: usize, mut rank: u128) -> Vec<usize> {
+    let mut unused: Vec<usize> = (0..n).collect();
+    let mut indices = Vec::with_capacity(k);
+    for j in 0..k {
+        // How many arrangements share each choice for position j.
+        let block = count::checked_permutation((n - j - 1) as u128, (k - j - 1) as u128).unwrap_or(u128::MAX);
+        let pick = (rank / block) as usize;
+        rank %= block;
+        indices.push(unused.remove(pick.min(unused.len() - 1)));
+    }
+    indices
+}
+
+/// Indices (ascending) of the `rank`-th selection of `k` out of `n` letters.
+pub fn unrank_combination(n: usize, k: usize, mut rank: u128) -> Vec<usize> {
+    let mut indices = Vec::with_capacity(k);
+    let mut next = 0;
+    for j in 0..k {
+        loop {
+            // Selections that keep `next` at position j.
+            let block = count::checked_binomial((n - next - 1) as u128, (k - j - 1) as u128).unwrap_or(u128::MAX);
+            if rank < block || next + (k - j) >= n {
+                break;
+            }
+            rank -= block;
+            next += 1;
+        }
+        indices.push(next);
+        next += 1;
+    }
+    indices
+}
+
+/// Rank of the arrangement `indices` of `n` letters, the inverse of
+/// [`unrank_permutation`], or `None` if it overflows.
+pub fn rank_permutation(n: usize, indices: &[usize]) -> Option<u128> {
+    let k = indices.len();
+    let mut rank = 0u128;
+    for (j, &index) in indices.iter().enumerate() {
+        // Unused letters that would come before this one at position j.
+        let smaller = (0..index).filter(|i| !indices[..j].contains(i)).count() as u128;
+        let block = count::checked_permutation((n - j - 1) as u128, (k - j - 1) as u128)?;
+        rank = rank.checked_add(smaller.checked_mul(block)?)?;
+    }
+    Some(rank)
+}
+
+/// Rank of the ascending selection `indices` out of `n` letters, the
+/// inverse of [`unrank_combination`], or `None` if it overflows.
+pub fn rank_combination(n: usize, indices: &[usize]) -> Option<u128> {
+    let k = indices.len();
+    let mut rank = 0u128;
+    let mut next = 0;
+    for (j, &index) in indices.iter().enumerate() {
+        // Skip every selection that holds a smaller letter at position j.
+        for skipped in next..index {
+            rank = rank.checked_add(count::checked_binomial((n - skipped - 1) as u128, (k - j - 1) as u128)?)?;
+        }
+        next = index + 1;
+    }
+    Some(rank)
+}
+
+/// Digits of `rank` written in base `n` with `k` places.
+pub fn unrank_product(n: usize, k: usize, mut rank: u128) -> Vec<usize> {
+    let mut digits = vec![0; k];
+    if n == 0 {
+        return digits;
+    }
+    for digit in digits.iter_mut().rev() {
+        *digit = (rank % n as u128) as usize;
+        rank /= n as u128;
+    }
+    digits
+}
+
+/// Position of `word` among the distinct arrangements of its own letters
+/// in lexicographic order, or `None` if the count overflows.
+pub fn rank_arrangement(word: &[char]) -> Option<u128> {
+    let mut letters = tally(word);
+    let mut rank = 0u128;
+    for &c in word {
+        for i in 0..letters.len() {
+            let (letter, n) = letters[i];
+            if letter == c {
+                letters[i].1 -= 1;
+                break;
+            }
+            if n > 0 {
+                // Every arrangement with `letter` here comes first.
+                letters[i].1 -= 1;
+                rank = rank.checked_add(arrangements(&letters)?)?;
+                letters[i].1 += 1;
+            }
+        }
+    }
+    Some(rank)
+}
+
+/// The `rank`-th distinct arrangement of `chars` in lexicographic order;
+/// `None` past the last one or if the count overflows.
+pub fn unrank_arrangement(chars: &[char], mut rank: u128) -> Option<Vec<char>> {
+    let mut letters = tally(chars);
+    let mut word = Vec::with_capacity(chars.len());
+    while word.len() < chars.len() {
+        let mut picked = false;
+        for i in 0..letters.len() {
+            if letters[i].1 == 0 {
+                continue;
+            }
+            letters[i].1 -= 1;
+            let block = arrangements(&letters)?;
+            if rank < block {
+                word.push(letters[i].0);
+                picked = true;
+                break;
+            }
+            rank -= block;
+            letters[i].1 += 1;
+        }
+        if !picked {
+            return None;
+        }
+    }
+    Some(word)
+}
+
+/// Distinct letters in order, with how often each occurs.
+fn tally(chars: &[char]) -> Vec<(char, u128)> {
+    let mut sorted = chars.to_vec();
+    sorted.sort_unstable();
+    let mut letters: Vec<(char, u128)> = Vec::new();
+    for c in sorted {
+        match letters.last_mut() {
+            Some((last, n)) if *last == c => *n += 1,
+            _ => letters.push((c, 1)),
+        }
+    }
+    letters
+}
+
+fn arrangements(letters: &[(char, u128)]) -> Option<u128> {
+    let counts: Vec<u128> = letters.iter().map(|&(_, n)| n).collect();
+    count::checked_multinomial(&counts)
+}
+
+/// One of `count` equal parts of a keyspace, written `i/n` with `i` from 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Slice {
+    /// Zero-based.
+    pub index: u128,
+    pub count: u128,
+}
+
+impl Slice {
+    /// The ranks this slice covers out of `total`. Sizes differ by at most
+    /// one and the slices `1/n..=n/n` tile the keyspace exactly.
+    pub fn range(&self, total: u128) -> Range<u128> {
+        let (size, extra) = (total / self.count, total % self.count);
+        let start = |i: u128| size * i + i.min(extra);
+        start(self.index)..start(self.index + 1)
+    }
+}
+
+impl FromStr for Slice {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, String> {
+        let (i, n) = s.split_once('/').ok_or_else(|| format!("expected i/n, got '{}'", s))?;
+        let i: u128 = i.trim().parse().map_err(|_| format!("invalid slice number '{}'", i))?;
+        let n: u128 = n.trim().parse().map_err(|_| format!("invalid slice count '{}'", n))?;
+        if n == 0 || i == 0 || i > n {
+            return Err(format!("slice must be between 1/{n} and {n}/{n}", n = n.max(1)));
+        }
+        Ok(Slice { index: i - 1, count: n })
+    }
+}
+
+impl fmt::Display for Slice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.index + 1, self.count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+
+    #[test]
+    fn permutation_ranks_round_trip_in_generation_order() {
+        let chars = ['a', 'b', 'c', 'd', 'e'];
+        let words: Vec<String> = generate::permutations(chars.to_vec(), 3, 3).collect();
+        assert_eq!(Some(words.len() as u128), count::checked_permutation(5, 3));
+        for (rank, word) in words.iter().enumerate() {
+            let indices = unrank_permutation(5, 3, rank as u128);
+            assert_eq!(indices.iter().map(|&i| chars[i]).collect::<String>(), *word);
+            assert_eq!(rank_permutation(5, &indices), Some(rank as u128));
+        }
+    }
+
+    #[test]
+    fn combination_ranks_round_trip_in_lexicographic_order() {
+        let (n, k) = (6, 3);
+        let mut rank = 0;
+        for a in 0..n {
+            for b in a + 1..n {
+                for c in b + 1..n {
+                    assert_eq!(unrank_combination(n, k, rank), [a, b, c]);
+                    assert_eq!(rank_combination(n, &[a, b, c]), Some(rank));
+                    rank += 1;
+                }
+            }
+        }
+        assert_eq!(Some(rank), count::checked_binomial(n as u128, k as u128));
+    }
+
+    #[test]
+    fn product_ranks_follow_the_odometer() {
+        let chars = ['a', 'b', 'c'];
+        for (rank, word) in generate::Product::new(&chars, 3, 0).enumerate() {
+            let spelled: String = unrank_product(3, 3, rank as u128).into_iter().map(|i| chars[i]).collect();
+            assert_eq!(spelled, word);
+        }
+    }
+
+    #[test]
+    fn arrangement_ranks_skip_repeats() {
+        let chars: Vec<char> = "aabc".chars().collect();
+        let words: Vec<Vec<char>> = (0..).map_while(|rank| unrank_arrangement(&chars, rank)).collect();
+        // 4! / 2! distinct arrangements, in lexicographic order.
+        assert_eq!(words.len(), 12);
+        assert!(words.windows(2).all(|pair| pair[0] < pair[1]));
+        for (rank, word) in words.iter().enumerate() {
+            assert_eq!(rank_arrangement(word), Some(rank as u128));
+        }
+    }
+
+    #[test]
+    fn slices_tile_the_keyspace() {
+        for total in [0, 1, 7, 100, 101, u128::MAX] {
+            for count in [1, 2, 3, 7, 64] {
+                let ranges: Vec<Range<u128>> = (0..count).map(|index| Slice { index, count }.range(total)).collect();
+                assert_eq!(ranges[0].start, 0);
+                assert_eq!(ranges[count as usize - 1].end, total);
+                assert!(ranges.windows(2).all(|pair| pair[0].end == pair[1].start));
+                let sizes: Vec<u128> = ranges.iter().map(|range| range.end - range.start).collect();
+                assert!(sizes.iter().max().unwrap() - sizes.iter().min().unwrap() <= 1);
+            }
+        }
+    }
+
+    #[test]
+    fn slices_parse_and_display() {
+        assert_eq!("2/5".parse::<Slice>(), Ok(Slice { index: 1, count: 5 }));
+        assert_eq!(Slice { index: 1, count: 5 }.to_string(), "2/5");
+        for bad in ["0/3", "4/3", "1/0", "1", "a/3", "1/b"] {
+            assert!(bad.parse::<Slice>().is_err(), "{}", bad);
+        }
+    }
+}
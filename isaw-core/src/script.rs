@@ -0,0 +1,555 @@
+//! Keep/drop rules for `--filter-script`, written without recompiling.
+//!
+//! A script is one expression, kept candidates being those it is true for,
+//! e.g. `vowels >= 2 and not ends_with(word, "q")`. It can use
+//!
+//! - `word`, the candidate; `length`, its length in characters; `score`,
+//!   its English-likeness ([`Model::average`], NaN when too short to
+//!   judge); `vowels` and `consonants`, counts of its ASCII letters;
+//! - numbers, `"strings"` (with `\"` and `\\`), `true` and `false`;
+//! - `or`, `and` and `not` (or `||`, `&&`, `!`), `== != < <= > >=`,
+//!   `+ - * / %` (`+` joins strings too) and `in`, as in `"qu" in word`;
+//! - `len(s)`, `count(s, letters)`, `unique(s)` (distinct characters),
+//!   `contains(s, t)`, `starts_with(s, t)`, `ends_with(s, t)`, `lower(s)`,
+//!   `upper(s)`, `reverse(s)` and `matches(s, "regex")`.
+//!
+//! `#` starts a comment and line breaks count as spaces. Types are checked
+//! when the script is parsed, so a mistake is reported once, with its line,
+//! rather than for every candidate.
+
+use crate::ngram::Model;
+use crate::Matcher;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt;
+
+pub struct Script {
+    expr: Expr,
+    model: Model,
+}
+
+impl Script {
+    /// Parses and type-checks `source`; `score` is measured with `model`.
+    pub fn parse(source: &str, model: Model) -> Result<Self, String> {
+        let tokens = tokens(source)?;
+        if tokens.is_empty() {
+            return Err("the script is empty".to_string());
+        }
+        let mut parser = Parser { tokens, at: 0 };
+        let typed = parser.or()?;
+        if parser.at < parser.tokens.len() {
+            return Err(parser.error("expected an operator or the end of the script"));
+        }
+        let expr = parser.check(typed, Type::Bool, "the script")?;
+        Ok(Script { expr, model })
+    }
+
+    pub fn is_match(&self, word: &str) -> bool {
+        self.eval(&self.expr, word).bool()
+    }
+
+    fn eval<'a>(&'a self, expr: &'a Expr, word: &'a str) -> Value<'a> {
+        match expr {
+            Expr::Num(n) => Value::Num(*n),
+            Expr::Str(s) => Value::Str(Cow::Borrowed(s)),
+            Expr::Bool(b) => Value::Bool(*b),
+            Expr::Word => Value::Str(Cow::Borrowed(word)),
+            Expr::Length => Value::Num(word.chars().count() as f64),
+            Expr::Score => Value::Num(self.model.average(word).unwrap_or(f64::NAN)),
+            Expr::Vowels => Value::Num(word.chars().filter(|&c| is_vowel(c)).count() as f64),
+            Expr::Consonants => Value::Num(word.chars().filter(|&c| c.is_ascii_alphabetic() && !is_vowel(c)).count() as f64),
+            Expr::Not(inner) => Value::Bool(!self.eval(inner, word).bool()),
+            Expr::Neg(inner) => Value::Num(-self.eval(inner, word).num()),
+            Expr::And(a, b) => Value::Bool(self.eval(a, word).bool() && self.eval(b, word).bool()),
+            Expr::Or(a, b) => Value::Bool(self.eval(a, word).bool() || self.eval(b, word).bool()),
+            Expr::Compare(op, a, b) => Value::Bool(op.holds(self.eval(a, word).compare(&self.eval(b, word)))),
+            Expr::Arith(op, a, b) => Value::Num(op.apply(self.eval(a, word).num(), self.eval(b, word).num())),
+            Expr::Concat(a, b) => Value::Str(Cow::Owned(format!("{}{}", self.eval(a, word).str(), self.eval(b, word).str()))),
+            Expr::Matches(subject, regex) => Value::Bool(regex.is_match(self.eval(subject, word).str())),
+            Expr::Call(function, args) => function.apply(&args.iter().map(|arg| self.eval(arg, word)).collect::<Vec<_>>()),
+        }
+    }
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c.to_ascii_lowercase(), 'a' | 'e' | 'i' | 'o' | 'u')
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Type {
+    Num,
+    Str,
+    Bool,
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Type::Num => "a number",
+            Type::Str => "a string",
+            Type::Bool => "true/false",
+        })
+    }
+}
+
+enum Expr {
+    Num(f64),
+    Str(String),
+    Bool(bool),
+    Word,
+    Length,
+    Score,
+    Vowels,
+    Consonants,
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Compare, Box<Expr>, Box<Expr>),
+    Arith(Arith, Box<Expr>, Box<Expr>),
+    Concat(Box<Expr>, Box<Expr>),
+    /// A regex, compiled once from a literal pattern.
+    Matches(Box<Expr>, Matcher),
+    Call(Function, Vec<Expr>),
+}
+
+/// Values while evaluating; the parser has already made sure each
+/// operation gets the kind it expects.
+enum Value<'a> {
+    Num(f64),
+    Str(Cow<'a, str>),
+    Bool(bool),
+}
+
+impl Value<'_> {
+    fn num(&self) -> f64 {
+        match self {
+            Value::Num(n) => *n,
+            _ => f64::NAN,
+        }
+    }
+
+    fn str(&self) -> &str {
+        match self {
+            Value::Str(s) => s,
+            _ => "",
+        }
+    }
+
+    fn bool(&self) -> bool {
+        matches!(self, Value::Bool(true))
+    }
+
+    /// `None` when either side is NaN.
+    fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Num(a), Value::Num(b)) => a.partial_cmp(b),
+            (Value::Str(a), Value::Str(b)) => Some(a.cmp(b)),
+            (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Compare {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+const COMPARISONS: [(&str, Compare); 6] = [("==", Compare::Eq), ("!=", Compare::Ne), ("<=", Compare::Le), (">=", Compare::Ge), ("<", Compare::Lt), (">", Compare::Gt)];
+
+impl Compare {
+    fn holds(self, ordering: Option<Ordering>) -> bool {
+        match ordering {
+            Some(ordering) => match self {
+                Compare::Eq => ordering.is_eq(),
+                Compare::Ne => ordering.is_ne(),
+                Compare::Lt => ordering.is_lt(),
+                Compare::Le => ordering.is_le(),
+                Compare::Gt => ordering.is_gt(),
+                Compare::Ge => ordering.is_ge(),
+            },
+            // Like NaN in Rust: unequal to everything, ordered with nothing.
+            None => self == Compare::Ne,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Arith {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl Arith {
+    fn apply(self, a: f64, b: f64) -> f64 {
+        match self {
+            Arith::Add => a + b,
+            Arith::Sub => a - b,
+            Arith::Mul => a * b,
+            Arith::Div => a / b,
+            Arith::Rem => a % b,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Function {
+    Len,
+    Count,
+    Unique,
+    Contains,
+    StartsWith,
+    EndsWith,
+    Lower,
+    Upper,
+    Reverse,
+}
+
+/// Name, parameter types and result type of every function but `matches`.
+const FUNCTIONS: [(&str, Function, &[Type], Type); 9] = [
+    ("len", Function::Len, &[Type::Str], Type::Num),
+    ("count", Function::Count, &[Type::Str, Type::Str], Type::Num),
+    ("unique", Function::Unique, &[Type::Str], Type::Num),
+    ("contains", Function::Contains, &[Type::Str, Type::Str], Type::Bool),
+    ("starts_with", Function::StartsWith, &[Type::Str, Type::Str], Type::Bool),
+    ("ends_with", Function::EndsWith, &[Type::Str, Type::Str], Type::Bool),
+    ("lower", Function::Lower, &[Type::Str], Type::Str),
+    ("upper", Function::Upper, &[Type::Str], Type::Str),
+    ("reverse", Function::Reverse, &[Type::Str], Type::Str),
+];
+
+impl Function {
+    fn apply(self, args: &[Value]) -> Value<'static> {
+        let text = args[0].str();
+        let other = args.get(1).map_or("", Value::str);
+        match self {
+            Function::Len => Value::Num(text.chars().count() as f64),
+            Function::Count => Value::Num(text.chars().filter(|&c| other.contains(c)).count() as f64),
+            Function::Unique => {
+                let mut chars: Vec<char> = text.chars().collect();
+                chars.sort_unstable();
+                chars.dedup();
+                Value::Num(chars.len() as f64)
+            }
+            Function::Contains => Value::Bool(text.contains(other)),
+            Function::StartsWith => Value::Bool(text.starts_with(other)),
+            Function::EndsWith => Value::Bool(text.ends_with(other)),
+            Function::Lower => Value::Str(Cow::Owned(text.to_lowercase())),
+            Function::Upper => Value::Str(Cow::Owned(text.to_uppercase())),
+            Function::Reverse => Value::Str(Cow::Owned(text.chars().rev().collect())),
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Token {
+    Num(f64),
+    Str(String),
+    Name(String),
+    Symbol(&'static str),
+}
+
+/// Longer symbols first, so `<=` isn't read as `<` and `=`.
+const SYMBOLS: [&str; 17] = ["||", "&&", "==", "!=", "<=", ">=", "<", ">", "!", "+", "-", "*", "/", "%", "(", ")", ","];
+
+/// The tokens of `source`, each with its line number.
+fn tokens(source: &str) -> Result<Vec<(Token, usize)>, String> {
+    let mut tokens = Vec::new();
+    for (line, text) in source.lines().enumerate() {
+        let line = line + 1;
+        let mut rest = text;
+        loop {
+            rest = rest.trim_start();
+            let Some(c) = rest.chars().next() else { break };
+            let token = if c == '#' {
+                break;
+            } else if c.is_ascii_digit() || c == '.' {
+                let end = rest.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(rest.len());
+                let number = &rest[..end];
+                rest = &rest[end..];
+                Token::Num(number.parse().map_err(|_| format!("line {}: '{}' is not a number", line, number))?)
+            } else if c == '"' {
+                let mut string = String::new();
+                let mut chars = rest[1..].char_indices();
+                let mut end = None;
+                while let Some((i, c)) = chars.next() {
+                    match c {
+                        '"' => {
+                            end = Some(i + 2);
+                            break;
+                        }
+                        '\\' => string.push(chars.next().map_or('\\', |(_, c)| c)),
+                        c => string.push(c),
+                    }
+                }
+                rest = &rest[end.ok_or_else(|| format!("line {}: unterminated string", line))?..];
+                Token::Str(string)
+            } else if c.is_alphabetic() || c == '_' {
+                let end = rest.find(|c: char| !c.is_alphanumeric() && c != '_').unwrap_or(rest.len());
+                let name = rest[..end].to_string();
+                rest = &rest[end..];
+                Token::Name(name)
+            } else {
+                let symbol = SYMBOLS.iter().find(|&&symbol| rest.starts_with(symbol)).ok_or_else(|| format!("line {}: unexpected '{}'", line, c))?;
+                rest = &rest[symbol.len()..];
+                Token::Symbol(symbol)
+            };
+            tokens.push((token, line));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent, loosest binding first: `or`, `and`, `not`,
+/// comparisons and `in`, `+ -`, `* / %`, unary `-`.
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    at: usize,
+}
+
+type Typed = (Expr, Type);
+
+impl Parser {
+    /// Consumes the next token if it is this symbol or keyword.
+    fn eat(&mut self, word: &str) -> bool {
+        let hit = match self.tokens.get(self.at) {
+            Some((Token::Symbol(symbol), _)) => *symbol == word,
+            Some((Token::Name(name), _)) => name == word,
+            _ => false,
+        };
+        self.at += hit as usize;
+        hit
+    }
+
+    /// `message`, placed at the line of the token being read.
+    fn error(&self, message: impl fmt::Display) -> String {
+        match self.tokens.get(self.at).or(self.tokens.last()) {
+            Some((_, line)) => format!("line {}: {}", line, message),
+            None => message.to_string(),
+        }
+    }
+
+    fn check(&self, (expr, found): Typed, wanted: Type, what: &str) -> Result<Expr, String> {
+        match found == wanted {
+            true => Ok(expr),
+            false => Err(self.error(format!("{} needs {}, not {}", what, wanted, found))),
+        }
+    }
+
+    fn or(&mut self) -> Result<Typed, String> {
+        let mut left = self.and()?;
+        while self.eat("or") || self.eat("||") {
+            let right = self.and()?;
+            left = (Expr::Or(Box::new(self.check(left, Type::Bool, "'or'")?), Box::new(self.check(right, Type::Bool, "'or'")?)), Type::Bool);
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Typed, String> {
+        let mut left = self.not()?;
+        while self.eat("and") || self.eat("&&") {
+            let right = self.not()?;
+            left = (Expr::And(Box::new(self.check(left, Type::Bool, "'and'")?), Box::new(self.check(right, Type::Bool, "'and'")?)), Type::Bool);
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Typed, String> {
+        if self.eat("not") || self.eat("!") {
+            let inner = self.not()?;
+            return Ok((Expr::Not(Box::new(self.check(inner, Type::Bool, "'not'")?)), Type::Bool));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Typed, String> {
+        let left = self.sum()?;
+        if self.eat("in") {
+            let right = self.sum()?;
+            let needle = self.check(left, Type::Str, "'in'")?;
+            let haystack = self.check(right, Type::Str, "'in'")?;
+            return Ok((Expr::Call(Function::Contains, vec![haystack, needle]), Type::Bool));
+        }
+        let Some(&(symbol, op)) = COMPARISONS.iter().find(|(symbol, _)| self.eat(symbol)) else { return Ok(left) };
+        let right = self.sum()?;
+        if left.1 != right.1 {
+            return Err(self.error(format!("'{}' can't compare {} with {}", symbol, left.1, right.1)));
+        }
+        if left.1 == Type::Bool && !matches!(op, Compare::Eq | Compare::Ne) {
+            return Err(self.error(format!("'{}' can't order true/false; use == or !=", symbol)));
+        }
+        Ok((Expr::Compare(op, Box::new(left.0), Box::new(right.0)), Type::Bool))
+    }
+
+    fn sum(&mut self) -> Result<Typed, String> {
+        let mut left = self.product()?;
+        loop {
+            let (symbol, op) = if self.eat("+") {
+                ("'+'", Arith::Add)
+            } else if self.eat("-") {
+                ("'-'", Arith::Sub)
+            } else {
+                return Ok(left);
+            };
+            let right = self.product()?;
+            left = match (op, left.1, right.1) {
+                (Arith::Add, Type::Str, Type::Str) => (Expr::Concat(Box::new(left.0), Box::new(right.0)), Type::Str),
+                _ => (Expr::Arith(op, Box::new(self.check(left, Type::Num, symbol)?), Box::new(self.check(right, Type::Num, symbol)?)), Type::Num),
+            };
+        }
+    }
+
+    fn product(&mut self) -> Result<Typed, String> {
+        let mut left = self.unary()?;
+        loop {
+            let (symbol, op) = if self.eat("*") {
+                ("'*'", Arith::Mul)
+            } else if self.eat("/") {
+                ("'/'", Arith::Div)
+            } else if self.eat("%") {
+                ("'%'", Arith::Rem)
+            } else {
+                return Ok(left);
+            };
+            let right = self.unary()?;
+            left = (Expr::Arith(op, Box::new(self.check(left, Type::Num, symbol)?), Box::new(self.check(right, Type::Num, symbol)?)), Type::Num);
+        }
+    }
+
+    fn unary(&mut self) -> Result<Typed, String> {
+        if self.eat("-") {
+            let inner = self.unary()?;
+            return Ok((Expr::Neg(Box::new(self.check(inner, Type::Num, "'-'")?)), Type::Num));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Typed, String> {
+        let Some((token, line)) = self.tokens.get(self.at).cloned() else { return Err(self.error("expected a value at the end of the script")) };
+        self.at += 1;
+        match token {
+            Token::Num(n) => Ok((Expr::Num(n), Type::Num)),
+            Token::Str(s) => Ok((Expr::Str(s), Type::Str)),
+            Token::Symbol("(") => {
+                let inner = self.or()?;
+                match self.eat(")") {
+                    true => Ok(inner),
+                    false => Err(self.error("expected ')'")),
+                }
+            }
+            Token::Name(name) if self.eat("(") => self.call(&name, line),
+            Token::Name(name) => match name.as_str() {
+                "true" => Ok((Expr::Bool(true), Type::Bool)),
+                "false" => Ok((Expr::Bool(false), Type::Bool)),
+                "word" => Ok((Expr::Word, Type::Str)),
+                "length" => Ok((Expr::Length, Type::Num)),
+                "score" => Ok((Expr::Score, Type::Num)),
+                "vowels" => Ok((Expr::Vowels, Type::Num)),
+                "consonants" => Ok((Expr::Consonants, Type::Num)),
+                _ => Err(format!("line {}: unknown name '{}' (known: word, length, score, vowels, consonants)", line, name)),
+            },
+            Token::Symbol(symbol) => Err(format!("line {}: expected a value, found '{}'", line, symbol)),
+        }
+    }
+
+    /// A call, its opening parenthesis already read.
+    fn call(&mut self, name: &str, line: usize) -> Result<Typed, String> {
+        let mut args = Vec::new();
+        if !self.eat(")") {
+            loop {
+                args.push(self.or()?);
+                if self.eat(")") {
+                    break;
+                }
+                if !self.eat(",") {
+                    return Err(self.error("expected ',' or ')'"));
+                }
+            }
+        }
+        if name == "matches" {
+            let mut args = args.into_iter();
+            let (Some(subject), Some((Expr::Str(pattern), _)), None) = (args.next(), args.next(), args.next()) else {
+                return Err(format!("line {}: matches() takes a string and a regex written as a literal \"string\"", line));
+            };
+            let regex = Matcher::regex(&pattern).map_err(|e| format!("line {}: invalid regex: {}", line, e))?;
+            return Ok((Expr::Matches(Box::new(self.check(subject, Type::Str, "matches()")?), regex), Type::Bool));
+        }
+        let Some(&(_, function, params, result)) = FUNCTIONS.iter().find(|(known, ..)| *known == name) else {
+            let known: Vec<&str> = FUNCTIONS.iter().map(|(known, ..)| *known).chain(["matches"]).collect();
+            return Err(format!("line {}: unknown function '{}' (known: {})", line, name, known.join(", ")));
+        };
+        if args.len() != params.len() {
+            return Err(format!("line {}: {}() takes {} argument{}", line, name, params.len(), if params.len() == 1 { "" } else { "s" }));
+        }
+        let args = args.into_iter().zip(params).enumerate().map(|(i, (arg, &param))| self.check(arg, param, &format!("argument {} of {}()", i + 1, name))).collect::<Result<_, _>>()?;
+        Ok((Expr::Call(function, args), result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<Script, String> {
+        Script::parse(source, Model::english())
+    }
+
+    fn error(source: &str) -> String {
+        match parse(source) {
+            Ok(_) => panic!("'{}' parsed", source),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn keeps_what_the_expression_is_true_for() {
+        let script = parse("vowels >= 2 and not ends_with(word, \"q\") # no q\n and length < 6").unwrap();
+        assert!(script.is_match("tea"));
+        assert!(!script.is_match("teaq"));
+        assert!(!script.is_match("tr"));
+        assert!(!script.is_match("aeiouxy"));
+
+        let script = parse("\"qu\" in word || upper(reverse(word)) == \"TAC\" && count(word, \"c\") + 1 == 2").unwrap();
+        assert!(script.is_match("quit") && script.is_match("cat") && !script.is_match("act"));
+        assert!(parse("unique(word) == len(word) and matches(word, \"^[a-c]+$\")").unwrap().is_match("cab"));
+        let script = parse("!(consonants % 2 == 1) && lower(\"A\\\"\") == \"a\\\"\"").unwrap();
+        assert!(script.is_match("cat") && !script.is_match("cart"));
+    }
+
+    #[test]
+    fn reports_mistakes_with_their_line() {
+        let cases = [
+            ("", "the script is empty"),
+            ("   # only a comment", "the script is empty"),
+            ("length >", "expected a value at the end of the script"),
+            ("length > 2 3", "expected an operator or the end of the script"),
+            ("(length > 2", "expected ')'"),
+            ("\n\nsize > 2", "line 3: unknown name 'size'"),
+            ("shout(word)", "line 1: unknown function 'shout'"),
+            ("len(word, word) > 1", "line 1: len() takes 1 argument"),
+            ("contains(word) ", "line 1: contains() takes 2 arguments"),
+            ("matches(word, lower(\"x\"))", "matches() takes a string and a regex written as a literal"),
+            ("word == \"open", "line 1: unterminated string"),
+            ("length > 1.2.3", "line 1: '1.2.3' is not a number"),
+            ("length @ 2", "line 1: unexpected '@'"),
+            ("length", "the script needs"),
+            ("word > 3", "can't compare"),
+            ("true < false", "can't order true/false"),
+            ("not length", "needs"),
+            ("count(word, word, 1)", "count() takes 2 arguments"),
+        ];
+        for (source, expected) in cases {
+            let e = error(source);
+            assert!(e.contains(expected), "'{}' gave '{}', expected '{}'", source, e, expected);
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Block-range stand-ins for `\p{Script}` classes in regex patterns.
+//!
+//! regex-lite has no Unicode property classes, so `\p{Greek}` and friends are
+//! rewritten into explicit code point ranges before compiling:
+//! `[\p{Greek}a]` becomes `[\x{370}-\x{3FF}...a]` and `\P{Greek}` its
+//! negation. This is not the Unicode Script property. Only the scripts in
+//! [`BLOCKS`] are known, each approximated by the blocks it is written in,
+//! which covers the letters but may take in a few unassigned or shared code
+//! points. Other properties (`\p{L}`, `\p{Emoji}`, ...) are rejected; the
+//! `full-regex` feature compiles patterns with the regex crate instead,
+//! which knows them all, and skips this rewrite.
+
+/// Scripts and the code point blocks standing in for them.
+pub const BLOCKS: &[(&str, &[(u32, u32)])] = &[
+    ("Latin", &[(0x41, 0x5a), (0x61, 0x7a), (0xaa, 0xaa), (0xba, 0xba), (0xc0, 0xd6), (0xd8, 0xf6), (0xf8, 0x24f), (0x1e00, 0x1eff), (0x2c60, 0x2c7f), (0xa720, 0xa7ff), (0xff21, 0xff3a), (0xff41, 0xff5a)]),
+    ("Greek", &[(0x370, 0x3ff), (0x1f00, 0x1fff)]),
+    ("Cyrillic", &[(0x400, 0x52f), (0x1c80, 0x1c8f), (0x2de0, 0x2dff), (0xa640, 0xa69f)]),
+    ("Armenian", &[(0x531, 0x58f)]),
+    ("Hebrew", &[(0x591, 0x5ff), (0xfb1d, 0xfb4f)]),
+    ("Arabic", &[(0x600, 0x6ff), (0x750, 0x77f), (0x8a0, 0x8ff), (0xfb50, 0xfdff), (0xfe70, 0xfeff)]),
+    ("Devanagari", &[(0x900, 0x97f), (0xa8e0, 0xa8ff)]),
+    ("Thai", &[(0xe00, 0xe7f)]),
+    ("Georgian", &[(0x10a0, 0x10ff), (0x2d00, 0x2d2f)]),
+    ("Hangul", &[(0x1100, 0x11ff), (0x3130, 0x318f), (0xac00, 0xd7af)]),
+    ("Hiragana", &[(0x3040, 0x309f)]),
+    ("Katakana", &[(0x30a0, 0x30ff), (0x31f0, 0x31ff)]),
+    ("Han", &[(0x2e80, 0x2fdf), (0x3005, 0x3005), (0x3007, 0x3007), (0x3021, 0x3029), (0x3038, 0x303b), (0x3400, 0x4dbf), (0x4e00, 0x9fff), (0xf900, 0xfaff), (0x20000, 0x2fa1f)]),
+];
+
+/// `pattern` with every `\p{..}`/`\P{..}` replaced by ranges; fails for
+/// unknown scripts and for `\P` inside a bracket class, which has no
+/// equivalent there.
+pub fn expand(pattern: &str) -> Result<String, String> {
+    let mut expanded = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars().peekable();
+    // Nesting of bracket classes, and whether the class just opened.
+    let mut depth = 0;
+    let mut opened = false;
+    while let Some(c) = chars.next() {
+        let just_opened = std::mem::take(&mut opened);
+        match c {
+            '\\' => match chars.next() {
+                Some(kind @ ('p' | 'P')) => {
+                    let name = match chars.next() {
+                        Some('{') => chars.by_ref().take_while(|&c| c != '}').collect(),
+                        Some(c) => c.to_string(),
+                        None => return Err(format!("incomplete \\{}", kind)),
+                    };
+                    let ranges = ranges(&name)?;
+                    match (kind, depth) {
+                        ('p', 0) => expanded.push_str(&format!("[{}]", ranges)),
+                        ('P', 0) => expanded.push_str(&format!("[^{}]", ranges)),
+                        ('p', _) => expanded.push_str(&ranges),
+                        _ => return Err(format!("\\P{{{}}} can't be used inside [...]; use [^...] instead", name)),
+                    }
+                }
+                Some(escaped) => {
+                    expanded.push('\\');
+                    expanded.push(escaped);
+                }
+                None => expanded.push('\\'),
+            },
+            '[' => {
+                depth += 1;
+                opened = true;
+                expanded.push(c);
+                if chars.peek() == Some(&'^') {
+                    expanded.push(chars.next().expect("peeked"));
+                }
+            }
+            // A `]` first in a class is a literal.
+            ']' if depth > 0 && !just_opened => {
+                depth -= 1;
+                expanded.push(c);
+            }
+            c => expanded.push(c),
+        }
+    }
+    Ok(expanded)
+}
+
+/// The class body for a script's blocks, as `\x{..}-\x{..}` ranges.
+fn ranges(name: &str) -> Result<String, String> {
+    // Accept the `Script=Greek` and `sc=Greek` spellings too.
+    let bare = name.rsplit('=').next().unwrap_or(name).trim();
+    let normalized = |s: &str| s.chars().filter(|c| !matches!(c, '_' | ' ' | '-')).collect::<String>().to_ascii_lowercase();
+    let (_, ranges) = BLOCKS
+        .iter()
+        .find(|(script, _)| normalized(script) == normalized(bare))
+        .ok_or_else(|| format!("unknown script '{}' in \\p{{..}}; only these are approximated by block: {}", name, BLOCKS.iter().map(|(s, _)| *s).collect::<Vec<_>>().join(", ")))?;
+    Ok(ranges.iter().map(|&(start, end)| format!("\\x{{{:X}}}-\\x{{{:X}}}", start, end)).collect())
+}
@@ -0,0 +1,68 @@
+//! Rewrites applied to every candidate before it is matched and printed.
+
+use crate::{cipher, dna};
+use std::str::FromStr;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Transform {
+    /// Rotate letters N places (ROT-N).
+    Rot(u8),
+    Reverse,
+    Upper,
+    Lower,
+    /// Pair every base with its complement.
+    Complement { rna: bool },
+    /// The complementary strand, reversed.
+    ReverseComplement { rna: bool },
+    /// Append the word reversed, making a palindrome: `abc` becomes `abccba`,
+    /// or `abcba` when `odd` shares the middle letter.
+    Mirror { odd: bool },
+    /// Put fixed text in front, e.g. `www.`.
+    Prefix(String),
+    /// Append fixed text, e.g. `2024!` or `.com`.
+    Suffix(String),
+}
+
+impl Transform {
+    pub fn apply(&self, word: &str) -> String {
+        match *self {
+            Transform::Prefix(ref prefix) => format!("{}{}", prefix, word),
+            Transform::Suffix(ref suffix) => format!("{}{}", word, suffix),
+            Transform::Rot(n) => cipher::shift(word, n),
+            Transform::Reverse => word.chars().rev().collect(),
+            Transform::Upper => word.to_uppercase(),
+            Transform::Lower => word.to_lowercase(),
+            Transform::Complement { rna } => dna::complement(word, rna),
+            Transform::ReverseComplement { rna } => dna::reverse_complement(word, rna),
+            Transform::Mirror { odd } => {
+                let skip = usize::from(odd && !word.is_empty());
+                word.chars().chain(word.chars().rev().skip(skip)).collect()
+            }
+        }
+    }
+}
+
+/// Parses a transform name: `rot13` (or any `rotN`), `reverse`, `upper`,
+/// `lower`, `complement` or `reverse-complement`.
+impl FromStr for Transform {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, String> {
+        Ok(match s {
+            "reverse" => Transform::Reverse,
+            "upper" => Transform::Upper,
+            "lower" => Transform::Lower,
+            "complement" => Transform::Complement { rna: false },
+            "reverse-complement" => Transform::ReverseComplement { rna: false },
+            _ => {
+                let n = s.strip_prefix("rot").and_then(|n| n.parse::<u8>().ok()).filter(|n| (1..26).contains(n));
+                Transform::Rot(n.ok_or_else(|| format!("unknown transform '{}' (expected rotN, reverse, upper, lower, complement or reverse-complement)", s))?)
+            }
+        })
+    }
+}
+
+/// Runs `word` through every transform in order.
+pub fn apply_all(transforms: &[Transform], word: String) -> String {
+    transforms.iter().fold(word, |word, transform| transform.apply(&word))
+}
@@ -0,0 +1,128 @@
+//! Prefix tree over wordlist entries, so generators can stop extending a
+//! candidate as soon as no word starts with it.
+//!
+//! Keys are lowercased letter by letter, the Unicode folding
+//! [`Matcher`](crate::Matcher) uses to ignore case. That folds everything
+//! the ASCII-only [`Dictionary`](crate::dictionary::Dictionary) lookups do,
+//! so pruning never drops a word the dictionary would accept.
+
+/// `word` as the trie keys it: each letter lowercased on its own.
+pub fn fold(word: &str) -> String {
+    word.chars().flat_map(char::to_lowercase).collect()
+}
+
+/// Index of the empty prefix.
+pub const ROOT: u32 = 0;
+
+#[derive(Clone, Debug, Default)]
+struct Node {
+    /// Edges, sorted by letter.
+    next: Vec<(char, u32)>,
+    /// Whether the path to this node is a whole word.
+    word: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Trie {
+    nodes: Vec<Node>,
+}
+
+impl Default for Trie {
+    fn default() -> Self {
+        Trie { nodes: vec![Node::default()] }
+    }
+}
+
+impl Trie {
+    pub fn insert(&mut self, word: &str) {
+        let mut node = ROOT;
+        for c in word.chars().flat_map(char::to_lowercase) {
+            let edges = &self.nodes[node as usize].next;
+            node = match edges.binary_search_by_key(&c, |&(c, _)| c) {
+                Ok(i) => edges[i].1,
+                Err(i) => {
+                    let child = self.nodes.len() as u32;
+                    self.nodes[node as usize].next.insert(i, (c, child));
+                    self.nodes.push(Node::default());
+                    child
+                }
+            };
+        }
+        self.nodes[node as usize].word = true;
+    }
+
+    /// The node reached from `node` by the letter `c`, if any word continues that way.
+    pub fn child(&self, node: u32, c: char) -> Option<u32> {
+        // A few capitals lowercase to more than one letter.
+        c.to_lowercase().try_fold(node, |node, c| {
+            let edges = &self.nodes[node as usize].next;
+            edges.binary_search_by_key(&c, |&(c, _)| c).ok().map(|i| edges[i].1)
+        })
+    }
+
+    /// The letters continuing from `node` and the nodes they lead to, in
+    /// letter order.
+    pub fn children(&self, node: u32) -> impl Iterator<Item = (char, u32)> + '_ {
+        self.nodes[node as usize].next.iter().copied()
+    }
+
+    /// Whether the path to `node` spells a word.
+    pub fn is_word(&self, node: u32) -> bool {
+        self.nodes[node as usize].word
+    }
+
+    /// Up to `limit` words starting with `prefix`, shortest first and in
+    /// letter order within a length; the prefix itself counts if it's a word.
+    pub fn completions(&self, prefix: &str, limit: usize) -> Vec<String> {
+        let mut start = ROOT;
+        for c in prefix.chars() {
+            match self.child(start, c) {
+                Some(next) => start = next,
+                None => return Vec::new(),
+            }
+        }
+        // Breadth first, so each level is a word length.
+        let mut words = Vec::new();
+        let mut queue = std::collections::VecDeque::from([(start, fold(prefix))]);
+        while let Some((node, word)) = queue.pop_front() {
+            if words.len() == limit {
+                break;
+            }
+            if self.is_word(node) {
+                words.push(word.clone());
+            }
+            for (c, child) in self.children(node) {
+                let mut longer = word.clone();
+                longer.push(c);
+                queue.push_back((child, longer));
+            }
+        }
+        words
+    }
+
+    /// Number of words stored.
+    pub fn len(&self) -> usize {
+        self.nodes.iter().filter(|node| node.word).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn completions_fold_case_beyond_ascii() {
+        let mut trie = Trie::default();
+        for word in ["Éclair", "éclat", "echo", "Straße"] {
+            trie.insert(word);
+        }
+        assert_eq!(trie.completions("ÉCL", 10), ["éclat", "éclair"]);
+        assert_eq!(trie.completions("e", 10), ["echo"]);
+        assert_eq!(trie.completions("STRASSE", 10), Vec::<String>::new());
+        assert_eq!(trie.completions("STRAẞ", 10), ["straße"]);
+    }
+}
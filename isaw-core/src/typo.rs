@@ -0,0 +1,189 @@
+//! Plausible misspellings of a word on a given keyboard layout.
+
+use std::collections::{HashMap, HashSet};
+
+/// Counts of the 2,400 commonest words in public-domain English prose and
+/// verse (`data/english-words.txt`), for ranking suggestions when no other
+/// counts are given.
+pub const ENGLISH_WORD_COUNTS: &str = include_str!("../data/english-words.txt");
+
+/// Parses `WORD COUNT` lines into counts keyed by the lowercased word,
+/// skipping `#` comments and lines that don't fit.
+pub fn word_counts(text: &str) -> HashMap<String, u64> {
+    text.lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let (word, count) = (fields.next()?, fields.next()?.parse().ok()?);
+            Some((word.to_lowercase(), count))
+        })
+        .collect()
+}
+
+/// Layout names with their three letter rows, top to bottom. Rows keep
+/// their punctuation so columns stay aligned; each row sits slightly right
+/// of the one above, so key `i` touches keys `i` and `i + 1` above it and
+/// `i - 1` and `i` below it.
+pub const LAYOUTS: [(&str, [&str; 3]); 3] = [
+    ("qwerty", ["qwertyuiop", "asdfghjkl;", "zxcvbnm,./"]),
+    ("azerty", ["azertyuiop", "qsdfghjklm", "wxcvbn,;:!"]),
+    ("dvorak", ["',.pyfgcrl", "aoeuidhtns", ";qjkxbmwvz"]),
+];
+
+/// The layout names, for argument parsers.
+pub const NAMES: [&str; 3] = [LAYOUTS[0].0, LAYOUTS[1].0, LAYOUTS[2].0];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// A letter left out.
+    Omission,
+    /// A letter typed twice.
+    Doubled,
+    /// Two neighbouring letters swapped.
+    Transposition,
+    /// A letter replaced by an adjacent key.
+    Substitution,
+    /// An adjacent key hit along with the intended one.
+    Insertion,
+}
+
+impl Kind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kind::Omission => "omission",
+            Kind::Doubled => "doubled",
+            Kind::Transposition => "transposition",
+            Kind::Substitution => "adjacent key",
+            Kind::Insertion => "extra key",
+        }
+    }
+}
+
+/// The keys touching `key` on the layout called `layout`, letters only.
+pub fn neighbours(layout: &str, key: char) -> Vec<char> {
+    let Some((_, rows)) = LAYOUTS.iter().find(|(name, _)| *name == layout) else {
+        return Vec::new();
+    };
+    let rows: Vec<Vec<char>> = rows.iter().map(|row| row.chars().collect()).collect();
+    let Some((r, i)) = rows.iter().enumerate().find_map(|(r, row)| row.iter().position(|&c| c == key).map(|i| (r, i))) else {
+        return Vec::new();
+    };
+    let mut keys = Vec::new();
+    let mut add = |row: usize, column: Option<usize>| {
+        if let Some(&c) = column.and_then(|column| rows.get(row)?.get(column)) {
+            keys.push(c);
+        }
+    };
+    add(r, i.checked_sub(1));
+    add(r, Some(i + 1));
+    if r > 0 {
+        add(r - 1, Some(i));
+        add(r - 1, Some(i + 1));
+    }
+    add(r + 1, i.checked_sub(1));
+    add(r + 1, Some(i));
+    keys.retain(|c| c.is_alphabetic());
+    keys
+}
+
+/// Every distinct misspelling of `word` (lowercased) with how it arises,
+/// in the order of [`Kind`]; the word itself is never included.
+pub fn typos(word: &str, layout: &str) -> Vec<(String, Kind)> {
+    let chars: Vec<char> = word.to_lowercase().chars().collect();
+    let spell = |chars: &[char]| chars.iter().collect::<String>();
+    let mut candidates = Vec::new();
+
+    for i in 0..chars.len() {
+        let mut typo = chars.clone();
+        typo.remove(i);
+        candidates.push((spell(&typo), Kind::Omission));
+    }
+    for i in 0..chars.len() {
+        let mut typo = chars.clone();
+        typo.insert(i, chars[i]);
+        candidates.push((spell(&typo), Kind::Doubled));
+    }
+    for i in 1..chars.len() {
+        let mut typo = chars.clone();
+        typo.swap(i - 1, i);
+        candidates.push((spell(&typo), Kind::Transposition));
+    }
+    for (i, &c) in chars.iter().enumerate() {
+        for key in neighbours(layout, c) {
+            let mut typo = chars.clone();
+            typo[i] = key;
+            candidates.push((spell(&typo), Kind::Substitution));
+        }
+    }
+    for (i, &c) in chars.iter().enumerate() {
+        for key in neighbours(layout, c) {
+            for at in [i, i + 1] {
+                let mut typo = chars.clone();
+                typo.insert(at, key);
+                candidates.push((spell(&typo), Kind::Insertion));
+            }
+        }
+    }
+
+    let original = spell(&chars);
+    let mut seen = HashSet::new();
+    candidates.retain(|(typo, _)| *typo != original && seen.insert(typo.clone()));
+    candidates
+}
+
+/// Edit distance from one word to dictionary words, for spelling
+/// suggestions: inserting, deleting or substituting a letter or swapping
+/// two neighbours costs 1, but substituting an adjacent key on the layout
+/// only [`ADJACENT`], as that is the likelier slip. Case is ignored.
+pub struct Distance {
+    word: Vec<char>,
+    /// Adjacent keys for each letter of `word`.
+    near: Vec<Vec<char>>,
+}
+
+/// Cost of hitting a neighbouring key instead of the right one.
+pub const ADJACENT: f64 = 0.5;
+
+impl Distance {
+    pub fn new(word: &str, layout: &str) -> Self {
+        let word: Vec<char> = word.to_lowercase().chars().collect();
+        let near = word.iter().map(|&c| neighbours(layout, c)).collect();
+        Distance { word, near }
+    }
+
+    /// The distance to `other`, or `None` if it is more than `max`.
+    pub fn to(&self, other: &str, max: f64) -> Option<f64> {
+        let other: Vec<char> = other.to_lowercase().chars().collect();
+        if (self.word.len() as f64 - other.len() as f64).abs() > max {
+            return None;
+        }
+        // Optimal string alignment over three rows: two back for swaps.
+        let width = other.len() + 1;
+        let mut rows = vec![vec![0.0; width]; 3];
+        rows[0] = (0..width).map(|j| j as f64).collect();
+        for i in 1..=self.word.len() {
+            let (a, near) = (self.word[i - 1], &self.near[i - 1]);
+            let [before, previous, current] = rows.get_disjoint_mut([(i + 1) % 3, (i + 2) % 3, i % 3]).expect("distinct rows");
+            current[0] = i as f64;
+            for j in 1..width {
+                let b = other[j - 1];
+                let substitution = match (a == b, near.contains(&b)) {
+                    (true, _) => 0.0,
+                    (false, true) => ADJACENT,
+                    (false, false) => 1.0,
+                };
+                let mut best = (previous[j] + 1.0).min(current[j - 1] + 1.0).min(previous[j - 1] + substitution);
+                if i > 1 && j > 1 && a == other[j - 2] && self.word[i - 2] == b {
+                    best = best.min(before[j - 2] + 1.0);
+                }
+                current[j] = best;
+            }
+            // Every path runs through this row, so none can come in under max.
+            if current.iter().all(|&d| d > max) {
+                return None;
+            }
+        }
+        let distance = rows[self.word.len() % 3][other.len()];
+        (distance <= max).then_some(distance)
+    }
+}
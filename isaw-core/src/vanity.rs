@@ -0,0 +1,112 @@
+//! Vanity hashes: candidates `c` for which `hash(prefix || c || suffix)`
+//! begins with chosen hex digits, as for vanity git object ids or
+//! proof-of-work style puzzles.
+//!
+//! With an object type, the input is wrapped the way git hashes objects,
+//! `<type> <length>\0<content>`, so the digest is the id git would give
+//! the object.
+
+use crate::{sha1, sha256};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    Sha1,
+    Sha256,
+}
+
+/// Accepts `sha1` and `sha256`.
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_ascii_lowercase().replace('-', "").as_str() {
+            "sha1" => Ok(Algorithm::Sha1),
+            "sha256" => Ok(Algorithm::Sha256),
+            _ => Err(format!("unknown hash algorithm '{}' (expected sha1 or sha256)", s)),
+        }
+    }
+}
+
+impl Algorithm {
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Sha1 => "sha1",
+            Algorithm::Sha256 => "sha256",
+        }
+    }
+
+    /// Hex digits in a digest.
+    pub fn width(self) -> usize {
+        match self {
+            Algorithm::Sha1 => 40,
+            Algorithm::Sha256 => 64,
+        }
+    }
+
+    pub fn digest(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Algorithm::Sha1 => sha1::digest(data).to_vec(),
+            Algorithm::Sha256 => sha256::digest(data).to_vec(),
+        }
+    }
+}
+
+pub struct Vanity {
+    algorithm: Algorithm,
+    prefix: Vec<u8>,
+    suffix: Vec<u8>,
+    /// Git object type to wrap the input as, e.g. `blob` or `commit`.
+    object: Option<String>,
+    /// The wanted leading digest nibbles.
+    target: Vec<u8>,
+}
+
+impl Vanity {
+    /// Fails unless `target` is hex digits that fit in a digest.
+    pub fn new(algorithm: Algorithm, target: &str, prefix: Vec<u8>, suffix: Vec<u8>, object: Option<String>) -> Result<Self, String> {
+        let target = target
+            .chars()
+            .map(|c| c.to_digit(16).map(|n| n as u8))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(|| format!("'{}' is not hex", target))?;
+        if target.is_empty() || target.len() > algorithm.width() {
+            return Err(format!("a {} prefix has 1 to {} hex digits", algorithm.name(), algorithm.width()));
+        }
+        Ok(Vanity { algorithm, prefix, suffix, object, target })
+    }
+
+    /// Candidates tried for each hit, on average.
+    pub fn difficulty(&self) -> f64 {
+        16f64.powi(self.target.len() as i32)
+    }
+
+    /// The bytes hashed for `candidate`.
+    pub fn input(&self, candidate: &str) -> Vec<u8> {
+        let length = self.prefix.len() + candidate.len() + self.suffix.len();
+        let mut data = Vec::with_capacity(length + 32);
+        if let Some(object) = &self.object {
+            data.extend_from_slice(format!("{} {}\0", object, length).as_bytes());
+        }
+        data.extend_from_slice(&self.prefix);
+        data.extend_from_slice(candidate.as_bytes());
+        data.extend_from_slice(&self.suffix);
+        data
+    }
+
+    pub fn digest(&self, candidate: &str) -> Vec<u8> {
+        self.algorithm.digest(&self.input(candidate))
+    }
+
+    pub fn hex_digest(&self, candidate: &str) -> String {
+        self.digest(candidate).iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    pub fn is_match(&self, candidate: &str) -> bool {
+        let digest = self.digest(candidate);
+        self.target.iter().enumerate().all(|(i, &nibble)| {
+            let byte = digest[i / 2];
+            nibble == if i % 2 == 0 { byte >> 4 } else { byte & 0xf }
+        })
+    }
+}
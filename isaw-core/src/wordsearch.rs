@@ -0,0 +1,82 @@
+//! Word search puzzles: the dictionary words hidden in a letter grid,
+//! read in a straight line in any of the eight directions.
+//!
+//! From every cell and direction the trie is walked one letter at a time,
+//! so a line is abandoned as soon as no word starts with what it spells.
+
+use crate::trie::{Trie, ROOT};
+
+/// Row and column steps, clockwise from north.
+pub const DIRECTIONS: [(&str, isize, isize); 8] = [
+    ("N", -1, 0),
+    ("NE", -1, 1),
+    ("E", 0, 1),
+    ("SE", 1, 1),
+    ("S", 1, 0),
+    ("SW", 1, -1),
+    ("W", 0, -1),
+    ("NW", -1, -1),
+];
+
+#[derive(Clone, Debug)]
+pub struct Hit {
+    pub word: String,
+    /// 0-based row and column of the first letter.
+    pub row: usize,
+    pub column: usize,
+    /// Index into [`DIRECTIONS`].
+    pub direction: usize,
+}
+
+impl Hit {
+    /// The cells the word covers, first letter first.
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let (_, dr, dc) = DIRECTIONS[self.direction];
+        (0..self.word.chars().count() as isize).map(move |i| ((self.row as isize + dr * i) as usize, (self.column as isize + dc * i) as usize))
+    }
+}
+
+/// The letters of a grid, one row per non-blank line; spaces between
+/// letters are ignored.
+pub fn parse(spec: &str) -> Result<Vec<Vec<char>>, String> {
+    let rows: Vec<Vec<char>> = spec
+        .lines()
+        .map(|line| line.chars().filter(|c| !c.is_whitespace()).collect::<Vec<char>>())
+        .filter(|row| !row.is_empty())
+        .collect();
+    if rows.is_empty() {
+        return Err("the grid is empty".to_string());
+    }
+    if let Some(i) = rows.iter().position(|row| row.len() != rows[0].len()) {
+        return Err(format!("row {} has {} letters, row 1 has {}", i + 1, rows[i].len(), rows[0].len()));
+    }
+    Ok(rows)
+}
+
+/// Every word of `trie` at least `min` letters long found in `grid`, in
+/// grid order. Palindromes are found once per direction they read in.
+pub fn find(grid: &[Vec<char>], trie: &Trie, min: usize) -> Vec<Hit> {
+    let (height, width) = (grid.len() as isize, grid.first().map_or(0, Vec::len) as isize);
+    let mut hits = Vec::new();
+    for row in 0..height {
+        for column in 0..width {
+            for (direction, &(_, dr, dc)) in DIRECTIONS.iter().enumerate() {
+                let (mut r, mut c) = (row, column);
+                let mut node = ROOT;
+                let mut word = String::new();
+                while (0..height).contains(&r) && (0..width).contains(&c) {
+                    let letter = grid[r as usize][c as usize];
+                    let Some(next) = trie.child(node, letter) else { break };
+                    node = next;
+                    word.push(letter);
+                    if trie.is_word(node) && word.chars().count() >= min {
+                        hits.push(Hit { word: word.clone(), row: row as usize, column: column as usize, direction });
+                    }
+                    r += dr;
+                    c += dc;
+                }
+            }
+        }
+    }
+    hits
+}
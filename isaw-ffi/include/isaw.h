@@ -0,0 +1,53 @@
+/*
+ * C interface to the isaw generation engine (libisaw_ffi).
+ *
+ * Every generator returns an opaque IsawIter. Pull results with
+ * isaw_iter_next(), or push them into a callback with isaw_iter_for_each(),
+ * then release the handle with isaw_iter_free(). Strings are NUL-terminated
+ * UTF-8. Constructors return NULL on error; isaw_last_error() explains why.
+ */
+
+#ifndef ISAW_H
+#define ISAW_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct IsawIter IsawIter;
+
+/* Return nonzero to stop iterating. */
+typedef int (*IsawCallback)(const char *word, size_t len, void *user_data);
+
+#define ISAW_IGNORE_CASE 1u
+#define ISAW_REGEX 2u
+
+/* max == 0 means "all letters". */
+IsawIter *isaw_permutations_new(const char *letters, size_t min, size_t max);
+IsawIter *isaw_combinations_new(const char *letters, size_t length);
+IsawIter *isaw_words_new(const char *letters, size_t min, size_t max, bool unique);
+IsawIter *isaw_search_new(const char *pattern, const char *alphabet, size_t length, uint32_t flags);
+
+/* Dictionary words (one per line in the file at `dictionary`) that are
+ * anagrams of all of `letters`, or that `letters` can spell. */
+IsawIter *isaw_anagrams_new(const char *letters, const char *dictionary);
+IsawIter *isaw_dictionary_words_new(const char *letters, const char *dictionary, size_t min);
+
+/* NULL when exhausted. The string is valid until the next call on `iter`;
+ * `*len` is its length in bytes. Dictionary lines holding a NUL byte are
+ * skipped, since they cannot be C strings. */
+const char *isaw_iter_next(IsawIter *iter, size_t *len);
+size_t isaw_iter_for_each(IsawIter *iter, IsawCallback callback, void *user_data);
+void isaw_iter_free(IsawIter *iter);
+
+const char *isaw_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* ISAW_H */
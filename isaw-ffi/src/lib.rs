@@ -0,0 +1,251 @@
+//! C ABI for embedding the isaw engine; see `include/isaw.h` for the C view.
+//!
+//! Generators are exposed two ways: as opaque [`IsawIter`] handles that the
+//! caller pulls from with [`isaw_iter_next`], or through [`isaw_iter_for_each`]
+//! which pushes every result into a callback. All strings are NUL-terminated
+//! UTF-8. Functions that can fail return NULL and leave a message for
+//! [`isaw_last_error`].
+
+use isaw_core::dictionary::Dictionary;
+use isaw_core::rack::{self, Rack};
+use isaw_core::{generate, Matcher};
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::ptr;
+
+/// Called once per result with the word, its length in bytes (excluding the
+/// NUL) and the caller's `user_data`. Returning nonzero stops the iteration.
+pub type IsawCallback = extern "C" fn(word: *const c_char, len: usize, user_data: *mut c_void) -> c_int;
+
+/// Opaque generator handle.
+pub struct IsawIter {
+    inner: Box<dyn Iterator<Item = String>>,
+    current: CString,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn set_error(message: String) {
+    let message = CString::new(message).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = message);
+}
+
+fn new_iter(inner: impl Iterator<Item = String> + 'static) -> *mut IsawIter {
+    Box::into_raw(Box::new(IsawIter { inner: Box::new(inner), current: CString::default() }))
+}
+
+/// Reads a caller string, recording an error for NULL or invalid UTF-8.
+unsafe fn input<'a>(s: *const c_char, what: &str) -> Option<&'a str> {
+    if s.is_null() {
+        set_error(format!("{} must not be NULL", what));
+        return None;
+    }
+    match unsafe { CStr::from_ptr(s) }.to_str() {
+        Ok(s) => Some(s),
+        Err(_) => {
+            set_error(format!("{} is not valid UTF-8", what));
+            None
+        }
+    }
+}
+
+/// Message describing the most recent failure on this thread ("" if none).
+/// The pointer stays valid until the next failing call on the same thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn isaw_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ptr())
+}
+
+/// Permutations of `min..=max` letters; `max == 0` means all letters.
+///
+/// # Safety
+///
+/// `letters` must be a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn isaw_permutations_new(letters: *const c_char, min: usize, max: usize) -> *mut IsawIter {
+    let Some(letters) = (unsafe { input(letters, "letters") }) else { return ptr::null_mut() };
+    let chars: Vec<char> = letters.chars().collect();
+    let max = if max == 0 { chars.len() } else { max };
+    new_iter(generate::permutations(chars, min, max))
+}
+
+/// Selections of `length` letters where order doesn't matter.
+///
+/// # Safety
+///
+/// `letters` must be a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn isaw_combinations_new(letters: *const c_char, length: usize) -> *mut IsawIter {
+    let Some(letters) = (unsafe { input(letters, "letters") }) else { return ptr::null_mut() };
+    new_iter(generate::combinations(letters.chars().collect(), length))
+}
+
+/// Words of `min..=max` letters (`max == 0` means all letters), optionally
+/// without repeats.
+///
+/// # Safety
+///
+/// `letters` must be a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn isaw_words_new(letters: *const c_char, min: usize, max: usize, unique: bool) -> *mut IsawIter {
+    let Some(letters) = (unsafe { input(letters, "letters") }) else { return ptr::null_mut() };
+    let chars: Vec<char> = letters.chars().collect();
+    let max = if max == 0 { chars.len() } else { max };
+    new_iter(generate::words(chars, min, max, unique))
+}
+
+/// Case-insensitive substring matching for [`isaw_search_new`].
+pub const ISAW_IGNORE_CASE: u32 = 1;
+/// Treat the [`isaw_search_new`] pattern as a regex.
+pub const ISAW_REGEX: u32 = 2;
+
+/// `length`-letter strings over `alphabet` matching `pattern`. Returns NULL
+/// if the regex does not compile.
+///
+/// # Safety
+///
+/// `pattern` and `alphabet` must be NUL-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn isaw_search_new(pattern: *const c_char, alphabet: *const c_char, length: usize, flags: u32) -> *mut IsawIter {
+    let Some(pattern) = (unsafe { input(pattern, "pattern") }) else { return ptr::null_mut() };
+    let Some(alphabet) = (unsafe { input(alphabet, "alphabet") }) else { return ptr::null_mut() };
+
+    let matcher = if flags & ISAW_REGEX != 0 {
+        match Matcher::regex(pattern) {
+            Ok(matcher) => matcher,
+            Err(e) => {
+                set_error(format!("Invalid regex: {}", e));
+                return ptr::null_mut();
+            }
+        }
+    } else {
+        Matcher::substring(pattern, flags & ISAW_IGNORE_CASE != 0)
+    };
+
+    let chars: Vec<char> = alphabet.chars().collect();
+    let words = generate::Product::new(&chars, length, 0)
+        .filter(move |word| matcher.is_match(word));
+    new_iter(words)
+}
+
+/// Loads a wordlist, one word per line, recording an error on failure.
+fn load_dictionary(path: &str) -> Option<Dictionary> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            set_error(format!("Cannot read {}: {}", path, e));
+            return None;
+        }
+    };
+    Dictionary::new(bytes).map_err(|e| set_error(format!("Cannot use {}: {}", path, e))).ok()
+}
+
+/// The words in `dictionary` matching `keep`, each once, in file order.
+fn dictionary_matches(dictionary: &Dictionary, mut keep: impl FnMut(&str) -> bool) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    dictionary.words().filter(|word| keep(word) && seen.insert(word.to_lowercase())).map(str::to_string).collect()
+}
+
+/// Words in the wordlist at `dictionary` that use every one of `letters`
+/// exactly once, ignoring case. Returns NULL if the file can't be read.
+///
+/// # Safety
+///
+/// `letters` and `dictionary` must be NUL-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn isaw_anagrams_new(letters: *const c_char, dictionary: *const c_char) -> *mut IsawIter {
+    let Some(letters) = (unsafe { input(letters, "letters") }) else { return ptr::null_mut() };
+    let Some(path) = (unsafe { input(dictionary, "dictionary") }) else { return ptr::null_mut() };
+    let Some(dictionary) = load_dictionary(path) else { return ptr::null_mut() };
+    let signature = rack::signature(letters);
+    new_iter(dictionary_matches(&dictionary, |word| rack::signature(word) == signature).into_iter())
+}
+
+/// Words of at least `min` letters in the wordlist at `dictionary` that
+/// `letters` can spell, each letter used at most as often as it is given,
+/// like `isaw words -d`. Returns NULL if the file can't be read.
+///
+/// # Safety
+///
+/// `letters` and `dictionary` must be NUL-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn isaw_dictionary_words_new(letters: *const c_char, dictionary: *const c_char, min: usize) -> *mut IsawIter {
+    let Some(letters) = (unsafe { input(letters, "letters") }) else { return ptr::null_mut() };
+    let Some(path) = (unsafe { input(dictionary, "dictionary") }) else { return ptr::null_mut() };
+    let Some(dictionary) = load_dictionary(path) else { return ptr::null_mut() };
+    let rack = Rack::new(letters);
+    new_iter(dictionary_matches(&dictionary, |word| word.chars().count() >= min && rack.can_spell(word)).into_iter())
+}
+
+/// Advances the iterator. Returns the next result, or NULL when exhausted.
+/// The string is owned by the iterator and valid until the next call; its
+/// byte length is stored in `*len` when `len` is non-NULL. Results with a
+/// NUL byte in them (only possible from dictionary lines) cannot be C
+/// strings and are skipped.
+///
+/// # Safety
+///
+/// `iter` must be a live handle from one of the `*_new` functions.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn isaw_iter_next(iter: *mut IsawIter, len: *mut usize) -> *const c_char {
+    let iter = unsafe { &mut *iter };
+    let Some(word) = iter.inner.by_ref().find_map(|word| CString::new(word).ok()) else { return ptr::null() };
+    iter.current = word;
+    if !len.is_null() {
+        unsafe { *len = iter.current.as_bytes().len() };
+    }
+    iter.current.as_ptr()
+}
+
+/// Feeds every remaining result to `callback`; returns how many were delivered.
+///
+/// # Safety
+///
+/// `iter` must be a live handle from one of the `*_new` functions.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn isaw_iter_for_each(iter: *mut IsawIter, callback: IsawCallback, user_data: *mut c_void) -> usize {
+    let mut delivered = 0;
+    let mut len = 0;
+    loop {
+        let word = unsafe { isaw_iter_next(iter, &mut len) };
+        if word.is_null() {
+            break;
+        }
+        delivered += 1;
+        if callback(word, len, user_data) != 0 {
+            break;
+        }
+    }
+    delivered
+}
+
+/// Releases an iterator. Passing NULL is a no-op.
+///
+/// # Safety
+///
+/// `iter` must be NULL or a handle that has not been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn isaw_iter_free(iter: *mut IsawIter) {
+    if !iter.is_null() {
+        drop(unsafe { Box::from_raw(iter) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_with_nul_are_skipped_and_lengths_match_the_buffer() {
+        let words = vec!["a\0b".to_string(), "cd".to_string()];
+        let iter = new_iter(words.into_iter());
+        let mut len = 0;
+        let word = unsafe { isaw_iter_next(iter, &mut len) };
+        assert_eq!(unsafe { CStr::from_ptr(word) }.to_bytes(), b"cd");
+        assert_eq!(len, 2);
+        assert!(unsafe { isaw_iter_next(iter, &mut len) }.is_null());
+        unsafe { isaw_iter_free(iter) };
+    }
+}
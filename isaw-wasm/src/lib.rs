@@ -0,0 +1,121 @@
+//! WebAssembly bindings for running the isaw engine in a browser, via
+//! wasm-bindgen. Strings and arrays of strings cross the boundary as plain
+//! JavaScript values, and bad input (a regex that does not compile) throws.
+//!
+//! ```js
+//! import init, { permutations, search, Dictionary } from "./pkg/isaw_wasm.js";
+//! await init();
+//! permutations("abc", 2);                           // ["ab", "ac", ...]
+//! const words = new Dictionary(await (await fetch("words.txt")).text());
+//! words.anagrams("listen");                         // ["enlist", "listen", "silent", ...]
+//! search("^c.t$", undefined, 3, false, true);
+//! ```
+//!
+//! Anagrams come from a wordlist the page supplies once as a [`Dictionary`],
+//! the way the FFI takes a dictionary file, rather than from every
+//! rearrangement of the letters.
+
+use isaw_core::rack::{self, Rack};
+use isaw_core::{dictionary, generate, Matcher};
+use std::collections::HashSet;
+use wasm_bindgen::prelude::*;
+
+/// Permutations of `min..=max` letters (defaults: 1 and all of them).
+#[wasm_bindgen]
+pub fn permutations(letters: &str, min: Option<usize>, max: Option<usize>) -> Vec<String> {
+    let chars: Vec<char> = letters.chars().collect();
+    let max = max.unwrap_or(chars.len()).min(chars.len());
+    generate::permutations(chars, min.unwrap_or(1), max).collect()
+}
+
+/// Selections of `length` letters where order doesn't matter.
+#[wasm_bindgen]
+pub fn combinations(letters: &str, length: usize) -> Vec<String> {
+    generate::combinations(letters.chars().collect(), length).collect()
+}
+
+/// `length`-letter strings (default 3) over `letters` (default a-z) that
+/// match `pattern`, a substring unless `regex` is set.
+#[wasm_bindgen]
+pub fn search(pattern: &str, letters: Option<String>, length: Option<usize>, ignore_case: Option<bool>, regex: Option<bool>) -> Result<Vec<String>, JsError> {
+    let matcher = matcher(pattern, ignore_case.unwrap_or(false), regex.unwrap_or(false)).map_err(|e| JsError::new(&e))?;
+    let chars: Vec<char> = letters.as_deref().unwrap_or("abcdefghijklmnopqrstuvwxyz").chars().collect();
+    Ok(generate::Product::new(&chars, length.unwrap_or(3), 0).filter(|word| matcher.is_match(word)).collect())
+}
+
+fn matcher(pattern: &str, ignore_case: bool, regex: bool) -> Result<Matcher, String> {
+    match regex {
+        true => Matcher::regexes(&[pattern.to_string()], ignore_case).map_err(|e| format!("Invalid regex: {}", e)),
+        false => Ok(Matcher::substring(pattern, ignore_case)),
+    }
+}
+
+/// A wordlist, one word per line, to find anagrams and spellable words in.
+#[wasm_bindgen]
+pub struct Dictionary {
+    inner: dictionary::Dictionary,
+}
+
+#[wasm_bindgen]
+impl Dictionary {
+    #[wasm_bindgen(constructor)]
+    pub fn new(text: String) -> Result<Dictionary, JsError> {
+        dictionary::Dictionary::new(text.into_bytes()).map(|inner| Dictionary { inner }).map_err(JsError::new)
+    }
+
+    /// Words using every one of `letters` exactly once, ignoring case.
+    pub fn anagrams(&self, letters: &str) -> Vec<String> {
+        let signature = rack::signature(letters);
+        self.matches(|word| rack::signature(word) == signature)
+    }
+
+    /// Words of at least `min` letters (default 2) that `letters` can
+    /// spell, each letter used at most as often as it is given.
+    pub fn words(&self, letters: &str, min: Option<usize>) -> Vec<String> {
+        let rack = Rack::new(letters);
+        let min = min.unwrap_or(2);
+        self.matches(|word| word.chars().count() >= min && rack.can_spell(word))
+    }
+
+    /// Whether the wordlist holds `word`.
+    pub fn contains(&self, word: &str) -> bool {
+        self.inner.contains(word)
+    }
+}
+
+impl Dictionary {
+    /// The words matching `keep`, each once, in file order.
+    fn matches(&self, mut keep: impl FnMut(&str) -> bool) -> Vec<String> {
+        let mut seen = HashSet::new();
+        self.inner.words().filter(|word| keep(word) && seen.insert(word.to_lowercase())).map(str::to_string).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words() -> Dictionary {
+        Dictionary { inner: dictionary::Dictionary::new(b"listen\nsilent\nenlist\nlist\ntinsel\nlistens\n".to_vec()).unwrap() }
+    }
+
+    #[test]
+    fn anagrams_come_from_the_dictionary() {
+        assert_eq!(words().anagrams("Silent"), ["listen", "silent", "enlist", "tinsel"]);
+        assert!(words().anagrams("tsil").contains(&"list".to_string()));
+        assert!(words().anagrams("xyz").is_empty());
+    }
+
+    #[test]
+    fn spellable_words_respect_min() {
+        assert_eq!(words().words("stil", Some(4)), ["list"]);
+    }
+
+    #[test]
+    fn generators_and_search() {
+        assert_eq!(permutations("ab", None, None), ["a", "b", "ab", "ba"]);
+        assert_eq!(combinations("abc", 2), ["ab", "ac", "bc"]);
+        assert_eq!(search("^c.t$", Some("act".to_string()), Some(3), None, Some(true)).unwrap(), ["cat", "cct", "ctt"]);
+        assert!(matcher("(", false, true).is_err());
+    }
+}
@@ -0,0 +1,30 @@
+// The wasm-bindgen build, exercised from Node. Run after
+//   cargo build -p isaw-wasm --release --target wasm32-unknown-unknown
+//   wasm-bindgen --target nodejs --out-dir isaw-wasm/pkg target/wasm32-unknown-unknown/release/isaw_wasm.wasm
+// with `node --test isaw-wasm/tests`.
+
+import test from "node:test";
+import assert from "node:assert/strict";
+import { createRequire } from "node:module";
+
+const isaw = createRequire(import.meta.url)("../pkg/isaw_wasm.js");
+
+test("anagrams come from the caller's wordlist", () => {
+  const words = new isaw.Dictionary("listen\nsilent\nenlist\nlist\ntinsel\n");
+  assert.deepEqual(words.anagrams("silent"), ["listen", "silent", "enlist", "tinsel"]);
+  assert.deepEqual(words.anagrams("xyz"), []);
+  assert.deepEqual(words.words("stil", 4), ["list"]);
+  assert.ok(words.contains("enlist"));
+  words.free();
+});
+
+test("generators take optional arguments", () => {
+  assert.deepEqual(isaw.permutations("ab"), ["a", "b", "ab", "ba"]);
+  assert.deepEqual(isaw.permutations("abc", 3, 3).length, 6);
+  assert.deepEqual(isaw.combinations("abc", 2), ["ab", "ac", "bc"]);
+  assert.deepEqual(isaw.search("^c.t$", "act", 3, false, true), ["cat", "cct", "ctt"]);
+});
+
+test("a bad regex throws", () => {
+  assert.throws(() => isaw.search("(", undefined, 3, false, true), /Invalid regex/);
+});
@@ -0,0 +1,49 @@
+//! `isaw analyze`: n-gram tables from a corpus, and loading them back.
+//!
+//! Anything taking letter statistics — `--ngrams` for the cipher solvers
+//! and score filters, `--corpus` for `isaw sample` — accepts a tables file
+//! as well as its usual input, so every scoring feature can be tuned to a
+//! language from one corpus.
+
+use crate::cli::AnalyzeArgs;
+use crate::Result;
+use colored::*;
+use isaw_core::ngram::{self, Model, Tables};
+use std::io::Write;
+use std::path::Path;
+
+pub fn analyze(out: &mut dyn Write, args: AnalyzeArgs) -> Result<()> {
+    let text = std::fs::read_to_string(&args.corpus).map_err(|e| format!("Cannot read {}: {}", args.corpus.display(), e))?;
+    writeln!(out, "{}", format!("📊 Analyzing {}", args.corpus.display()).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    let tables = Tables::from_text(&text);
+    for n in 1..=4 {
+        let grams = tables.counts(n);
+        let total: u64 = grams.iter().map(|(_, count)| count).sum();
+        let top: Vec<&str> = grams.iter().take(5).map(|(gram, _)| gram.as_str()).collect();
+        writeln!(out, "  {}-grams: {:>8} distinct, {:>10} total  {}", n, grams.len(), total, top.join(" ").dimmed())?;
+    }
+    std::fs::write(&args.output, tables.to_bytes()).map_err(|e| format!("Cannot write {}: {}", args.output.display(), e))?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    writeln!(out, "{}", format!("✨ Wrote tables to {}", args.output.display()).green().bold())?;
+    Ok(())
+}
+
+/// A model from `GRAM COUNT` lines or an `isaw analyze` tables file.
+pub fn model(path: &Path) -> Result<Model> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let model = match Tables::recognize(&bytes) {
+        true => Tables::from_bytes(&bytes).and_then(|tables| tables.model()),
+        false => Model::parse(&String::from_utf8_lossy(&bytes)),
+    };
+    Ok(model.map_err(|e| format!("{}: {}", path.display(), e))?)
+}
+
+/// Single-letter counts of a text file or an `isaw analyze` tables file.
+pub fn letter_counts(path: &Path) -> Result<Vec<(String, u64)>> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    match Tables::recognize(&bytes) {
+        true => Ok(Tables::from_bytes(&bytes).map_err(|e| format!("{}: {}", path.display(), e))?.counts(1).to_vec()),
+        false => Ok(ngram::counts(&String::from_utf8_lossy(&bytes), 1)),
+    }
+}
@@ -0,0 +1,163 @@
+//! `isaw bench`: how long would this job take?
+//!
+//! Runs growing slices from the start of the job's keyspace until a sample
+//! takes about `--seconds`, then extrapolates the measured rate (generation
+//! and matching together, exactly as the real run would do them) to the
+//! whole keyspace. `--estimate` does the same with a short sample before
+//! a real run, and asks whether to go on.
+
+use crate::cli::{letters_or_preset, BenchArgs, Commands};
+use crate::distributed::parse_job;
+use crate::output::{human, si};
+use crate::{run, Invalid, Problem, Result};
+use colored::*;
+use isaw_core::case;
+use isaw_core::generate::Keyspace;
+use isaw_core::rank::Slice;
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
+
+/// Counts the result lines a sample prints.
+struct Lines(u64);
+
+impl Write for Lines {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.iter().filter(|&&b| b == b'\n').count() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Seconds `--estimate` samples for.
+const ESTIMATE_SECONDS: f64 = 1.0;
+
+pub fn bench(out: &mut dyn Write, args: BenchArgs) -> Result<()> {
+    let mut cli = parse_job(&args.job)?;
+    if cli.command.run_opts_mut().is_some_and(|run| run.slice.is_some()) {
+        return Err("bench picks its own sample; drop --slice from the job".into());
+    }
+    let total = total(&cli.command, "Only generating subcommands can be benchmarked")?;
+    let target = Duration::from_secs_f64(args.seconds.max(0.1));
+
+    writeln!(out, "{}", format!("⏱  Benchmarking `{}`", args.job.join(" ")).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    out.flush()?;
+
+    let (candidates, matches, elapsed) = sample(&args.job, total, target)?;
+    let rate = candidates as f64 / elapsed.as_secs_f64().max(1e-9);
+    let share = matches as f64 / candidates.max(1) as f64;
+    writeln!(out, "  Keyspace:   {} candidates", total.to_string().white().bold())?;
+    writeln!(out, "  Sampled:    {} candidates in {:.2}s", candidates, elapsed.as_secs_f64())?;
+    writeln!(out, "  Throughput: {} candidates/sec", si(rate).yellow().bold())?;
+    writeln!(out, "  Matches:    {} ({:.4}%), ~{} projected", matches, share * 100.0, si(share * total as f64))?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    writeln!(out, "{}", format!("✨ Projected full run: {}", human(total as f64 / rate)).green().bold())?;
+    Ok(())
+}
+
+/// `--estimate`: samples the run `job` describes, prints how long all of
+/// it (or its `--slice`) will take on stderr and asks whether to start.
+/// Anything but yes, including stdin running dry, means no.
+pub fn estimate(job: &[String]) -> Result<bool> {
+    let mut cli = parse_job(job)?;
+    let keyspace = total(&cli.command, "--estimate only works for permutations, combinations, words, search and dna")?;
+    // The sample comes from the start of the keyspace; the slice only
+    // changes how much there is to extrapolate to.
+    let total = match cli.command.run_opts_mut().and_then(|run| run.slice) {
+        Some(slice) => {
+            let range = slice.range(keyspace);
+            range.end - range.start
+        }
+        None => keyspace,
+    };
+
+    errln!("{}", "⏱  Estimating the run's duration...".dimmed());
+    let (candidates, matches, elapsed) = sample(job, keyspace, Duration::from_secs_f64(ESTIMATE_SECONDS))?;
+    let rate = candidates as f64 / elapsed.as_secs_f64().max(1e-9);
+    let share = matches as f64 / candidates.max(1) as f64;
+    errln!("{}", format!("⏱  {} candidates at {}/sec, ~{} matches: about {}", si(total as f64), si(rate), si(share * total as f64), human(total as f64 / rate)).cyan().bold());
+    eprint!("Continue? [y/N] ");
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Candidates in the keyspace of `command`, which must not be empty.
+fn total(command: &Commands, unsupported: &str) -> Result<u128> {
+    let (keyspace, n, lengths) = keyspace(command).ok_or(unsupported)?;
+    let total = keyspace.total(n, lengths).ok_or_else(|| Invalid(Problem::Overflow, "Keyspace too large to benchmark".to_string()))?;
+    if total == 0 {
+        return Err("The job's keyspace is empty".into());
+    }
+    Ok(total)
+}
+
+/// Runs growing slices from the start of `job`'s keyspace of `total`
+/// until one takes about `target`; `(candidates, matches, elapsed)`.
+fn sample(job: &[String], total: u128, target: Duration) -> Result<(u128, u64, Duration)> {
+    let mut sample: u128 = 10_000.min(total);
+    let (candidates, matches, elapsed) = loop {
+        let parts = total.div_ceil(sample);
+        let slice = Slice { index: 0, count: parts };
+        let range = slice.range(total);
+        let candidates = range.end - range.start;
+
+        let mut job = parse_job(job)?;
+        if let Some(run) = job.command.run_opts_mut() {
+            run.slice = Some(slice);
+            run.quiet = true;
+            run.sort = false;
+            run.estimate = false;
+        }
+        let mut lines = Lines(0);
+        let started = Instant::now();
+        run(job.command, &mut lines)?;
+        let elapsed = started.elapsed();
+
+        if elapsed >= target / 2 || parts == 1 {
+            break (candidates, lines.0, elapsed);
+        }
+        // Aim straight for the target, but don't trust tiny samples too far.
+        let scale = (target.as_secs_f64() / elapsed.as_secs_f64().max(1e-6)).min(16.0);
+        sample = ((sample as f64 * scale) as u128).clamp(sample + 1, total);
+    };
+    Ok((candidates, matches, elapsed))
+}
+
+/// The keyspace a job enumerates: its kind, letter count and lengths.
+fn keyspace(command: &Commands) -> Option<(Keyspace, usize, RangeInclusive<usize>)> {
+    match command {
+        Commands::Permutations(args) => {
+            let n = letters_or_preset(&args.letters, &args.alphabet).chars().count();
+            Some((Keyspace::Permutations, n, args.min..=args.max.unwrap_or(n)))
+        }
+        Commands::Words(args) => {
+            let n = letters_or_preset(&args.letters, &args.alphabet).chars().count();
+            Some((Keyspace::Permutations, n, args.min..=args.max.unwrap_or(n)))
+        }
+        Commands::Combinations(args) => {
+            let n = letters_or_preset(&args.letters, &args.alphabet).chars().count();
+            Some((Keyspace::Combinations, n, args.lengths(n)))
+        }
+        // A template's mixed-radix keyspace slices like one letter from its
+        // total, so it can stand in as a product of length 1.
+        Commands::Search(args) if args.template.is_some() => {
+            let positions = isaw_core::mask::template(args.template.as_deref()?).ok()?;
+            let total = positions.iter().try_fold(1usize, |total, chars| total.checked_mul(chars.len()))?;
+            Some((Keyspace::Product, total, 1..=1))
+        }
+        // --any-case widens Search's alphabet rather than multiplying results.
+        Commands::Search(args) => {
+            let letters: Vec<char> = args.letters().chars().collect();
+            let n = if args.run.any_case { case::expand(&letters).len() } else { letters.len() };
+            Some((Keyspace::Product, n, args.lengths()))
+        }
+        Commands::Dna(args) => Some((Keyspace::Product, if args.run.any_case { 8 } else { 4 }, args.length..=args.length)),
+        _ => None,
+    }
+}
@@ -0,0 +1,417 @@
+//! Cipher cracking: candidate plaintexts ranked by how English they look.
+//!
+//! Candidates are scored with an n-gram [`Model`] (`--ngrams`, or the
+//! built-in English quadgrams) and, when wordlists are given, by how many of their
+//! words are real; real words outrank n-gram fitness.
+//!
+//! `isaw subst` searches the 26! substitution keys by hill-climbing: from a
+//! random key, swap two letters and keep the swap whenever fitness improves,
+//! until a thousand swaps in a row fail. Restarts run in parallel from
+//! different random keys and their results are ranked together.
+//!
+//! `isaw cryptogram` solves cryptoquotes the way people do, by word
+//! shapes: a ciphertext word can only stand for wordlist words whose
+//! letters repeat in the same places (`XQQX` for `deed`, `noon`, ...). Keys
+//! that make every word real are found by backtracking, tightest words
+//! first, and ranked by n-gram fitness.
+//!
+//! `isaw vigenere` treats each key letter's column as a Caesar shift and
+//! keeps its likeliest shifts by chi-squared distance from English letter
+//! frequencies. The keys built from those are ranked by n-gram fitness,
+//! and the best are refined one column at a time.
+
+use crate::cli::{CaesarArgs, CryptogramArgs, SubstArgs, VigenereArgs};
+use crate::rng::{self, Rng};
+use crate::{analyze, wordlist, Result};
+use colored::*;
+use isaw_core::{cipher, count, ngram};
+use isaw_core::dictionary::Dictionary;
+use isaw_core::ngram::Model;
+use rayon::prelude::*;
+use std::cmp::Ordering;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// How plausible a candidate plaintext is.
+#[derive(Clone, Copy, Debug)]
+pub struct Score {
+    /// Words found in the wordlists, out of `words`.
+    pub known: usize,
+    pub words: usize,
+    /// n-gram log probability.
+    pub fitness: f64,
+}
+
+impl Score {
+    fn rank(&self, other: &Score) -> Ordering {
+        self.known.cmp(&other.known).then(self.fitness.total_cmp(&other.fitness))
+    }
+}
+
+pub struct Scorer {
+    model: Model,
+    dictionaries: Vec<(String, Arc<Dictionary>)>,
+}
+
+impl Scorer {
+    pub fn new(ngrams: Option<&Path>, dictionary: &[PathBuf], lang: &[String]) -> Result<Self> {
+        let model = match ngrams {
+            Some(path) => analyze::model(path)?,
+            None => Model::english_quadgrams(),
+        };
+        Ok(Scorer { model, dictionaries: wordlist::resolve(dictionary, lang)? })
+    }
+
+    pub fn model(&self) -> &Model {
+        &self.model
+    }
+
+    pub fn score(&self, text: &str) -> Score {
+        self.with_fitness(text, self.model.score(text))
+    }
+
+    /// `text`'s word hits, with fitness measured by the caller.
+    pub fn with_fitness(&self, text: &str, fitness: f64) -> Score {
+        let (mut known, mut words) = (0, 0);
+        if !self.dictionaries.is_empty() {
+            for word in text.split(|c: char| !c.is_alphabetic()).filter(|word| !word.is_empty()) {
+                words += 1;
+                if self.dictionaries.iter().any(|(_, d)| d.contains(word)) {
+                    known += 1;
+                }
+            }
+        }
+        Score { known, words, fitness }
+    }
+
+    /// The score's text form: fitness, plus word hits with wordlists.
+    fn describe(&self, score: &Score) -> String {
+        match self.dictionaries.is_empty() {
+            true => format!("{:.1}", score.fitness),
+            false => format!("{:.1}  {}/{} words", score.fitness, score.known, score.words),
+        }
+    }
+}
+
+pub fn caesar(out: &mut dyn Write, args: CaesarArgs) -> Result<()> {
+    let CaesarArgs { ciphertext, dictionary, lang, ngrams } = args;
+    let scorer = Scorer::new(ngrams.as_deref(), &dictionary, &lang)?;
+    let candidates: Vec<(u8, String, Score)> = (1..26)
+        .map(|shift| {
+            // Decrypting a forward shift of `shift` rotates back by it.
+            let plaintext = cipher::shift(&ciphertext, 26 - shift);
+            let score = scorer.score(&plaintext);
+            (shift, plaintext, score)
+        })
+        .collect();
+    let best = candidates.iter().max_by(|a, b| a.2.rank(&b.2)).map(|(shift, _, _)| *shift);
+    
+    writeln!(out, "{}", format!("🔓 Caesar shifts of '{}'", ciphertext).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    for (shift, plaintext, score) in &candidates {
+        let line = format!("  ROT {:>2}  {}", shift, plaintext);
+        if Some(*shift) == best {
+            write!(out, "{}", line.yellow().bold())?;
+        } else {
+            write!(out, "{}", line)?;
+        }
+        writeln!(out, "  {}", scorer.describe(score).dimmed())?;
+    }
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    if let Some((shift, plaintext, _)) = candidates.iter().find(|(shift, _, _)| Some(*shift) == best) {
+        writeln!(out, "{}", format!("✨ Most likely: ROT {} → {}", shift, plaintext).green().bold())?;
+    }
+    Ok(())
+}
+
+/// Swaps without improvement before a climb gives up.
+const PATIENCE: u32 = 1000;
+
+pub fn subst(out: &mut dyn Write, args: SubstArgs) -> Result<()> {
+    let SubstArgs { ciphertext, dictionary, lang, ngrams, restarts, top, seed } = args;
+    let scorer = Scorer::new(ngrams.as_deref(), &dictionary, &lang)?;
+    let letters = letters(&ciphertext)?;
+    let seed = rng::seed_or_clock(seed);
+    
+    writeln!(out, "{}", format!("🔑 Solving substitution cipher ({} restarts, seed {})", restarts, seed).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    
+    let keys: Vec<[u8; 26]> = (0..restarts as u64).into_par_iter().map(|restart| climb(scorer.model(), &letters, seed.wrapping_add(restart))).collect();
+    let mut candidates: Vec<([u8; 26], String, Score)> = Vec::new();
+    for key in keys {
+        let plaintext = cipher::substitute(&ciphertext, &key);
+        if !candidates.iter().any(|(_, seen, _)| *seen == plaintext) {
+            let score = scorer.score(&plaintext);
+            candidates.push((key, plaintext, score));
+        }
+    }
+    candidates.sort_by(|a, b| b.2.rank(&a.2));
+    candidates.truncate(top);
+    
+    let used: Vec<bool> = (0..26u8).map(|c| letters.contains(&c)).collect();
+    for (i, (key, plaintext, score)) in candidates.iter().enumerate() {
+        let line = format!("  {}. {}", i + 1, plaintext);
+        if i == 0 {
+            write!(out, "{}", line.yellow().bold())?;
+        } else {
+            write!(out, "{}", line)?;
+        }
+        writeln!(out, "  {}", scorer.describe(score).dimmed())?;
+        // Only letters of the ciphertext have a meaningful mapping.
+        let mapping: String = (0..26).map(|c| if used[c] { (key[c] + b'a') as char } else { '.' }).collect();
+        writeln!(out, "     {}", format!("ABCDEFGHIJKLMNOPQRSTUVWXYZ → {}", mapping).dimmed())?;
+    }
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    writeln!(out, "{}", format!("✨ {} distinct solutions from {} restarts", candidates.len(), restarts).green().bold())?;
+    Ok(())
+}
+
+pub fn cryptogram(out: &mut dyn Write, args: CryptogramArgs) -> Result<()> {
+    let CryptogramArgs { ciphertext, dictionary, lang, ngrams, misses, limit, top } = args;
+    letters(&ciphertext)?;
+    let scorer = Scorer::new(ngrams.as_deref(), &dictionary, &lang)?;
+    let as_letters = |word: &str| word.bytes().map(|b| b.to_ascii_lowercase() - b'a').collect::<Vec<u8>>();
+    let cipher_words: Vec<Vec<u8>> = ciphertext.split(|c: char| !c.is_ascii_alphabetic()).filter(|word| !word.is_empty()).map(as_letters).collect();
+    let mut words: Vec<Vec<u8>> = scorer
+        .dictionaries
+        .iter()
+        .flat_map(|(_, words)| words.words())
+        .filter(|word| !word.is_empty() && word.bytes().all(|b| b.is_ascii_alphabetic()))
+        .map(as_letters)
+        .collect();
+    words.sort_unstable();
+    words.dedup();
+    
+    writeln!(out, "{}", format!("🧩 Solving cryptogram ({} words, {} in the wordlist)", cipher_words.len(), words.len()).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    
+    let keys = cipher::pattern_keys(&cipher_words, &words, misses, limit);
+    let mut candidates: Vec<([Option<u8>; 26], String, Score)> = Vec::new();
+    for key in &keys {
+        let plaintext = cipher::substitute_partial(&ciphertext, key);
+        if !candidates.iter().any(|(_, seen, _)| *seen == plaintext) {
+            // Unsolved letters count against fitness like impossible ones.
+            let score = scorer.score(&plaintext.replace('?', ""));
+            candidates.push((*key, plaintext, score));
+        }
+    }
+    candidates.sort_by(|a, b| b.2.rank(&a.2));
+    let solutions = candidates.len();
+    candidates.truncate(top);
+    
+    for (i, (key, plaintext, score)) in candidates.iter().enumerate() {
+        let line = format!("  {}. {}", i + 1, plaintext);
+        if i == 0 {
+            write!(out, "{}", line.yellow().bold())?;
+        } else {
+            write!(out, "{}", line)?;
+        }
+        writeln!(out, "  {}", scorer.describe(score).dimmed())?;
+        let mapping: String = key.iter().map(|p| p.map_or('.', |p| (p + b'a') as char)).collect();
+        writeln!(out, "     {}", format!("ABCDEFGHIJKLMNOPQRSTUVWXYZ → {}", mapping).dimmed())?;
+    }
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    let searched = match keys.len() >= limit {
+        true => format!(" (stopped after {} keys; raise --limit for more)", limit),
+        false => String::new(),
+    };
+    match solutions {
+        0 if misses == 0 => writeln!(out, "{}", "✨ No key turns every word into a wordlist word; try --misses 1".green().bold())?,
+        0 => writeln!(out, "{}", format!("✨ No key fits with up to {} unknown word{}", misses, if misses == 1 { "" } else { "s" }).green().bold())?,
+        n => writeln!(out, "{}", format!("✨ {} distinct solution{}{}", n, if n == 1 { "" } else { "s" }, searched).green().bold())?,
+    }
+    Ok(())
+}
+
+/// Shifts per column worth trying, the likeliest by chi-squared first.
+const CHOICES: usize = 3;
+
+/// Full keys ranked by n-gram fitness before refining the best.
+const MAX_KEYS: u128 = 1 << 16;
+
+pub fn vigenere(out: &mut dyn Write, args: VigenereArgs) -> Result<()> {
+    let VigenereArgs { ciphertext, length, known_plaintext, dictionary, lang, ngrams, top } = args;
+    let scorer = Scorer::new(ngrams.as_deref(), &dictionary, &lang)?;
+    let letters = letters(&ciphertext)?;
+    
+    if let Some(crib) = known_plaintext {
+        let stream = cipher::key_stream(&ciphertext, &crib);
+        let key = match length {
+            Some(n) if stream.len() >= n => &stream[..n],
+            Some(n) => return Err(format!("--known-plaintext needs at least {} letters to recover a {}-letter key", n, n).into()),
+            None => &stream[..cipher::period(&stream)],
+        };
+        if key.is_empty() {
+            return Err("--known-plaintext has no letters".into());
+        }
+        let plaintext = cipher::vigenere_decrypt(&ciphertext, key);
+        writeln!(out, "{}", format!("🔑 Key from known plaintext: {}", key_name(key)).cyan().bold())?;
+        writeln!(out, "{}", "─".repeat(50).dimmed())?;
+        writeln!(out, "  {}", plaintext.yellow().bold())?;
+        writeln!(out, "{}", "─".repeat(50).dimmed())?;
+        writeln!(out, "{}", format!("✨ Decrypted with {}-letter key {}", key.len(), key_name(key)).green().bold())?;
+        return Ok(());
+    }
+    
+    let length = length.ok_or("Give a key length with -n or a crib with --known-plaintext")?;
+    if length == 0 {
+        return Err("The key length must be at least 1".into());
+    }
+    // Each column is a Caesar shift, so χ² picks its likeliest shifts alone.
+    let shifts: Vec<Vec<u8>> = (0..length)
+        .map(|column| {
+            let mut by_chi: Vec<(f64, u8)> = (0..26u8)
+                .map(|shift| {
+                    let plain: Vec<u8> = letters.iter().skip(column).step_by(length).map(|&c| (c + 26 - shift) % 26).collect();
+                    (ngram::chi_squared(&plain), shift)
+                })
+                .collect();
+            by_chi.sort_by(|a, b| a.0.total_cmp(&b.0));
+            by_chi.into_iter().map(|(_, shift)| shift).collect()
+        })
+        .collect();
+    let choices = (1..=CHOICES).rev().find(|&c| count::power(c as u128, length as u128).is_some_and(|keys| keys <= MAX_KEYS)).unwrap_or(1);
+    let keys = count::power(choices as u128, length as u128).unwrap_or(1);
+    writeln!(out, "{}", format!("🔑 Ranking {} Vigenère keys of length {} ({} best shift{} per column by χ²)", keys, length, choices, if choices == 1 { "" } else { "s" }).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    
+    // The n-gram model then judges whole keys, where adjacent columns meet.
+    let fitness = |key: &[u8], plain: &mut [u8]| {
+        for (i, (p, &c)) in plain.iter_mut().zip(&letters).enumerate() {
+            *p = (c + 26 - key[i % key.len()]) % 26;
+        }
+        scorer.model.score_letters(plain)
+    };
+    let mut best = (0..keys)
+        .into_par_iter()
+        .fold(
+            || (vec![0; letters.len()], Vec::new()),
+            |(mut plain, mut best), index| {
+                let mut rest = index;
+                let key: Vec<u8> = shifts
+                    .iter()
+                    .map(|column| {
+                        let shift = column[(rest % choices as u128) as usize];
+                        rest /= choices as u128;
+                        shift
+                    })
+                    .collect();
+                let score = fitness(&key, &mut plain);
+                keep(&mut best, top, score, key);
+                (plain, best)
+            },
+        )
+        .map(|(_, best)| best)
+        .reduce(Vec::new, |mut a, b| {
+            for (score, key) in b {
+                keep(&mut a, top, score, key);
+            }
+            a
+        });
+    // Short columns can fool χ², so try every shift of each column in turn.
+    let mut plain = vec![0; letters.len()];
+    for (mut score, mut key) in best.clone() {
+        let mut improved = true;
+        while improved {
+            improved = false;
+            for column in 0..length {
+                for shift in 0..26 {
+                    let original = key[column];
+                    key[column] = shift;
+                    let tried = fitness(&key, &mut plain);
+                    if tried > score {
+                        score = tried;
+                        improved = true;
+                    } else {
+                        key[column] = original;
+                    }
+                }
+            }
+        }
+        if !best.iter().any(|(_, seen)| *seen == key) {
+            keep(&mut best, top, score, key);
+        }
+    }
+    
+    let mut candidates: Vec<(Vec<u8>, String, Score)> = best
+        .into_iter()
+        .map(|(fitness, key)| {
+            let plaintext = cipher::vigenere_decrypt(&ciphertext, &key);
+            let score = scorer.with_fitness(&plaintext, fitness);
+            (key, plaintext, score)
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.2.rank(&a.2));
+    for (i, (key, plaintext, score)) in candidates.iter().enumerate() {
+        let line = format!("  {}  {}", key_name(key), plaintext);
+        if i == 0 {
+            write!(out, "{}", line.yellow().bold())?;
+        } else {
+            write!(out, "{}", line)?;
+        }
+        writeln!(out, "  {}", scorer.describe(score).dimmed())?;
+    }
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    if let Some((key, _, _)) = candidates.first() {
+        writeln!(out, "{}", format!("✨ Most likely key: {}", key_name(key)).green().bold())?;
+    }
+    Ok(())
+}
+
+/// Adds `key` to the `top` best-scoring keys in `best`, highest first.
+fn keep(best: &mut Vec<(f64, Vec<u8>)>, top: usize, score: f64, key: Vec<u8>) {
+    if best.len() == top && best.last().is_none_or(|(worst, _)| score <= *worst) {
+        return;
+    }
+    let at = best.partition_point(|(other, _)| *other >= score);
+    best.insert(at, (score, key));
+    best.truncate(top);
+}
+
+/// Key shifts as letters, 0 = A.
+fn key_name(key: &[u8]) -> String {
+    key.iter().map(|&k| (k + b'A') as char).collect()
+}
+
+/// The ASCII letters of `text` as 0..26.
+fn letters(text: &str) -> Result<Vec<u8>> {
+    let letters: Vec<u8> = text.bytes().filter(u8::is_ascii_alphabetic).map(|b| b.to_ascii_uppercase() - b'A').collect();
+    if letters.is_empty() {
+        return Err("The ciphertext has no letters to solve".into());
+    }
+    Ok(letters)
+}
+
+/// One hill-climb from a random key; returns the best key it reached.
+fn climb(model: &Model, letters: &[u8], seed: u64) -> [u8; 26] {
+    let mut rng = Rng(seed);
+    let mut key: [u8; 26] = std::array::from_fn(|i| i as u8);
+    for i in (1..26).rev() {
+        key.swap(i, rng.below(i + 1));
+    }
+    let mut plain = vec![0; letters.len()];
+    let mut best = fitness(model, letters, &key, &mut plain);
+    let mut stale = 0;
+    while stale < PATIENCE {
+        let (a, b) = (rng.below(26), rng.below(26));
+        key.swap(a, b);
+        let score = fitness(model, letters, &key, &mut plain);
+        if score > best {
+            best = score;
+            stale = 0;
+        } else {
+            key.swap(a, b);
+            stale += 1;
+        }
+    }
+    key
+}
+
+/// Deciphers `letters` with `key` into `plain` and scores the result.
+fn fitness(model: &Model, letters: &[u8], key: &[u8; 26], plain: &mut [u8]) -> f64 {
+    for (p, &c) in plain.iter_mut().zip(letters) {
+        *p = key[c as usize];
+    }
+    model.score_letters(plain)
+}
@@ -0,0 +1,1717 @@
+use crate::output::OutputFormat;
+use crate::parallel::Merge;
+use crate::stats::StatsFormat;
+use crate::summary::SummaryFormat;
+use clap::builder::PossibleValuesParser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use isaw_core::{alphabet, plate, typo};
+use isaw_core::leet::Leet;
+use isaw_core::necklace::Symmetry;
+use isaw_core::rank::Slice;
+use isaw_core::transform::Transform;
+use isaw_core::vanity::Algorithm;
+use std::num::NonZeroUsize;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(name = "isaw")]
+#[command(about = "Generate letter combinations and search for words/sentences", long_about = None)]
+pub struct Cli {
+    /// Forward the command to a running `isaw daemon` (optionally at =SOCKET)
+    #[cfg(unix)]
+    #[arg(long, global = true, value_name = "SOCKET", num_args = 0..=1, require_equals = true)]
+    pub connect: Option<Option<PathBuf>>,
+
+    /// Log phase timings to stderr; -vv for more detail
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Format of the -v log lines
+    #[arg(long, global = true, value_enum, value_name = "FORMAT", default_value = "text")]
+    pub log_format: StatsFormat,
+
+    /// Worker threads for parallel work (default: one per core)
+    #[arg(long, global = true, value_name = "N")]
+    pub threads: Option<NonZeroUsize>,
+
+    /// Do everything on one thread, like --threads 1
+    #[arg(long, global = true, conflicts_with = "threads")]
+    pub no_parallel: bool,
+
+    #[command(subcommand)]
+    pub command: Commands,
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    /// Generate all permutations of given letters
+    Permutations(PermutationsArgs),
+
+    /// Generate all combinations (order doesn't matter)
+    Combinations(CombinationsArgs),
+
+    /// Generate all possible words from letters (like Scrabble)
+    Words(WordsArgs),
+
+    /// Search through custom alphabet combinations
+    Search(SearchArgs),
+
+    /// Search DNA/RNA sequences for a motif with IUPAC ambiguity codes
+    Dna(DnaArgs),
+
+    /// Enumerate or search licence plates and other structured codes
+    Plates(PlatesArgs),
+
+    /// Find suffixes that give a hash starting with chosen hex digits
+    Vanity(VanityArgs),
+
+    /// Print a de Bruijn sequence: every string of a length in one string
+    Debruijn(DebruijnArgs),
+
+    /// Count total combinations possible
+    Count(CountArgs),
+
+    /// Encode text as Morse, or decode dots and dashes (all readings if unspaced)
+    Morse(MorseArgs),
+
+    /// Try every Caesar shift of a ciphertext and rank the results
+    Caesar(CaesarArgs),
+
+    /// Solve a substitution cipher by hill-climbing on n-gram statistics
+    Subst(SubstArgs),
+
+    /// Search Vigenère keys of a given length and rank the decryptions
+    Vigenere(VigenereArgs),
+
+    /// Solve a cryptoquote by matching word shapes against a wordlist
+    Cryptogram(CryptogramArgs),
+
+    /// Generate realistic misspellings of a word
+    Typos(TyposArgs),
+
+    /// Suggest the dictionary words a misspelling was probably meant to be
+    Suggest(SuggestArgs),
+
+    /// Complete a prefix to dictionary words
+    Complete(CompleteArgs),
+
+    /// Narrow saved results with filters typed at a prompt
+    Refine(RefineArgs),
+
+    /// Letter, bigram and trigram frequencies of a text or file
+    Freq(FreqArgs),
+
+    /// Build reusable letter and n-gram tables from a corpus
+    Analyze(AnalyzeArgs),
+
+    /// Keyspace size, entropy and time to exhaust a password space
+    Entropy(EntropyArgs),
+
+    /// Print the arrangement at a zero-based rank without generating the rest
+    Nth(NthArgs),
+
+    /// Print the zero-based rank of an arrangement, the inverse of `nth`
+    Rank(RankArgs),
+
+    /// Print the next permutation of a word in lexicographic order
+    Next(StepArgs),
+
+    /// Print the previous permutation of a word in lexicographic order
+    Prev(StepArgs),
+
+    /// Compose, invert and inspect words as permutations of their letters
+    Perm(PermArgs),
+
+    /// Check whether two phrases are anagrams of each other
+    IsAnagram(IsAnagramArgs),
+
+    /// List the palindromic dictionary words that can be spelled from letters
+    Palindrome(PalindromeArgs),
+
+    /// Split letters into exactly K dictionary words that use them all
+    Split(SplitArgs),
+
+    /// Suggest word sequences whose initials spell an acronym
+    Acronym(AcronymArgs),
+
+    /// Draw random candidates, uniformly or weighted by letter frequency
+    Sample(SampleArgs),
+
+    /// Find words encoding a number in the Major memory system
+    Mnemonic(MnemonicArgs),
+
+    /// Find the shortest word ladder between two words
+    Ladder(LadderArgs),
+
+    /// Fill a crossword grid with dictionary words
+    Fill(FillArgs),
+
+    /// Find the dictionary words hidden in a word search grid
+    Wordsearch(WordsearchArgs),
+
+    /// Group a wordlist into sets of anagrams
+    Group(GroupArgs),
+
+    /// Find the longest dictionary words that can be spelled from letters
+    Longest(LongestArgs),
+
+    /// List the dictionary words a text's letters can spell
+    Coverage(CoverageArgs),
+
+    /// Manage wordlists kept in isaw's data directory
+    Dict(DictArgs),
+
+    /// Serve commands over a unix socket for `isaw --connect` clients, or
+    /// over WebSocket to web pages
+    #[cfg(unix)]
+    #[command(visible_alias = "serve")]
+    Daemon(DaemonArgs),
+
+    /// Split a job into slices and hand them to `isaw worker`s over TCP
+    Coordinate(CoordinateArgs),
+
+    /// Process slices handed out by an `isaw coordinate` run
+    Worker(WorkerArgs),
+
+    /// Measure a job's throughput and project how long the full run takes
+    Bench(BenchArgs),
+}
+
+impl Commands {
+    /// The shared run options, for subcommands that generate candidates.
+    pub fn run_opts_mut(&mut self) -> Option<&mut RunOpts> {
+        match self {
+            Commands::Permutations(args) => Some(&mut args.run),
+            Commands::Combinations(args) => Some(&mut args.run),
+            Commands::Words(args) => Some(&mut args.run),
+            Commands::Search(args) => Some(&mut args.run),
+            Commands::Dna(args) => Some(&mut args.run),
+            _ => None,
+        }
+    }
+}
+
+/// Options shared by every generating subcommand.
+#[derive(Args, Clone, Default)]
+pub struct RunOpts {
+    /// Only enumerate slice I of N equal parts of the keyspace (e.g. 2/4)
+    #[arg(long, value_name = "I/N")]
+    pub slice: Option<Slice>,
+
+    /// Enumerate from the last candidate back to the first (of the slice, with --slice)
+    #[arg(long)]
+    pub reverse: bool,
+
+    /// Skip the first N candidates (of the slice, with --slice), as printed
+    /// when a run is interrupted with Ctrl-C
+    #[arg(long, value_name = "N")]
+    pub resume: Option<u128>,
+
+    /// Print only the results, without banners or highlighting
+    #[arg(short, long)]
+    pub quiet: bool,
+
+    /// Keep only words found in this wordlist (a file, or a list managed by
+    /// `isaw dict`); repeat to accept words from any of several
+    #[arg(short, long, value_name = "FILE")]
+    pub dictionary: Vec<PathBuf>,
+
+    /// Keep only words from the wordlists configured for these languages
+    #[arg(long, value_name = "LANG", value_delimiter = ',')]
+    pub lang: Vec<String>,
+
+    /// Keep only candidates matching one of these globs as a whole
+    /// (? is one character, * any run), e.g. 'c?t*'
+    #[arg(long, value_name = "GLOB")]
+    pub glob: Vec<String>,
+
+    /// Keep only candidates at least this English-looking: the average log10
+    /// probability of their letters, from about -1 (very) to -3 (not at all)
+    #[arg(long, value_name = "SCORE", allow_hyphen_values = true)]
+    pub min_score: Option<f64>,
+
+    /// Keep only candidates at most this English-looking (see --min-score)
+    #[arg(long, value_name = "SCORE", allow_hyphen_values = true)]
+    pub max_score: Option<f64>,
+
+    /// Score --min-score/--max-score with these statistics (`GRAM COUNT`
+    /// lines or `isaw analyze` tables) instead of English letter frequencies
+    #[arg(long, value_name = "FILE")]
+    pub ngrams: Option<PathBuf>,
+
+    /// Keep only candidates this expression is true for, e.g. `vowels >= 2
+    /// and not ends_with(word, "q")`; see the README for the language
+    #[arg(long, value_name = "FILE")]
+    pub filter_script: Option<PathBuf>,
+
+    /// Keep only candidates whose SHA-256 digest is one of these (hex);
+    /// candidates are hashed in batches, on the GPU when built with the
+    /// `gpu` feature and one is present, otherwise on the CPU across cores
+    #[arg(long, value_name = "HEX", value_delimiter = ',')]
+    pub hash: Vec<String>,
+
+    /// Show the gloss for each result from FILE (`word<TAB>definition` lines)
+    #[arg(long, value_name = "FILE")]
+    pub define: Option<PathBuf>,
+
+    /// Try every candidate in each mix of upper and lower case: "ab" also
+    /// gives "Ab", "aB" and "AB"
+    #[arg(long)]
+    pub any_case: bool,
+
+    /// Rewrite each candidate before matching, in order (rotN, reverse,
+    /// upper, lower, complement, reverse-complement)
+    #[arg(long, value_name = "LIST", value_delimiter = ',')]
+    pub transform: Vec<Transform>,
+
+    /// Put TEXT in front of every candidate, after --transform and before matching
+    #[arg(long, value_name = "TEXT")]
+    pub prefix: Option<String>,
+
+    /// Append TEXT to every candidate, after --transform and before matching
+    #[arg(long, value_name = "TEXT")]
+    pub suffix: Option<String>,
+
+    /// Keep only results this shell command prints back when they are piped
+    /// to its stdin, one per line, in batches
+    #[arg(long, value_name = "COMMAND")]
+    pub filter_cmd: Option<String>,
+
+    /// Stop the whole run, parallel workers included, once N results are printed
+    #[arg(long, value_name = "N")]
+    pub first: Option<u64>,
+
+    /// For scripts: print only the first match and exit 0, or exit 3 without
+    /// output if there is none
+    #[arg(long)]
+    pub fail_fast: bool,
+
+    /// Insert results into table `results` of this SQLite database (via the
+    /// sqlite3 shell) instead of printing them
+    #[arg(long, value_name = "FILE", conflicts_with = "format")]
+    pub output_sqlite: Option<PathBuf>,
+
+    /// Stream result lines to a collector at HOST:PORT over TCP instead of printing them
+    #[arg(long, value_name = "HOST:PORT", conflicts_with_all = ["output_sqlite", "format"])]
+    pub output_tcp: Option<String>,
+
+    /// Like --output-tcp, one UDP datagram per line (delivery is not guaranteed)
+    #[arg(long, value_name = "HOST:PORT", conflicts_with_all = ["output_sqlite", "format", "output_tcp"])]
+    pub output_udp: Option<String>,
+
+    /// Time a short sample of the run first, print how long all of it
+    /// will take and ask whether to continue
+    #[arg(long)]
+    pub estimate: bool,
+
+    /// Start even when stdout is a file without room for every result,
+    /// warning instead of refusing
+    #[arg(long)]
+    pub force: bool,
+
+    /// How results are written to stdout; parquet implies --quiet
+    #[arg(long, value_enum, value_name = "FORMAT", default_value = "text")]
+    pub format: OutputFormat,
+
+    /// Write at most N results per second, for downstream systems that
+    /// can't take them at full speed
+    #[arg(long, value_name = "N", value_parser = rate)]
+    pub rate: Option<f64>,
+
+    /// Print SEP between the letters of every result, e.g. a-b-c (display only)
+    #[arg(long, value_name = "SEP")]
+    pub join: Option<String>,
+
+    /// Also print the leetspeak variants of every result (a→4/@, e→3, ...)
+    #[arg(long)]
+    pub leet: bool,
+
+    /// Substitutions for --leet instead of the built-in ones, e.g. a=4@,e=3
+    #[arg(long, value_name = "TABLE", requires = "leet")]
+    pub leet_table: Option<Leet>,
+
+    /// Drop results already printed earlier in the run, e.g. at a shorter length
+    #[arg(long)]
+    pub dedup: bool,
+
+    /// MiB of results --dedup remembers exactly before switching to a Bloom filter
+    #[arg(long, value_name = "MIB", default_value = "256", requires = "dedup")]
+    pub dedup_memory: usize,
+
+    /// Print results in sorted order
+    #[arg(long)]
+    pub sort: bool,
+
+    /// MiB of results --sort keeps in memory before spilling to temp files
+    #[arg(long, value_name = "MIB", default_value = "256", requires = "sort")]
+    pub sort_memory: usize,
+
+    /// Trade speed for memory on small machines: --dedup, --sort and
+    /// --unique keep at most 16 MiB, and parallel work is done in small
+    /// pieces that are printed as they come
+    #[arg(long)]
+    pub low_mem: bool,
+
+    /// Memory for results, e.g. 2G, split between --dedup, --sort and the
+    /// generator's own buffers; each spills to temp files once its share
+    /// is full
+    #[arg(long, value_name = "SIZE", value_parser = memory)]
+    pub max_memory: Option<usize>,
+
+    /// Print timing, throughput and peak memory to stderr at the end
+    #[arg(long, value_enum, value_name = "FORMAT", num_args = 0..=1, require_equals = true, default_missing_value = "text")]
+    pub stats: Option<StatsFormat>,
+
+    /// Print the run's parameters, totals and any limit that stopped it to
+    /// stderr at the end, as one JSON object
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    pub summary: Option<SummaryFormat>,
+
+    /// Print a breakdown of the results (by length, first letter, score) to stderr at the end
+    #[arg(long, value_enum, value_name = "FORMAT", num_args = 0..=1, require_equals = true, default_missing_value = "text")]
+    pub report: Option<StatsFormat>,
+
+    /// Most English-like results --report lists
+    #[arg(long, value_name = "N", default_value = "10", requires = "report")]
+    pub report_top: usize,
+
+    /// Print how many results use each letter to stderr at the end
+    #[arg(long)]
+    pub letter_stats: bool,
+}
+
+/// Slowest `--rate`, one result every 1000 seconds; anything slower is
+/// more likely a typo than a plan.
+pub const MIN_RATE: f64 = 0.001;
+
+/// `--rate`: results per second, finite and at least [`MIN_RATE`].
+fn rate(s: &str) -> Result<f64, String> {
+    let rate: f64 = s.trim().parse().map_err(|_| format!("expected results per second like 20 or 0.5, got '{}'", s))?;
+    match rate.is_finite() && rate >= MIN_RATE {
+        true => Ok(rate),
+        false => Err(format!("rate must be a finite number of at least {} per second", MIN_RATE)),
+    }
+}
+
+/// `--max-memory`: bytes as a number with an optional K, M, G or T
+/// (binary multiples) after it.
+fn memory(s: &str) -> Result<usize, String> {
+    let s = s.trim();
+    let (number, unit) = s.split_at(s.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(s.len()));
+    let scale: u64 = match unit.to_ascii_uppercase().trim_end_matches("IB").trim_end_matches('B') {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        _ => return Err(format!("unknown unit '{}', expected K, M, G or T", unit)),
+    };
+    let number: f64 = number.trim().parse().map_err(|_| format!("expected a size like 512M or 2G, got '{}'", s))?;
+    match number * scale as f64 {
+        bytes if bytes >= 1.0 && bytes < usize::MAX as f64 => Ok(bytes as usize),
+        _ => Err(format!("size '{}' is out of range", s)),
+    }
+}
+
+/// MiB `--low-mem` lets each of `--dedup`, `--sort` and `--unique` keep.
+pub const LOW_MEM_MIB: usize = 16;
+
+impl RunOpts {
+    /// Bytes of results `--dedup` remembers exactly.
+    pub fn dedup_budget(&self) -> usize {
+        self.budget(self.dedup_memory)
+    }
+
+    /// Bytes of results `--sort` holds before spilling to temp files.
+    pub fn sort_budget(&self) -> usize {
+        self.budget(self.sort_memory)
+    }
+
+    /// Bytes each user of memory may take under `--max-memory`: `--dedup`,
+    /// `--sort` and the generator's results in flight get equal shares.
+    pub fn memory_share(&self) -> Option<usize> {
+        self.max_memory.map(|max| max / (1 + self.dedup as usize + self.sort as usize))
+    }
+
+    fn budget(&self, mib: usize) -> usize {
+        let mib = if self.low_mem { mib.min(LOW_MEM_MIB) } else { mib };
+        let bytes = mib.saturating_mul(1 << 20);
+        self.memory_share().map_or(bytes, |share| bytes.min(share))
+    }
+
+    /// `--transform`, then `--prefix`/`--suffix`: the rewrites every
+    /// candidate goes through before matching.
+    pub fn transforms(&self) -> Vec<Transform> {
+        let decorations = [self.prefix.clone().map(Transform::Prefix), self.suffix.clone().map(Transform::Suffix)];
+        self.transform.iter().cloned().chain(decorations.into_iter().flatten()).collect()
+    }
+}
+
+#[derive(Args)]
+pub struct PermutationsArgs {
+    /// Letters to permute (e.g., "abc")
+    #[arg(required_unless_present = "alphabet")]
+    pub letters: Option<String>,
+
+    /// Use a built-in alphabet instead of typing the letters
+    #[arg(long, value_name = "PRESET", value_parser = PossibleValuesParser::new(alphabet::NAMES), conflicts_with = "letters")]
+    pub alphabet: Option<String>,
+
+    /// Minimum length of combinations
+    #[arg(short, long, default_value = "1")]
+    pub min: usize,
+
+    /// Maximum length of combinations (defaults to letter count)
+    #[arg(short = 'x', long)]
+    pub max: Option<usize>,
+
+    /// Order to arrange each selection of letters in
+    #[arg(long, value_enum, default_value = "lex", conflicts_with_all = ["parallel", "slice", "reverse", "resume"])]
+    pub order: PermutationOrder,
+
+    /// What to do when letters repeat, so some arrangements come out twice
+    #[arg(long, value_enum, value_name = "HOW", default_value = "warn")]
+    pub repeats: Repeats,
+
+    /// One arrangement per class equal under rotation (necklaces) or rotation+reflection (bracelets)
+    #[arg(long, value_name = "SYMMETRY", conflicts_with_all = ["parallel", "palindromes_only"])]
+    pub distinct_under: Option<Symmetry>,
+
+    /// Search for specific word/sentence in results (repeat to match any of several)
+    #[arg(short, long, value_name = "TERM")]
+    pub search: Vec<String>,
+
+    /// Read more search terms from FILE, one per line, all matched in one pass
+    #[arg(long, visible_alias = "patterns-file", value_name = "FILE")]
+    pub search_file: Option<PathBuf>,
+
+    /// Case insensitive search
+    #[arg(short, long)]
+    pub ignore_case: bool,
+
+    /// Generate on all cores; results are merged in order unless =unordered
+    #[arg(long, value_enum, value_name = "MERGE", num_args = 0..=1, require_equals = true, default_missing_value = "ordered", conflicts_with_all = ["slice", "reverse"])]
+    pub parallel: Option<Merge>,
+
+    /// Only palindromes, built directly from their first halves
+    #[arg(long, conflicts_with_all = ["parallel", "slice", "reverse", "resume"])]
+    pub palindromes_only: bool,
+
+    /// Match search terms as subsequences: letters in order, gaps allowed
+    #[arg(long)]
+    pub subsequence: bool,
+
+    #[command(flatten)]
+    pub run: RunOpts,
+}
+
+#[derive(Args)]
+pub struct CombinationsArgs {
+    /// Letters to combine (e.g., "abc")
+    #[arg(required_unless_present = "alphabet")]
+    pub letters: Option<String>,
+
+    /// Use a built-in alphabet instead of typing the letters
+    #[arg(long, value_name = "PRESET", value_parser = PossibleValuesParser::new(alphabet::NAMES), conflicts_with = "letters")]
+    pub alphabet: Option<String>,
+
+    /// Length of combinations
+    #[arg(short, long, default_value = "2", conflicts_with_all = ["min", "max"])]
+    pub length: usize,
+
+    /// Shortest combinations, for a range of lengths (defaults to 1 with --max)
+    #[arg(short, long)]
+    pub min: Option<usize>,
+
+    /// Longest combinations, for a range of lengths (defaults to letter count)
+    #[arg(short = 'x', long)]
+    pub max: Option<usize>,
+
+    /// Order to generate them in
+    #[arg(long, value_enum, default_value = "lex", conflicts_with_all = ["slice", "reverse", "resume"])]
+    pub order: CombinationOrder,
+
+    /// One selection per class of positions on a circle equal under rotation or rotation+reflection
+    #[arg(long, value_name = "SYMMETRY")]
+    pub distinct_under: Option<Symmetry>,
+
+    /// Search for specific pattern in results (repeat to match any of several)
+    #[arg(short, long, value_name = "TERM")]
+    pub search: Vec<String>,
+
+    /// Read more search terms from FILE, one per line, all matched in one pass
+    #[arg(long, visible_alias = "patterns-file", value_name = "FILE")]
+    pub search_file: Option<PathBuf>,
+
+    /// Case insensitive search
+    #[arg(short, long)]
+    pub ignore_case: bool,
+
+    /// Match search terms as subsequences: letters in order, gaps allowed
+    #[arg(long)]
+    pub subsequence: bool,
+
+    #[command(flatten)]
+    pub run: RunOpts,
+}
+
+impl CombinationsArgs {
+    /// The lengths to generate: `--length`, or `--min..=--max` over `n` letters.
+    pub fn lengths(&self, n: usize) -> RangeInclusive<usize> {
+        match (self.min, self.max) {
+            (None, None) => self.length..=self.length,
+            (min, max) => min.unwrap_or(1)..=max.unwrap_or(n),
+        }
+    }
+}
+
+/// Layouts `count --bytes` can project the size of.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SizeFormat {
+    /// Bare results, as with --quiet
+    Text,
+    /// One `{"value":...}` object per line
+    Jsonl,
+    /// A `value` header, then one (quoted if need be) result per line
+    Csv,
+}
+
+/// Generation order for `permutations`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum PermutationOrder {
+    /// Input order, as ranks and slices count them
+    Lex,
+    /// Steinhaus–Johnson–Trotter: each arrangement swaps two neighbouring letters
+    Sjt,
+    /// Heap's algorithm: each arrangement swaps two letters
+    Heap,
+}
+
+/// `--repeats`: handling letters given more than once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Repeats {
+    /// Say which letters repeat, then generate every arrangement anyway
+    Warn,
+    /// Generate each distinct arrangement once, like --unique
+    Unique,
+    /// Generate every arrangement without comment
+    Keep,
+}
+
+/// Generation order for `combinations`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum CombinationOrder {
+    /// Input order, as ranks and slices count them
+    Lex,
+    /// Revolving door: each combination swaps one letter of the last
+    Gray,
+}
+
+#[derive(Args)]
+pub struct WordsArgs {
+    /// Available letters (e.g., "abcde")
+    #[arg(required_unless_present_any = ["alphabet", "from_text"])]
+    pub letters: Option<String>,
+
+    /// Use a built-in alphabet instead of typing the letters
+    #[arg(long, value_name = "PRESET", value_parser = PossibleValuesParser::new(alphabet::NAMES), conflicts_with = "letters")]
+    pub alphabet: Option<String>,
+
+    /// Use the letters of a document, each as often as it occurs there
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["letters", "alphabet"])]
+    pub from_text: Option<PathBuf>,
+
+    /// Minimum word length
+    #[arg(short, long, default_value = "2")]
+    pub min: usize,
+
+    /// Maximum word length (defaults to letter count)
+    #[arg(short = 'x', long)]
+    pub max: Option<usize>,
+
+    /// Search for specific pattern (repeat to match any of several)
+    #[arg(short, long, value_name = "TERM")]
+    pub search: Vec<String>,
+
+    /// Read more search terms from FILE, one per line, all matched in one pass
+    #[arg(long, visible_alias = "patterns-file", value_name = "FILE")]
+    pub search_file: Option<PathBuf>,
+
+    /// Only show unique combinations (within the slice when --slice is used)
+    #[arg(short, long)]
+    pub unique: bool,
+
+    /// What to do when letters repeat, so some words come out twice
+    #[arg(long, value_enum, value_name = "HOW", default_value = "warn")]
+    pub repeats: Repeats,
+
+    /// Like --unique, but in fixed memory via a bloom filter that wrongly
+    /// drops about FP_RATE of the words (default 0.001)
+    #[arg(long, value_name = "FP_RATE", num_args = 0..=1, require_equals = true, default_missing_value = "0.001", conflicts_with_all = ["unique", "parallel"])]
+    pub unique_approx: Option<f64>,
+
+    /// Generate on all cores; results are merged in order unless =unordered
+    #[arg(long, value_enum, value_name = "MERGE", num_args = 0..=1, require_equals = true, default_missing_value = "ordered", conflicts_with_all = ["slice", "reverse"])]
+    pub parallel: Option<Merge>,
+
+    /// Only palindromes, built directly from their first halves
+    #[arg(long, conflicts_with_all = ["parallel", "slice", "reverse", "resume", "unique_approx"])]
+    pub palindromes_only: bool,
+
+    /// Match search terms as subsequences: letters in order, gaps allowed
+    #[arg(long)]
+    pub subsequence: bool,
+
+    #[command(flatten)]
+    pub run: RunOpts,
+}
+
+/// The letters given on the command line, or those of the `--alphabet`
+/// preset (clap makes sure there is one or the other).
+pub fn letters_or_preset(letters: &Option<String>, preset: &Option<String>) -> String {
+    match (letters, preset.as_deref().and_then(alphabet::preset)) {
+        (Some(letters), _) => letters.clone(),
+        (None, Some(preset)) => preset.to_string(),
+        (None, None) => String::new(),
+    }
+}
+
+/// Alphabet when neither `--letters` nor `--alphabet` is given.
+pub const DEFAULT_ALPHABET: &str = "abcdefghijklmnopqrstuvwxyz";
+
+/// Search's default alphabet: `$ISAW_ALPHABET`, else the first line of
+/// `$XDG_CONFIG_HOME/isaw/alphabet` (or `~/.config/isaw/alphabet`), else
+/// a-z. Either may be a preset name or the letters themselves.
+pub fn default_alphabet() -> String {
+    let configured = std::env::var("ISAW_ALPHABET").ok().or_else(|| {
+        let base = match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from(std::env::var_os("HOME").unwrap_or_default()).join(".config"),
+        };
+        let text = std::fs::read_to_string(base.join("isaw").join("alphabet")).ok()?;
+        text.lines().next().map(str::to_string)
+    });
+    match configured.as_deref().map(str::trim).filter(|letters| !letters.is_empty()) {
+        Some(letters) => alphabet::preset(letters).unwrap_or(letters).to_string(),
+        None => DEFAULT_ALPHABET.to_string(),
+    }
+}
+
+#[derive(Args)]
+pub struct SearchArgs {
+    /// Patterns to search for; candidates matching any of them are shown
+    /// (optional with --template, which then prints every candidate)
+    #[arg(required_unless_present_any = ["search_file", "template"])]
+    pub patterns: Vec<String>,
+
+    /// Read more patterns from FILE, one per line, all matched in one pass
+    #[arg(long, visible_alias = "patterns-file", value_name = "FILE")]
+    pub search_file: Option<PathBuf>,
+
+    /// Letters/alphabet to use (defaults to $ISAW_ALPHABET, the config file, or a-z)
+    #[arg(short, long)]
+    pub letters: Option<String>,
+
+    /// Use a built-in alphabet instead of typing the letters
+    #[arg(long, value_name = "PRESET", value_parser = PossibleValuesParser::new(alphabet::NAMES), conflicts_with = "letters")]
+    pub alphabet: Option<String>,
+
+    /// Add the digits 0-9 to the alphabet
+    #[arg(long)]
+    pub include_digits: bool,
+
+    /// Add the uppercase letters A-Z to the alphabet
+    #[arg(long)]
+    pub include_upper: bool,
+
+    /// Crunch-style template instead of an alphabet and length, e.g. pass@@##
+    /// (@ lowercase, `,` uppercase, # digit, ^ symbol, \ escapes; the rest is literal)
+    #[arg(short, long, conflicts_with_all = ["letters", "alphabet", "include_digits", "include_upper", "length", "min", "max", "palindromes_only"])]
+    pub template: Option<String>,
+
+    /// Length of combinations to search
+    #[arg(short = 'n', long, default_value = "3", conflicts_with_all = ["min", "max"])]
+    pub length: usize,
+
+    /// Shortest length, to search a range of lengths (defaults to 1 with --max)
+    #[arg(short, long)]
+    pub min: Option<usize>,
+
+    /// Longest length, to search a range of lengths (defaults to --min)
+    #[arg(short = 'x', long)]
+    pub max: Option<usize>,
+
+    /// Case insensitive search
+    #[arg(short, long)]
+    pub ignore_case: bool,
+
+    /// Treat patterns as regexes; several are matched in one pass per candidate
+    #[arg(short, long)]
+    pub regex: bool,
+
+    /// Only palindromes, built directly from their first halves
+    #[arg(long)]
+    pub palindromes_only: bool,
+
+    /// Match patterns as subsequences: letters in order, gaps allowed
+    #[arg(long, conflicts_with = "regex")]
+    pub subsequence: bool,
+
+    #[command(flatten)]
+    pub run: RunOpts,
+}
+
+#[derive(Args)]
+pub struct DnaArgs {
+    /// Motif to look for, e.g. TATAWR (N = any base, R = A/G, Y = C/T, ...)
+    pub motif: String,
+
+    /// Sequence length in bases
+    #[arg(short = 'n', long, default_value = "6")]
+    pub length: usize,
+
+    /// Use the RNA alphabet ACGU instead of ACGT
+    #[arg(long)]
+    pub rna: bool,
+
+    /// Match and print the complement of each sequence
+    #[arg(long, conflicts_with = "reverse_complement")]
+    pub complement: bool,
+
+    /// Match and print the reverse complement of each sequence
+    #[arg(long)]
+    pub reverse_complement: bool,
+
+    /// Print sequences split into codons (triplets)
+    #[arg(long)]
+    pub codons: bool,
+
+    #[command(flatten)]
+    pub run: RunOpts,
+}
+
+#[derive(Args)]
+pub struct PlatesArgs {
+    /// Plate pattern, e.g. LLL-DDD (L letter, D digit, A either, \ escapes; the rest is literal)
+    #[arg(required_unless_present = "region")]
+    pub pattern: Option<String>,
+
+    /// Use a regional pattern and the letters it issues
+    #[arg(long, value_parser = PossibleValuesParser::new(plate::NAMES), conflicts_with = "pattern")]
+    pub region: Option<String>,
+
+    /// Letters for L and A positions (defaults to A-Z, or the region's)
+    #[arg(short, long)]
+    pub letters: Option<String>,
+
+    /// Only plates containing one of these, ignoring case; repeatable or comma-separated
+    #[arg(short, long, value_name = "TEXT", value_delimiter = ',')]
+    pub contains: Vec<String>,
+
+    /// Leave out plates containing any of these, ignoring case; repeatable or comma-separated
+    #[arg(short, long, value_name = "TEXT", value_delimiter = ',')]
+    pub exclude: Vec<String>,
+
+    /// Read more banned substrings from FILE, one per line
+    #[arg(long, value_name = "FILE")]
+    pub exclude_file: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub run: RunOpts,
+}
+
+#[derive(Args)]
+pub struct VanityArgs {
+    /// Hex digits the digest must start with (e.g. 0000 or cafe)
+    pub target: String,
+
+    /// Text hashed before each candidate but not printed (--prefix is part of the candidate)
+    #[arg(long, value_name = "TEXT", default_value = "", conflicts_with = "before_file")]
+    pub before: String,
+
+    /// Hash the contents of FILE before each candidate, e.g. `git cat-file commit HEAD` output
+    #[arg(long, value_name = "FILE")]
+    pub before_file: Option<PathBuf>,
+
+    /// Text hashed after each candidate but not printed
+    #[arg(long, value_name = "TEXT", default_value = "")]
+    pub after: String,
+
+    /// Hash algorithm: sha1 or sha256 (defaults to sha1 with --object, git's)
+    #[arg(long, value_name = "NAME")]
+    pub algorithm: Option<Algorithm>,
+
+    /// Hash the input as a git object of this type, giving its object id
+    #[arg(long, value_name = "TYPE", value_parser = ["blob", "commit", "tree", "tag"])]
+    pub object: Option<String>,
+
+    /// Letters/alphabet for candidates (defaults to $ISAW_ALPHABET, the config file, or a-z)
+    #[arg(short, long)]
+    pub letters: Option<String>,
+
+    /// Use a built-in alphabet instead of typing the letters
+    #[arg(long, value_name = "PRESET", value_parser = PossibleValuesParser::new(alphabet::NAMES), conflicts_with = "letters")]
+    pub alphabet: Option<String>,
+
+    /// Candidate length
+    #[arg(short = 'n', long, default_value = "6", conflicts_with_all = ["min", "max"])]
+    pub length: usize,
+
+    /// Shortest candidates, to try a range of lengths (defaults to 1 with --max)
+    #[arg(short, long)]
+    pub min: Option<usize>,
+
+    /// Longest candidates, to try a range of lengths (defaults to --min)
+    #[arg(short = 'x', long)]
+    pub max: Option<usize>,
+
+    #[command(flatten)]
+    pub run: RunOpts,
+}
+
+impl VanityArgs {
+    pub fn lengths(&self) -> RangeInclusive<usize> {
+        match (self.min, self.max) {
+            (None, None) => self.length..=self.length,
+            (min, max) => {
+                let min = min.unwrap_or(1);
+                min..=max.unwrap_or(min)
+            }
+        }
+    }
+}
+
+impl SearchArgs {
+    /// The alphabet to search: `--letters`, the `--alphabet` preset, or the
+    /// default, plus whatever `--include-*` adds that isn't there yet.
+    pub fn letters(&self) -> String {
+        let mut letters = match (&self.letters, &self.alphabet) {
+            (None, None) => default_alphabet(),
+            (letters, preset) => letters_or_preset(letters, preset),
+        };
+        let extra = [(self.include_digits, "digits"), (self.include_upper, "upper")];
+        for (_, name) in extra.into_iter().filter(|&(included, _)| included) {
+            for c in alphabet::preset(name).unwrap_or_default().chars() {
+                if !letters.contains(c) {
+                    letters.push(c);
+                }
+            }
+        }
+        letters
+    }
+
+    /// The lengths to search: `--length`, or `--min..=--max`.
+    pub fn lengths(&self) -> RangeInclusive<usize> {
+        match (self.min, self.max) {
+            (None, None) => self.length..=self.length,
+            (min, max) => {
+                let min = min.unwrap_or(1);
+                min..=max.unwrap_or(min)
+            }
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct CountArgs {
+    /// Letters available
+    #[arg(required_unless_present = "alphabet")]
+    pub letters: Option<String>,
+
+    /// Use a built-in alphabet instead of typing the letters
+    #[arg(long, value_name = "PRESET", value_parser = PossibleValuesParser::new(alphabet::NAMES), conflicts_with = "letters")]
+    pub alphabet: Option<String>,
+
+    /// Minimum length
+    #[arg(short, long, default_value = "1")]
+    pub min: usize,
+
+    /// Maximum length
+    #[arg(short = 'x', long)]
+    pub max: Option<usize>,
+
+    /// Count combinations instead of permutations
+    #[arg(short, long)]
+    pub combinations: bool,
+
+    /// Count classes equal under rotation or rotation+reflection instead
+    #[arg(long, value_name = "SYMMETRY")]
+    pub distinct_under: Option<Symmetry>,
+
+    /// Also project the output's size, written one result per line as FORMAT
+    #[arg(long, value_enum, value_name = "FORMAT", num_args = 0..=1, default_missing_value = "text")]
+    pub bytes: Option<SizeFormat>,
+
+    /// Size results as printed with --join SEP between their letters
+    #[arg(long, value_name = "SEP", requires = "bytes")]
+    pub join: Option<String>,
+}
+
+#[derive(Args)]
+pub struct DebruijnArgs {
+    /// Letters/alphabet to use (defaults to a-z)
+    #[arg(short, long)]
+    pub letters: Option<String>,
+
+    /// A built-in alphabet, or the letters themselves (`--alphabet 01`)
+    #[arg(long, value_name = "PRESET|LETTERS", conflicts_with = "letters")]
+    pub alphabet: Option<String>,
+
+    /// Length of the strings the sequence covers
+    #[arg(short = 'n', long, default_value = "3")]
+    pub length: usize,
+
+    /// Print the cyclic sequence, without repeating its start at the end
+    #[arg(long)]
+    pub cyclic: bool,
+
+    /// Print only the sequence
+    #[arg(short, long)]
+    pub quiet: bool,
+}
+
+#[derive(Args)]
+pub struct EntropyArgs {
+    /// Characters each position may take (defaults to a-z)
+    #[arg(short, long, conflicts_with = "mask")]
+    pub letters: Option<String>,
+
+    /// Use a built-in alphabet instead of typing the letters
+    #[arg(long, value_name = "PRESET", value_parser = PossibleValuesParser::new(alphabet::NAMES), conflicts_with_all = ["letters", "mask"])]
+    pub alphabet: Option<String>,
+
+    /// Number of positions
+    #[arg(short = 'n', long, required_unless_present = "mask", conflicts_with = "mask")]
+    pub length: Option<usize>,
+
+    /// Hashcat-style mask instead, e.g. ?u?l?l?l?d?d (?l ?u ?d ?s ?a ?h ?H)
+    #[arg(long)]
+    pub mask: Option<String>,
+
+    /// Guesses per second to estimate exhaustion time at; repeatable
+    #[arg(long, value_name = "RATE", value_delimiter = ',', default_value = "1e3,1e6,1e9,1e12")]
+    pub rate: Vec<f64>,
+}
+
+#[derive(Args)]
+pub struct NthArgs {
+    /// Input letters, in the order generation uses (e.g., "abcde")
+    pub letters: String,
+
+    /// Zero-based rank
+    pub rank: u128,
+
+    /// Letters per arrangement; defaults to all of them
+    #[arg(short, long)]
+    pub length: Option<usize>,
+
+    /// Rank k-subsets (the combinations keyspace) instead of arrangements
+    #[arg(short, long)]
+    pub combinations: bool,
+}
+
+#[derive(Args)]
+pub struct RankArgs {
+    /// Input letters, in the order generation uses (e.g., "abcde")
+    pub letters: String,
+
+    /// Arrangement (or with --combinations, selection) to rank
+    pub word: String,
+
+    /// Rank k-subsets (the combinations keyspace) instead of arrangements
+    #[arg(short, long)]
+    pub combinations: bool,
+}
+
+#[derive(Args)]
+pub struct StepArgs {
+    /// Word to step from (e.g., "abdc")
+    pub word: String,
+
+    /// Number of permutations to move
+    #[arg(default_value = "1")]
+    pub count: u128,
+}
+
+#[derive(Args)]
+pub struct PermArgs {
+    #[command(subcommand)]
+    pub command: PermCommand,
+}
+
+#[derive(Subcommand)]
+pub enum PermCommand {
+    /// Show a permutation's cycles, inverse, order and parity
+    Info(PermWordArgs),
+
+    /// Print the permutation that undoes WORD
+    Inverse(PermWordArgs),
+
+    /// Print FIRST followed by SECOND as one permutation
+    Compose(PermComposeArgs),
+
+    /// Turn cycle notation such as "(a c)(b d e)" into a word
+    Cycles(PermCyclesArgs),
+}
+
+#[derive(Args)]
+pub struct PermWordArgs {
+    /// Arrangement of the reference letters (e.g., "bca")
+    pub word: String,
+
+    /// Ordering the word permutes; defaults to its letters sorted
+    #[arg(short, long, value_name = "LETTERS")]
+    pub reference: Option<String>,
+}
+
+#[derive(Args)]
+pub struct PermComposeArgs {
+    /// Permutation applied first
+    pub first: String,
+
+    /// Permutation applied second
+    pub second: String,
+
+    /// Ordering both words permute; defaults to the first word's letters sorted
+    #[arg(short, long, value_name = "LETTERS")]
+    pub reference: Option<String>,
+}
+
+#[derive(Args)]
+pub struct PermCyclesArgs {
+    /// Disjoint cycles, e.g. "(a c)(b d e)"
+    pub cycles: String,
+
+    /// Ordering to permute; defaults to the letters in the cycles sorted
+    #[arg(short, long, value_name = "LETTERS")]
+    pub reference: Option<String>,
+}
+
+#[derive(Args)]
+pub struct IsAnagramArgs {
+    /// First word or phrase
+    pub first: String,
+
+    /// Second word or phrase
+    pub second: String,
+
+    /// Only report through the exit status (and stderr when they differ)
+    #[arg(short, long)]
+    pub quiet: bool,
+}
+
+#[derive(Args)]
+pub struct LongestArgs {
+    /// Available letters (e.g., "retinas")
+    #[arg(required_unless_present = "alphabet")]
+    pub letters: Option<String>,
+
+    /// Use a built-in alphabet instead of typing the letters
+    #[arg(long, value_name = "PRESET", value_parser = PossibleValuesParser::new(alphabet::NAMES), conflicts_with = "letters")]
+    pub alphabet: Option<String>,
+
+    /// Wordlist to search (a file or `isaw dict` name); repeatable
+    #[arg(short, long, value_name = "FILE", required_unless_present = "lang")]
+    pub dictionary: Vec<PathBuf>,
+
+    /// Search the wordlists configured for these languages
+    #[arg(long, value_name = "LANG", value_delimiter = ',')]
+    pub lang: Vec<String>,
+
+    /// Show the gloss for each word from FILE (`word<TAB>definition` lines)
+    #[arg(long, value_name = "FILE")]
+    pub define: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct CoverageArgs {
+    /// Text whose letters make the inventory
+    #[arg(long, value_name = "FILE")]
+    pub text: PathBuf,
+
+    /// Wordlist to check (a file or `isaw dict` name); repeatable
+    #[arg(short, long, value_name = "FILE", required_unless_present = "lang")]
+    pub dictionary: Vec<PathBuf>,
+
+    /// Check the wordlists configured for these languages
+    #[arg(long, value_name = "LANG", value_delimiter = ',')]
+    pub lang: Vec<String>,
+
+    /// Give each sentence its own inventory
+    #[arg(long)]
+    pub per_sentence: bool,
+
+    /// Only list words with at least N letters
+    #[arg(short, long, value_name = "N", default_value = "1")]
+    pub min: usize,
+}
+
+#[derive(Args)]
+pub struct MorseArgs {
+    /// Text to encode, or Morse to decode (`.... ..`, `/` between words)
+    pub input: String,
+
+    /// Keep only decodings found in this wordlist (a file or `isaw dict` name); repeatable
+    #[arg(short, long, value_name = "FILE")]
+    pub dictionary: Vec<PathBuf>,
+
+    /// Keep only decodings from the wordlists configured for these languages
+    #[arg(long, value_name = "LANG", value_delimiter = ',')]
+    pub lang: Vec<String>,
+
+    /// Stop after this many decodings of unspaced code
+    #[arg(long, value_name = "N")]
+    pub limit: Option<u64>,
+
+    /// Print only the results, without banners
+    #[arg(short, long)]
+    pub quiet: bool,
+}
+
+#[derive(Args)]
+pub struct CaesarArgs {
+    /// Text to decrypt
+    pub ciphertext: String,
+
+    /// Rank shifts by words found in this wordlist (a file or `isaw dict` name); repeatable
+    #[arg(short, long, value_name = "FILE")]
+    pub dictionary: Vec<PathBuf>,
+
+    /// Rank shifts by the wordlists configured for these languages
+    #[arg(long, value_name = "LANG", value_delimiter = ',')]
+    pub lang: Vec<String>,
+
+    /// Letter statistics as `GRAM COUNT` lines or `isaw analyze` tables (defaults to built-in English quadgrams)
+    #[arg(long, value_name = "FILE")]
+    pub ngrams: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct SubstArgs {
+    /// Text to decrypt
+    pub ciphertext: String,
+
+    /// Rank solutions by words found in this wordlist (a file or `isaw dict` name); repeatable
+    #[arg(short, long, value_name = "FILE")]
+    pub dictionary: Vec<PathBuf>,
+
+    /// Rank solutions by the wordlists configured for these languages
+    #[arg(long, value_name = "LANG", value_delimiter = ',')]
+    pub lang: Vec<String>,
+
+    /// Letter statistics as `GRAM COUNT` lines or `isaw analyze` tables (defaults to built-in English quadgrams)
+    #[arg(long, value_name = "FILE")]
+    pub ngrams: Option<PathBuf>,
+
+    /// Independent hill-climbs from random keys
+    #[arg(long, default_value = "100")]
+    pub restarts: usize,
+
+    /// Number of solutions to show
+    #[arg(long, default_value = "3")]
+    pub top: usize,
+
+    /// Seed for the random starting keys, to reproduce a run
+    #[arg(long)]
+    pub seed: Option<u64>,
+}
+
+#[derive(Args)]
+pub struct CryptogramArgs {
+    /// Text to decrypt
+    pub ciphertext: String,
+
+    /// Wordlist the plaintext words come from (a file or `isaw dict` name); repeatable
+    #[arg(short, long, value_name = "FILE", required_unless_present = "lang")]
+    pub dictionary: Vec<PathBuf>,
+
+    /// Take plaintext words from the wordlists configured for these languages
+    #[arg(long, value_name = "LANG", value_delimiter = ',')]
+    pub lang: Vec<String>,
+
+    /// Letter statistics as `GRAM COUNT` lines or `isaw analyze` tables, for ranking
+    #[arg(long, value_name = "FILE")]
+    pub ngrams: Option<PathBuf>,
+
+    /// Ciphertext words allowed to be missing from the wordlist, such as names
+    #[arg(long, value_name = "N", default_value = "0")]
+    pub misses: usize,
+
+    /// Stop looking after this many keys
+    #[arg(long, value_name = "N", default_value = "10000")]
+    pub limit: usize,
+
+    /// Number of solutions to show
+    #[arg(long, default_value = "5")]
+    pub top: usize,
+}
+
+#[derive(Args)]
+pub struct VigenereArgs {
+    /// Text to decrypt
+    pub ciphertext: String,
+
+    /// Key length to search
+    #[arg(short = 'n', long, required_unless_present = "known_plaintext")]
+    pub length: Option<usize>,
+
+    /// Plaintext the message starts with; derives the key instead of searching
+    #[arg(long, value_name = "TEXT")]
+    pub known_plaintext: Option<String>,
+
+    /// Rank decryptions by words found in this wordlist (a file or `isaw dict` name); repeatable
+    #[arg(short, long, value_name = "FILE")]
+    pub dictionary: Vec<PathBuf>,
+
+    /// Rank decryptions by the wordlists configured for these languages
+    #[arg(long, value_name = "LANG", value_delimiter = ',')]
+    pub lang: Vec<String>,
+
+    /// Rank keys by `GRAM COUNT` lines or `isaw analyze` tables (defaults to built-in English quadgrams)
+    #[arg(long, value_name = "FILE")]
+    pub ngrams: Option<PathBuf>,
+
+    /// Number of keys to show
+    #[arg(long, default_value = "5")]
+    pub top: usize,
+}
+
+#[derive(Args)]
+pub struct TyposArgs {
+    /// Word to misspell
+    pub word: String,
+
+    /// Keyboard layout for adjacent-key typos
+    #[arg(long, default_value = "qwerty", value_parser = PossibleValuesParser::new(typo::NAMES))]
+    pub layout: String,
+
+    /// Keep only typos found in this wordlist (a file or `isaw dict` name); repeatable
+    #[arg(short, long, value_name = "FILE")]
+    pub dictionary: Vec<PathBuf>,
+
+    /// Keep only typos from the wordlists configured for these languages
+    #[arg(long, value_name = "LANG", value_delimiter = ',')]
+    pub lang: Vec<String>,
+
+    /// Print only the typos, without banners or kinds
+    #[arg(short, long)]
+    pub quiet: bool,
+}
+
+#[derive(Args)]
+pub struct SuggestArgs {
+    /// Word to correct
+    pub word: String,
+
+    /// Wordlist to suggest from (a file or `isaw dict` name); repeatable
+    #[arg(short, long, value_name = "FILE", required_unless_present = "lang")]
+    pub dictionary: Vec<PathBuf>,
+
+    /// Suggest from the wordlists configured for these languages
+    #[arg(long, value_name = "LANG", value_delimiter = ',')]
+    pub lang: Vec<String>,
+
+    /// Keyboard layout; slips to an adjacent key count half an edit
+    #[arg(long, default_value = "qwerty", value_parser = PossibleValuesParser::new(typo::NAMES))]
+    pub layout: String,
+
+    /// Word frequencies as `WORD COUNT` lines, to rank equally close suggestions
+    /// (defaults to built-in counts of common English words; words without a
+    /// count rank by their order in the wordlist, as in most-common lists)
+    #[arg(long, value_name = "FILE")]
+    pub frequencies: Option<PathBuf>,
+
+    /// Farthest suggestion, in edits
+    #[arg(long, value_name = "EDITS", default_value = "2")]
+    pub max_distance: f64,
+
+    /// Number of suggestions
+    #[arg(long, default_value = "10")]
+    pub limit: usize,
+
+    /// Print only the suggestions, without banners or distances
+    #[arg(short, long)]
+    pub quiet: bool,
+}
+
+#[derive(Args)]
+pub struct CompleteArgs {
+    /// Start of the word
+    pub prefix: String,
+
+    /// Wordlist to complete from (a file or `isaw dict` name); repeatable
+    #[arg(short, long, value_name = "FILE", required_unless_present = "lang")]
+    pub dictionary: Vec<PathBuf>,
+
+    /// Complete from the wordlists configured for these languages
+    #[arg(long, value_name = "LANG", value_delimiter = ',')]
+    pub lang: Vec<String>,
+
+    /// Number of completions
+    #[arg(long, default_value = "20")]
+    pub limit: usize,
+
+    /// Print only the completions, one per line, for scripts and editors
+    #[arg(short, long)]
+    pub quiet: bool,
+}
+
+#[derive(Args)]
+pub struct RefineArgs {
+    /// Results to refine, one per line (e.g. saved with -q > results.txt)
+    pub results: PathBuf,
+}
+
+#[derive(Args)]
+pub struct FreqArgs {
+    /// Text to analyze, or a file to read it from
+    pub input: String,
+
+    /// Rows shown per table
+    #[arg(long, default_value = "10")]
+    pub top: usize,
+
+    /// Print every count as one JSON object
+    #[arg(long, conflicts_with = "counts")]
+    pub json: bool,
+
+    /// Print all N-letter n-grams as `GRAM COUNT` lines, for --ngrams
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(1..=4))]
+    pub counts: Option<u8>,
+}
+
+#[derive(Args)]
+pub struct AnalyzeArgs {
+    /// Text file to count letters and n-grams in
+    pub corpus: PathBuf,
+
+    /// Where to write the tables, for --ngrams and `sample --corpus`
+    #[arg(short, long, value_name = "FILE")]
+    pub output: PathBuf,
+}
+
+#[derive(Args)]
+pub struct PalindromeArgs {
+    /// Available letters (e.g., "racecars")
+    #[arg(required_unless_present = "alphabet")]
+    pub letters: Option<String>,
+
+    /// Use a built-in alphabet instead of typing the letters
+    #[arg(long, value_name = "PRESET", value_parser = PossibleValuesParser::new(alphabet::NAMES), conflicts_with = "letters")]
+    pub alphabet: Option<String>,
+
+    /// Wordlist to search (a file or `isaw dict` name); repeatable
+    #[arg(short, long, value_name = "FILE", required_unless_present = "lang")]
+    pub dictionary: Vec<PathBuf>,
+
+    /// Search the wordlists configured for these languages
+    #[arg(long, value_name = "LANG", value_delimiter = ',')]
+    pub lang: Vec<String>,
+
+    /// Shortest palindrome to list
+    #[arg(short, long, default_value = "2")]
+    pub min: usize,
+}
+
+#[derive(Args)]
+pub struct SplitArgs {
+    /// Letters to split; spaces and punctuation are ignored
+    pub letters: String,
+
+    /// Number of words
+    #[arg(short, default_value = "2")]
+    pub k: usize,
+
+    /// Shortest word allowed in a split
+    #[arg(short, long, default_value = "2")]
+    pub min: usize,
+
+    /// Wordlist the words must come from (a file or `isaw dict` name); repeatable
+    #[arg(short, long, value_name = "FILE", required_unless_present = "lang")]
+    pub dictionary: Vec<PathBuf>,
+
+    /// Take words from the wordlists configured for these languages
+    #[arg(long, value_name = "LANG", value_delimiter = ',')]
+    pub lang: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct SampleArgs {
+    /// Letters to draw from (defaults to $ISAW_ALPHABET, the config file, or a-z)
+    #[arg(short, long)]
+    pub letters: Option<String>,
+
+    /// Use a built-in alphabet instead of typing the letters
+    #[arg(long, value_name = "PRESET", value_parser = PossibleValuesParser::new(alphabet::NAMES), conflicts_with = "letters")]
+    pub alphabet: Option<String>,
+
+    /// Length of each candidate
+    #[arg(short = 'n', long, default_value = "6")]
+    pub length: usize,
+
+    /// Number of candidates
+    #[arg(long, default_value = "10")]
+    pub limit: usize,
+
+    /// Draw letters by English letter frequency instead of uniformly
+    #[arg(long)]
+    pub weighted: bool,
+
+    /// With --weighted, use the letter frequencies of this text (or `isaw analyze` tables) instead
+    #[arg(long, value_name = "FILE", requires = "weighted")]
+    pub corpus: Option<PathBuf>,
+
+    /// Seed for the draws, to reproduce a run
+    #[arg(long)]
+    pub seed: Option<u64>,
+}
+
+#[derive(Args)]
+pub struct AcronymArgs {
+    /// Letters the initials must spell (e.g., NASA)
+    pub acronym: String,
+
+    /// Wordlist to draw words from (a file or `isaw dict` name); repeatable
+    #[arg(short, long, value_name = "FILE", required_unless_present = "lang")]
+    pub dictionary: Vec<PathBuf>,
+
+    /// Draw words from the wordlists configured for these languages
+    #[arg(long, value_name = "LANG", value_delimiter = ',')]
+    pub lang: Vec<String>,
+
+    /// Word length per position: N, N-M or * (e.g. 4,3-6,*); missing positions are free
+    #[arg(long, value_name = "LIST", value_delimiter = ',')]
+    pub lengths: Vec<String>,
+
+    /// Part-of-speech tags as `word<TAB>tag[,tag]` lines, for --shape
+    #[arg(long, value_name = "FILE", requires = "shape")]
+    pub pos: Option<PathBuf>,
+
+    /// Tag required per position, or * (e.g. adj,noun,*,verb)
+    #[arg(long, value_name = "LIST", value_delimiter = ',', requires = "pos")]
+    pub shape: Vec<String>,
+
+    /// Number of suggestions
+    #[arg(long, default_value = "10")]
+    pub limit: usize,
+
+    /// Seed for picking suggestions, to reproduce a run
+    #[arg(long)]
+    pub seed: Option<u64>,
+}
+
+#[derive(Args)]
+pub struct MnemonicArgs {
+    /// Digits to encode (e.g., 3141592)
+    pub number: String,
+
+    /// Wordlist to draw words from (a file or `isaw dict` name); repeatable
+    #[arg(short, long, value_name = "FILE", required_unless_present = "lang")]
+    pub dictionary: Vec<PathBuf>,
+
+    /// Draw words from the wordlists configured for these languages
+    #[arg(long, value_name = "LANG", value_delimiter = ',')]
+    pub lang: Vec<String>,
+
+    /// Most words per phrase
+    #[arg(short, long, default_value = "3", value_parser = clap::value_parser!(u8).range(1..))]
+    pub words: u8,
+
+    /// Stop after this many phrases
+    #[arg(long, default_value = "50")]
+    pub limit: usize,
+}
+
+#[derive(Args)]
+pub struct LadderArgs {
+    /// Word to start from
+    pub start: String,
+
+    /// Word to reach
+    pub end: String,
+
+    /// Wordlist the steps must come from (a file or `isaw dict` name); repeatable
+    #[arg(short, long, value_name = "FILE", required_unless_present = "lang")]
+    pub dictionary: Vec<PathBuf>,
+
+    /// Take steps from the wordlists configured for these languages
+    #[arg(long, value_name = "LANG", value_delimiter = ',')]
+    pub lang: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct FillArgs {
+    /// Grid file: one line per row, `#` a block, `.` an open cell, letters prefilled
+    pub grid: PathBuf,
+
+    /// Wordlist the entries must come from (a file or `isaw dict` name); repeatable
+    #[arg(short, long, value_name = "FILE", required_unless_present = "lang")]
+    pub dictionary: Vec<PathBuf>,
+
+    /// Take entries from the wordlists configured for these languages
+    #[arg(long, value_name = "LANG", value_delimiter = ',')]
+    pub lang: Vec<String>,
+
+    /// Number of fills to show
+    #[arg(long, default_value = "1")]
+    pub limit: usize,
+}
+
+#[derive(Args)]
+pub struct WordsearchArgs {
+    /// Grid file: one row of letters per line
+    #[arg(long, value_name = "FILE")]
+    pub grid: PathBuf,
+
+    /// Wordlist to look for (a file or `isaw dict` name); repeatable
+    #[arg(short, long, value_name = "FILE", required_unless_present = "lang")]
+    pub dictionary: Vec<PathBuf>,
+
+    /// Look for words from the wordlists configured for these languages
+    #[arg(long, value_name = "LANG", value_delimiter = ',')]
+    pub lang: Vec<String>,
+
+    /// Only words with at least N letters
+    #[arg(short, long, value_name = "N", default_value = "3")]
+    pub min: usize,
+
+    /// Print the grid with the found words' letters in color
+    #[arg(long)]
+    pub highlight: bool,
+}
+
+#[derive(Args)]
+pub struct GroupArgs {
+    /// Wordlist to group (a file or `isaw dict` name); repeatable
+    #[arg(short, long, value_name = "FILE", required_unless_present = "lang")]
+    pub dictionary: Vec<PathBuf>,
+
+    /// Group the wordlists configured for these languages
+    #[arg(long, value_name = "LANG", value_delimiter = ',')]
+    pub lang: Vec<String>,
+
+    /// Only show groups with at least N words
+    #[arg(short, long, value_name = "N", default_value = "2")]
+    pub min: usize,
+
+    /// Print one tab-separated group per line, without banners
+    #[arg(short, long)]
+    pub quiet: bool,
+}
+
+#[derive(Args)]
+pub struct DictArgs {
+    #[command(subcommand)]
+    pub command: DictCommand,
+}
+
+#[derive(Subcommand)]
+pub enum DictCommand {
+    /// Copy a local wordlist in under NAME
+    Add(DictAddArgs),
+
+    /// Show the managed wordlists
+    List,
+
+    /// Delete a managed wordlist
+    Remove(DictRemoveArgs),
+
+    /// Download a wordlist (with curl or wget) under NAME
+    Fetch(DictFetchArgs),
+
+    /// Check managed wordlists against the sha256 recorded when they were added
+    Verify(DictVerifyArgs),
+}
+
+#[derive(Args)]
+pub struct DictAddArgs {
+    /// Name to refer to the list by (e.g. "en")
+    pub name: String,
+
+    /// Wordlist to copy in
+    pub file: PathBuf,
+}
+
+#[derive(Args)]
+pub struct DictRemoveArgs {
+    /// Name of the list to delete
+    pub name: String,
+}
+
+#[derive(Args)]
+pub struct DictFetchArgs {
+    /// Name to refer to the list by (e.g. "en")
+    pub name: String,
+
+    /// Where to download it from
+    pub url: String,
+
+    /// Expected SHA-256 of the download, or of the curated list as `dict list` shows it; refuse it on mismatch
+    #[arg(long, value_name = "HEX")]
+    pub sha256: Option<String>,
+}
+
+#[derive(Args)]
+pub struct DictVerifyArgs {
+    /// Only this list (default: all of them)
+    pub name: Option<String>,
+}
+
+#[cfg(unix)]
+#[derive(Args)]
+pub struct DaemonArgs {
+    /// Socket path (defaults to $XDG_RUNTIME_DIR/isaw.sock)
+    #[arg(long)]
+    pub socket: Option<PathBuf>,
+
+    /// Stream queries to web pages over WebSocket at HOST:PORT instead
+    #[arg(long, value_name = "HOST:PORT", conflicts_with = "socket")]
+    pub websocket: Option<String>,
+
+    /// Serve queries to other backends over gRPC (proto/isaw.proto) at HOST:PORT instead
+    #[cfg(feature = "grpc")]
+    #[arg(long, value_name = "HOST:PORT", conflicts_with_all = ["socket", "websocket"])]
+    pub grpc: Option<String>,
+
+    /// Serve Prometheus metrics at http://HOST:PORT/metrics
+    #[arg(long, value_name = "HOST:PORT")]
+    pub metrics: Option<String>,
+}
+
+#[derive(Args)]
+pub struct CoordinateArgs {
+    /// Address to accept workers on
+    #[arg(long, default_value = "0.0.0.0:7878")]
+    pub listen: String,
+
+    /// Number of slices to cut the keyspace into
+    #[arg(long, default_value = "256")]
+    pub tasks: u128,
+
+    /// Seconds of silence after which a worker counts as dead and its slice is re-issued
+    #[arg(long, default_value = "60")]
+    pub timeout: u64,
+
+    /// The job to distribute, e.g. `search zz -n 6`
+    #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true, value_name = "COMMAND")]
+    pub job: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct WorkerArgs {
+    /// Coordinator address (host:port)
+    #[arg(long, value_name = "ADDR")]
+    pub join: String,
+}
+
+#[derive(Args)]
+pub struct BenchArgs {
+    /// Roughly how long to sample for
+    #[arg(long, default_value = "3")]
+    pub seconds: f64,
+
+    /// The job to measure, e.g. `search zz -n 8`
+    #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true, value_name = "COMMAND")]
+    pub job: Vec<String>,
+}
@@ -0,0 +1,213 @@
+//! `isaw daemon` and the `isaw --connect` client.
+//!
+//! The daemon keeps one long-lived process around so scripted callers don't
+//! pay startup costs on every invocation. Each connection carries a single
+//! command line; the daemon runs it in-process and streams the output back.
+//!
+//! Wire format (all integers little-endian):
+//!
+//! - request: `u32` argument count, then per argument a `u32` byte length and
+//!   the UTF-8 bytes, then the client's working directory the same way (as
+//!   raw bytes), then one byte telling whether the client wants colors.
+//!
+//! The daemon answers one request at a time, so it simply moves into the
+//! client's directory for each one, and relative paths mean what the client
+//! meant. Diagnostics written during the request are captured into `e`
+//! frames instead of reaching the daemon's own stderr.
+//! - response: a sequence of frames, each a tag byte, a `u32` payload length
+//!   and the payload. `o` carries stdout bytes, `e` stderr bytes and `x` ends
+//!   the response with the exit code as an `i32`.
+
+use crate::cli::{Cli, Commands};
+use crate::{exit_code, log, metrics, run, NotFound, Result, NOT_FOUND};
+use clap::Parser;
+use colored::*;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, BufWriter, Read, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+const STDOUT: u8 = b'o';
+const STDERR: u8 = b'e';
+const EXIT: u8 = b'x';
+
+/// `$XDG_RUNTIME_DIR/isaw.sock`, falling back to a per-user file in the temp dir.
+pub fn default_socket() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => PathBuf::from(dir).join("isaw.sock"),
+        None => {
+            let user = std::env::var("USER").unwrap_or_default();
+            std::env::temp_dir().join(format!("isaw-{}.sock", user))
+        }
+    }
+}
+
+/// Accepts connections forever, answering one query at a time.
+pub fn serve(out: &mut dyn Write, socket: &Path) -> Result<()> {
+    if socket.exists() {
+        // A live daemon still answers; a socket left behind by a killed one doesn't.
+        if UnixStream::connect(socket).is_ok() {
+            return Err(format!("A daemon is already listening on {}", socket.display()).into());
+        }
+        fs::remove_file(socket)?;
+    }
+    let listener = UnixListener::bind(socket)?;
+
+    writeln!(out, "{}", format!("🛰  isaw daemon listening on {}", socket.display()).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    out.flush()?;
+
+    for stream in listener.incoming() {
+        let result = stream.and_then(handle);
+        // Clients hanging up early (`| head`) is routine, not worth reporting.
+        if let Err(e) = result.or_else(|e| if e.kind() == io::ErrorKind::BrokenPipe { Ok(()) } else { Err(e) }) {
+            eprintln!("{}", format!("Connection failed: {}", e).red());
+        }
+    }
+    Ok(())
+}
+
+fn handle(mut stream: UnixStream) -> io::Result<()> {
+    let (args, cwd, color) = read_request(&mut stream)?;
+    let mut out = BufWriter::new(FrameWriter { stream: stream.try_clone()?, tag: STDOUT });
+    let mut err = FrameWriter { stream: stream.try_clone()?, tag: STDERR };
+
+    colored::control::set_override(color);
+    let started = Instant::now();
+    let argv = std::iter::once("isaw".to_string()).chain(args);
+    log::capture(Box::new(FrameWriter { stream: stream.try_clone()?, tag: STDERR }));
+    let code = match Cli::try_parse_from(argv) {
+        _ if let Err(e) = std::env::set_current_dir(&cwd) => {
+            writeln!(err, "{}", format!("Cannot work in {}: {}", cwd.display(), e).red())?;
+            1
+        }
+        Ok(cli) if matches!(cli.command, Commands::Daemon(_)) => {
+            writeln!(err, "{}", "Cannot start a daemon through --connect".red())?;
+            1
+        }
+        Ok(cli) => {
+            log::init(cli.verbose, cli.log_format);
+            match run(cli.command, &mut out) {
+                Ok(()) => 0,
+                Err(e) if e.is::<NotFound>() => NOT_FOUND,
+                Err(e) => {
+                    out.flush()?;
+                    writeln!(err, "{}", e.to_string().red())?;
+                    exit_code(e.as_ref())
+                }
+            }
+        }
+        Err(e) => {
+            // --help and --version are reported as "errors" that belong on stdout.
+            let text = if color { e.render().ansi().to_string() } else { e.render().to_string() };
+            if e.use_stderr() {
+                err.write_all(text.as_bytes())?;
+            } else {
+                out.write_all(text.as_bytes())?;
+            }
+            e.exit_code()
+        }
+    };
+    log::release();
+    colored::control::unset_override();
+    metrics::query(code == 0 || code == NOT_FOUND, started.elapsed());
+
+    out.flush()?;
+    write_frame(&mut stream, EXIT, &code.to_le_bytes())
+}
+
+/// Sends `args` to the daemon at `socket` and relays its output; returns the exit code.
+pub fn connect(socket: &Path, args: &[String]) -> Result<i32> {
+    let mut stream = UnixStream::connect(socket)
+        .map_err(|e| format!("Cannot reach daemon at {}: {}", socket.display(), e))?;
+
+    let mut request = Vec::new();
+    request.extend_from_slice(&(args.len() as u32).to_le_bytes());
+    for arg in args {
+        request.extend_from_slice(&(arg.len() as u32).to_le_bytes());
+        request.extend_from_slice(arg.as_bytes());
+    }
+    let cwd = std::env::current_dir()?;
+    request.extend_from_slice(&(cwd.as_os_str().len() as u32).to_le_bytes());
+    request.extend_from_slice(cwd.as_os_str().as_bytes());
+    request.push(colored::control::SHOULD_COLORIZE.should_colorize() as u8);
+    stream.write_all(&request)?;
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    loop {
+        let mut tag = [0u8; 1];
+        if stream.read_exact(&mut tag).is_err() {
+            return Err("Daemon closed the connection before finishing".into());
+        }
+        let payload = read_chunk(&mut stream)?;
+        match tag[0] {
+            STDOUT => stdout.write_all(&payload)?,
+            STDERR => {
+                stdout.flush()?;
+                io::stderr().write_all(&payload)?;
+            }
+            EXIT => {
+                stdout.flush()?;
+                let code = payload.try_into().map(i32::from_le_bytes).unwrap_or(1);
+                return Ok(code);
+            }
+            other => return Err(format!("Unexpected frame {:?} from daemon", other as char).into()),
+        }
+    }
+}
+
+fn read_request(stream: &mut UnixStream) -> io::Result<(Vec<String>, PathBuf, bool)> {
+    let count = read_u32(stream)?;
+    let mut args = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let bytes = read_chunk(stream)?;
+        let arg = String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        args.push(arg);
+    }
+    let cwd = PathBuf::from(OsStr::from_bytes(&read_chunk(stream)?));
+    let mut color = [0u8; 1];
+    stream.read_exact(&mut color)?;
+    Ok((args, cwd, color[0] != 0))
+}
+
+fn read_u32(stream: &mut UnixStream) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    stream.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_chunk(stream: &mut UnixStream) -> io::Result<Vec<u8>> {
+    let len = read_u32(stream)?;
+    let mut buf = vec![0u8; len as usize];
+    stream.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn write_frame(stream: &mut UnixStream, tag: u8, payload: &[u8]) -> io::Result<()> {
+    let mut frame = Vec::with_capacity(payload.len() + 5);
+    frame.push(tag);
+    frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    frame.extend_from_slice(payload);
+    stream.write_all(&frame)
+}
+
+/// Wraps every write into a frame carrying `tag`.
+struct FrameWriter {
+    stream: UnixStream,
+    tag: u8,
+}
+
+impl Write for FrameWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        write_frame(&mut self.stream, self.tag, buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
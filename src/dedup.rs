@@ -0,0 +1,179 @@
+//! `--dedup`: drops results that were already printed earlier in the run.
+//!
+//! Results are remembered exactly until they fill the memory budget; past
+//! that the set is folded into a Bloom filter of the same size, so memory
+//! stays bounded at the cost of occasionally dropping a new result.
+//!
+//! Under `--max-memory` a full set is spilled to a sorted temp file
+//! instead and memory starts over, so results stay exact. Each spilled
+//! run keeps a small filter and a sparse index in memory, about two bytes
+//! per result, and is only read when the filter says a result may be in it.
+
+use crate::sort::temp_path;
+use colored::*;
+use isaw_core::bloom::Bloom;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+/// False-positive rate the filter is sized for at the budget's capacity.
+const FP_RATE: f64 = 0.001;
+
+/// Bits per remembered word at [`FP_RATE`]: -ln(p) / ln(2)².
+const BITS_PER_WORD: f64 = 14.4;
+
+/// Words of a spilled run per entry of its index.
+const STRIDE: usize = 64;
+
+pub struct Dedup {
+    budget: usize,
+    bytes: usize,
+    seen: HashSet<String>,
+    bloom: Option<Bloom>,
+    /// Whether a full set is spilled to disk rather than folded.
+    spill: bool,
+    runs: Vec<Run>,
+}
+
+impl Dedup {
+    /// Remembers roughly `budget` bytes of results exactly.
+    pub fn new(budget: usize) -> Self {
+        Dedup { budget, bytes: 0, seen: HashSet::new(), bloom: None, spill: false, runs: Vec::new() }
+    }
+
+    /// Like [`Dedup::new`], but spills to temp files past the budget.
+    pub fn spilling(budget: usize) -> Self {
+        Dedup { spill: true, ..Dedup::new(budget) }
+    }
+
+    /// Records `word`; false if it was (probably) seen before.
+    pub fn insert(&mut self, word: &str) -> bool {
+        if let Some(bloom) = &mut self.bloom {
+            return bloom.insert(word);
+        }
+        if self.seen.contains(word) {
+            return false;
+        }
+        match self.runs.iter_mut().try_fold(false, |found, run| Ok::<_, io::Error>(found || run.contains(word)?)) {
+            Ok(true) => return false,
+            Ok(false) => {}
+            Err(e) => self.give_up(e),
+        }
+        self.bytes += word.len() + std::mem::size_of::<String>();
+        self.seen.insert(word.to_string());
+        if self.bytes >= self.budget {
+            match self.spill {
+                true => {
+                    if let Err(e) = self.spill_run() {
+                        self.give_up(e);
+                    }
+                }
+                false => self.fold(),
+            }
+        }
+        true
+    }
+
+    /// Writes the exact set out as a run and empties it.
+    fn spill_run(&mut self) -> io::Result<()> {
+        let run = Run::write(&self.seen)?;
+        self.runs.push(run);
+        self.seen.clear();
+        self.bytes = 0;
+        Ok(())
+    }
+
+    /// Falls back to folding when temp files fail; what was spilled can't
+    /// be checked any more, so those results may come out again.
+    fn give_up(&mut self, e: io::Error) {
+        errln!("{}", format!("⚠  Cannot use temp files for --dedup ({}); remembering results approximately from now on", e).yellow());
+        self.runs.clear();
+        self.spill = false;
+        self.fold();
+    }
+
+    /// Swaps the exact set for a filter with room for the rest of the run.
+    fn fold(&mut self) {
+        let capacity = (self.budget as f64 * 8.0 / BITS_PER_WORD) as u128;
+        let Some(mut bloom) = Bloom::new(capacity.max(self.seen.len() as u128 * 2), FP_RATE) else {
+            return;
+        };
+        for word in self.seen.drain() {
+            bloom.insert(&word);
+        }
+        self.seen.shrink_to_fit();
+        self.bloom = Some(bloom);
+    }
+}
+
+/// A spilled set: its words sorted in a temp file, with every
+/// [`STRIDE`]th one and its offset kept in memory.
+struct Run {
+    path: PathBuf,
+    file: BufReader<File>,
+    bloom: Bloom,
+    index: Vec<(String, u64)>,
+}
+
+impl Run {
+    fn write(seen: &HashSet<String>) -> io::Result<Self> {
+        let mut words: Vec<&String> = seen.iter().collect();
+        words.sort_unstable();
+        let path = temp_path("dedup");
+        let mut bloom = Bloom::new(words.len() as u128, FP_RATE).ok_or_else(|| io::Error::other("filter too large"))?;
+        let mut index = Vec::with_capacity(words.len() / STRIDE + 1);
+        let written = (|| {
+            let mut file = BufWriter::new(File::create(&path)?);
+            let mut offset = 0;
+            for (i, word) in words.iter().enumerate() {
+                if i % STRIDE == 0 {
+                    index.push((word.to_string(), offset));
+                }
+                bloom.insert(word);
+                writeln!(file, "{}", word)?;
+                offset += word.len() as u64 + 1;
+            }
+            file.flush()?;
+            File::open(&path)
+        })();
+        match written {
+            Ok(file) => Ok(Run { path, file: BufReader::new(file), bloom, index }),
+            Err(e) => {
+                let _ = fs::remove_file(&path);
+                Err(e)
+            }
+        }
+    }
+
+    fn contains(&mut self, word: &str) -> io::Result<bool> {
+        if !self.bloom.contains(word) {
+            return Ok(false);
+        }
+        let entry = self.index.partition_point(|(first, _)| first.as_str() <= word);
+        if entry == 0 {
+            return Ok(false);
+        }
+        self.file.seek(SeekFrom::Start(self.index[entry - 1].1))?;
+        let mut line = String::new();
+        for _ in 0..STRIDE {
+            line.clear();
+            if self.file.read_line(&mut line)? == 0 {
+                break;
+            }
+            match line.trim_end_matches('\n').cmp(word) {
+                Ordering::Less => {}
+                Ordering::Equal => return Ok(true),
+                Ordering::Greater => break,
+            }
+        }
+        Ok(false)
+    }
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
@@ -0,0 +1,279 @@
+//! `isaw dict`: wordlists managed in isaw's data directory.
+//!
+//! Lists live in `$XDG_DATA_HOME/isaw/dictionaries` (or
+//! `~/.local/share/isaw/dictionaries`) as `<name>.txt`, next to an `index`
+//! of tab-separated `name, sha256, word count, source` lines recording where
+//! each list came from and what it should hash to.
+//!
+//! Adding a list curates it on the way in: line endings are normalized,
+//! surrounding whitespace, blank lines and repeated words are dropped. The
+//! recorded hash is of the curated list exactly as stored, and it is checked
+//! whenever the list is opened, so a list edited or damaged in place is
+//! refused rather than used; `isaw dict verify` checks them all at once.
+//! `--dictionary <name>` and `--lang <name>` then find managed lists by name.
+
+use crate::cli::{DictAddArgs, DictArgs, DictCommand, DictFetchArgs, DictRemoveArgs, DictVerifyArgs};
+use crate::Result;
+use colored::*;
+use isaw_core::sha256;
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+struct Entry {
+    name: String,
+    sha256: String,
+    words: usize,
+    source: String,
+}
+
+pub fn dict(out: &mut dyn Write, args: DictArgs) -> Result<()> {
+    match args.command {
+        DictCommand::Add(args) => add(out, args),
+        DictCommand::List => list(out),
+        DictCommand::Remove(args) => remove(out, args),
+        DictCommand::Fetch(args) => fetch(out, args),
+        DictCommand::Verify(args) => verify(out, args),
+    }
+}
+
+fn data_dir() -> PathBuf {
+    let base = match std::env::var_os("XDG_DATA_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME").unwrap_or_default()).join(".local").join("share"),
+    };
+    base.join("isaw").join("dictionaries")
+}
+
+/// The file of the managed list called `name`, if there is one.
+pub fn managed(name: &str) -> Option<PathBuf> {
+    let path = data_dir().join(format!("{}.txt", name));
+    (valid_name(name) && path.is_file()).then_some(path)
+}
+
+/// The SHA-256 the index records for the managed list called `name`.
+pub fn recorded_sha256(name: &str) -> Result<Option<String>> {
+    Ok(read_index()?.into_iter().find(|entry| entry.name == name).map(|entry| entry.sha256))
+}
+
+/// Fails unless `bytes`, the managed list `name`, hash to what the index says.
+pub fn check(name: &str, bytes: &[u8], expected: &str) -> Result<()> {
+    let actual = sha256::hex_digest(bytes);
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(format!("Dictionary '{}' has sha256 {} but was installed as {}; it changed on disk, so add or fetch it again", name, actual, expected).into());
+    }
+    Ok(())
+}
+
+fn add(out: &mut dyn Write, args: DictAddArgs) -> Result<()> {
+    let bytes = fs::read(&args.file).map_err(|e| format!("Cannot read {}: {}", args.file.display(), e))?;
+    let source = fs::canonicalize(&args.file).unwrap_or(args.file).display().to_string();
+    install(out, &args.name, &bytes, source)
+}
+
+fn fetch(out: &mut dyn Write, args: DictFetchArgs) -> Result<()> {
+    if !valid_name(&args.name) {
+        return Err(invalid_name(&args.name));
+    }
+    writeln!(out, "{}", format!("⬇️  Fetching {}", args.url).cyan().bold())?;
+    out.flush()?;
+    let bytes = download(&args.url)?;
+    let curated = curate(&bytes);
+    if let Some(expected) = &args.sha256 {
+        // Either the publisher's digest of the file, or the one `dict list`
+        // showed for the same list installed elsewhere.
+        let (download, stored) = (sha256::hex_digest(&bytes), sha256::hex_digest(curated.text.as_bytes()));
+        let verified = match () {
+            _ if download.eq_ignore_ascii_case(expected) => "download",
+            _ if stored.eq_ignore_ascii_case(expected) => "curated list",
+            _ => return Err(format!("Checksum mismatch for {}: expected {}, got {} for the download and {} for the curated list", args.url, expected, download, stored).into()),
+        };
+        writeln!(out, "{}", format!("   Checksum of the {} verified", verified).dimmed())?;
+    }
+    store(out, &args.name, curated, args.url)
+}
+
+fn verify(out: &mut dyn Write, args: DictVerifyArgs) -> Result<()> {
+    let entries: Vec<Entry> = read_index()?.into_iter().filter(|entry| args.name.as_ref().is_none_or(|name| entry.name == *name)).collect();
+    if let Some(name) = &args.name
+        && entries.is_empty()
+    {
+        return Err(format!("No managed dictionary named '{}'", name).into());
+    }
+    writeln!(out, "{}", format!("🔏 Verifying {} dictionar{}", entries.len(), if entries.len() == 1 { "y" } else { "ies" }).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    let mut bad = 0;
+    for entry in &entries {
+        let path = data_dir().join(format!("{}.txt", entry.name));
+        let problem = match fs::read(&path) {
+            Ok(bytes) => check(&entry.name, &bytes, &entry.sha256).err().map(|_| format!("sha256 {}", sha256::hex_digest(&bytes))),
+            Err(e) => Some(e.to_string()),
+        };
+        match problem {
+            None => writeln!(out, "  {:<12} {}", entry.name.yellow().bold(), "ok".green())?,
+            Some(problem) => {
+                bad += 1;
+                writeln!(out, "  {:<12} {}  {}", entry.name.yellow().bold(), "changed".red().bold(), problem.dimmed())?;
+            }
+        }
+    }
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    if bad > 0 {
+        return Err(format!("{} of {} dictionaries no longer match the index", bad, entries.len()).into());
+    }
+    writeln!(out, "{}", "✨ Every dictionary matches its recorded sha256".green().bold())?;
+    Ok(())
+}
+
+fn remove(out: &mut dyn Write, args: DictRemoveArgs) -> Result<()> {
+    let mut entries = read_index()?;
+    let before = entries.len();
+    entries.retain(|entry| entry.name != args.name);
+    let path = managed(&args.name);
+    if entries.len() == before && path.is_none() {
+        return Err(format!("No managed dictionary named '{}'", args.name).into());
+    }
+    if let Some(path) = path {
+        fs::remove_file(path)?;
+    }
+    write_index(&entries)?;
+    writeln!(out, "{}", format!("🗑  Removed dictionary '{}'", args.name).green().bold())?;
+    Ok(())
+}
+
+fn list(out: &mut dyn Write) -> Result<()> {
+    let entries = read_index()?;
+    writeln!(out, "{}", format!("📚 Dictionaries in {}", data_dir().display()).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    for entry in &entries {
+        writeln!(
+            out,
+            "  {:<12} {:>9} words  {}  {}",
+            entry.name.yellow().bold(),
+            entry.words,
+            entry.sha256.get(..12).unwrap_or(&entry.sha256).dimmed(),
+            entry.source.dimmed()
+        )?;
+    }
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    writeln!(out, "{}", format!("✨ {} dictionaries", entries.len()).green().bold())?;
+    Ok(())
+}
+
+/// A wordlist as it will be stored.
+struct Curated {
+    text: String,
+    words: usize,
+}
+
+/// One trimmed line per word, blank lines and repeats dropped.
+fn curate(bytes: &[u8]) -> Curated {
+    let text = String::from_utf8_lossy(bytes);
+    let mut seen = HashSet::new();
+    let mut curated = String::with_capacity(text.len());
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if seen.insert(line) {
+            curated.push_str(line);
+            curated.push('\n');
+        }
+    }
+    Curated { words: seen.len(), text: curated }
+}
+
+/// Curates `bytes` and stores them as list `name`, replacing any old copy.
+fn install(out: &mut dyn Write, name: &str, bytes: &[u8], source: String) -> Result<()> {
+    store(out, name, curate(bytes), source)
+}
+
+/// Stores a curated list as `name`, recording the hash of what is written.
+fn store(out: &mut dyn Write, name: &str, curated: Curated, source: String) -> Result<()> {
+    if !valid_name(name) {
+        return Err(invalid_name(name));
+    }
+    let Curated { text, words } = curated;
+    let sha256 = sha256::hex_digest(text.as_bytes());
+
+    let dir = data_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+    write_atomic(&dir.join(format!("{}.txt", name)), text.as_bytes())?;
+
+    let mut entries = read_index()?;
+    entries.retain(|entry| entry.name != name);
+    entries.push(Entry { name: name.to_string(), sha256: sha256.clone(), words, source });
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    write_index(&entries)?;
+
+    writeln!(out, "{}", format!("✨ Installed '{}': {} words, sha256 {}", name, words, sha256).green().bold())?;
+    Ok(())
+}
+
+/// Downloads with whichever of curl or wget is installed.
+fn download(url: &str) -> Result<Vec<u8>> {
+    let attempts: [(&str, &[&str]); 2] = [("curl", &["-fsSL", "--"]), ("wget", &["-qO-", "--"])];
+    for (tool, flags) in attempts {
+        let output = match Command::new(tool).args(flags).arg(url).output() {
+            Ok(output) => output,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("Cannot run {}: {}", tool, e).into()),
+        };
+        if !output.status.success() {
+            let message = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Download of {} failed: {}", url, message.trim()).into());
+        }
+        return Ok(output.stdout);
+    }
+    Err("Fetching needs curl or wget on the PATH".into())
+}
+
+fn index_path() -> PathBuf {
+    data_dir().join("index")
+}
+
+fn read_index() -> Result<Vec<Entry>> {
+    let path = index_path();
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Cannot read {}: {}", path.display(), e).into()),
+    };
+    Ok(text
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, '\t');
+            Some(Entry {
+                name: fields.next()?.to_string(),
+                sha256: fields.next()?.to_string(),
+                words: fields.next()?.parse().ok()?,
+                source: fields.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect())
+}
+
+fn write_index(entries: &[Entry]) -> Result<()> {
+    let mut text = String::new();
+    for entry in entries {
+        text.push_str(&format!("{}\t{}\t{}\t{}\n", entry.name, entry.sha256, entry.words, entry.source));
+    }
+    fs::create_dir_all(data_dir())?;
+    write_atomic(&index_path(), text.as_bytes())
+}
+
+/// Writes then renames, so readers never see a half-written file.
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    let partial = path.with_extension("partial");
+    fs::write(&partial, bytes)?;
+    fs::rename(&partial, path)?;
+    Ok(())
+}
+
+/// Names become file names, so keep them to a safe alphabet.
+fn valid_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn invalid_name(name: &str) -> Box<dyn std::error::Error> {
+    format!("Invalid dictionary name '{}': use letters, digits, '-' and '_'", name).into()
+}
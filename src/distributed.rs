@@ -0,0 +1,271 @@
+//! `isaw coordinate` / `isaw worker`: one job spread over several machines.
+//!
+//! The coordinator cuts the job's keyspace into `--tasks` slices (the same
+//! ones `--slice i/n` selects) and hands them out over TCP. Workers run each
+//! slice with `--quiet` and send the matches back; the coordinator prints
+//! them in slice order. A worker that disconnects or goes silent for longer
+//! than `--timeout` has its slice put back in the queue.
+//!
+//! The protocol is line based:
+//!
+//! ```text
+//! worker: HELLO
+//! coord:  JOB <argc> <heartbeat secs>, then argc lines of arguments
+//! worker: NEXT
+//! coord:  TASK <i>/<n> | WAIT | DONE
+//! worker: PING*, MATCH <word>*, END        (after TASK, then NEXT again)
+//! ```
+
+use crate::cli::{Cli, CoordinateArgs, WorkerArgs};
+use crate::{run, Result};
+use clap::Parser;
+use colored::*;
+use isaw_core::rank::Slice;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Default)]
+struct State {
+    pending: VecDeque<u128>,
+    assigned: HashSet<u128>,
+    finished: BTreeMap<u128, Vec<String>>,
+    workers: usize,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    changed: Condvar,
+    job: Vec<String>,
+    tasks: u128,
+    timeout: Duration,
+}
+
+impl Shared {
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+pub fn coordinate(out: &mut dyn Write, args: CoordinateArgs) -> Result<()> {
+    if args.tasks == 0 {
+        return Err("--tasks must be at least 1".into());
+    }
+    let mut cli = parse_job(&args.job)?;
+    match cli.command.run_opts_mut() {
+        Some(run) if run.slice.is_none() => {}
+        Some(_) => return Err("The coordinator chooses the slices; drop --slice from the job".into()),
+        None => return Err("Only generating subcommands can be distributed".into()),
+    }
+    if args.job.iter().any(|arg| arg.contains('\n')) {
+        return Err("Job arguments cannot contain newlines".into());
+    }
+
+    let listener = TcpListener::bind(&args.listen)?;
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State { pending: (0..args.tasks).collect(), ..State::default() }),
+        changed: Condvar::new(),
+        job: args.job.clone(),
+        tasks: args.tasks,
+        timeout: Duration::from_secs(args.timeout.max(3)),
+    });
+
+    writeln!(out, "{}", format!("🌐 Coordinating `{}` in {} slices", args.job.join(" "), args.tasks).cyan().bold())?;
+    writeln!(out, "{}", format!("   Workers join with: isaw worker --join {}", listener.local_addr()?).dimmed())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    out.flush()?;
+
+    let accepting = Arc::clone(&shared);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let shared = Arc::clone(&accepting);
+            thread::spawn(move || serve_worker(&shared, stream));
+        }
+    });
+
+    let mut next = 0;
+    let mut matches = 0;
+    while next < shared.tasks {
+        let ready = {
+            let mut state = shared.lock();
+            while !state.finished.contains_key(&next) {
+                state = shared.changed.wait(state).unwrap_or_else(|e| e.into_inner());
+            }
+            state.finished.remove(&next).unwrap_or_default()
+        };
+        for word in &ready {
+            writeln!(out, "  {}", word)?;
+        }
+        matches += ready.len();
+        next += 1;
+    }
+
+    let workers = shared.lock().workers;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    writeln!(out, "{}", format!("✨ Found {} matches in {} slices from {} workers", matches, shared.tasks, workers).green().bold())?;
+    Ok(())
+}
+
+/// Talks to one worker until it is done or dies, re-queueing its slice on failure.
+fn serve_worker(shared: &Shared, stream: TcpStream) {
+    let mut current = None;
+    let result = drive_worker(shared, stream, &mut current);
+
+    let mut state = shared.lock();
+    if let Some(task) = current {
+        if let Err(e) = result {
+            errln!("{}", format!("Worker lost ({}); re-issuing slice {}/{}", e, task + 1, shared.tasks).yellow());
+        }
+        state.assigned.remove(&task);
+        state.pending.push_front(task);
+    }
+    shared.changed.notify_all();
+}
+
+fn drive_worker(shared: &Shared, stream: TcpStream, current: &mut Option<u128>) -> Result<()> {
+    stream.set_read_timeout(Some(shared.timeout))?;
+    let mut writer = stream.try_clone()?;
+    let mut lines = BufReader::new(stream).lines();
+    let mut next_line = || -> Result<String> { Ok(lines.next().ok_or("connection closed")??) };
+
+    if next_line()? != "HELLO" {
+        return Err("unexpected greeting".into());
+    }
+    shared.lock().workers += 1;
+    let heartbeat = (shared.timeout.as_secs() / 3).max(1);
+    writeln!(writer, "JOB {} {}", shared.job.len(), heartbeat)?;
+    for arg in &shared.job {
+        writeln!(writer, "{}", arg)?;
+    }
+
+    loop {
+        if next_line()? != "NEXT" {
+            return Err("expected NEXT".into());
+        }
+        let task = {
+            let mut state = shared.lock();
+            match state.pending.pop_front() {
+                Some(task) => {
+                    state.assigned.insert(task);
+                    Some(task)
+                }
+                None if state.assigned.is_empty() => None,
+                None => {
+                    drop(state);
+                    writeln!(writer, "WAIT")?;
+                    continue;
+                }
+            }
+        };
+        let Some(task) = task else {
+            writeln!(writer, "DONE")?;
+            return Ok(());
+        };
+        *current = Some(task);
+        writeln!(writer, "TASK {}/{}", task + 1, shared.tasks)?;
+
+        let mut found = Vec::new();
+        loop {
+            let line = next_line()?;
+            if line == "END" {
+                break;
+            } else if let Some(word) = line.strip_prefix("MATCH ") {
+                found.push(word.to_string());
+            } else if line != "PING" {
+                return Err(format!("unexpected message '{}'", line).into());
+            }
+        }
+
+        let mut state = shared.lock();
+        // A slice re-issued after a timeout may come back twice; keep one copy.
+        if state.assigned.remove(&task) {
+            state.finished.insert(task, found);
+        }
+        *current = None;
+        shared.changed.notify_all();
+    }
+}
+
+pub fn work(out: &mut dyn Write, args: WorkerArgs) -> Result<()> {
+    let stream = TcpStream::connect(&args.join).map_err(|e| format!("Cannot reach coordinator at {}: {}", args.join, e))?;
+    let writer = Arc::new(Mutex::new(stream.try_clone()?));
+    let mut lines = BufReader::new(stream).lines();
+    let mut next_line = || -> Result<String> { Ok(lines.next().ok_or("Coordinator closed the connection")??) };
+    let send = |line: &str| -> Result<()> {
+        let mut stream = writer.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(writeln!(stream, "{}", line)?)
+    };
+
+    send("HELLO")?;
+    let header = next_line()?;
+    let mut fields = header.strip_prefix("JOB ").ok_or("Unexpected reply from coordinator")?.split(' ');
+    let argc: usize = fields.next().and_then(|n| n.parse().ok()).ok_or("Malformed JOB line")?;
+    let heartbeat: u64 = fields.next().and_then(|n| n.parse().ok()).unwrap_or(10);
+    let job = (0..argc).map(|_| next_line()).collect::<Result<Vec<_>>>()?;
+
+    writeln!(out, "{}", format!("🛠  Working on `{}` for {}", job.join(" "), args.join).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    out.flush()?;
+
+    let mut slices = 0;
+    loop {
+        send("NEXT")?;
+        let reply = next_line()?;
+        let slice: Slice = match reply.as_str() {
+            "DONE" => break,
+            "WAIT" => {
+                thread::sleep(Duration::from_secs(1));
+                continue;
+            }
+            _ => reply.strip_prefix("TASK ").ok_or("Unexpected reply from coordinator")?.parse()?,
+        };
+
+        let mut cli = parse_job(&job)?;
+        if let Some(run) = cli.command.run_opts_mut() {
+            run.slice = Some(slice);
+            run.quiet = true;
+        }
+
+        // Keep the coordinator from declaring us dead during long slices.
+        let mut results = Vec::new();
+        let outcome = thread::scope(|scope| {
+            let (stop, stopped) = mpsc::channel::<()>();
+            let send = &send;
+            scope.spawn(move || {
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(Duration::from_secs(heartbeat)) {
+                    if send("PING").is_err() {
+                        break;
+                    }
+                }
+            });
+            let outcome = run(cli.command, &mut results);
+            drop(stop);
+            outcome
+        });
+        outcome?;
+
+        let text = String::from_utf8_lossy(&results);
+        let mut found = 0;
+        for word in text.lines() {
+            send(&format!("MATCH {}", word))?;
+            found += 1;
+        }
+        send("END")?;
+        slices += 1;
+        writeln!(out, "  Slice {}: {} matches", slice.to_string().yellow(), found)?;
+        out.flush()?;
+    }
+
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    writeln!(out, "{}", format!("✨ Processed {} slices", slices).green().bold())?;
+    Ok(())
+}
+
+pub fn parse_job(job: &[String]) -> Result<Cli> {
+    let argv = std::iter::once("isaw").chain(job.iter().map(String::as_str));
+    Cli::try_parse_from(argv).map_err(|e| format!("Invalid job: {}", e).into())
+}
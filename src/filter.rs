@@ -0,0 +1,73 @@
+//! `--filter-cmd`: results vetted by an external program.
+//!
+//! Results are collected into batches of [`BATCH`] and piped, one per line,
+//! to the command run through `sh -c`; whichever of them it prints back are
+//! kept, in their original order, and anything else it prints is ignored.
+//! A fresh process per batch means a filter only has to read stdin to the
+//! end and write stdout, like `grep -vxFf trademarks.txt` does. As with
+//! grep, exit status 1 just means nothing passed; higher ones are errors.
+
+use std::collections::HashSet;
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+
+/// Results per run of the command.
+pub const BATCH: usize = 10_000;
+
+pub struct Filter {
+    command: String,
+    pending: Vec<String>,
+}
+
+impl Filter {
+    pub fn new(command: &str) -> Self {
+        Filter { command: command.to_string(), pending: Vec::with_capacity(BATCH) }
+    }
+
+    /// Holds back one result; true once a batch is ready for [`Filter::run`].
+    pub fn push(&mut self, word: &str) -> bool {
+        self.pending.push(word.to_string());
+        self.pending.len() >= BATCH
+    }
+
+    /// Pipes the held-back results through the command and returns those it
+    /// kept.
+    pub fn run(&mut self) -> io::Result<Vec<String>> {
+        let batch = std::mem::take(&mut self.pending);
+        if batch.is_empty() {
+            return Ok(batch);
+        }
+        let failed = |e: io::Error| io::Error::new(e.kind(), format!("--filter-cmd '{}': {}", self.command, e));
+        let mut child = Command::new("sh").arg("-c").arg(&self.command).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn().map_err(failed)?;
+        let mut input = child.stdin.take().expect("piped stdin");
+        let mut output = child.stdout.take().expect("piped stdout");
+        // Write from another thread so a filter answering as it reads can't
+        // fill its stdout pipe while we are still blocked on its stdin; the
+        // writer owns stdin and closes it when done.
+        let words = &batch;
+        let echoed = std::thread::scope(|scope| {
+            let writer = scope.spawn(move || {
+                let mut lines = String::with_capacity(words.iter().map(|word| word.len() + 1).sum());
+                for word in words {
+                    lines.push_str(word);
+                    lines.push('\n');
+                }
+                // A filter may exit before reading everything; that's its answer.
+                match input.write_all(lines.as_bytes()) {
+                    Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+                    written => written,
+                }
+            });
+            let mut echoed = String::new();
+            let read = output.read_to_string(&mut echoed);
+            writer.join().expect("filter writer panicked").and(read).map(|_| echoed)
+        });
+        let echoed = echoed.map_err(failed)?;
+        let status = child.wait().map_err(failed)?;
+        if !matches!(status.code(), Some(0 | 1)) {
+            return Err(io::Error::other(format!("--filter-cmd '{}' failed ({})", self.command, status)));
+        }
+        let kept: HashSet<&str> = echoed.lines().collect();
+        Ok(batch.into_iter().filter(|word| kept.contains(word.as_str())).collect())
+    }
+}
@@ -0,0 +1,124 @@
+//! `isaw daemon --grpc`: the WebSocket query, served over gRPC.
+//!
+//! The service is generated from `proto/isaw.proto`, so other backends can
+//! generate typed clients from the same file. `Query` takes a generating
+//! command as its argument list and streams a `Match` per result, a
+//! `Progress` about once a second and a final `Done`, exactly the frames
+//! [`websocket`](crate::websocket) sends.
+//!
+//! Each query runs on a blocking thread and hands its events to the call
+//! through a channel of [`BUFFER`] events. When a client reads slower than
+//! the query generates, the channel fills and the run waits for it; when a
+//! client cancels the call, the channel closes and the run stops with the
+//! next result.
+
+use crate::websocket::{self, Event};
+use crate::{metrics, Invalid, Result};
+use colored::*;
+use std::io::{self, Write};
+use std::time::Instant;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tonic::{Request, Response, Status};
+
+mod proto {
+    tonic::include_proto!("isaw.v1");
+}
+
+use proto::engine_server::{Engine, EngineServer};
+use proto::query_event::Event as Reply;
+use proto::{Done, Match, Progress, QueryEvent, QueryRequest};
+
+/// Events a query may get ahead of its client.
+const BUFFER: usize = 256;
+
+/// Accepts gRPC calls on `address` forever.
+pub fn serve(out: &mut dyn Write, address: &str) -> Result<()> {
+    let listener = std::net::TcpListener::bind(address).map_err(|e| format!("Cannot listen on {}: {}", address, e))?;
+    writeln!(out, "{}", format!("🛰  isaw daemon serving gRPC queries on {}", listener.local_addr()?).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    out.flush()?;
+    // Results are bare words; nothing reading them wants terminal colors.
+    colored::control::set_override(false);
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(run(listener))
+}
+
+async fn run(listener: std::net::TcpListener) -> Result<()> {
+    listener.set_nonblocking(true)?;
+    let incoming = TcpListenerStream::new(tokio::net::TcpListener::from_std(listener)?);
+    tonic::transport::Server::builder().add_service(EngineServer::new(Service)).serve_with_incoming(incoming).await?;
+    Ok(())
+}
+
+struct Service;
+
+#[tonic::async_trait]
+impl Engine for Service {
+    type QueryStream = ReceiverStream<std::result::Result<QueryEvent, Status>>;
+
+    async fn query(&self, request: Request<QueryRequest>) -> std::result::Result<Response<Self::QueryStream>, Status> {
+        let cli = websocket::prepare(&request.into_inner().args).map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let (events, stream) = mpsc::channel(BUFFER);
+        tokio::task::spawn_blocking(move || {
+            let send = |event: Event| {
+                let reply = match event {
+                    Event::Match(value) => Reply::Match(Match { value: value.to_string() }),
+                    Event::Progress { matches, elapsed } => Reply::Progress(Progress { matches, elapsed_seconds: elapsed.as_secs_f64() }),
+                    Event::Done { matches, elapsed } => Reply::Done(Done { matches, elapsed_seconds: elapsed.as_secs_f64() }),
+                };
+                events.blocking_send(Ok(QueryEvent { event: Some(reply) })).map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))
+            };
+            let started = Instant::now();
+            let outcome = websocket::query(cli, &send);
+            metrics::query(outcome.is_ok(), started.elapsed());
+            if let Err(e) = outcome {
+                let status = match e.is::<Invalid>() {
+                    true => Status::invalid_argument(e.to_string()),
+                    false => Status::unknown(e.to_string()),
+                };
+                // Nobody to tell if the client already hung up.
+                let _ = events.blocking_send(Err(status));
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(stream)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proto::engine_client::EngineClient;
+
+    /// Serves on a free local port and runs `job` against it, returning
+    /// the events received or the status the call failed with.
+    fn call(job: &[&str]) -> std::result::Result<Vec<Reply>, Status> {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.spawn(async move { run(listener).await.unwrap() });
+        runtime.block_on(async {
+            let mut client = EngineClient::connect(address).await.unwrap();
+            let mut stream = client.query(QueryRequest { args: job.iter().map(|arg| arg.to_string()).collect() }).await?.into_inner();
+            let mut replies = Vec::new();
+            while let Some(event) = stream.message().await? {
+                replies.extend(event.event);
+            }
+            Ok(replies)
+        })
+    }
+
+    #[test]
+    fn streams_matches_then_done() {
+        let replies = call(&["permutations", "abc", "-m", "3"]).unwrap();
+        let matches: Vec<&str> = replies.iter().filter_map(|reply| if let Reply::Match(m) = reply { Some(m.value.as_str()) } else { None }).collect();
+        assert_eq!(matches, ["abc", "acb", "bac", "bca", "cab", "cba"]);
+        assert!(matches!(replies.last(), Some(Reply::Done(Done { matches: 6, .. }))));
+    }
+
+    #[test]
+    fn rejects_commands_that_cannot_stream() {
+        assert_eq!(call(&["count", "abc"]).unwrap_err().code(), tonic::Code::InvalidArgument);
+        assert_eq!(call(&["nonsense"]).unwrap_err().code(), tonic::Code::InvalidArgument);
+    }
+}
@@ -0,0 +1,41 @@
+//! Ctrl-C as a request to stop.
+//!
+//! The first SIGINT only raises a flag that the generators poll, so the run
+//! winds down the way `--first` ends it: the summary is still printed, with
+//! a `--resume` offset for what is left. A second Ctrl-C kills as usual.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Whether Ctrl-C has been pressed.
+pub fn requested() -> bool {
+    REQUESTED.load(Ordering::Relaxed)
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::ffi::c_int;
+
+    pub const SIGINT: c_int = 2;
+    pub const SIG_DFL: usize = 0;
+
+    unsafe extern "C" {
+        pub fn signal(signum: c_int, handler: usize) -> usize;
+    }
+}
+
+#[cfg(unix)]
+extern "C" fn handle(_: std::ffi::c_int) {
+    REQUESTED.store(true, Ordering::Relaxed);
+    // Back to the default, so pressing again ends the process right away.
+    unsafe { sys::signal(sys::SIGINT, sys::SIG_DFL) };
+}
+
+/// Catches Ctrl-C from now on; does nothing where signals don't exist.
+pub fn install() {
+    #[cfg(unix)]
+    unsafe {
+        sys::signal(sys::SIGINT, handle as extern "C" fn(std::ffi::c_int) as usize)
+    };
+}
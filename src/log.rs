@@ -0,0 +1,124 @@
+//! `-v`/`-vv`: diagnostics on stderr about where a run spends its time.
+//!
+//! `-v` reports each phase (loading dictionaries, building the pruning
+//! trie, generating and matching, merging sorted runs) with its duration;
+//! `-vv` adds per-file and per-step detail, including how the generation
+//! time splits between producing candidates and matching them. With
+//! `--log-format json` every event is one JSON object per line.
+//!
+//! Diagnostics go through [`stderr`] (via the crate's `errln!`) rather than
+//! straight to the process's stderr, so `isaw daemon` can [`capture`] them
+//! per request and send them back to the client that asked.
+
+use crate::report::json_string;
+use crate::stats::StatsFormat;
+use std::fmt::{self, Display};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+static JSON: AtomicBool = AtomicBool::new(false);
+static STARTED: OnceLock<Instant> = OnceLock::new();
+/// Where diagnostics go instead of stderr while a daemon request runs.
+static CAPTURE: Mutex<Option<Box<dyn Write + Send>>> = Mutex::new(None);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// Phases and their timings (`-v`).
+    Debug = 1,
+    /// Everything else (`-vv`).
+    Trace = 2,
+}
+
+impl Level {
+    fn name(self) -> &'static str {
+        match self {
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        }
+    }
+}
+
+pub fn init(verbosity: u8, format: StatsFormat) {
+    VERBOSITY.store(verbosity, Ordering::Relaxed);
+    JSON.store(matches!(format, StatsFormat::Json), Ordering::Relaxed);
+    STARTED.get_or_init(Instant::now);
+}
+
+/// Sends diagnostics to `sink` until [`release`] is called.
+#[cfg(unix)]
+pub fn capture(sink: Box<dyn Write + Send>) {
+    *CAPTURE.lock().unwrap_or_else(|e| e.into_inner()) = Some(sink);
+}
+
+/// Sends diagnostics back to the process's stderr.
+#[cfg(unix)]
+pub fn release() {
+    CAPTURE.lock().unwrap_or_else(|e| e.into_inner()).take();
+}
+
+/// Writes `text` to the capturing sink, or to stderr without one. Like
+/// `eprint!`, a failed write is not worth failing a run over; unlike it,
+/// it doesn't panic either.
+pub fn stderr(text: fmt::Arguments) {
+    let mut capture = CAPTURE.lock().unwrap_or_else(|e| e.into_inner());
+    let _ = match capture.as_mut() {
+        Some(sink) => sink.write_fmt(text),
+        None => io::stderr().write_fmt(text),
+    };
+}
+
+pub fn enabled(level: Level) -> bool {
+    VERBOSITY.load(Ordering::Relaxed) >= level as u8
+}
+
+/// Writes `message` with its `fields` to stderr, if `level` is enabled.
+pub fn event(level: Level, message: &str, fields: &[(&str, &dyn Display)]) {
+    if !enabled(level) {
+        return;
+    }
+    let at = STARTED.get_or_init(Instant::now).elapsed().as_secs_f64();
+    if JSON.load(Ordering::Relaxed) {
+        let mut line = format!("{{\"t\":{:.6},\"level\":\"{}\",\"message\":{}", at, level.name(), json_string(message));
+        for (key, value) in fields {
+            line.push_str(&format!(",{}:{}", json_string(key), json_value(&value.to_string())));
+        }
+        line.push('}');
+        errln!("{}", line);
+    } else {
+        let mut line = format!("[{:>9.3}s] {}", at, message);
+        for (key, value) in fields {
+            line.push_str(&format!(" {}={}", key, value));
+        }
+        errln!("{}", line);
+    }
+}
+
+/// Runs `f` as the phase `name`, logging how long it took at `-v`.
+pub fn timed<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    if !enabled(Level::Debug) {
+        return f();
+    }
+    let started = Instant::now();
+    let value = f();
+    phase(name, started.elapsed(), &[]);
+    value
+}
+
+/// Logs that the phase `name` took `elapsed`, with any extra `fields`.
+pub fn phase(name: &str, elapsed: Duration, fields: &[(&str, &dyn Display)]) {
+    let ms = format!("{:.3}", elapsed.as_secs_f64() * 1e3);
+    let mut all: Vec<(&str, &dyn Display)> = vec![("phase", &name), ("ms", &ms)];
+    all.extend_from_slice(fields);
+    event(Level::Debug, "phase finished", &all);
+}
+
+/// Numbers stay numbers in JSON; everything else becomes a string.
+fn json_value(value: &str) -> String {
+    match value.parse::<f64>() {
+        Ok(n) if n.is_finite() => value.to_string(),
+        _ => json_string(value),
+    }
+}
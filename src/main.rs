@@ -1,392 +1,2135 @@
-use clap::{Parser, Subcommand};
+use clap::Parser;
+use cli::*;
 use colored::*;
-use itertools::Itertools;
-use rayon::prelude::*;
-use std::collections::HashSet;
-
-#[derive(Parser)]
-#[command(name = "isaw")]
-#[command(about = "Generate letter combinations and search for words/sentences", long_about = None)]
-struct Cli {
-    #[command(subcommand)]
-    command: Commands,
-}
-
-#[derive(Subcommand)]
-enum Commands {
-    /// Generate all permutations of given letters
-    Permutations {
-        /// Letters to permute (e.g., "abc")
-        letters: String,
-        
-        /// Minimum length of combinations
-        #[arg(short, long, default_value = "1")]
-        min: usize,
-        
-        /// Maximum length of combinations (defaults to letter count)
-        #[arg(short = 'x', long)]
-        max: Option<usize>,
-        
-        /// Search for specific word/sentence in results
-        #[arg(short, long)]
-        search: Option<String>,
-        
-        /// Case insensitive search
-        #[arg(short, long)]
-        ignore_case: bool,
-    },
-    
-    /// Generate all combinations (order doesn't matter)
-    Combinations {
-        /// Letters to combine (e.g., "abc")
-        letters: String,
-        
-        /// Length of combinations
-        #[arg(short, long, default_value = "2")]
-        length: usize,
-        
-        /// Search for specific pattern in results
-        #[arg(short, long)]
-        search: Option<String>,
-        
-        /// Case insensitive search
-        #[arg(short, long)]
-        ignore_case: bool,
-    },
-    
-    /// Generate all possible words from letters (like Scrabble)
-    Words {
-        /// Available letters (e.g., "abcde")
-        letters: String,
-        
-        /// Minimum word length
-        #[arg(short, long, default_value = "2")]
-        min: usize,
-        
-        /// Maximum word length (defaults to letter count)
-        #[arg(short = 'x', long)]
-        max: Option<usize>,
-        
-        /// Search for specific pattern
-        #[arg(short, long)]
-        search: Option<String>,
-        
-        /// Only show unique combinations
-        #[arg(short, long)]
-        unique: bool,
-    },
-    
-    /// Search through custom alphabet combinations
-    Search {
-        /// Pattern to search for
-        pattern: String,
-        
-        /// Letters/alphabet to use (defaults to a-z)
-        #[arg(short, long)]
-        letters: Option<String>,
-        
-        /// Length of combinations to search
-        #[arg(short = 'n', long, default_value = "3")]
-        length: usize,
-        
-        /// Case insensitive search
-        #[arg(short, long)]
-        ignore_case: bool,
-        
-        /// Use regex pattern
-        #[arg(short, long)]
-        regex: bool,
-    },
-    
-    /// Count total combinations possible
-    Count {
-        /// Letters available
-        letters: String,
-        
-        /// Minimum length
-        #[arg(short, long, default_value = "1")]
-        min: usize,
-        
-        /// Maximum length
-        #[arg(short = 'x', long)]
-        max: Option<usize>,
-        
-        /// Count combinations instead of permutations
-        #[arg(short, long)]
-        combinations: bool,
-    },
+use isaw_core::bloom::Bloom;
+use isaw_core::fill::{self, Cell, Grid};
+use isaw_core::generate::{self, Keyspace};
+use isaw_core::necklace::{self, Symmetry};
+use isaw_core::ngram::Model;
+use isaw_core::perm::Perm;
+use isaw_core::rank;
+use isaw_core::rack::{self, Rack};
+use isaw_core::script::Script;
+use isaw_core::transform::{self, Transform};
+use isaw_core::trie::{self, Trie};
+use isaw_core::typo;
+use isaw_core::vanity::{Algorithm, Vanity};
+use isaw_core::{case, count, debruijn, dna, ladder, major, mask, morse, ngram, plate, wordsearch, Matcher};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+use std::ops::{Range, RangeInclusive};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// `eprintln!` through [`log::stderr`], so the daemon can hand each
+/// request's diagnostics to its client.
+macro_rules! errln {
+    ($($arg:tt)*) => {
+        $crate::log::stderr(format_args!("{}\n", format_args!($($arg)*)))
+    };
 }
 
+mod analyze;
+mod bench;
+mod cipher;
+mod cli;
+#[cfg(unix)]
+mod daemon;
+mod dedup;
+mod dict;
+mod distributed;
+mod filter;
+#[cfg(all(unix, feature = "grpc"))]
+mod grpc;
+mod interrupt;
+mod log;
+mod metrics;
+mod output;
+mod parallel;
+mod parquet;
+mod refine;
+mod report;
+mod rng;
+mod sort;
+mod space;
+mod sqlite;
+mod stats;
+mod stream;
+mod summary;
+#[cfg(unix)]
+mod websocket;
+mod wordlist;
+mod writer;
+
+use output::{human, si, Output};
+use report::json_string;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
 fn main() {
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
+    log::init(cli.verbose, cli.log_format);
+    let threads = if cli.no_parallel { Some(1) } else { cli.threads.map(usize::from) };
+    if let Some(threads) = threads {
+        // Only fails if a pool already exists, and none has been started yet.
+        let _ = rayon::ThreadPoolBuilder::new().num_threads(threads).build_global();
+    }
 
-    match cli.command {
-        Commands::Permutations { letters, min, max, search, ignore_case } => {
-            generate_permutations(&letters, min, max, search, ignore_case);
+    #[cfg(unix)]
+    if let Some(socket) = cli.connect {
+        let socket = socket.unwrap_or_else(daemon::default_socket);
+        match daemon::connect(&socket, &forwarded_args()) {
+            Ok(code) => std::process::exit(code),
+            Err(e) => exit_with(e),
         }
-        Commands::Combinations { letters, length, search, ignore_case } => {
-            generate_combinations(&letters, length, search, ignore_case);
+    }
+
+    // Only generating runs know how to stop early and say where they were.
+    if cli.command.run_opts_mut().is_some() {
+        interrupt::install();
+        space::watch_stdout();
+    }
+    if cli.command.run_opts_mut().is_some_and(|run| run.estimate) {
+        match bench::estimate(&forwarded_args()) {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(e) => exit_with(e),
         }
-        Commands::Words { letters, min, max, search, unique } => {
-            generate_words(&letters, min, max, search, unique);
+    }
+    if let Err(e) = writer::threaded(io::stdout(), |out| run(cli.command, out)) {
+        exit_with(e);
+    }
+}
+
+/// `--fail-fast` ran out of candidates without printing a match.
+#[derive(Debug)]
+struct NotFound;
+
+impl std::fmt::Display for NotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "No candidate matched")
+    }
+}
+
+impl std::error::Error for NotFound {}
+
+/// Exit code for [`NotFound`], apart from 1 for real errors.
+const NOT_FOUND: i32 = 3;
+
+/// Arguments that can't produce anything, caught before the run starts.
+#[derive(Debug)]
+struct Invalid(Problem, String);
+
+impl std::fmt::Display for Invalid {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.1)
+    }
+}
+
+impl std::error::Error for Invalid {}
+
+/// What is wrong with [`Invalid`] arguments. Each kind exits with its own
+/// code, after clap's 2 for malformed arguments and [`NOT_FOUND`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Problem {
+    /// Lengths that can't produce anything: zero, or a minimum above the
+    /// maximum or past the letters given.
+    Lengths,
+    /// No letters to generate from.
+    Letters,
+    /// Patterns that don't compile, or none at all.
+    Pattern,
+    /// A keyspace or count too large for u128.
+    Overflow,
+}
+
+impl Problem {
+    fn code(self) -> i32 {
+        match self {
+            Problem::Lengths => 4,
+            Problem::Letters => 5,
+            Problem::Pattern => 6,
+            Problem::Overflow => 7,
         }
-        Commands::Search { pattern, letters, length, ignore_case, regex } => {
-            search_combinations(&pattern, letters, length, ignore_case, regex);
+    }
+}
+
+/// The exit code for an error that ended a run.
+fn exit_code(e: &(dyn std::error::Error + 'static)) -> i32 {
+    match e.downcast_ref::<Invalid>() {
+        Some(Invalid(problem, _)) => problem.code(),
+        None if e.is::<NotFound>() => NOT_FOUND,
+        None => 1,
+    }
+}
+
+/// Rejects runs that would quietly print nothing useful: no letters, a
+/// length of zero, a minimum length above the maximum, or, when letters
+/// can't repeat, a minimum longer than the letters given. A maximum past
+/// them only warns.
+fn check_lengths(letters: usize, lengths: &RangeInclusive<usize>, repeats: bool) -> Result<()> {
+    let (min, max) = (*lengths.start(), *lengths.end());
+    if letters == 0 {
+        return Err(Box::new(Invalid(Problem::Letters, "No letters to generate from".to_string())));
+    }
+    if min == 0 {
+        return Err(Box::new(Invalid(Problem::Lengths, "Length 0 only gives the empty string; lengths start at 1".to_string())));
+    }
+    let plural = if letters == 1 { "" } else { "s" };
+    if !repeats && min > letters {
+        return Err(Box::new(Invalid(Problem::Lengths, format!("Minimum length {} is more than the {} letter{} given, so nothing can be formed", min, letters, plural))));
+    }
+    if min > max {
+        return Err(Box::new(Invalid(Problem::Lengths, format!("Minimum length {} is greater than maximum length {}", min, max))));
+    }
+    if !repeats && max > letters {
+        errln!("{}", format!("⚠  Maximum length {} is more than the {} letter{} given; results stop at length {}", max, letters, plural, letters).yellow());
+    }
+    Ok(())
+}
+
+/// Notes whether anything at all was written, for `--fail-fast`.
+struct Watched<'a> {
+    out: &'a mut dyn Write,
+    written: bool,
+}
+
+impl Write for Watched<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.written |= !buf.is_empty();
+        self.out.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// Executes a parsed subcommand, writing all regular output to `out`.
+///
+/// `--fail-fast` runs quietly until the first match, which is all that gets
+/// printed; finding none is a [`NotFound`] error.
+fn run(mut command: Commands, out: &mut dyn Write) -> Result<()> {
+    let fail_fast = match command.run_opts_mut() {
+        Some(run) if run.fail_fast => {
+            run.quiet = true;
+            run.first = Some(1);
+            true
         }
-        Commands::Count { letters, min, max, combinations } => {
-            count_combinations(&letters, min, max, combinations);
+        _ => false,
+    };
+    if !fail_fast {
+        return dispatch(command, out);
+    }
+    let mut watched = Watched { out, written: false };
+    dispatch(command, &mut watched)?;
+    match watched.written {
+        true => Ok(()),
+        false => Err(Box::new(NotFound)),
+    }
+}
+
+fn dispatch(command: Commands, out: &mut dyn Write) -> Result<()> {
+    match command {
+        Commands::Permutations(args) => generate_permutations(out, args),
+        Commands::Combinations(args) => generate_combinations(out, args),
+        Commands::Words(args) => generate_words(out, args),
+        Commands::Search(args) => search_combinations(out, args),
+        Commands::Dna(args) => search_sequences(out, args),
+        Commands::Plates(args) => plates(out, args),
+        Commands::Vanity(args) => vanity(out, args),
+        Commands::Debruijn(args) => de_bruijn(out, args),
+        Commands::Count(args) => count_combinations(out, args),
+        Commands::Entropy(args) => entropy(out, args),
+        Commands::Nth(args) => nth(out, args),
+        Commands::Rank(args) => rank_of(out, args),
+        Commands::Next(args) => step(out, args, true),
+        Commands::Prev(args) => step(out, args, false),
+        Commands::Perm(args) => permutation(out, args),
+        Commands::IsAnagram(args) => is_anagram(out, args),
+        Commands::Longest(args) => longest_words(out, args),
+        Commands::Coverage(args) => coverage(out, args),
+        Commands::Palindrome(args) => palindromic_words(out, args),
+        Commands::Split(args) => split_letters(out, args),
+        Commands::Acronym(args) => acronym(out, args),
+        Commands::Sample(args) => sample(out, args),
+        Commands::Mnemonic(args) => mnemonic(out, args),
+        Commands::Ladder(args) => word_ladder(out, args),
+        Commands::Fill(args) => fill_grid(out, args),
+        Commands::Wordsearch(args) => word_search(out, args),
+        Commands::Group(args) => anagram_groups(out, args),
+        Commands::Freq(args) => frequencies(out, args),
+        Commands::Analyze(args) => analyze::analyze(out, args),
+        Commands::Typos(args) => typos(out, args),
+        Commands::Suggest(args) => suggest(out, args),
+        Commands::Complete(args) => complete(out, args),
+        Commands::Refine(args) => refine::refine(out, args),
+        Commands::Morse(args) => morse(out, args),
+        Commands::Caesar(args) => cipher::caesar(out, args),
+        Commands::Subst(args) => cipher::subst(out, args),
+        Commands::Vigenere(args) => cipher::vigenere(out, args),
+        Commands::Cryptogram(args) => cipher::cryptogram(out, args),
+        Commands::Dict(args) => dict::dict(out, args),
+        #[cfg(unix)]
+        Commands::Daemon(args) => {
+            if let Some(address) = &args.metrics {
+                metrics::serve(out, address)?;
+            }
+            #[cfg(feature = "grpc")]
+            if let Some(address) = &args.grpc {
+                return grpc::serve(out, address);
+            }
+            match args.websocket {
+                Some(address) => websocket::serve(out, &address),
+                None => daemon::serve(out, &args.socket.unwrap_or_else(daemon::default_socket)),
+            }
         }
+        Commands::Coordinate(args) => distributed::coordinate(out, args),
+        Commands::Worker(args) => distributed::work(out, args),
+        Commands::Bench(args) => bench::bench(out, args),
+    }
+}
+
+/// Command-line arguments with `--connect` removed, as sent to the daemon
+/// and replayed by `--estimate`.
+fn forwarded_args() -> Vec<String> {
+    std::env::args()
+        .skip(1)
+        .filter(|arg| arg != "--connect" && !arg.starts_with("--connect="))
+        .collect()
+}
+
+fn exit_with(e: Box<dyn std::error::Error>) -> ! {
+    // A closed pipe (e.g. `isaw ... | head`) is a normal way to stop reading.
+    if e.downcast_ref::<io::Error>().is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe) {
+        std::process::exit(0);
+    }
+    if e.is::<NotFound>() {
+        std::process::exit(NOT_FOUND);
     }
+    eprintln!("{}", e.to_string().red());
+    std::process::exit(exit_code(e.as_ref()));
 }
 
-fn generate_permutations(letters: &str, min: usize, max: Option<usize>, search: Option<String>, ignore_case: bool) {
+fn generate_permutations(out: &mut dyn Write, args: PermutationsArgs) -> Result<()> {
+    let PermutationsArgs { letters, alphabet, min, max, order, repeats, distinct_under, search, search_file, ignore_case, parallel, palindromes_only, subsequence, run } = args;
+    let letters = cli::letters_or_preset(&letters, &alphabet);
+    let mut out = Output::new(out, &run)?;
+    let out = &mut out;
     let chars: Vec<char> = letters.chars().collect();
     let max_len = max.unwrap_or(chars.len());
+    check_lengths(chars.len(), &(min..=max_len), false)?;
+    let repeated = repeated_letters(&chars);
+    // A necklace is one class however many times its letters were typed.
+    let unique = (repeats == Repeats::Unique || distinct_under.is_some()) && !repeated.is_empty();
+    if palindromes_only || distinct_under.is_some() || order != PermutationOrder::Lex || unique {
+        out.unresumable();
+    }
+    out.describe("permutations", vec![("letters", json_string(&letters)), ("min", min.to_string()), ("max", max_len.to_string()), ("search", summary::list(&search))]);
     
-    println!("{}", format!("📝 Generating permutations of '{}' (length {} to {})", letters, min, max_len).cyan().bold());
-    println!("{}", "─".repeat(50).dimmed());
-    
-    let mut count = 0;
-    let mut matches = 0;
+    out.info(format!("📝 Generating permutations of '{}' (length {} to {})", letters, min, max_len).cyan().bold())?;
+    if repeats == Repeats::Warn && !palindromes_only && distinct_under.is_none() {
+        repeats_note(out, &repeated)?;
+    }
+    let range = slice_range(out, &run, Keyspace::Permutations, chars.len(), min..=max_len)?;
+    out.rule()?;
     
-    for len in min..=max_len {
-        for perm in chars.iter().permutations(len) {
-            let word: String = perm.into_iter().collect();
-            count += 1;
-            
-            if let Some(ref search_term) = search {
-                let (word_check, term_check) = if ignore_case {
-                    (word.to_lowercase(), search_term.to_lowercase())
-                } else {
-                    (word.clone(), search_term.clone())
-                };
-                
-                if word_check.contains(&term_check) {
-                    matches += 1;
-                    print_highlighted(&word, search_term, ignore_case);
+    let matcher = both(substring_matcher(search, search_file, ignore_case, subsequence)?, run_matcher(&run)?);
+    space::check(&run, Keyspace::Permutations, &chars, min..=max_len, range.as_ref(), matcher.is_some() || palindromes_only || distinct_under.is_some() || unique)?;
+    let (count, matches) = match (range, parallel) {
+        _ if palindromes_only => emit(out, generate::palindromes(&chars, min, max_len), matcher.as_ref(), &run)?,
+        (None, Some(merge)) => {
+            let blocks = generate::blocks(&chars, min, max_len, unique);
+            parallel::emit(out, &chars, &blocks, unique, matcher.as_ref(), merge, &run)?
+        }
+        (range, _) => {
+            // Lexicographic order skips repeats as it goes; the others filter them.
+            let filter = unique && (range.is_some() || order != PermutationOrder::Lex);
+            let candidates: Box<dyn Iterator<Item = String>> = match (range, order) {
+                (Some(range), _) => ranked(Keyspace::Permutations, chars, min..=max_len, range, run.reverse),
+                (None, PermutationOrder::Lex) => Box::new(generate::words(chars, min, max_len, unique)),
+                (None, PermutationOrder::Sjt) => Box::new(generate::sjt(chars, min, max_len)),
+                (None, PermutationOrder::Heap) => Box::new(generate::heap(chars, min, max_len)),
+            };
+            let candidates = match filter {
+                true => distinct(candidates, &run),
+                false => candidates,
+            };
+            match distinct_under {
+                Some(symmetry) => {
+                    let necklaces = candidates.filter(|word| necklace::is_canonical(&word.chars().collect::<Vec<_>>(), symmetry));
+                    emit(out, necklaces, matcher.as_ref(), &run)?
                 }
-            } else {
-                println!("  {}", word);
+                None => emit(out, candidates, matcher.as_ref(), &run)?,
             }
         }
-    }
+    };
     
-    println!("{}", "─".repeat(50).dimmed());
-    if search.is_some() {
-        println!("{}", format!("✨ Found {} matches out of {} permutations", matches, count).green().bold());
+    out.finish(matcher.as_ref())?;
+    out.rule()?;
+    if matcher.is_some() {
+        out.info(format!("✨ Found {} matches out of {} permutations", matches, count).green().bold())?;
     } else {
-        println!("{}", format!("✨ Generated {} permutations", count).green().bold());
+        out.info(format!("✨ Generated {} permutations", count).green().bold())?;
     }
+    out.done(count, matcher.is_some().then_some(matches));
+    Ok(())
 }
 
-fn generate_combinations(letters: &str, length: usize, search: Option<String>, ignore_case: bool) {
+fn generate_combinations(out: &mut dyn Write, args: CombinationsArgs) -> Result<()> {
+    let letters = cli::letters_or_preset(&args.letters, &args.alphabet);
+    let n = letters.chars().count();
+    let lengths = args.lengths(n);
+    check_lengths(n, &lengths, false)?;
+    let CombinationsArgs { order, distinct_under, search, search_file, ignore_case, subsequence, run, .. } = args;
+    let mut out = Output::new(out, &run)?;
+    let out = &mut out;
     let chars: Vec<char> = letters.chars().collect();
+    if distinct_under.is_some() {
+        distinct_positions(&chars)?;
+    }
+    if distinct_under.is_some() || order != CombinationOrder::Lex {
+        out.unresumable();
+    }
+    out.describe("combinations", vec![("letters", json_string(&letters)), ("min", lengths.start().to_string()), ("max", lengths.end().to_string()), ("search", summary::list(&search))]);
     
-    println!("{}", format!("🔤 Generating combinations of '{}' (length {})", letters, length).cyan().bold());
-    println!("{}", "─".repeat(50).dimmed());
+    let described = match lengths.start() == lengths.end() {
+        true => format!("length {}", lengths.start()),
+        false => format!("length {} to {}", lengths.start(), lengths.end()),
+    };
+    out.info(format!("🔤 Generating combinations of '{}' ({})", letters, described).cyan().bold())?;
+    let range = slice_range(out, &run, Keyspace::Combinations, n, lengths.clone())?;
+    out.rule()?;
     
-    let mut count = 0;
-    let mut matches = 0;
+    let matcher = both(substring_matcher(search, search_file, ignore_case, subsequence)?, run_matcher(&run)?);
+    space::check(&run, Keyspace::Combinations, &chars, lengths.clone(), range.as_ref(), matcher.is_some() || distinct_under.is_some())?;
+    // Each length runs on its own so the summary can break them down.
+    let mut per_length = Vec::new();
+    let ks: Vec<usize> = if run.reverse { lengths.clone().rev().collect() } else { lengths.clone().collect() };
+    let offsets: Vec<u128> = lengths
+        .clone()
+        .scan(0u128, |offset, k| {
+            let start = *offset;
+            *offset = offset.saturating_add(Keyspace::Combinations.size(n, k).unwrap_or(u128::MAX));
+            Some(start)
+        })
+        .collect();
+    for k in ks {
+        if out.stopping() {
+            break;
+        }
+        let candidates: Box<dyn Iterator<Item = String>> = match &range {
+            Some(range) => {
+                let offset = offsets[k - lengths.start()];
+                let size = Keyspace::Combinations.size(n, k).unwrap_or(u128::MAX);
+                let (start, end) = (range.start.max(offset), range.end.min(offset.saturating_add(size)));
+                if start >= end {
+                    continue;
+                }
+                ranked(Keyspace::Combinations, chars.clone(), k..=k, start - offset..end - offset, run.reverse)
+            }
+            None if order == CombinationOrder::Gray => Box::new(generate::revolving_door(chars.clone(), k)),
+            None => Box::new(generate::combinations(chars.clone(), k)),
+        };
+        let counts = match distinct_under {
+            Some(symmetry) => {
+                let necklaces = candidates.filter(|word| necklace::is_canonical_subset(&positions(word, &chars), n, symmetry));
+                emit(out, necklaces, matcher.as_ref(), &run)?
+            }
+            None => emit(out, candidates, matcher.as_ref(), &run)?,
+        };
+        per_length.push((k, counts));
+    }
+    let count = per_length.iter().map(|(_, (count, _))| count).sum();
+    let matches = per_length.iter().map(|(_, (_, matches))| matches).sum();
     
-    for combo in chars.iter().combinations(length) {
-        let word: String = combo.into_iter().collect();
-        count += 1;
-        
-        if let Some(ref search_term) = search {
-            let (word_check, term_check) = if ignore_case {
-                (word.to_lowercase(), search_term.to_lowercase())
-            } else {
-                (word.clone(), search_term.clone())
-            };
-            
-            if word_check.contains(&term_check) {
-                matches += 1;
-                print_highlighted(&word, search_term, ignore_case);
+    out.finish(matcher.as_ref())?;
+    out.rule()?;
+    if per_length.len() > 1 {
+        per_length.sort_by_key(|&(k, _)| k);
+        for (k, (count, matches)) in per_length {
+            match matcher {
+                Some(_) => out.info(format!("  Length {}: {} of {}", k.to_string().yellow(), matches, count))?,
+                None => out.info(format!("  Length {}: {}", k.to_string().yellow(), count))?,
             }
-        } else {
-            println!("  {}", word);
         }
     }
-    
-    println!("{}", "─".repeat(50).dimmed());
-    if search.is_some() {
-        println!("{}", format!("✨ Found {} matches out of {} combinations", matches, count).green().bold());
+    if matcher.is_some() {
+        out.info(format!("✨ Found {} matches out of {} combinations", matches, count).green().bold())?;
     } else {
-        println!("{}", format!("✨ Generated {} combinations", count).green().bold());
+        out.info(format!("✨ Generated {} combinations", count).green().bold())?;
+    }
+    out.done(count, matcher.is_some().then_some(matches));
+    Ok(())
+}
+
+/// Where a selection's letters sit in `chars`, taking the earliest copy
+/// after the previous one when letters repeat.
+fn positions(word: &str, chars: &[char]) -> Vec<usize> {
+    let mut positions: Vec<usize> = Vec::new();
+    for c in word.chars() {
+        let from = positions.last().map_or(0, |&i| i + 1);
+        positions.extend((from..chars.len()).find(|&i| chars[i] == c));
     }
+    positions
 }
 
-fn generate_words(letters: &str, min: usize, max: Option<usize>, search: Option<String>, unique: bool) {
+fn generate_words(out: &mut dyn Write, args: WordsArgs) -> Result<()> {
+    let WordsArgs { letters, alphabet, from_text, min, max, search, search_file, unique, repeats, unique_approx, parallel, palindromes_only, subsequence, run } = args;
+    let letters = match &from_text {
+        Some(path) => text_letters(path)?,
+        None => cli::letters_or_preset(&letters, &alphabet),
+    };
+    // A document repeats letters so much that only distinct words make sense.
+    let warn = repeats == Repeats::Warn && !unique && from_text.is_none() && unique_approx.is_none() && !palindromes_only;
+    let unique = unique || from_text.is_some() || repeats == Repeats::Unique;
+    let mut out = Output::new(out, &run)?;
+    let out = &mut out;
     let chars: Vec<char> = letters.chars().collect();
     let max_len = max.unwrap_or(chars.len());
+    check_lengths(chars.len(), &(min..=max_len), false)?;
+    if palindromes_only || unique || unique_approx.is_some() {
+        out.unresumable();
+    }
+    out.describe("words", vec![("letters", json_string(&letters)), ("min", min.to_string()), ("max", max_len.to_string()), ("search", summary::list(&search))]);
     
-    println!("{}", format!("📖 Generating word combinations from '{}' (length {} to {})", letters, min, max_len).cyan().bold());
-    println!("{}", "─".repeat(50).dimmed());
+    let source = match &from_text {
+        Some(path) => format!("the {} letters of {}", chars.len(), path.display()),
+        None => format!("'{}'", letters),
+    };
+    out.info(format!("📖 Generating word combinations from {} (length {} to {})", source, min, max_len).cyan().bold())?;
+    if warn {
+        repeats_note(out, &repeated_letters(&chars))?;
+    }
+    let range = slice_range(out, &run, Keyspace::Permutations, chars.len(), min..=max_len)?;
+    // Untransformed candidates can walk the dictionary instead of being
+    // generated and filtered, but only an unsliced keyspace in plain order.
+    let trie = match (range.is_none() && !palindromes_only && run.transforms().is_empty()).then(|| dictionary_trie(&run, &letters, min, max_len)) {
+        Some(trie) => trie?,
+        None => None,
+    };
+    if let Some(trie) = &trie {
+        out.unresumable();
+        let words = trie.len();
+        out.info(format!("   Pruning with {} dictionary word{} that fit the letters", words, if words == 1 { "" } else { "s" }).dimmed())?;
+    }
+    let bloom = match unique_approx {
+        Some(fp_rate) if trie.is_none() => {
+            let items = match &range {
+                Some(range) => range.end - range.start,
+                None => Keyspace::Permutations.total(chars.len(), min..=max_len).ok_or_else(|| Invalid(Problem::Overflow, "Keyspace too large for --unique-approx".to_string()))?,
+            };
+            Some(bloom_filter(out, items, fp_rate)?)
+        }
+        _ => None,
+    };
+    out.rule()?;
     
-    let mut seen: HashSet<String> = HashSet::new();
+    let matcher = both(substring_matcher(search, search_file, true, subsequence)?, run_matcher(&run)?);
+    space::check(&run, Keyspace::Permutations, &chars, min..=max_len, range.as_ref(), matcher.is_some() || trie.is_some() || palindromes_only || unique || unique_approx.is_some())?;
+    let (count, matches) = match (range, parallel, bloom) {
+        _ if let Some(trie) = &trie => emit(out, generate::dictionary_words(chars, min, max_len, unique || unique_approx.is_some(), trie), matcher.as_ref(), &run)?,
+        _ if palindromes_only => emit(out, generate::palindromes(&chars, min, max_len), matcher.as_ref(), &run)?,
+        (range, _, Some(bloom)) => {
+            let candidates: Box<dyn Iterator<Item = String>> = match range {
+                Some(range) => ranked(Keyspace::Permutations, chars, min..=max_len, range, run.reverse),
+                None => Box::new(generate::permutations(chars, min, max_len)),
+            };
+            emit(out, generate::unique_approx(candidates, bloom), matcher.as_ref(), &run)?
+        }
+        (Some(range), _, None) => {
+            let candidates = ranked(Keyspace::Permutations, chars, min..=max_len, range, run.reverse);
+            if unique {
+                emit(out, distinct(candidates, &run), matcher.as_ref(), &run)?
+            } else {
+                emit(out, candidates, matcher.as_ref(), &run)?
+            }
+        }
+        (None, Some(merge), None) => {
+            let blocks = generate::blocks(&chars, min, max_len, unique);
+            parallel::emit(out, &chars, &blocks, unique, matcher.as_ref(), merge, &run)?
+        }
+        (None, None, None) => emit(out, generate::words(chars, min, max_len, unique), matcher.as_ref(), &run)?,
+    };
+    
+    out.finish(matcher.as_ref())?;
+    out.rule()?;
+    if trie.is_some() {
+        out.info(format!("✨ Found {} matches among {} dictionary words", matches, count).green().bold())?;
+    } else if matcher.is_some() {
+        out.info(format!("✨ Found {} matches out of {} words", matches, count).green().bold())?;
+    } else {
+        out.info(format!("✨ Generated {} words", count).green().bold())?;
+    }
+    out.done(count, matcher.is_some().then_some(matches));
+    Ok(())
+}
+
+/// A trie of the `--dictionary`/`--lang` words that `letters` could spell
+/// at `min..=max` letters, or `None` without dictionaries.
+fn dictionary_trie(run: &RunOpts, letters: &str, min: usize, max: usize) -> Result<Option<Arc<Trie>>> {
+    let dictionaries = log::timed("dictionary load", || wordlist::resolve(&run.dictionary, &run.lang))?;
+    if dictionaries.is_empty() {
+        return Ok(None);
+    }
+    let rack = Rack::new(letters);
+    let mut sorted: Vec<char> = letters.chars().collect();
+    sorted.sort_unstable();
+    let filter = format!("spelled from {} at {}..={}", sorted.into_iter().collect::<String>(), min, max);
+    Ok(Some(wordlist::trie(&dictionaries, filter, |word| (min..=max).contains(&word.chars().count()) && rack.can_spell(word))))
+}
+
+/// [`generate::unique`], or the bounded set `--dedup` uses: spilling to
+/// disk under `--max-memory`, and with `--low-mem` folding into a filter
+/// that may drop a few new candidates once it is full.
+fn distinct<'a>(candidates: impl Iterator<Item = String> + 'a, run: &RunOpts) -> Box<dyn Iterator<Item = String> + 'a> {
+    let mut seen = match (run.memory_share(), run.low_mem) {
+        (Some(share), low_mem) => dedup::Dedup::spilling(if low_mem { share.min(LOW_MEM_MIB << 20) } else { share }),
+        (None, true) => dedup::Dedup::new(LOW_MEM_MIB << 20),
+        (None, false) => return Box::new(generate::unique(candidates)),
+    };
+    Box::new(candidates.filter(move |word| seen.insert(word)))
+}
+
+/// The letters `chars` has more than once, in order.
+fn repeated_letters(chars: &[char]) -> String {
+    let mut repeated: Vec<char> = chars.iter().enumerate().filter(|&(i, c)| chars[..i].contains(c)).map(|(_, &c)| c).collect();
+    repeated.sort_unstable();
+    repeated.dedup();
+    repeated.into_iter().collect()
+}
+
+/// Selections up to symmetry are sets of positions on a circle, which a
+/// repeated letter would make ambiguous.
+fn distinct_positions(chars: &[char]) -> Result<()> {
+    match repeated_letters(chars) {
+        repeated if repeated.is_empty() => Ok(()),
+        repeated => Err(Box::new(Invalid(Problem::Letters, format!("Repeated letters '{}': combinations under --distinct-under need distinct letters", repeated)))),
+    }
+}
+
+/// `--repeats warn`: says under the header that repeated letters will
+/// repeat results, and how to avoid it.
+fn repeats_note(out: &mut Output, repeated: &str) -> Result<()> {
+    if !repeated.is_empty() {
+        out.info(format!("   '{}' given more than once, so some results repeat; --repeats unique prints each once", repeated).dimmed())?;
+    }
+    Ok(())
+}
+
+/// Sizes the `--unique-approx` filter for `items` words and announces it.
+fn bloom_filter(out: &mut Output, items: u128, fp_rate: f64) -> Result<Bloom> {
+    if !(fp_rate > 0.0 && fp_rate < 1.0) {
+        return Err("--unique-approx rate must be between 0 and 1".into());
+    }
+    let bloom = Bloom::new(items, fp_rate).ok_or_else(|| Invalid(Problem::Overflow, "Keyspace too large for --unique-approx".to_string()))?;
+    let mib = bloom.bytes() as f64 / (1024.0 * 1024.0);
+    out.info(format!("   Approximate dedup: {:.1} MiB bloom filter, ~{} false-positive rate", mib, fp_rate).dimmed())?;
+    Ok(bloom)
+}
+
+/// Search terms from `--search` and `--search-file`, in that order.
+fn search_terms(mut terms: Vec<String>, file: Option<PathBuf>) -> Result<Vec<String>> {
+    if let Some(path) = file {
+        let text = std::fs::read_to_string(&path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        terms.extend(text.lines().filter(|line| !line.is_empty()).map(String::from));
+    }
+    Ok(terms)
+}
+
+/// Every letter in the document at `path`, lowercased and sorted, repeats
+/// included: the bag of tiles it could spell with.
+fn text_letters(path: &std::path::Path) -> Result<String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let mut letters: Vec<char> = text.chars().filter(|c| c.is_alphabetic()).flat_map(char::to_lowercase).collect();
+    if letters.is_empty() {
+        return Err(format!("{} contains no letters", path.display()).into());
+    }
+    letters.sort_unstable();
+    Ok(letters.into_iter().collect())
+}
+
+/// A substring matcher for the given terms, or `None` if there are none.
+fn substring_matcher(terms: Vec<String>, file: Option<PathBuf>, ignore_case: bool, subsequence: bool) -> Result<Option<Matcher>> {
+    let terms = search_terms(terms, file)?;
+    Ok((!terms.is_empty()).then(|| match subsequence {
+        true => Matcher::subsequences(&terms, ignore_case),
+        false => Matcher::substrings(&terms, ignore_case),
+    }))
+}
+
+/// The filters every generator shares: `--glob`, `--min-score`/`--max-score`,
+/// `--dictionary`, `--lang` and `--hash`.
+fn run_matcher(run: &RunOpts) -> Result<Option<Matcher>> {
+    let globs = (!run.glob.is_empty()).then(|| Matcher::Glob(run.glob.clone()));
+    let scores = match run.min_score.is_some() || run.max_score.is_some() {
+        true => Some(Matcher::Score {
+            model: run.ngrams.as_deref().map_or(Ok(Model::english()), analyze::model)?,
+            min: run.min_score.unwrap_or(f64::NEG_INFINITY),
+            max: run.max_score.unwrap_or(f64::INFINITY),
+        }),
+        false => None,
+    };
+    let script = match &run.filter_script {
+        Some(path) => {
+            let source = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+            let model = run.ngrams.as_deref().map_or(Ok(Model::english()), analyze::model)?;
+            Some(Matcher::Script(Box::new(Script::parse(&source, model).map_err(|e| format!("{}: {}", path.display(), e))?)))
+        }
+        None => None,
+    };
+    let hashes = match run.hash.is_empty() {
+        true => None,
+        false => Some(Matcher::sha256(&run.hash).map_err(|digest| Invalid(Problem::Pattern, format!("'{}' is not a hex SHA-256 digest", digest)))?),
+    };
+    let dictionaries = log::timed("dictionary load", || wordlist::resolve(&run.dictionary, &run.lang))?;
+    if log::enabled(log::Level::Debug) && !dictionaries.is_empty() {
+        // Indexes are built on first lookup; build them now to time it.
+        let words = log::timed("dictionary index", || dictionaries.iter().map(|(_, words)| words.len()).sum::<usize>());
+        log::event(log::Level::Trace, "dictionaries indexed", &[("lists", &dictionaries.len()), ("words", &words)]);
+    }
+    let words = (!dictionaries.is_empty()).then_some(Matcher::Dictionary(dictionaries));
+    Ok(both(both(both(both(globs, scores), script), words), hashes))
+}
+
+/// A matcher requiring both, or whichever of them exists.
+fn both(first: Option<Matcher>, second: Option<Matcher>) -> Option<Matcher> {
+    match (first, second) {
+        (Some(first), Some(second)) => Some(first.and(second)),
+        (first, second) => first.or(second),
+    }
+}
+
+/// The ranks `slice` covers, announced under the header, less what
+/// `--resume` skips; `None` without either.
+fn slice_range(out: &mut Output, run: &RunOpts, keyspace: Keyspace, n: usize, lengths: RangeInclusive<usize>) -> Result<Option<Range<u128>>> {
+    let Some(slice) = run.slice else {
+        // Going backwards needs ranks even for the whole keyspace.
+        return match run.reverse || run.resume.is_some() {
+            true => Ok(Some(resumed(out, run, 0..keyspace.total(n, lengths).ok_or_else(|| Invalid(Problem::Overflow, "Keyspace too large to rank".to_string()))?)?)),
+            false => Ok(None),
+        };
+    };
+    let total = keyspace.total(n, lengths).ok_or_else(|| Invalid(Problem::Overflow, "Keyspace too large to slice".to_string()))?;
+    let range = slice.range(total);
+    out.info(format!("   Slice {}: candidates {}..{} of {}", slice, range.start, range.end, total).dimmed())?;
+    Ok(Some(resumed(out, run, range)?))
+}
+
+/// `range` without the first `--resume` candidates in the run's direction.
+fn resumed(out: &mut Output, run: &RunOpts, range: Range<u128>) -> Result<Range<u128>> {
+    let Some(skip) = run.resume else {
+        return Ok(range);
+    };
+    let skip = skip.min(range.end - range.start);
+    out.info(format!("   Resuming after {} candidate{}", skip, if skip == 1 { "" } else { "s" }).dimmed())?;
+    Ok(match run.reverse {
+        true => range.start..range.end - skip,
+        false => range.start + skip..range.end,
+    })
+}
+
+/// The candidates with ranks in `range`, last first when `reverse` is set.
+fn ranked(keyspace: Keyspace, chars: Vec<char>, lengths: RangeInclusive<usize>, range: Range<u128>, reverse: bool) -> Box<dyn Iterator<Item = String>> {
+    match reverse {
+        true => Box::new(keyspace.candidates_rev(chars, lengths, range)),
+        false => Box::new(keyspace.candidates(chars, lengths, range)),
+    }
+}
+
+/// Prints every candidate (or only the matching ones, highlighted) and
+/// returns `(generated, matched)`. With `--any-case` each case variant
+/// counts as a candidate of its own.
+fn emit(out: &mut Output, candidates: impl Iterator<Item = String>, matcher: Option<&Matcher>, run: &RunOpts) -> Result<(u64, u64)> {
+    if run.any_case {
+        // Variants are counted one by one, not as ranks of the keyspace.
+        out.unresumable();
+    }
+    let candidates: Box<dyn Iterator<Item = String>> = match (run.any_case, run.reverse) {
+        (false, _) => Box::new(candidates),
+        (true, false) => Box::new(candidates.flat_map(|word| case::variants(&word))),
+        (true, true) => Box::new(candidates.flat_map(|word| case::variants(&word).collect::<Vec<_>>().into_iter().rev())),
+    };
+    let transforms = run.transforms();
     let mut count = 0;
     let mut matches = 0;
+    // With -vv, time the matcher on its own to show where the time goes.
+    let profile = log::enabled(log::Level::Trace);
+    let started = Instant::now();
+    let mut matching = Duration::ZERO;
     
-    for len in min..=max_len {
-        for perm in chars.iter().permutations(len) {
-            let word: String = perm.into_iter().collect();
-            
-            if unique {
-                if seen.contains(&word) {
-                    continue;
+    for word in candidates {
+        if out.stopping() {
+            break;
+        }
+        count += 1;
+        let word = transform::apply_all(&transforms, word);
+        
+        match matcher {
+            Some(matcher) => {
+                let at = profile.then(Instant::now);
+                let hit = matcher.is_match(&word);
+                if let Some(at) = at {
+                    matching += at.elapsed();
                 }
-                seen.insert(word.clone());
-            }
-            
-            count += 1;
-            
-            if let Some(ref search_term) = search {
-                if word.to_lowercase().contains(&search_term.to_lowercase()) {
+                if hit {
                     matches += 1;
-                    print_highlighted(&word, search_term, true);
+                    out.result(&word, Some(matcher))?;
                 }
-            } else {
-                println!("  {}", word);
             }
+            None => out.result(&word, None)?,
         }
     }
     
-    println!("{}", "─".repeat(50).dimmed());
-    if search.is_some() {
-        println!("{}", format!("✨ Found {} matches out of {} words", matches, count).green().bold());
-    } else {
-        println!("{}", format!("✨ Generated {} words", count).green().bold());
+    if profile {
+        let rest = started.elapsed().saturating_sub(matching);
+        log::phase("generation", rest, &[("candidates", &count)]);
+        if matcher.is_some() {
+            log::phase("matching", matching, &[("matched", &matches)]);
+        }
     }
+    Ok((count, matches))
 }
 
-fn search_combinations(pattern: &str, letters: Option<String>, length: usize, ignore_case: bool, regex: bool) {
-    let alphabet = letters.unwrap_or_else(|| "abcdefghijklmnopqrstuvwxyz".to_string());
-    let chars: Vec<char> = alphabet.chars().collect();
+fn search_combinations(out: &mut dyn Write, args: SearchArgs) -> Result<()> {
+    if args.template.is_some() {
+        return search_template(out, args);
+    }
+    let alphabet = args.letters();
+    let lengths = args.lengths();
+    check_lengths(alphabet.chars().count(), &lengths, true)?;
+    let SearchArgs { patterns, search_file, ignore_case, regex, palindromes_only, subsequence, run, .. } = args;
+    let mut out = Output::new(out, &run)?;
+    let out = &mut out;
+    let patterns = search_terms(patterns, search_file)?;
+    if patterns.is_empty() {
+        return Err(Box::new(Invalid(Problem::Pattern, "No patterns to search for".to_string())));
+    }
+    let pattern = patterns.join("', '");
+    // Search's keyspace is a product, so both cases simply join the alphabet.
+    let chars: Vec<char> = match run.any_case {
+        true => case::expand(&alphabet.chars().collect::<Vec<_>>()),
+        false => alphabet.chars().collect(),
+    };
+    let alphabet: String = chars.iter().collect();
+    out.describe("search", vec![("patterns", summary::list(&patterns)), ("alphabet", json_string(&alphabet)), ("min", lengths.start().to_string()), ("max", lengths.end().to_string()), ("palindromes", palindromes_only.to_string())]);
     
-    println!("{}", format!("🔍 Searching for '{}' in {}-letter combinations", pattern, length).cyan().bold());
-    println!("{}", format!("   Using alphabet: {}", alphabet).dimmed());
-    println!("{}", "─".repeat(50).dimmed());
+    let described = match lengths.start() == lengths.end() {
+        true => lengths.start().to_string(),
+        false => format!("{} to {}", lengths.start(), lengths.end()),
+    };
+    out.info(format!("🔍 Searching for '{}' in {}-letter {}", pattern, described, if palindromes_only { "palindromes" } else { "combinations" }).cyan().bold())?;
+    out.info(format!("   Using alphabet: {}", alphabet).dimmed())?;
+    // Palindromes are enumerated by their first halves, mirrored.
+    let half = |length: usize| if palindromes_only { length.div_ceil(2) } else { length };
+    let sizes = lengths
+        .clone()
+        .map(|length| count::power(chars.len() as u128, half(length) as u128))
+        .collect::<Option<Vec<u128>>>()
+        .ok_or_else(|| Invalid(Problem::Overflow, "Keyspace too large to search".to_string()))?;
+    let total = sizes.iter().try_fold(0u128, |total, &size| total.checked_add(size)).ok_or_else(|| Invalid(Problem::Overflow, "Keyspace too large to search".to_string()))?;
+    let range = match run.slice {
+        Some(slice) => {
+            let range = slice.range(total);
+            out.info(format!("   Slice {}: candidates {}..{} of {}", slice, range.start, range.end, total).dimmed())?;
+            range
+        }
+        None => 0..total,
+    };
+    let range = resumed(out, &run, range)?;
+    out.rule()?;
     
-    let results: Vec<String> = if regex {
-        let re = regex_lite::Regex::new(pattern).unwrap_or_else(|e| {
-            eprintln!("{}", format!("Invalid regex: {}", e).red());
-            std::process::exit(1);
-        });
-        
-        generate_all_combinations(&chars, length)
-            .into_par_iter()
-            .filter(|word| re.is_match(word))
-            .collect()
+    let matcher = if regex {
+        Matcher::regexes(&patterns, ignore_case).map_err(|e| Invalid(Problem::Pattern, format!("Invalid regex: {}", e)))?
+    } else if subsequence {
+        Matcher::subsequences(&patterns, ignore_case)
     } else {
-        let search_pattern = if ignore_case { pattern.to_lowercase() } else { pattern.to_string() };
-        
-        generate_all_combinations(&chars, length)
-            .into_par_iter()
-            .filter(|word| {
-                let w = if ignore_case { word.to_lowercase() } else { word.clone() };
-                w.contains(&search_pattern)
-            })
-            .collect()
+        Matcher::substrings(&patterns, ignore_case)
     };
+    let matcher = match run_matcher(&run)? {
+        Some(words) => matcher.and(words),
+        None => matcher,
+    };
+    
+    let labelled = lengths.start() != lengths.end();
+    let mut per_length = search_lengths(out, &chars, lengths, range, &matcher, palindromes_only, &run)?;
+    let searched = per_length.iter().map(|&(_, searched, _)| searched).sum();
+    let found = per_length.iter().map(|&(_, _, found)| found).sum();
+    
+    out.finish(Some(&matcher))?;
+    out.rule()?;
+    if labelled {
+        per_length.sort_by_key(|&(length, _, _)| length);
+        for (length, searched, found) in per_length {
+            out.info(format!("  Length {}: {} of {}", length.to_string().yellow(), found, searched))?;
+        }
+    }
+    out.info(format!("✨ Found {} matches", found).green().bold())?;
+    out.done(searched, Some(found));
+    Ok(())
+}
+
+/// Searches `range` of the candidates of every length in `lengths`, which
+/// rank through each length in turn, in parallel; palindromes are
+/// enumerated by their first halves, mirrored. Returns how many candidates
+/// each length searched and found.
+fn search_lengths(out: &mut Output, chars: &[char], lengths: RangeInclusive<usize>, range: Range<u128>, matcher: &Matcher, palindromes: bool, run: &RunOpts) -> Result<Vec<(usize, u64, u64)>> {
+    let half = |length: usize| if palindromes { length.div_ceil(2) } else { length };
+    let labelled = lengths.start() != lengths.end();
+    // Each length gets its share of the ranks.
+    let mut parts = Vec::new();
+    let mut offset = 0;
+    for length in lengths {
+        let size = count::power(chars.len() as u128, half(length) as u128).ok_or_else(|| Invalid(Problem::Overflow, "Keyspace too large to search".to_string()))?;
+        let (start, end) = (range.start.max(offset), range.end.min(offset + size));
+        if start < end {
+            parts.push((length, start - offset..end - offset));
+        }
+        offset += size;
+    }
+    if run.reverse {
+        parts.reverse();
+    }
+    let mut per_length = Vec::new();
+    for (length, ranks) in parts {
+        if out.stopping() {
+            break;
+        }
+        let transforms = match palindromes {
+            true => [vec![Transform::Mirror { odd: length % 2 == 1 }], run.transforms()].concat(),
+            false => run.transforms(),
+        };
+        if labelled {
+            out.info(format!("   Length {}", length).dimmed())?;
+        }
+        let (searched, found) = parallel::search(out, chars, half(length), ranks, run, matcher, &transforms)?;
+        per_length.push((length, searched, found));
+    }
+    Ok(per_length)
+}
+
+/// `search --template`: only the template's slots vary, the rest is fixed
+/// text. Without patterns every candidate is printed.
+fn search_template(out: &mut dyn Write, args: SearchArgs) -> Result<()> {
+    let SearchArgs { patterns, search_file, template, ignore_case, regex, subsequence, run, .. } = args;
+    let template = template.unwrap_or_default();
+    let mut out = Output::new(out, &run)?;
+    let out = &mut out;
+    let patterns = search_terms(patterns, search_file)?;
+    let positions = mask::template(&template).map_err(|e| format!("Invalid template: {}", e))?;
+    out.describe("search", vec![("template", json_string(&template)), ("patterns", summary::list(&patterns))]);
+    let total = positions.iter().try_fold(1u128, |total, chars| total.checked_mul(chars.len() as u128)).ok_or_else(|| Invalid(Problem::Overflow, "Keyspace too large to search".to_string()))?;
     
-    for word in &results {
-        print_highlighted(word, pattern, ignore_case);
+    out.info(format!("🔍 Generating from template '{}'", template).cyan().bold())?;
+    if !patterns.is_empty() {
+        out.info(format!("   Searching for '{}'", patterns.join("', '")).dimmed())?;
     }
+    let slots = positions.iter().filter(|chars| chars.len() > 1).count();
+    out.info(format!("   {} slot{}, {} candidates", slots, if slots == 1 { "" } else { "s" }, total).dimmed())?;
+    let range = match run.slice {
+        Some(slice) => {
+            let range = slice.range(total);
+            out.info(format!("   Slice {}: candidates {}..{} of {}", slice, range.start, range.end, total).dimmed())?;
+            range
+        }
+        None => 0..total,
+    };
+    let range = resumed(out, &run, range)?;
+    out.rule()?;
     
-    println!("{}", "─".repeat(50).dimmed());
-    println!("{}", format!("✨ Found {} matches", results.len()).green().bold());
+    let matcher = match (patterns.is_empty(), regex) {
+        (true, _) => None,
+        (false, true) => Some(Matcher::regexes(&patterns, ignore_case).map_err(|e| Invalid(Problem::Pattern, format!("Invalid regex: {}", e)))?),
+        (false, false) if subsequence => Some(Matcher::subsequences(&patterns, ignore_case)),
+        (false, false) => Some(Matcher::substrings(&patterns, ignore_case)),
+    };
+    let matcher = match (matcher, run_matcher(&run)?) {
+        (Some(matcher), Some(words)) => Some(matcher.and(words)),
+        (matcher, words) => matcher.or(words),
+    };
+    let (generated, found) = emit(out, generate::masked(positions, range, run.reverse), matcher.as_ref(), &run)?;
+    
+    out.finish(matcher.as_ref())?;
+    out.rule()?;
+    match matcher {
+        Some(_) => out.info(format!("✨ Found {} matches", found).green().bold())?,
+        None => out.info(format!("✨ Generated {} candidates", generated).green().bold())?,
+    }
+    out.done(generated, matcher.is_some().then_some(found));
+    Ok(())
 }
 
-fn generate_all_combinations(chars: &[char], length: usize) -> Vec<String> {
-    if length == 0 {
-        return vec![String::new()];
+/// Every plate of a format, less those with banned substrings; like
+/// `search --template`, with plate-style slots and regional presets.
+fn plates(out: &mut dyn Write, args: PlatesArgs) -> Result<()> {
+    let PlatesArgs { pattern, region, letters, contains, exclude, exclude_file, run } = args;
+    let (format, region_letters) = match region.as_deref().and_then(plate::region) {
+        Some((format, letters)) => (format.to_string(), letters),
+        None => (pattern.unwrap_or_default(), mask::UPPER),
+    };
+    let letters = letters.as_deref().unwrap_or(region_letters);
+    let mut out = Output::new(out, &run)?;
+    let out = &mut out;
+    let banned = search_terms(exclude, exclude_file)?;
+    let positions = plate::positions(&format, letters).map_err(|e| format!("Invalid pattern: {}", e))?;
+    out.describe("plates", vec![("format", json_string(&format)), ("contains", summary::list(&contains)), ("exclude", summary::list(&banned))]);
+    let total = positions.iter().try_fold(1u128, |total, chars| total.checked_mul(chars.len() as u128)).ok_or_else(|| Invalid(Problem::Overflow, "Keyspace too large to enumerate".to_string()))?;
+    
+    let title = match &region {
+        Some(region) => format!("🚗 Plates in the {} format '{}'", region, format),
+        None => format!("🚗 Plates in the format '{}'", format),
+    };
+    out.info(title.cyan().bold())?;
+    if !contains.is_empty() {
+        out.info(format!("   Containing '{}'", contains.join("', '")).dimmed())?;
     }
+    if !banned.is_empty() {
+        out.info(format!("   Excluding {} banned substring{}", banned.len(), if banned.len() == 1 { "" } else { "s" }).dimmed())?;
+    }
+    out.info(format!("   {} candidates", total).dimmed())?;
+    let range = match run.slice {
+        Some(slice) => {
+            let range = slice.range(total);
+            out.info(format!("   Slice {}: candidates {}..{} of {}", slice, range.start, range.end, total).dimmed())?;
+            range
+        }
+        None => 0..total,
+    };
+    let range = resumed(out, &run, range)?;
+    out.rule()?;
     
-    let mut results = Vec::new();
+    // Banned plates are rejected by the matcher rather than skipped, so
+    // candidate counts and --resume offsets stay ranks of the format.
+    let wanted = (!contains.is_empty()).then(|| Matcher::substrings(&contains, true));
+    let allowed = (!banned.is_empty()).then(|| Matcher::substrings(&banned, true).negate());
+    let matcher = both(both(wanted, allowed), run_matcher(&run)?);
+    let (generated, found) = emit(out, generate::masked(positions, range, run.reverse), matcher.as_ref(), &run)?;
+    
+    out.finish(matcher.as_ref())?;
+    out.rule()?;
+    match matcher {
+        Some(_) => out.info(format!("✨ Found {} plates", found).green().bold())?,
+        None => out.info(format!("✨ Generated {} plates", generated).green().bold())?,
+    }
+    out.done(generated, matcher.is_some().then_some(found));
+    Ok(())
+}
+
+/// Candidates completing `before || candidate || after` to a digest that
+/// starts with the target digits, hashed across cores as they are
+/// generated.
+fn vanity(out: &mut dyn Write, args: VanityArgs) -> Result<()> {
+    let lengths = args.lengths();
+    let VanityArgs { target, before, before_file, after, algorithm, object, letters, alphabet, run, .. } = args;
+    let before = match &before_file {
+        Some(path) => std::fs::read(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?,
+        None => before.into_bytes(),
+    };
+    let algorithm = algorithm.unwrap_or(if object.is_some() { Algorithm::Sha1 } else { Algorithm::Sha256 });
+    let hash = Vanity::new(algorithm, &target, before, after.into_bytes(), object.clone()).map_err(|e| format!("Invalid target: {}", e))?;
+    let difficulty = hash.difficulty();
+    let mut out = Output::new(out, &run)?;
+    let out = &mut out;
+    let letters = match (&letters, &alphabet) {
+        (None, None) => cli::default_alphabet(),
+        (letters, preset) => cli::letters_or_preset(letters, preset),
+    };
+    let chars: Vec<char> = match run.any_case {
+        true => case::expand(&letters.chars().collect::<Vec<_>>()),
+        false => letters.chars().collect(),
+    };
+    check_lengths(chars.len(), &lengths, true)?;
+    let alphabet: String = chars.iter().collect();
+    out.describe("vanity", vec![("target", json_string(&target)), ("algorithm", json_string(algorithm.name())), ("alphabet", json_string(&alphabet)), ("min", lengths.start().to_string()), ("max", lengths.end().to_string())]);
     
-    fn helper(chars: &[char], current: String, length: usize, results: &mut Vec<String>) {
-        if current.len() == length {
-            results.push(current);
-            return;
+    let what = match &object {
+        Some(object) => format!("git {} ids", object),
+        None => format!("{} digests", algorithm.name()),
+    };
+    out.info(format!("🔑 Searching for {} starting with '{}'", what, target).cyan().bold())?;
+    out.info(format!("   Using alphabet: {}", alphabet).dimmed())?;
+    out.info(format!("   About 1 in {} candidates matches", difficulty).dimmed())?;
+    let total = lengths
+        .clone()
+        .try_fold(0u128, |total, length| total.checked_add(count::power(chars.len() as u128, length as u128)?))
+        .ok_or_else(|| Invalid(Problem::Overflow, "Keyspace too large to search".to_string()))?;
+    let range = match run.slice {
+        Some(slice) => {
+            let range = slice.range(total);
+            out.info(format!("   Slice {}: candidates {}..{} of {}", slice, range.start, range.end, total).dimmed())?;
+            range
         }
-        
-        for &c in chars {
-            let mut next = current.clone();
-            next.push(c);
-            helper(chars, next, length, results);
+        None => 0..total,
+    };
+    let range = resumed(out, &run, range)?;
+    out.rule()?;
+    
+    let matcher = Matcher::Vanity(hash);
+    let matcher = match run_matcher(&run)? {
+        Some(words) => matcher.and(words),
+        None => matcher,
+    };
+    let per_length = search_lengths(out, &chars, lengths, range, &matcher, false, &run)?;
+    let searched: u64 = per_length.iter().map(|&(_, searched, _)| searched).sum();
+    let found = per_length.iter().map(|&(_, _, found)| found).sum();
+    
+    out.finish(Some(&matcher))?;
+    out.rule()?;
+    out.info(format!("✨ Found {} matches", found).green().bold())?;
+    out.done(searched, Some(found));
+    Ok(())
+}
+
+fn search_sequences(out: &mut dyn Write, args: DnaArgs) -> Result<()> {
+    let DnaArgs { motif, length, rna, complement, reverse_complement, codons, run } = args;
+    let mut out = Output::new(out, &run)?;
+    if codons {
+        out.group(3);
+    }
+    let out = &mut out;
+    let regex = dna::motif_regex(&motif, rna).map_err(|c| format!("'{}' is not an IUPAC nucleotide code", c))?;
+    let (alphabet, kind) = if rna { (dna::RNA, "RNA") } else { (dna::DNA, "DNA") };
+    let chars: Vec<char> = match run.any_case {
+        true => case::expand(&alphabet.chars().collect::<Vec<_>>()),
+        false => alphabet.chars().collect(),
+    };
+    let transforms: Vec<Transform> = match (complement, reverse_complement) {
+        (true, _) => vec![Transform::Complement { rna }],
+        (_, true) => vec![Transform::ReverseComplement { rna }],
+        _ => Vec::new(),
+    };
+    let transforms = [transforms, run.transforms()].concat();
+    
+    out.describe("dna", vec![("motif", json_string(&motif)), ("length", length.to_string()), ("rna", rna.to_string())]);
+    out.info(format!("🧬 Searching for motif '{}' in {}-base {} sequences", motif, length, kind).cyan().bold())?;
+    out.info(format!("   Pattern: {}", regex).dimmed())?;
+    let range = slice_range(out, &run, Keyspace::Product, chars.len(), length..=length)?;
+    out.rule()?;
+    
+    let matcher = Matcher::regex(&regex).map_err(|e| format!("Invalid motif: {}", e))?;
+    let matcher = match run_matcher(&run)? {
+        Some(words) => matcher.and(words),
+        None => matcher,
+    };
+    
+    let range = match range {
+        Some(range) => range,
+        None => 0..count::power(chars.len() as u128, length as u128).ok_or_else(|| Invalid(Problem::Overflow, "Keyspace too large to search".to_string()))?,
+    };
+    let (searched, found) = parallel::search(out, &chars, length, range, &run, &matcher, &transforms)?;
+    
+    out.finish(Some(&matcher))?;
+    out.rule()?;
+    out.info(format!("✨ Found {} matching sequences", found).green().bold())?;
+    out.done(searched, Some(found));
+    Ok(())
+}
+
+fn de_bruijn(out: &mut dyn Write, args: DebruijnArgs) -> Result<()> {
+    let DebruijnArgs { letters, alphabet, length, cyclic, quiet } = args;
+    let letters = match (letters, alphabet) {
+        (Some(letters), _) => letters,
+        (None, Some(alphabet)) => isaw_core::alphabet::preset(&alphabet).map_or_else(|| alphabet.clone(), str::to_string),
+        (None, None) => cli::DEFAULT_ALPHABET.to_string(),
+    };
+    let chars: Vec<char> = letters.chars().collect();
+    if chars.is_empty() {
+        return Err(Box::new(Invalid(Problem::Letters, "A de Bruijn sequence needs at least one letter".to_string())));
+    }
+    if let Some(repeated) = chars.iter().enumerate().find_map(|(i, c)| chars[..i].contains(c).then_some(c)) {
+        return Err(Box::new(Invalid(Problem::Letters, format!("'{}' appears more than once in '{}'; each letter must be distinct", repeated, letters))));
+    }
+    if length == 0 {
+        return Err(Box::new(Invalid(Problem::Lengths, "A de Bruijn sequence needs a length of 1 or more".to_string())));
+    }
+    if !quiet {
+        writeln!(out, "{}", format!("🔁 De Bruijn sequence of length-{} strings over '{}'", length, letters).cyan().bold())?;
+        writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    }
+    
+    let mut written: u128 = 0;
+    let mut result = Ok(());
+    let mut line = String::new();
+    debruijn::sequence(chars.len(), length, |piece| {
+        line.extend(piece.iter().map(|&i| chars[i]));
+        written += piece.len() as u128;
+        // Flush in chunks so huge sequences stream instead of piling up.
+        if line.len() >= 1 << 16 && result.is_ok() {
+            result = out.write_all(line.as_bytes());
+            line.clear();
         }
+    });
+    result?;
+    // The sequence starts with `length` copies of the first letter, so a
+    // linear copy only needs `length - 1` of them to close the cycle.
+    if !cyclic {
+        line.extend(std::iter::repeat_n(chars[0], length - 1));
     }
+    writeln!(out, "{}", line)?;
     
-    helper(chars, String::new(), length, &mut results);
-    results
+    if !quiet {
+        writeln!(out, "{}", "─".repeat(50).dimmed())?;
+        let total = if cyclic { written } else { written + length as u128 - 1 };
+        writeln!(out, "{}", format!("✨ {} characters covering all {}^{} strings", total, chars.len(), length).green().bold())?;
+    }
+    Ok(())
 }
 
-fn count_combinations(letters: &str, min: usize, max: Option<usize>, combinations: bool) {
+fn count_combinations(out: &mut dyn Write, args: CountArgs) -> Result<()> {
+    let CountArgs { letters, alphabet, min, max, combinations, distinct_under, bytes, join } = args;
+    let letters = cli::letters_or_preset(&letters, &alphabet);
+    let letters = letters.as_str();
     let n = letters.chars().count();
     let max_len = max.unwrap_or(n);
+    if min > max_len {
+        return Err(Box::new(Invalid(Problem::Lengths, format!("Minimum length {} is greater than maximum length {}", min, max_len))));
+    }
+    let overflows = |k: usize| Invalid(Problem::Overflow, format!("The count at length {} overflows u128; lower --max", k));
+    let chars: Vec<char> = letters.chars().collect();
+    if distinct_under.is_some() && combinations {
+        distinct_positions(&chars)?;
+    }
+    let multiplicities: Vec<usize> = chars.iter().enumerate().filter(|&(i, c)| !chars[..i].contains(c)).map(|(_, c)| chars.iter().filter(|&d| d == c).count()).collect();
     
-    println!("{}", format!("📊 Counting {} for '{}'", 
+    let kind = match distinct_under {
+        Some(Symmetry::Rotation) => "necklaces",
+        Some(Symmetry::Dihedral) => "bracelets",
+        None => "",
+    };
+    writeln!(out, "{}", format!("📊 Counting {}{} for '{}'", 
         if combinations { "combinations" } else { "permutations" },
+        if kind.is_empty() { String::new() } else { format!(" as {}", kind) },
         letters
-    ).cyan().bold());
-    println!("{}", "─".repeat(50).dimmed());
+    ).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
     
+    let size = bytes.map(|format| space::Size::new(format, letters, join.as_deref().unwrap_or("")));
     let mut total: u128 = 0;
+    // `None` once the projection no longer fits in u128.
+    let mut total_bytes = Some(0u128);
     
     for k in min..=max_len {
-        let count = if combinations {
-            // C(n, k) = n! / (k! * (n-k)!)
-            binomial(n as u128, k as u128)
-        } else {
-            // P(n, k) = n! / (n-k)!
-            permutation(n as u128, k as u128)
-        };
+        let count = match (distinct_under, combinations) {
+            (Some(symmetry), true) => necklace::subsets(n, k, symmetry),
+            (Some(symmetry), false) => necklace::arrangements(&multiplicities, k, symmetry),
+            (None, true) => count::checked_binomial(n as u128, k as u128),
+            (None, false) => count::checked_permutation(n as u128, k as u128),
+        }
+        .ok_or_else(|| overflows(k))?;
         
-        println!("  Length {}: {}", format!("{}", k).yellow(), format!("{}", count).white().bold());
-        total += count;
+        write!(out, "  Length {}: {}", format!("{}", k).yellow(), format!("{}", count).white().bold())?;
+        if let Some(size) = &size {
+            let length_bytes = size.of(count, k);
+            let shown = length_bytes.map_or("overflows u128".to_string(), |bytes| format!("{}B", si(bytes as f64)));
+            write!(out, "  {}", format!("({})", shown).dimmed())?;
+            total_bytes = total_bytes.zip(length_bytes).and_then(|(total, bytes)| total.checked_add(bytes));
+        }
+        writeln!(out)?;
+        total = total.checked_add(count).ok_or_else(|| Invalid(Problem::Overflow, format!("The total up to length {} overflows u128; lower --max", k)))?;
     }
     
-    println!("{}", "─".repeat(50).dimmed());
-    println!("{}", format!("✨ Total: {}", total).green().bold());
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    writeln!(out, "{}", format!("✨ Total: {}", total).green().bold())?;
+    if let (Some(size), Some(format)) = (&size, bytes) {
+        let format = format!("{:?}", format).to_lowercase();
+        let lines = total.checked_add((size.header > 0) as u128);
+        let described = match (lines, total_bytes.and_then(|bytes| bytes.checked_add(size.header))) {
+            (Some(lines), Some(bytes)) => format!("   {} line{}, {} bytes ({}B) as {}", lines, if lines == 1 { "" } else { "s" }, bytes, si(bytes as f64), format),
+            _ => format!("   Size as {} overflows u128 bytes", format),
+        };
+        writeln!(out, "{}", described.dimmed())?;
+    }
+    Ok(())
 }
 
-fn factorial(n: u128) -> u128 {
-    (1..=n).product()
+fn entropy(out: &mut dyn Write, args: EntropyArgs) -> Result<()> {
+    let EntropyArgs { letters, alphabet, length, mask, rate } = args;
+    let (positions, described) = match &mask {
+        Some(mask) => (mask::parse(mask).map_err(|e| format!("Invalid mask: {}", e))?, format!("mask '{}'", mask)),
+        None => {
+            let letters = match (letters, alphabet) {
+                (None, None) => cli::DEFAULT_ALPHABET.to_string(),
+                (letters, alphabet) => cli::letters_or_preset(&letters, &alphabet),
+            };
+            let length = length.unwrap_or_default();
+            let chars: Vec<char> = letters.chars().collect();
+            (vec![chars; length], format!("{} characters from '{}'", length, letters))
+        }
+    };
+    // Logs keep the entropy exact even when the keyspace overflows u128.
+    let bits: f64 = positions.iter().map(|chars| (chars.len() as f64).log2()).sum();
+    let keyspace = positions.iter().try_fold(1u128, |total, chars| total.checked_mul(chars.len() as u128));
+    
+    writeln!(out, "{}", format!("🔐 Keyspace of {}", described).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    match keyspace {
+        Some(keyspace) => writeln!(out, "  Keyspace: {} ({})", keyspace.to_string().white().bold(), si(keyspace as f64))?,
+        None => writeln!(out, "  Keyspace: {}", format!("2^{:.1}", bits).white().bold())?,
+    }
+    writeln!(out, "  Entropy:  {} bits", format!("{:.1}", bits).white().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    for rate in &rate {
+        let secs = 2f64.powf(bits) / rate;
+        writeln!(out, "  At {:>6} guesses/s: {}", si(*rate), human(secs).yellow())?;
+    }
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    let strength = match bits {
+        b if b < 28.0 => "very weak",
+        b if b < 36.0 => "weak",
+        b if b < 60.0 => "reasonable",
+        b if b < 128.0 => "strong",
+        _ => "very strong",
+    };
+    writeln!(out, "{}", format!("✨ {:.1} bits: {}", bits, strength).green().bold())?;
+    Ok(())
 }
 
-fn binomial(n: u128, k: u128) -> u128 {
+/// Ranks count within one length, in the order `permutations` and
+/// `combinations` generate that length.
+fn nth(out: &mut dyn Write, args: NthArgs) -> Result<()> {
+    let NthArgs { letters, rank, length, combinations } = args;
+    let chars: Vec<char> = letters.chars().collect();
+    let (n, k) = (chars.len(), length.unwrap_or(chars.len()));
     if k > n {
-        return 0;
+        return Err(format!("Length {} is more than the {} letters given", k, n).into());
     }
-    factorial(n) / (factorial(k) * factorial(n - k))
+    let space = if combinations { Keyspace::Combinations } else { Keyspace::Permutations };
+    if let Some(size) = space.size(n, k)
+        && rank >= size
+    {
+        return Err(format!("Rank {} is out of range: there are {} of length {}", rank, size, k).into());
+    }
+    let indices = if combinations { rank::unrank_combination(n, k, rank) } else { rank::unrank_permutation(n, k, rank) };
+    writeln!(out, "{}", indices.into_iter().map(|i| chars[i]).collect::<String>())?;
+    Ok(())
 }
 
-fn permutation(n: u128, k: u128) -> u128 {
-    if k > n {
-        return 0;
+fn rank_of(out: &mut dyn Write, args: RankArgs) -> Result<()> {
+    let RankArgs { letters, word, combinations } = args;
+    let chars: Vec<char> = letters.chars().collect();
+    // Repeated letters take the earliest copy still free (after the last
+    // pick, for a selection), matching the first rank that produces the word.
+    let mut used = vec![false; chars.len()];
+    let mut indices = Vec::new();
+    for c in word.chars() {
+        let from = if combinations { indices.last().map_or(0, |&i| i + 1) } else { 0 };
+        let index = (from..chars.len())
+            .find(|&i| !used[i] && chars[i] == c)
+            .ok_or_else(|| format!("'{}' is not {} of '{}'", word, if combinations { "a selection" } else { "an arrangement" }, letters))?;
+        used[index] = true;
+        indices.push(index);
     }
-    factorial(n) / factorial(n - k)
+    let rank = if combinations { rank::rank_combination(chars.len(), &indices) } else { rank::rank_permutation(chars.len(), &indices) };
+    let rank = rank.ok_or_else(|| format!("The rank of '{}' overflows", word))?;
+    writeln!(out, "{}", rank)?;
+    Ok(())
 }
 
-fn print_highlighted(word: &str, pattern: &str, ignore_case: bool) {
-    if ignore_case {
-        let lower_word = word.to_lowercase();
-        let lower_pattern = pattern.to_lowercase();
-        
-        if let Some(pos) = lower_word.find(&lower_pattern) {
-            let before = &word[..pos];
-            let matched = &word[pos..pos + pattern.len()];
-            let after = &word[pos + pattern.len()..];
-            println!("  {}{}{}", before, matched.yellow().bold(), after);
-        } else {
-            println!("  {}", word);
-        }
-    } else if let Some(pos) = word.find(pattern) {
-        let before = &word[..pos];
-        let matched = &word[pos..pos + pattern.len()];
-        let after = &word[pos + pattern.len()..];
-        println!("  {}{}{}", before, matched.yellow().bold(), after);
+/// Prints the permutation `count` places after (or before) the word,
+/// jumping by rank rather than stepping through the ones in between.
+fn step(out: &mut dyn Write, args: StepArgs, forward: bool) -> Result<()> {
+    let StepArgs { word, count } = args;
+    let chars: Vec<char> = word.chars().collect();
+    let too_long = || format!("'{}' has too many arrangements to rank", word);
+    let rank = rank::rank_arrangement(&chars).ok_or_else(too_long)?;
+    let target = if forward { rank.checked_add(count) } else { rank.checked_sub(count) };
+    let next = target.and_then(|target| rank::unrank_arrangement(&chars, target));
+    let Some(next) = next else {
+        // The letters in descending order are the last arrangement.
+        let mut last = chars.clone();
+        last.sort_unstable_by(|a, b| b.cmp(a));
+        let left = if forward { rank::rank_arrangement(&last).ok_or_else(too_long)? - rank } else { rank };
+        let side = if forward { "after" } else { "before" };
+        return Err(format!("Only {} permutation{} come{} {} '{}'", left, if left == 1 { "" } else { "s" }, if left == 1 { "s" } else { "" }, side, word).into());
+    };
+    writeln!(out, "{}", next.into_iter().collect::<String>())?;
+    Ok(())
+}
+
+fn permutation(out: &mut dyn Write, args: PermArgs) -> Result<()> {
+    match args.command {
+        PermCommand::Info(PermWordArgs { word, reference }) => {
+            let perm = Perm::new(&word, reference.as_deref())?;
+            writeln!(out, "{}", format!("🔁 {} as a permutation of {}", word, perm.reference()).cyan().bold())?;
+            writeln!(out, "{}", "─".repeat(50).dimmed())?;
+            writeln!(out, "  {:<8} {}", "cycles", perm.to_string().yellow().bold())?;
+            writeln!(out, "  {:<8} {}", "inverse", perm.inverse().word())?;
+            let order = perm.order().map_or_else(|| "overflow".to_string(), |order| order.to_string());
+            writeln!(out, "  {:<8} {}", "order", order)?;
+            writeln!(out, "  {:<8} {}", "parity", if perm.is_even() { "even" } else { "odd" })?;
+        }
+        PermCommand::Inverse(PermWordArgs { word, reference }) => {
+            writeln!(out, "{}", Perm::new(&word, reference.as_deref())?.inverse().word())?;
+        }
+        PermCommand::Compose(PermComposeArgs { first, second, reference }) => {
+            let first = Perm::new(&first, reference.as_deref())?;
+            // Both sides must permute the same ordering.
+            let second = Perm::new(&second, Some(&first.reference()))?;
+            writeln!(out, "{}", first.then(&second)?.word())?;
+        }
+        PermCommand::Cycles(PermCyclesArgs { cycles, reference }) => {
+            writeln!(out, "{}", Perm::from_cycles(&cycles, reference.as_deref())?.word())?;
+        }
+    }
+    Ok(())
+}
+
+fn is_anagram(out: &mut dyn Write, args: IsAnagramArgs) -> Result<()> {
+    let IsAnagramArgs { first, second, quiet } = args;
+    // Only letters and digits count, so spacing and punctuation don't matter.
+    let rack = |text: &str| Rack::new(&text.chars().filter(|c| c.is_alphanumeric()).collect::<String>());
+    let difference = rack(&first).difference(&rack(&second));
+    let verdict = match difference.len() {
+        0 => Ok(()),
+        n => Err(format!("Not anagrams: {} letter{} differ", n, if n == 1 { "" } else { "s" }).into()),
+    };
+    if quiet {
+        return verdict;
+    }
+    
+    writeln!(out, "{}", format!("🔤 Comparing '{}' and '{}'", first, second).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    if difference.is_empty() {
+        writeln!(out, "{}", "✨ Anagrams: same letters, same counts".green().bold())?;
+        return verdict;
+    }
+    for (letter, mine, theirs) in &difference {
+        writeln!(out, "  {}  {} vs {}", letter.to_string().yellow().bold(), mine, theirs)?;
+    }
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    verdict
+}
+
+fn longest_words(out: &mut dyn Write, args: LongestArgs) -> Result<()> {
+    let LongestArgs { letters, alphabet, dictionary, lang, define } = args;
+    let letters = cli::letters_or_preset(&letters, &alphabet);
+    let rack = Rack::new(&letters);
+    let dictionaries = wordlist::resolve(&dictionary, &lang)?;
+    let definitions = define.as_deref().map(wordlist::open).transpose()?;
+    let names: Vec<&str> = dictionaries.iter().map(|(name, _)| name.as_str()).collect();
+    
+    writeln!(out, "{}", format!("🏆 Longest words from '{}' in {}", letters, names.join(", ")).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    
+    let mut best = 0;
+    // Each word with the dictionaries it came from.
+    let mut found: Vec<(&str, Vec<&str>)> = Vec::new();
+    for (name, words) in &dictionaries {
+        for word in words.words() {
+            let len = word.chars().count();
+            if len < best || len == 0 || !rack.can_spell(word) {
+                continue;
+            }
+            if len > best {
+                best = len;
+                found.clear();
+            }
+            match found.iter_mut().find(|(w, _)| *w == word) {
+                Some((_, sources)) => sources.push(name),
+                None => found.push((word, vec![name])),
+            }
+        }
+    }
+    
+    for (word, sources) in &found {
+        write!(out, "  {}", word.yellow().bold())?;
+        if dictionaries.len() > 1 {
+            write!(out, "  {}", format!("({})", sources.join(", ")).dimmed())?;
+        }
+        if let Some(gloss) = definitions.as_ref().and_then(|d| d.definition(word)) {
+            write!(out, "  {}", format!("— {}", gloss).dimmed())?;
+        }
+        writeln!(out)?;
+    }
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    match found.len() {
+        0 => writeln!(out, "{}", "✨ No dictionary word can be spelled from these letters".green().bold())?,
+        n => writeln!(out, "{}", format!("✨ {} word{} of length {}", n, if n == 1 { "" } else { "s" }, best).green().bold())?,
+    }
+    Ok(())
+}
+
+fn coverage(out: &mut dyn Write, args: CoverageArgs) -> Result<()> {
+    let CoverageArgs { text, dictionary, lang, per_sentence, min } = args;
+    let contents = std::fs::read_to_string(&text).map_err(|e| format!("Cannot read {}: {}", text.display(), e))?;
+    let dictionaries = wordlist::resolve(&dictionary, &lang)?;
+    let mut words: Vec<String> = dictionaries
+        .iter()
+        .flat_map(|(_, words)| words.words())
+        .filter(|word| word.chars().count() >= min.max(1))
+        .map(str::to_lowercase)
+        .collect();
+    // Longest first, so the most demanding words lead.
+    words.sort_unstable_by(|a, b| b.chars().count().cmp(&a.chars().count()).then_with(|| a.cmp(b)));
+    words.dedup();
+    let letters = |text: &str| text.chars().filter(|c| c.is_alphabetic()).collect::<String>();
+    let sentences: Vec<&str> = match per_sentence {
+        true => contents.split_inclusive(['.', '!', '?']).map(str::trim).filter(|s| !letters(s).is_empty()).collect(),
+        false => vec![contents.as_str()],
+    };
+    let inventory = letters(&contents);
+    if inventory.is_empty() {
+        return Err(format!("{} contains no letters", text.display()).into());
+    }
+    
+    writeln!(out, "{}", format!("📚 Dictionary words spelled from the {} letters of {}", inventory.chars().count(), text.display()).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    
+    // Words that fit at least one inventory.
+    let mut covered = vec![false; words.len()];
+    for (i, sentence) in sentences.iter().enumerate() {
+        let rack = Rack::new(&letters(sentence));
+        let fits: Vec<usize> = (0..words.len()).filter(|&w| rack.can_spell(&words[w])).collect();
+        if per_sentence {
+            let preview: String = sentence.split_whitespace().collect::<Vec<_>>().join(" ");
+            writeln!(out, "{}", format!("   Sentence {}: {}", i + 1, preview).dimmed())?;
+        }
+        for &w in &fits {
+            covered[w] = true;
+            writeln!(out, "  {}", words[w].yellow())?;
+        }
+    }
+    
+    let n = covered.iter().filter(|&&c| c).count();
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    writeln!(out, "{}", format!("✨ {} of {} dictionary word{} fit", n, words.len(), if words.len() == 1 { "" } else { "s" }).green().bold())?;
+    Ok(())
+}
+
+fn palindromic_words(out: &mut dyn Write, args: PalindromeArgs) -> Result<()> {
+    let PalindromeArgs { letters, alphabet, dictionary, lang, min } = args;
+    let letters = cli::letters_or_preset(&letters, &alphabet);
+    let rack = Rack::new(&letters);
+    let dictionaries = wordlist::resolve(&dictionary, &lang)?;
+    
+    writeln!(out, "{}", format!("🪞 Palindromes from '{}'", letters).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    let mut found: Vec<&str> = Vec::new();
+    for (_, words) in &dictionaries {
+        for word in words.words() {
+            let chars: Vec<char> = word.chars().flat_map(char::to_lowercase).collect();
+            if chars.len() >= min && chars.iter().eq(chars.iter().rev()) && rack.can_spell(word) && !found.iter().any(|w| w.eq_ignore_ascii_case(word)) {
+                found.push(word);
+            }
+        }
+    }
+    found.sort_by(|a, b| b.chars().count().cmp(&a.chars().count()).then_with(|| a.cmp(b)));
+    for word in &found {
+        writeln!(out, "  {}", word.yellow().bold())?;
+    }
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    writeln!(out, "{}", format!("✨ {} palindrome{}", found.len(), if found.len() == 1 { "" } else { "s" }).green().bold())?;
+    Ok(())
+}
+
+fn split_letters(out: &mut dyn Write, args: SplitArgs) -> Result<()> {
+    let SplitArgs { letters, k, min, dictionary, lang } = args;
+    let letters: String = letters.chars().filter(|c| c.is_alphanumeric()).collect();
+    let rack = Rack::new(&letters);
+    let dictionaries = wordlist::resolve(&dictionary, &lang)?;
+    
+    // Only words the letters can spell can be part of a split.
+    let mut words: Vec<String> = dictionaries
+        .iter()
+        .flat_map(|(_, words)| words.words())
+        .filter(|word| word.chars().count() >= min.max(1) && rack.can_spell(word))
+        .map(str::to_lowercase)
+        .collect();
+    words.sort_unstable();
+    words.dedup();
+    
+    writeln!(out, "{}", format!("✂️  {}-word splits of '{}'", k, letters).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    let mut found = 0;
+    let mut chosen = Vec::with_capacity(k);
+    split(out, &words, 0, &rack, k, &mut chosen, &mut found)?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    writeln!(out, "{}", format!("✨ {} split{}", found, if found == 1 { "" } else { "s" }).green().bold())?;
+    Ok(())
+}
+
+/// Prints every way to use up `rack` with `left` more words, taken in
+/// list order from `words[from..]` so each split appears once.
+fn split<'a>(out: &mut dyn Write, words: &'a [String], from: usize, rack: &Rack, left: usize, chosen: &mut Vec<&'a str>, found: &mut u64) -> Result<()> {
+    if left == 0 {
+        if rack.is_empty() {
+            *found += 1;
+            writeln!(out, "  {}", chosen.join(" + ").yellow().bold())?;
+        }
+        return Ok(());
+    }
+    for (i, word) in words.iter().enumerate().skip(from) {
+        if let Some(rest) = rack.without(word) {
+            chosen.push(word);
+            split(out, words, i, &rest, left - 1, chosen, found)?;
+            chosen.pop();
+        }
+    }
+    Ok(())
+}
+
+fn acronym(out: &mut dyn Write, args: AcronymArgs) -> Result<()> {
+    let AcronymArgs { acronym, dictionary, lang, lengths, pos, shape, limit, seed } = args;
+    let initials: Vec<char> = acronym.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect();
+    let lengths = lengths.iter().map(|spec| length_range(spec)).collect::<Result<Vec<_>>>()?;
+    let tags = pos.as_deref().map(wordlist::open).transpose()?;
+    let dictionaries = wordlist::resolve(&dictionary, &lang)?;
+    let mut words: Vec<String> = dictionaries.iter().flat_map(|(_, words)| words.words()).map(str::to_lowercase).collect();
+    words.sort_unstable();
+    words.dedup();
+    
+    // The words that fit each position.
+    let candidates: Vec<Vec<&str>> = initials
+        .iter()
+        .enumerate()
+        .map(|(i, &initial)| {
+            // Without a length constraint, skip one-letter words like "a".
+            let length = lengths.get(i).cloned().flatten().unwrap_or(2..=usize::MAX);
+            let tag = shape.get(i).filter(|tag| *tag != "*");
+            words
+                .iter()
+                .map(String::as_str)
+                .filter(|word| word.starts_with(initial) && length.contains(&word.chars().count()))
+                .filter(|word| match (tag, &tags) {
+                    (Some(tag), Some(tags)) => tags.definition(word).is_some_and(|t| t.split(',').any(|t| t.trim() == tag)),
+                    _ => true,
+                })
+                .collect()
+        })
+        .collect();
+    if let Some(i) = candidates.iter().position(Vec::is_empty) {
+        return Err(format!("No word fits position {} ('{}')", i + 1, initials[i]).into());
+    }
+    
+    let seed = rng::seed_or_clock(seed);
+    writeln!(out, "{}", format!("🔠 Expansions of {} (seed {})", acronym, seed).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    let total = candidates.iter().try_fold(1u128, |total, words| total.checked_mul(words.len() as u128));
+    let wanted = total.map_or(limit, |total| limit.min(total.min(usize::MAX as u128) as usize));
+    let mut rng = rng::Rng(seed);
+    let mut seen = HashSet::new();
+    // Give up on new picks eventually, in case few combinations remain.
+    let mut attempts = 0;
+    while seen.len() < wanted && attempts < wanted * 20 {
+        attempts += 1;
+        let pick: Vec<usize> = candidates.iter().map(|words| rng.below(words.len())).collect();
+        if !seen.insert(pick.clone()) {
+            continue;
+        }
+        let phrase: Vec<String> = pick
+            .iter()
+            .zip(&candidates)
+            .map(|(&i, words)| {
+                let mut chars = words[i].chars();
+                let initial: String = chars.next().into_iter().flat_map(char::to_uppercase).collect();
+                format!("{}{}", initial.yellow().bold(), chars.as_str())
+            })
+            .collect();
+        writeln!(out, "  {}", phrase.join(" "))?;
+    }
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    let choices: Vec<String> = candidates.iter().map(|words| words.len().to_string()).collect();
+    writeln!(out, "{}", format!("✨ {} suggestions from {} words per position", seen.len(), choices.join(" × ")).green().bold())?;
+    Ok(())
+}
+
+fn sample(out: &mut dyn Write, args: SampleArgs) -> Result<()> {
+    let SampleArgs { letters, alphabet, length, limit, weighted, corpus, seed } = args;
+    let letters = match (&letters, &alphabet) {
+        (None, None) => cli::default_alphabet(),
+        (letters, preset) => cli::letters_or_preset(letters, preset),
+    };
+    let chars: Vec<char> = letters.chars().collect();
+    if chars.is_empty() {
+        return Err("No letters to draw from".into());
+    }
+    // Cumulative weights, so a draw is a binary search.
+    let weights: Vec<f64> = match (weighted, &corpus) {
+        (false, _) => vec![1.0; chars.len()],
+        (true, None) => {
+            let english = |c: char| c.is_ascii_alphabetic().then(|| ngram::ENGLISH[(c.to_ascii_lowercase() as u8 - b'a') as usize]);
+            // Letters English doesn't rank get an average share.
+            let average = ngram::ENGLISH.iter().sum::<f64>() / 26.0;
+            chars.iter().map(|&c| english(c).unwrap_or(average)).collect()
+        }
+        (true, Some(path)) => {
+            let counts: HashMap<String, u64> = analyze::letter_counts(path)?.into_iter().collect();
+            chars.iter().map(|c| counts.get(&c.to_lowercase().to_string()).copied().unwrap_or(0) as f64).collect()
+        }
+    };
+    let cumulative: Vec<f64> = weights.iter().scan(0.0, |total, weight| {
+        *total += weight;
+        Some(*total)
+    }).collect();
+    let total = cumulative.last().copied().unwrap_or(0.0);
+    if total <= 0.0 {
+        return Err("None of the letters occur in the corpus".into());
+    }
+    
+    let seed = rng::seed_or_clock(seed);
+    writeln!(out, "{}", format!("🎲 Sampling {} random {}-letter candidate{} from '{}' (seed {})", limit, length, if limit == 1 { "" } else { "s" }, letters, seed).cyan().bold())?;
+    match (weighted, &corpus) {
+        (false, _) => {}
+        (true, None) => writeln!(out, "{}", "   Weighted by English letter frequencies".dimmed())?,
+        (true, Some(path)) => writeln!(out, "{}", format!("   Weighted by the letter frequencies of {}", path.display()).dimmed())?,
+    }
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    let mut rng = rng::Rng(seed);
+    for _ in 0..limit {
+        let word: String = (0..length)
+            .map(|_| {
+                // 53 random bits, scaled into the total weight.
+                let point = (rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64 * total;
+                chars[cumulative.partition_point(|&edge| edge <= point).min(chars.len() - 1)]
+            })
+            .collect();
+        writeln!(out, "  {}", word)?;
+    }
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    writeln!(out, "{}", format!("✨ Sampled {} candidate{}", limit, if limit == 1 { "" } else { "s" }).green().bold())?;
+    Ok(())
+}
+
+/// `N`, `N-M` or `*` (no constraint) as a range of word lengths.
+fn length_range(spec: &str) -> Result<Option<RangeInclusive<usize>>> {
+    let bad = || format!("Invalid length '{}': use N, N-M or *", spec);
+    if spec == "*" {
+        return Ok(None);
+    }
+    let (low, high) = spec.split_once('-').unwrap_or((spec, spec));
+    let low: usize = low.trim().parse().map_err(|_| bad())?;
+    let high: usize = high.trim().parse().map_err(|_| bad())?;
+    Ok(Some(low..=high))
+}
+
+fn mnemonic(out: &mut dyn Write, args: MnemonicArgs) -> Result<()> {
+    let MnemonicArgs { number, dictionary, lang, words, limit } = args;
+    let number: String = number.chars().filter(|c| !c.is_whitespace() && *c != ',' && *c != '.').collect();
+    if let Some(c) = number.chars().find(|c| !c.is_ascii_digit()) {
+        return Err(format!("'{}' is not a digit", c).into());
+    }
+    let dictionaries = wordlist::resolve(&dictionary, &lang)?;
+    
+    // Words by the digits they encode; words without consonants encode nothing.
+    let mut codes: HashMap<String, Vec<String>> = HashMap::new();
+    for word in dictionaries.iter().flat_map(|(_, words)| words.words()) {
+        let code = major::encode(word);
+        if !code.is_empty() && number.contains(&code) {
+            codes.entry(code).or_default().push(word.to_lowercase());
+        }
+    }
+    for words in codes.values_mut() {
+        words.sort_unstable();
+        words.dedup();
+    }
+    
+    writeln!(out, "{}", format!("🧠 Major system phrases for {}", number).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    let mut found = 0;
+    // Fewer words first: one word covering the whole number is the best mnemonic.
+    for count in 1..=words as usize {
+        let mut chosen = Vec::with_capacity(count);
+        phrases(out, &number, &codes, count, &mut chosen, &mut found, limit)?;
+    }
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    writeln!(out, "{}", format!("✨ {} phrase{}", found, if found == 1 { "" } else { "s" }).green().bold())?;
+    Ok(())
+}
+
+/// Prints phrases of exactly `left` more words spelling `digits`, until
+/// `limit` phrases have been printed.
+fn phrases<'a>(
+    out: &mut dyn Write,
+    digits: &str,
+    codes: &'a HashMap<String, Vec<String>>,
+    left: usize,
+    chosen: &mut Vec<(&'a str, &'a [String])>,
+    found: &mut usize,
+    limit: usize,
+) -> Result<()> {
+    if *found >= limit {
+        return Ok(());
+    }
+    if left == 0 {
+        if digits.is_empty() {
+            *found += 1;
+            let phrase: Vec<String> = chosen.iter().map(|(code, words)| format!("{} ({})", words.join("/").yellow().bold(), code.dimmed())).collect();
+            writeln!(out, "  {}", phrase.join(" + "))?;
+        }
+        return Ok(());
+    }
+    // Each remaining word needs at least one digit.
+    for end in 1..=digits.len().saturating_sub(left - 1) {
+        let (code, rest) = digits.split_at(end);
+        if let Some((code, words)) = codes.get_key_value(code) {
+            chosen.push((code, words));
+            phrases(out, rest, codes, left - 1, chosen, found, limit)?;
+            chosen.pop();
+        }
+    }
+    Ok(())
+}
+
+fn word_ladder(out: &mut dyn Write, args: LadderArgs) -> Result<()> {
+    let LadderArgs { start, end, dictionary, lang } = args;
+    if start.chars().count() != end.chars().count() {
+        return Err(format!("'{}' and '{}' differ in length; ladders change one letter at a time", start, end).into());
+    }
+    let dictionaries = wordlist::resolve(&dictionary, &lang)?;
+    let words = dictionaries.iter().flat_map(|(_, words)| words.words());
+    
+    writeln!(out, "{}", format!("🪜 Ladder from '{}' to '{}'", start, end).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    let Some(chain) = ladder::shortest(&start, &end, words) else {
+        return Err(format!("No ladder from '{}' to '{}' in these wordlists", start, end).into());
+    };
+    for (i, word) in chain.iter().enumerate() {
+        writeln!(out, "  {} {}", format!("{:>2}.", i).dimmed(), word.yellow().bold())?;
+    }
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    writeln!(out, "{}", format!("✨ {} step{}", chain.len() - 1, if chain.len() == 2 { "" } else { "s" }).green().bold())?;
+    Ok(())
+}
+
+fn fill_grid(out: &mut dyn Write, args: FillArgs) -> Result<()> {
+    let FillArgs { grid: path, dictionary, lang, limit } = args;
+    let spec = std::fs::read_to_string(&path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let grid = Grid::parse(&spec).map_err(|e| format!("Invalid grid in {}: {}", path.display(), e))?;
+    let slots = grid.slots();
+    if slots.is_empty() {
+        return Err(format!("{} has no runs of two or more open cells to fill", path.display()).into());
+    }
+    let dictionaries = wordlist::resolve(&dictionary, &lang)?;
+    let names: Vec<&str> = dictionaries.iter().map(|(name, _)| name.as_str()).collect();
+    // Only words as long as some slot, and made of letters alone.
+    let lengths: Vec<usize> = slots.iter().map(|slot| slot.cells.len()).collect();
+    let mut slot_lengths = lengths.clone();
+    slot_lengths.sort_unstable();
+    slot_lengths.dedup();
+    let trie = wordlist::trie(&dictionaries, format!("alphabetic at {:?}", slot_lengths), |word| lengths.contains(&word.chars().count()) && word.chars().all(char::is_alphabetic));
+    
+    writeln!(out, "{}", format!("🧩 Filling a {}×{} grid from {}", grid.width, grid.height, names.join(", ")).cyan().bold())?;
+    writeln!(out, "{}", format!("   {} slot{}, {} candidate word{}", slots.len(), if slots.len() == 1 { "" } else { "s" }, trie.len(), if trie.len() == 1 { "" } else { "s" }).dimmed())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    
+    let mut fills = 0;
+    let mut failed = None;
+    let steps = fill::fill(&grid, &trie, &interrupt::requested, &mut |filled| {
+        fills += 1;
+        if fills > 1 {
+            writeln!(out).ok();
+        }
+        if let Err(e) = print_fill(out, filled, &slots) {
+            failed = Some(e);
+            return false;
+        }
+        fills < limit
+    });
+    if let Some(e) = failed {
+        return Err(e);
+    }
+    
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    match fills {
+        0 => writeln!(out, "{}", format!("✨ No fill exists with these words ({} steps)", steps).green().bold())?,
+        n => writeln!(out, "{}", format!("✨ {} fill{} in {} steps", n, if n == 1 { "" } else { "s" }, steps).green().bold())?,
+    }
+    Ok(())
+}
+
+/// A filled grid, then its entries by clue number.
+fn print_fill(out: &mut dyn Write, grid: &Grid, slots: &[fill::Slot]) -> Result<()> {
+    for row in grid.cells.chunks(grid.width) {
+        let cells: Vec<String> = row
+            .iter()
+            .map(|cell| match cell {
+                Cell::Letter(c) => c.to_uppercase().to_string().yellow().bold().to_string(),
+                Cell::Open => ".".dimmed().to_string(),
+                Cell::Block => "#".dimmed().to_string(),
+            })
+            .collect();
+        writeln!(out, "  {}", cells.join(" "))?;
+    }
+    for across in [true, false] {
+        let entries: Vec<String> = slots.iter().filter(|slot| slot.across == across).map(|slot| format!("{} {}", slot.number, grid.word(slot).to_uppercase())).collect();
+        writeln!(out, "{}", format!("   {}: {}", if across { "Across" } else { "Down" }, entries.join(", ")).dimmed())?;
+    }
+    Ok(())
+}
+
+fn word_search(out: &mut dyn Write, args: WordsearchArgs) -> Result<()> {
+    let WordsearchArgs { grid: path, dictionary, lang, min, highlight } = args;
+    let spec = std::fs::read_to_string(&path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let grid = wordsearch::parse(&spec).map_err(|e| format!("Invalid grid in {}: {}", path.display(), e))?;
+    let (height, width) = (grid.len(), grid[0].len());
+    let dictionaries = wordlist::resolve(&dictionary, &lang)?;
+    let names: Vec<&str> = dictionaries.iter().map(|(name, _)| name.as_str()).collect();
+    // No word runs longer than the grid's longest line.
+    let longest = width.max(height);
+    let trie = wordlist::trie(&dictionaries, format!("at {}..={}", min.max(1), longest), |word| (min.max(1)..=longest).contains(&word.chars().count()));
+    let hits = wordsearch::find(&grid, &trie, min.max(1));
+    
+    writeln!(out, "{}", format!("🔎 Searching a {}×{} grid for words from {}", width, height, names.join(", ")).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    
+    if highlight {
+        let mut found = vec![vec![false; width]; height];
+        for (row, column) in hits.iter().flat_map(|hit| hit.cells()) {
+            found[row][column] = true;
+        }
+        for (letters, found) in grid.iter().zip(&found) {
+            let cells: Vec<String> = letters
+                .iter()
+                .zip(found)
+                .map(|(c, &found)| match found {
+                    true => c.to_string().yellow().bold().to_string(),
+                    false => c.to_string().dimmed().to_string(),
+                })
+                .collect();
+            writeln!(out, "  {}", cells.join(" "))?;
+        }
+        writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    }
+    for hit in &hits {
+        let (direction, _, _) = wordsearch::DIRECTIONS[hit.direction];
+        writeln!(out, "  {}  {}", hit.word.yellow().bold(), format!("row {}, column {}, {}", hit.row + 1, hit.column + 1, direction).dimmed())?;
+    }
+    
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    writeln!(out, "{}", format!("✨ Found {} word{}", hits.len(), if hits.len() == 1 { "" } else { "s" }).green().bold())?;
+    Ok(())
+}
+
+fn anagram_groups(out: &mut dyn Write, args: GroupArgs) -> Result<()> {
+    let GroupArgs { dictionary, lang, min, quiet } = args;
+    let dictionaries = wordlist::resolve(&dictionary, &lang)?;
+    let mut groups: HashMap<String, Vec<&str>> = HashMap::new();
+    for (_, words) in &dictionaries {
+        for word in words.words() {
+            let group = groups.entry(rack::signature(word)).or_default();
+            // The same word from another list, or in another case, is no new anagram.
+            if !group.iter().any(|w| w.eq_ignore_ascii_case(word)) {
+                group.push(word);
+            }
+        }
+    }
+    let mut groups: Vec<Vec<&str>> = groups.into_values().filter(|group| group.len() >= min.max(1)).collect();
+    for group in &mut groups {
+        group.sort_unstable();
+    }
+    groups.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    
+    if quiet {
+        for group in &groups {
+            writeln!(out, "{}", group.join("\t"))?;
+        }
+        return Ok(());
+    }
+    let names: Vec<&str> = dictionaries.iter().map(|(name, _)| name.as_str()).collect();
+    writeln!(out, "{}", format!("🗂  Anagram groups of {}+ words in {}", min, names.join(", ")).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    for group in &groups {
+        writeln!(out, "  {}  {}", group.join(", ").yellow(), format!("({})", group.len()).dimmed())?;
+    }
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    writeln!(out, "{}", format!("✨ {} groups", groups.len()).green().bold())?;
+    Ok(())
+}
+
+fn typos(out: &mut dyn Write, args: TyposArgs) -> Result<()> {
+    let TyposArgs { word, layout, dictionary, lang, quiet } = args;
+    let dictionaries = wordlist::resolve(&dictionary, &lang)?;
+    let typos: Vec<(String, typo::Kind)> = typo::typos(&word, &layout)
+        .into_iter()
+        .filter(|(typo, _)| dictionaries.is_empty() || dictionaries.iter().any(|(_, d)| d.contains(typo)))
+        .collect();
+    
+    if quiet {
+        for (typo, _) in &typos {
+            writeln!(out, "{}", typo)?;
+        }
+        return Ok(());
+    }
+    writeln!(out, "{}", format!("⌨️  Typos of '{}' on {}", word, layout).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    for (typo, kind) in &typos {
+        writeln!(out, "  {}  {}", typo, format!("({})", kind.name()).dimmed())?;
+    }
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    let n = typos.len();
+    writeln!(out, "{}", format!("✨ {} typo{}", n, if n == 1 { "" } else { "s" }).green().bold())?;
+    Ok(())
+}
+
+fn suggest(out: &mut dyn Write, args: SuggestArgs) -> Result<()> {
+    let SuggestArgs { word, dictionary, lang, layout, frequencies, max_distance, limit, quiet } = args;
+    let dictionaries = wordlist::resolve(&dictionary, &lang)?;
+    let counts = match &frequencies {
+        Some(path) => typo::word_counts(&std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?),
+        None => typo::word_counts(typo::ENGLISH_WORD_COUNTS),
+    };
+    let distance = typo::Distance::new(&word, &layout);
+    let known = dictionaries.iter().any(|(_, d)| d.contains(&word));
+    
+    // Each word once, at its first position across the wordlists.
+    let mut seen = HashSet::new();
+    let mut suggestions: Vec<(f64, u64, usize, &str)> = Vec::new();
+    for (position, candidate) in dictionaries.iter().flat_map(|(_, words)| words.words()).enumerate() {
+        if candidate.eq_ignore_ascii_case(&word) || !seen.insert(candidate.to_lowercase()) {
+            continue;
+        }
+        if let Some(d) = distance.to(candidate, max_distance) {
+            let count = counts.get(&candidate.to_lowercase()).copied().unwrap_or(0);
+            suggestions.push((d, count, position, candidate));
+        }
+    }
+    suggestions.sort_by(|a, b| a.0.total_cmp(&b.0).then(b.1.cmp(&a.1)).then(a.2.cmp(&b.2)));
+    suggestions.truncate(limit);
+    
+    if quiet {
+        for (_, _, _, suggestion) in &suggestions {
+            writeln!(out, "{}", suggestion)?;
+        }
+        return Ok(());
+    }
+    writeln!(out, "{}", format!("💡 Did you mean... ('{}' on {})", word, layout).cyan().bold())?;
+    if known {
+        writeln!(out, "{}", format!("   '{}' is in the wordlist itself", word).dimmed())?;
+    }
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    for (d, count, _, suggestion) in &suggestions {
+        let detail = match frequencies.is_some() {
+            true => format!("{} edit{}, seen {} times", d, if *d == 1.0 { "" } else { "s" }, count),
+            false => format!("{} edit{}", d, if *d == 1.0 { "" } else { "s" }),
+        };
+        writeln!(out, "  {}  {}", suggestion.yellow().bold(), detail.dimmed())?;
+    }
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    match suggestions.len() {
+        0 => writeln!(out, "{}", format!("✨ Nothing within {} edits", max_distance).green().bold())?,
+        n => writeln!(out, "{}", format!("✨ {} suggestion{}", n, if n == 1 { "" } else { "s" }).green().bold())?,
+    }
+    Ok(())
+}
+
+fn complete(out: &mut dyn Write, args: CompleteArgs) -> Result<()> {
+    let CompleteArgs { prefix, dictionary, lang, limit, quiet } = args;
+    let dictionaries = wordlist::resolve(&dictionary, &lang)?;
+    // Every word is still read once, but only those under the prefix are
+    // kept, so the trie holds just the branch that is walked.
+    let folded = trie::fold(&prefix);
+    let trie = wordlist::trie(&dictionaries, format!("starting {}", folded), |word| trie::fold(word).starts_with(&folded));
+    let completions = trie.completions(&prefix, limit);
+    
+    if quiet {
+        for completion in &completions {
+            writeln!(out, "{}", completion)?;
+        }
+        return Ok(());
+    }
+    writeln!(out, "{}", format!("⌨️  Completions of '{}'", prefix).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    for completion in &completions {
+        // Completions are lowercased, so they start with exactly `folded`.
+        let (start, rest) = completion.split_at(folded.len());
+        writeln!(out, "  {}{}", start.dimmed(), rest.yellow().bold())?;
+    }
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    let more = match trie.len() > completions.len() {
+        true => format!(" of {}", trie.len()),
+        false => String::new(),
+    };
+    match completions.len() {
+        0 => writeln!(out, "{}", format!("✨ No word starts with '{}'", prefix).green().bold())?,
+        n => writeln!(out, "{}", format!("✨ {}{} completion{}", n, more, if n == 1 && more.is_empty() { "" } else { "s" }).green().bold())?,
+    }
+    Ok(())
+}
+
+fn morse(out: &mut dyn Write, args: MorseArgs) -> Result<()> {
+    let MorseArgs { input, dictionary, lang, limit, quiet } = args;
+    if !morse::is_code(&input) {
+        let code = morse::encode(&input).map_err(|c| format!("'{}' has no Morse code", c))?;
+        return Ok(writeln!(out, "{}", code)?);
+    }
+    if input.contains([' ', '/']) {
+        let text = morse::decode(&input).map_err(|code| format!("'{}' is not a Morse letter", code))?;
+        return Ok(writeln!(out, "{}", text)?);
+    }
+    
+    let dictionaries = wordlist::resolve(&dictionary, &lang)?;
+    if !quiet {
+        writeln!(out, "{}", format!("📡 Readings of {}", input).cyan().bold())?;
+        writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    }
+    let mut found = 0;
+    let mut failed = None;
+    morse::decodings(&input, |text| {
+        if !dictionaries.is_empty() && !dictionaries.iter().any(|(_, d)| d.contains(text)) {
+            return true;
+        }
+        found += 1;
+        let written = if quiet { writeln!(out, "{}", text) } else { writeln!(out, "  {}", text.yellow().bold()) };
+        if let Err(e) = written {
+            failed = Some(e);
+            return false;
+        }
+        limit.is_none_or(|limit| found < limit)
+    });
+    if let Some(e) = failed {
+        return Err(e.into());
+    }
+    if !quiet {
+        writeln!(out, "{}", "─".repeat(50).dimmed())?;
+        writeln!(out, "{}", format!("✨ {} reading{}", found, if found == 1 { "" } else { "s" }).green().bold())?;
+    }
+    Ok(())
+}
+
+fn frequencies(out: &mut dyn Write, args: FreqArgs) -> Result<()> {
+    let FreqArgs { input, top, json, counts } = args;
+    let path = std::path::Path::new(&input);
+    let (text, source) = if path.is_file() {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        (text, path.display().to_string())
     } else {
-        println!("  {}", word);
+        (input.clone(), format!("'{}'", input))
+    };
+    
+    if let Some(n) = counts {
+        for (gram, count) in ngram::counts(&text, n as usize) {
+            writeln!(out, "{} {}", gram.to_uppercase(), count)?;
+        }
+        return Ok(());
+    }
+    let tables = [("Letters", ngram::counts(&text, 1)), ("Bigrams", ngram::counts(&text, 2)), ("Trigrams", ngram::counts(&text, 3))];
+    let letters: u64 = tables[0].1.iter().map(|(_, count)| count).sum();
+    
+    if json {
+        let objects: Vec<String> = tables
+            .iter()
+            .map(|(name, counts)| {
+                let fields: Vec<String> = counts.iter().map(|(gram, count)| format!("\"{}\":{}", gram, count)).collect();
+                format!("\"{}\":{{{}}}", name.to_lowercase(), fields.join(","))
+            })
+            .collect();
+        writeln!(out, "{{\"total\":{},{}}}", letters, objects.join(","))?;
+        return Ok(());
+    }
+    
+    writeln!(out, "{}", format!("📊 Frequencies in {} ({} letters)", source, letters).cyan().bold())?;
+    for (name, counts) in &tables {
+        writeln!(out, "{}", "─".repeat(50).dimmed())?;
+        writeln!(out, "{}", name.bold())?;
+        let total: u64 = counts.iter().map(|(_, count)| count).sum();
+        let most = counts.first().map_or(1, |(_, count)| *count);
+        for (gram, count) in counts.iter().take(top) {
+            let bar = "█".repeat((count * 30).div_ceil(most) as usize);
+            writeln!(out, "  {:<4}{:<31}{:>6.2}%  {}", gram.yellow(), bar.blue(), *count as f64 * 100.0 / total as f64, count.to_string().dimmed())?;
+        }
     }
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    writeln!(out, "{}", format!("✨ {} distinct letters, {} bigrams, {} trigrams", tables[0].1.len(), tables[1].1.len(), tables[2].1.len()).green().bold())?;
+    Ok(())
 }
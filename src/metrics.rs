@@ -0,0 +1,104 @@
+//! `isaw daemon --metrics`: Prometheus counters for a long-running daemon.
+//!
+//! Counts live in process-wide atomics, so every query the daemon answers,
+//! over its socket or WebSocket, adds to them without any plumbing: the
+//! daemon records each query and its latency, and [`Output`] records the
+//! candidates and results of every generating run. `GET /metrics` on the
+//! metrics address returns them in the Prometheus text format.
+//!
+//! [`Output`]: crate::output::Output
+
+// Runs count everywhere, but only the daemon, which is unix-only, reads
+// the counters back.
+#![cfg_attr(not(unix), allow(dead_code))]
+
+use crate::Result;
+use colored::*;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// Upper bounds of the latency histogram's buckets, in seconds.
+const BUCKETS: [f64; 12] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 10.0, 60.0, 600.0];
+
+static SUCCEEDED: AtomicU64 = AtomicU64::new(0);
+static FAILED: AtomicU64 = AtomicU64::new(0);
+static CANDIDATES: AtomicU64 = AtomicU64::new(0);
+static MATCHES: AtomicU64 = AtomicU64::new(0);
+/// Queries per bucket, the last for those slower than every bound.
+static LATENCIES: [AtomicU64; BUCKETS.len() + 1] = [const { AtomicU64::new(0) }; BUCKETS.len() + 1];
+static LATENCY_MICROS: AtomicU64 = AtomicU64::new(0);
+
+/// One query answered, successfully or not, after `took`.
+pub fn query(succeeded: bool, took: Duration) {
+    match succeeded {
+        true => SUCCEEDED.fetch_add(1, Ordering::Relaxed),
+        false => FAILED.fetch_add(1, Ordering::Relaxed),
+    };
+    let bucket = BUCKETS.iter().position(|&bound| took.as_secs_f64() <= bound).unwrap_or(BUCKETS.len());
+    LATENCIES[bucket].fetch_add(1, Ordering::Relaxed);
+    LATENCY_MICROS.fetch_add(took.as_micros() as u64, Ordering::Relaxed);
+}
+
+/// One generating run's totals.
+pub fn run(candidates: u64, matches: u64) {
+    CANDIDATES.fetch_add(candidates, Ordering::Relaxed);
+    MATCHES.fetch_add(matches, Ordering::Relaxed);
+}
+
+/// Serves `GET /metrics` at `address` from a thread of its own.
+pub fn serve(out: &mut dyn Write, address: &str) -> Result<()> {
+    let listener = TcpListener::bind(address).map_err(|e| format!("Cannot listen on {}: {}", address, e))?;
+    writeln!(out, "{}", format!("📈 Metrics on http://{}/metrics", listener.local_addr()?).cyan().bold())?;
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            // A scraper hanging up early costs it the sample, nothing more.
+            let _ = respond(stream);
+        }
+    });
+    Ok(())
+}
+
+fn respond(mut stream: TcpStream) -> std::io::Result<()> {
+    let mut request = String::new();
+    BufReader::new(&stream).read_line(&mut request)?;
+    let path = request.split_whitespace().nth(1).unwrap_or("");
+    match path == "/metrics" || path.starts_with("/metrics?") {
+        true => {
+            let body = render();
+            write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body)
+        }
+        false => stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Type: text/plain\r\nContent-Length: 23\r\nConnection: close\r\n\r\nMetrics are at /metrics"),
+    }
+}
+
+/// The counters in the Prometheus text exposition format.
+fn render() -> String {
+    let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+    let mut lines = vec![
+        "# HELP isaw_queries_total Queries the daemon answered, by outcome.".to_string(),
+        "# TYPE isaw_queries_total counter".to_string(),
+        format!("isaw_queries_total{{outcome=\"ok\"}} {}", load(&SUCCEEDED)),
+        format!("isaw_queries_total{{outcome=\"error\"}} {}", load(&FAILED)),
+        "# HELP isaw_candidates_generated_total Candidates generated by all runs.".to_string(),
+        "# TYPE isaw_candidates_generated_total counter".to_string(),
+        format!("isaw_candidates_generated_total {}", load(&CANDIDATES)),
+        "# HELP isaw_matches_total Results printed by all runs.".to_string(),
+        "# TYPE isaw_matches_total counter".to_string(),
+        format!("isaw_matches_total {}", load(&MATCHES)),
+        "# HELP isaw_query_duration_seconds Time taken to answer each query.".to_string(),
+        "# TYPE isaw_query_duration_seconds histogram".to_string(),
+    ];
+    let mut cumulative = 0;
+    for (bound, count) in BUCKETS.iter().zip(&LATENCIES) {
+        cumulative += load(count);
+        lines.push(format!("isaw_query_duration_seconds_bucket{{le=\"{}\"}} {}", bound, cumulative));
+    }
+    cumulative += load(&LATENCIES[BUCKETS.len()]);
+    lines.push(format!("isaw_query_duration_seconds_bucket{{le=\"+Inf\"}} {}", cumulative));
+    lines.push(format!("isaw_query_duration_seconds_sum {}", load(&LATENCY_MICROS) as f64 / 1e6));
+    lines.push(format!("isaw_query_duration_seconds_count {}", cumulative));
+    lines.join("\n") + "\n"
+}
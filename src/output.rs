@@ -0,0 +1,421 @@
+//! Terminal output shared by the generating subcommands.
+
+use crate::cli::RunOpts;
+use crate::dedup::Dedup;
+use crate::filter::Filter;
+use crate::parquet::Parquet;
+use crate::report::{LetterStats, Report};
+use crate::sort::ExternalSort;
+use crate::sqlite::Sqlite;
+use crate::stream::Stream;
+use crate::stats::Stats;
+use crate::summary::{Stop, Summary};
+use crate::{interrupt, log, metrics, wordlist, Result};
+use clap::ValueEnum;
+use colored::*;
+use isaw_core::dictionary::Dictionary;
+use isaw_core::leet::Leet;
+use isaw_core::Matcher;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// One result per line
+    #[default]
+    Text,
+    /// A Parquet file with columns value, length, score and matched
+    Parquet,
+}
+
+/// Separates result lines from the banners around them, so `--quiet` can
+/// leave nothing but bare results for scripts and workers.
+pub struct Output<'a> {
+    out: &'a mut dyn Write,
+    quiet: bool,
+    /// Results printed so far, for `--dedup`.
+    dedup: Option<Dedup>,
+    /// Results waiting to be vetted by `--filter-cmd`.
+    filter: Option<Filter>,
+    /// Results held back for `--sort` until [`Output::finish`].
+    sorted: Option<ExternalSort>,
+    stats: Option<Stats>,
+    /// The JSON run description for `--summary`.
+    summary: Option<Summary>,
+    /// Tallies of printed results for `--report`.
+    report: Option<Report>,
+    /// Letter usage across printed results for `--letter-stats`.
+    letters: Option<LetterStats>,
+    /// Glosses shown next to results for `--define`.
+    definitions: Option<Arc<Dictionary>>,
+    /// Variants printed for every result with `--leet`.
+    leet: Option<Arc<Leet>>,
+    /// Characters per space-separated group when printing, e.g. codons.
+    group: Option<usize>,
+    /// Where results go instead of `out` with `--output-sqlite`.
+    sqlite: Option<Sqlite>,
+    /// The collector results are sent to with `--output-tcp`/`--output-udp`.
+    stream: Option<Stream>,
+    /// Rows waiting to be written for `--format parquet`.
+    parquet: Option<Parquet>,
+    /// Printed between letters (or groups) instead, for `--join`.
+    join: Option<String>,
+    /// Paces results for `--rate`.
+    throttle: Option<Throttle>,
+    /// `--first`: how many results to print before the run stops.
+    first: Option<u64>,
+    /// Results per pattern, when several patterns are told apart.
+    tally: HashMap<String, u64>,
+    /// Results accepted so far, counted against `first`.
+    printed: u64,
+    /// Candidates `--resume` skipped, where a resume offset counts from.
+    resumed: u128,
+    /// Whether candidates are consumed in rank order, so an interrupted run
+    /// can say exactly where to pick up again.
+    resumable: bool,
+    /// When the run began, for the `-v` phase log.
+    started: Instant,
+}
+
+impl<'a> Output<'a> {
+    pub fn new(out: &'a mut dyn Write, run: &RunOpts) -> Result<Self> {
+        let dedup = run.dedup.then(|| match run.max_memory {
+            Some(_) => Dedup::spilling(run.dedup_budget()),
+            None => Dedup::new(run.dedup_budget()),
+        });
+        let filter = run.filter_cmd.as_deref().map(Filter::new);
+        let sorted = run.sort.then(|| ExternalSort::new(run.sort_budget()));
+        let definitions = run.define.as_deref().map(wordlist::open).transpose()?;
+        let sqlite = run.output_sqlite.as_deref().map(Sqlite::open).transpose()?;
+        let stream = match (&run.output_tcp, &run.output_udp) {
+            (Some(address), _) => Some(Stream::tcp(address)?),
+            (_, Some(address)) => Some(Stream::udp(address)?),
+            _ => None,
+        };
+        let parquet = (run.format == OutputFormat::Parquet).then(Parquet::new);
+        let throttle = match run.rate {
+            Some(rate) if rate.is_nan() || rate <= 0.0 => return Err("--rate must be above 0".into()),
+            rate => rate.map(Throttle::new),
+        };
+        let leet = run.leet.then(|| Arc::new(run.leet_table.clone().unwrap_or_default()));
+        Ok(Output { out, quiet: run.quiet || parquet.is_some(), dedup, filter, sorted, stats: run.stats.map(Stats::start), summary: run.summary.map(|_| Summary::start(run)), report: run.report.map(|format| Report::new(format, run.report_top)), letters: run.letter_stats.then(LetterStats::default), definitions, leet, group: None, sqlite, stream, parquet, join: run.join.clone(), throttle, first: run.first, tally: HashMap::new(), printed: 0, resumed: run.resume.unwrap_or(0), resumable: true, started: Instant::now() })
+    }
+    
+    /// Prints results in space-separated groups of `size` characters.
+    pub fn group(&mut self, size: usize) {
+        self.group = Some(size).filter(|&size| size > 0);
+    }
+    
+    /// Names the subcommand and its parameters for `--summary`.
+    pub fn describe(&mut self, command: &'static str, parameters: Vec<(&'static str, String)>) {
+        if let Some(summary) = &mut self.summary {
+            summary.describe(command, parameters);
+        }
+    }
+    
+    /// Ends the run: `--stats` for `generated` candidates, `matched` of which
+    /// passed the filter when there was one.
+    pub fn done(&mut self, generated: u64, matched: Option<u64>) {
+        // The reports below go to stderr; the results should reach the
+        // reader first, daemon clients included.
+        let _ = self.out.flush();
+        let matched_field = matched.map_or("-".to_string(), |m| m.to_string());
+        log::phase("generate and match", self.started.elapsed(), &[("candidates", &generated), ("matched", &matched_field)]);
+        metrics::run(generated, self.printed);
+        let interrupted = interrupt::requested();
+        let resume = (interrupted && self.resumable).then(|| self.resumed + generated as u128);
+        if interrupted {
+            let resume = match resume {
+                Some(offset) => format!("; continue with --resume {}", offset),
+                None => " (this mode can't be resumed exactly)".to_string(),
+            };
+            errln!("{}", format!("⏸  Interrupted after {} candidates{}", generated, resume).yellow().bold());
+        }
+        if let Some(stats) = &self.stats {
+            stats.report(generated, matched);
+        }
+        if let Some(summary) = &self.summary {
+            let stop = match (self.full(), interrupted) {
+                (true, _) => Some(Stop::First),
+                (_, true) => Some(Stop::Interrupted),
+                _ => None,
+            };
+            summary.print(generated, matched, self.printed, stop, resume);
+        }
+        if let Some(report) = &self.report {
+            report.print();
+        }
+        if let Some(letters) = &self.letters {
+            letters.print();
+        }
+    }
+    
+    /// Headers and summaries.
+    pub fn info(&mut self, line: impl Display) -> io::Result<()> {
+        if self.quiet {
+            return Ok(());
+        }
+        writeln!(self.out, "{}", line)
+    }
+    
+    pub fn rule(&mut self) -> io::Result<()> {
+        self.info("─".repeat(50).dimmed())
+    }
+    
+    /// Whether `--first` results have been printed already.
+    pub fn full(&self) -> bool {
+        self.first.is_some_and(|first| self.printed >= first)
+    }
+    
+    /// Whether generators should stop: `--first` is satisfied or Ctrl-C
+    /// was pressed.
+    pub fn stopping(&self) -> bool {
+        self.full() || interrupt::requested()
+    }
+    
+    /// Marks the run as not consuming candidates in rank order (parallel,
+    /// filtered or built rather than ranked), so no resume offset is offered.
+    pub fn unresumable(&mut self) {
+        self.resumable = false;
+    }
+    
+    /// One result, with the part `matcher` matched highlighted; with
+    /// `--leet`, followed by its variants.
+    pub fn result(&mut self, word: &str, matcher: Option<&Matcher>) -> io::Result<()> {
+        match self.leet.clone() {
+            Some(leet) => leet.variants(word).try_for_each(|variant| self.variant(&variant, matcher)),
+            None => self.variant(word, matcher),
+        }
+    }
+    
+    fn variant(&mut self, word: &str, matcher: Option<&Matcher>) -> io::Result<()> {
+        if self.full() {
+            return Ok(());
+        }
+        if let Some(dedup) = &mut self.dedup
+            && !dedup.insert(word)
+        {
+            return Ok(());
+        }
+        match self.filter.as_mut().map(|filter| filter.push(word)) {
+            Some(true) => self.vetted(matcher),
+            Some(false) => Ok(()),
+            None => self.accept(word, matcher),
+        }
+    }
+    
+    /// Runs `--filter-cmd` over the results held back and accepts those it kept.
+    fn vetted(&mut self, matcher: Option<&Matcher>) -> io::Result<()> {
+        let Some(filter) = &mut self.filter else { return Ok(()) };
+        for word in filter.run()? {
+            if self.full() {
+                break;
+            }
+            self.accept(&word, matcher)?;
+        }
+        Ok(())
+    }
+    
+    /// Counts and prints (or holds back for `--sort`) one result.
+    fn accept(&mut self, word: &str, matcher: Option<&Matcher>) -> io::Result<()> {
+        self.printed += 1;
+        if !self.quiet
+            && let Some(hits) = matcher.and_then(|m| m.hits(word))
+        {
+            for hit in hits {
+                *self.tally.entry(hit.to_string()).or_default() += 1;
+            }
+        }
+        if let Some(report) = &mut self.report {
+            report.record(word);
+        }
+        if let Some(letters) = &mut self.letters {
+            letters.record(word);
+        }
+        match &mut self.sorted {
+            Some(sorted) => sorted.push(word),
+            None => self.write_result(word, matcher),
+        }
+    }
+    
+    /// Prints the results held back for `--sort`; call before the summary.
+    pub fn finish(&mut self, matcher: Option<&Matcher>) -> io::Result<()> {
+        self.vetted(matcher)?;
+        if let Some(sorted) = self.sorted.take() {
+            log::timed("sort merge", || sorted.finish(|word| self.write_result(word, matcher)))?;
+        }
+        if let Some(sqlite) = self.sqlite.take() {
+            log::timed("sqlite commit", || sqlite.finish())?;
+        }
+        if let Some(stream) = self.stream.take() {
+            stream.finish()?;
+        }
+        if let Some(parquet) = self.parquet.take() {
+            parquet.finish(self.out)?;
+        }
+        self.hits_per_pattern(matcher)?;
+        if let Some(first) = self.first.filter(|_| self.full()) {
+            self.info(format!("   Stopped after the first {} result{}", first, if first == 1 { "" } else { "s" }).dimmed())?;
+        }
+        Ok(())
+    }
+    
+    /// How many results each of several patterns had, for `--search-file`.
+    fn hits_per_pattern(&mut self, matcher: Option<&Matcher>) -> io::Result<()> {
+        let Some(terms) = matcher.and_then(Matcher::terms).filter(|_| !self.quiet) else { return Ok(()) };
+        self.info("   Hits per pattern:".dimmed())?;
+        let mut silent = 0;
+        for term in &terms {
+            match self.tally.get(*term) {
+                Some(hits) => self.info(format!("     {}: {}", term, hits).dimmed())?,
+                None => silent += 1,
+            }
+        }
+        if silent > 0 {
+            self.info(format!("     {} of {} patterns matched nothing", silent, terms.len()).dimmed())?;
+        }
+        Ok(())
+    }
+    
+    fn write_result(&mut self, word: &str, matcher: Option<&Matcher>) -> io::Result<()> {
+        if let Some(throttle) = &mut self.throttle {
+            throttle.wait();
+        }
+        if let Some(sqlite) = &mut self.sqlite {
+            return sqlite.insert(word, matched_pattern(word, matcher).as_deref());
+        }
+        if let Some(stream) = &mut self.stream {
+            return stream.send(word);
+        }
+        if let Some(parquet) = &mut self.parquet {
+            return parquet.insert(self.out, word, matched_pattern(word, matcher).as_deref());
+        }
+        // Groups default to single letters when only the separator is given.
+        let layout = match (self.group, self.join.as_deref()) {
+            (None, None) => None,
+            (size, join) => Some((size.unwrap_or(1), join.unwrap_or(" "))),
+        };
+        if self.quiet {
+            return match layout {
+                Some((size, separator)) => writeln!(self.out, "{}", grouped(word, size, separator, 0..0).concat()),
+                None => writeln!(self.out, "{}", word),
+            };
+        }
+        let range = matcher.and_then(|m| m.find(word)).unwrap_or(0..0);
+        let [before, matched, after] = match layout {
+            Some((size, separator)) => grouped(word, size, separator, range),
+            None => [&word[..range.start], &word[range.clone()], &word[range.end..]].map(str::to_string),
+        };
+        if matched.is_empty() {
+            write!(self.out, "  {}{}", before, after)?;
+        } else {
+            write!(self.out, "  {}{}{}", before, matched.yellow().bold(), after)?;
+        }
+        // With several search terms, say which of them this result contains.
+        if let Some(hits) = matcher.and_then(|m| m.hits(word)) {
+            write!(self.out, "  {}", format!("[{}]", hits.join(", ")).dimmed())?;
+        }
+        // With several dictionaries, say which of them know this word.
+        if let Some(sources) = matcher.and_then(|m| m.sources(word)) {
+            write!(self.out, "  {}", format!("({})", sources.join(", ")).dimmed())?;
+        }
+        if let Some(digest) = matcher.and_then(|m| m.digest(word)) {
+            write!(self.out, "  {}", digest.dimmed())?;
+        }
+        if let Some(gloss) = self.definitions.as_ref().and_then(|d| d.definition(word)) {
+            write!(self.out, "  {}", format!("— {}", gloss).dimmed())?;
+        }
+        writeln!(self.out)
+    }
+}
+
+/// Spaces results evenly at a rate, without bursts after a quiet spell.
+struct Throttle {
+    interval: Duration,
+    /// When the next result may go out.
+    due: Instant,
+}
+
+impl Throttle {
+    /// `per_second` is a `--rate`, so finite and at least [`MIN_RATE`].
+    ///
+    /// [`MIN_RATE`]: crate::cli::MIN_RATE
+    fn new(per_second: f64) -> Self {
+        Throttle { interval: Duration::from_secs_f64(1.0 / per_second), due: Instant::now() }
+    }
+    
+    /// Sleeps until the next result is due; Ctrl-C cuts the wait short.
+    fn wait(&mut self) {
+        let now = Instant::now();
+        while let Some(left) = self.due.checked_duration_since(Instant::now()).filter(|left| !left.is_zero()) {
+            if interrupt::requested() {
+                break;
+            }
+            std::thread::sleep(left.min(Duration::from_millis(100)));
+        }
+        self.due = self.due.max(now) + self.interval;
+    }
+}
+
+/// What `matcher` matched in `word`, for the database and Parquet sinks:
+/// the patterns it contains when there are several, else the matched text.
+fn matched_pattern(word: &str, matcher: Option<&Matcher>) -> Option<String> {
+    match matcher.and_then(|m| m.hits(word)) {
+        Some(hits) => Some(hits.join(", ")),
+        None => matcher.and_then(|m| m.find(word)).map(|range| word[range].to_string()),
+    }
+}
+
+/// `word` split into groups of `size` characters with `separator` between
+/// them, then cut into the text before, inside and after the byte `range`.
+/// A separator only joins the highlighted part when both its neighbours are
+/// in it.
+fn grouped(word: &str, size: usize, separator: &str, range: Range<usize>) -> [String; 3] {
+    let mut parts: [String; 3] = Default::default();
+    let part = |at: usize| if at < range.start { 0 } else if at < range.end { 1 } else { 2 };
+    let mut previous = 0;
+    for (i, (at, c)) in word.char_indices().enumerate() {
+        let current = part(at);
+        if i > 0 && i % size == 0 {
+            let space = if previous == 1 && current != 1 { current } else if current == 1 && previous != 1 { previous } else { current };
+            parts[space].push_str(separator);
+        }
+        parts[current].push(c);
+        previous = current;
+    }
+    parts
+}
+
+/// `1234567.0` as `1.2M`.
+pub fn si(x: f64) -> String {
+    const UNITS: [(f64, &str); 4] = [(1e12, "T"), (1e9, "G"), (1e6, "M"), (1e3, "k")];
+    match UNITS.iter().find(|(scale, _)| x >= *scale) {
+        Some((scale, unit)) => format!("{:.1}{}", x / scale, unit),
+        None => format!("{:.0}", x),
+    }
+}
+
+/// Seconds as `3d 4h 5m` / `4h 5m 6s` / `0.42s`.
+pub fn human(secs: f64) -> String {
+    if !secs.is_finite() {
+        return "forever".to_string();
+    }
+    if secs < 60.0 {
+        return format!("{:.2}s", secs);
+    }
+    let years = secs / (365.25 * 86400.0);
+    if years >= 1000.0 {
+        return format!("{} years", si(years));
+    }
+    let secs = secs as u64;
+    let (d, h, m, s) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+    match (d, h) {
+        (0, 0) => format!("{}m {}s", m, s),
+        (0, _) => format!("{}h {}m {}s", h, m, s),
+        _ => format!("{}d {}h {}m", d, h, m),
+    }
+}
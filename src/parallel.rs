@@ -0,0 +1,332 @@
+//! Multi-threaded generation and matching.
+//!
+//! Permutations/Words cut the keyspace into [`Block`]s (one per length and
+//! first letter); Search walks its keyspace in fixed-size [`Product`] chunks.
+//! Either way rayon workers generate and filter the pieces independently.
+
+use crate::cli::RunOpts;
+use crate::output::Output;
+use crate::sort::temp_path;
+use crate::{interrupt, Result};
+use clap::ValueEnum;
+use isaw_core::case;
+use isaw_core::generate::{Block, Keyspace, Product};
+use isaw_core::transform::{self, Transform};
+use isaw_core::Matcher;
+use rayon::iter::Either;
+use rayon::prelude::*;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::mpsc;
+
+/// How results from parallel workers are put back together.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Merge {
+    /// Same order as single-threaded generation
+    Ordered,
+    /// Print batches as soon as any worker finishes them
+    Unordered,
+}
+
+/// Words are handed to the printer in batches of this size.
+const BATCH: usize = 4096;
+
+/// Everything a worker found in one batch: how many candidates it
+/// generated and which of them matched.
+type Batch = (u64, Vec<String>);
+
+/// Parallel counterpart of `emit`: prints what matches and returns
+/// `(generated, matched)`.
+pub fn emit(out: &mut Output, chars: &[char], blocks: &[Block], unique: bool, matcher: Option<&Matcher>, merge: Merge, run: &RunOpts) -> Result<(u64, u64)> {
+    // Blocks finish in no particular order, so there is no prefix to resume after.
+    out.unresumable();
+    let mut count = 0;
+    let mut matches = 0;
+    let mut print = |out: &mut Output, (generated, words): Batch| -> Result<()> {
+        count += generated;
+        for word in &words {
+            if out.full() {
+                break;
+            }
+            out.result(word, matcher)?;
+            matches += 1;
+        }
+        Ok(())
+    };
+
+    match merge {
+        // One block at a time, printed as it is generated instead of held
+        // until its window is done: the same order, on one core.
+        Merge::Ordered if run.low_mem => {
+            for block in blocks {
+                let mut printed = Ok(());
+                run_block(chars, *block, unique, matcher, run, |batch| {
+                    printed = print(out, batch);
+                    printed.is_ok() && !out.stopping()
+                });
+                printed?;
+                if out.stopping() {
+                    break;
+                }
+            }
+        }
+        Merge::Ordered => {
+            // A few blocks per thread at a time keeps memory bounded while
+            // still letting every core work.
+            let window = rayon::current_num_threads() * 2;
+            let limit = run.memory_share().map_or(usize::MAX, |share| share / window);
+            for chunk in blocks.chunks(window) {
+                let results: Vec<io::Result<Held>> = chunk
+                    .par_iter()
+                    .map(|block| {
+                        // No block can contribute more than --first results,
+                        // unless --dedup might still drop some of them.
+                        let mut held = Held::new(limit);
+                        let mut kept = 0;
+                        let mut failed = None;
+                        run_block(chars, *block, unique, matcher, run, |batch| {
+                            kept += batch.1.len() as u64;
+                            if let Err(e) = held.push(batch) {
+                                failed = Some(e);
+                                return false;
+                            }
+                            run.dedup || run.first.is_none_or(|first| kept < first)
+                        });
+                        failed.map_or(Ok(held), Err)
+                    })
+                    .collect();
+                for held in results {
+                    held?.drain(|batch| print(out, batch))?;
+                }
+                if out.stopping() {
+                    break;
+                }
+            }
+        }
+        Merge::Unordered => {
+            let (tx, rx) = mpsc::sync_channel::<Batch>(rayon::current_num_threads() * 2);
+            std::thread::scope(|scope| -> Result<()> {
+                scope.spawn(move || {
+                    // A failed send means the printer gave up; stop generating.
+                    let _ = blocks.par_iter().try_for_each_with(tx, |tx, block| {
+                        let mut open = true;
+                        run_block(chars, *block, unique, matcher, run, |batch| {
+                            open = tx.send(batch).is_ok();
+                            open
+                        });
+                        if open { Ok(()) } else { Err(()) }
+                    });
+                });
+                // Dropping the receiver once --first is satisfied stops the workers.
+                for batch in rx {
+                    print(out, batch)?;
+                    if out.stopping() {
+                        break;
+                    }
+                }
+                Ok(())
+            })?;
+        }
+    }
+
+    Ok((count, matches))
+}
+
+/// A block's batches waiting for their turn to print. Past `limit` bytes
+/// (a share of `--max-memory`) the rest go to a temp file.
+struct Held {
+    limit: usize,
+    bytes: usize,
+    batches: Vec<Batch>,
+    spilled: Option<(PathBuf, BufWriter<File>)>,
+}
+
+impl Held {
+    fn new(limit: usize) -> Self {
+        Held { limit, bytes: 0, batches: Vec::new(), spilled: None }
+    }
+
+    fn push(&mut self, (generated, words): Batch) -> io::Result<()> {
+        if self.spilled.is_none() {
+            let size: usize = words.iter().map(|word| word.len() + std::mem::size_of::<String>()).sum();
+            if self.bytes + size <= self.limit {
+                self.bytes += size;
+                self.batches.push((generated, words));
+                return Ok(());
+            }
+            let path = temp_path("block");
+            let file = File::create(&path)?;
+            self.spilled = Some((path, BufWriter::new(file)));
+        }
+        let (_, file) = self.spilled.as_mut().expect("just spilled");
+        // A header per batch, then its words one per line.
+        writeln!(file, "{} {}", generated, words.len())?;
+        for word in &words {
+            writeln!(file, "{}", word)?;
+        }
+        Ok(())
+    }
+
+    /// Hands every batch to `print` in order, reading back the spilled ones.
+    fn drain(mut self, mut print: impl FnMut(Batch) -> Result<()>) -> Result<()> {
+        for batch in std::mem::take(&mut self.batches) {
+            print(batch)?;
+        }
+        let Some((path, file)) = &mut self.spilled else { return Ok(()) };
+        file.flush()?;
+        let mut lines = BufReader::new(File::open(path)?).lines();
+        while let Some(header) = lines.next().transpose()? {
+            let (generated, len) = header.split_once(' ').ok_or("Corrupt spill file")?;
+            let (generated, len): (u64, usize) = (generated.parse()?, len.parse()?);
+            let words = lines.by_ref().take(len).collect::<io::Result<Vec<String>>>()?;
+            print((generated, words))?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Held {
+    fn drop(&mut self) {
+        if let Some((path, _)) = &self.spilled {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Generates one block, passing batches to `send` until it returns false.
+fn run_block(chars: &[char], block: Block, unique: bool, matcher: Option<&Matcher>, run: &RunOpts, mut send: impl FnMut(Batch) -> bool) {
+    let transforms = run.transforms();
+    // Smaller batches with --first, so the printer hears of matches early.
+    let batch = run.first.map_or(BATCH, |first| (first as usize).clamp(1, BATCH));
+    // Blocks never share a (length, first letter) pair, so per-block
+    // dedup is enough to make the whole run unique.
+    let words = if unique {
+        Either::Left(block.distinct_permutations(chars))
+    } else {
+        Either::Right(block.permutations(chars))
+    };
+    let words = match run.any_case {
+        true => Either::Left(words.flat_map(|word| case::variants(&word))),
+        false => Either::Right(words),
+    };
+    let mut generated = 0;
+    let mut kept = Vec::new();
+
+    for word in words {
+        if interrupt::requested() {
+            break;
+        }
+        generated += 1;
+        let word = transform::apply_all(&transforms, word);
+        if matcher.is_none_or(|m| m.is_match(&word)) {
+            kept.push(word);
+            if kept.len() == batch {
+                if !send((generated, std::mem::take(&mut kept))) {
+                    return;
+                }
+                generated = 0;
+            }
+        }
+    }
+    send((generated, kept));
+}
+
+/// Candidates per Search chunk.
+const CHUNK: u128 = 1 << 16;
+
+/// Candidates per Search chunk with `--low-mem`.
+const LOW_MEM_CHUNK: u128 = 1 << 12;
+
+/// Smallest Search chunk `--max-memory` shrinks them to.
+const MIN_CHUNK: u128 = 1 << 8;
+
+/// Filters the `ranks` of the `chars^length` Search keyspace on all cores
+/// and prints the matches in keyspace order; returns `(searched, matched)`.
+///
+/// A generator thread produces windows of fixed-size chunks, filters each
+/// window in parallel and hands the matches to the printer through a
+/// bounded channel, so peak memory no longer grows with the keyspace.
+///
+/// Candidates pass through `transforms` before they are matched and printed.
+/// Ctrl-C stops the generator between windows, so whatever was searched is
+/// a whole prefix of `ranks`.
+pub fn search(out: &mut Output, chars: &[char], length: usize, ranks: Range<u128>, run: &RunOpts, matcher: &Matcher, transforms: &[Transform]) -> Result<(u64, u64)> {
+    let reverse = run.reverse;
+    // Smaller pieces, fewer of them in flight: less held by matches that
+    // haven't been printed yet.
+    let (chunk, windows) = if run.low_mem { (LOW_MEM_CHUNK, 1) } else { (CHUNK, 4) };
+    let window = rayon::current_num_threads() as u128 * windows;
+    // Two windows can be in flight, every candidate of them matching.
+    let chunk = match run.memory_share() {
+        Some(share) => chunk.min(share as u128 / (window * 2 * (length + std::mem::size_of::<String>()) as u128)).max(MIN_CHUNK),
+        None => chunk,
+    };
+    let (tx, rx) = mpsc::sync_channel::<Batch>(window as usize);
+
+    std::thread::scope(|scope| -> Result<(u64, u64)> {
+        scope.spawn(move || {
+            // Chunks are handed out from the top down when reversing, and
+            // each is walked backwards, so the output is exactly reversed.
+            let chunk_at = |i: u128| match reverse {
+                true => ranks.end.saturating_sub((i + 1) * chunk).max(ranks.start)..ranks.end - i * chunk,
+                false => ranks.start + i * chunk..(ranks.start + (i + 1) * chunk).min(ranks.end),
+            };
+            let chunks_total = (ranks.end - ranks.start).div_ceil(chunk);
+            let mut next = 0;
+            while next < chunks_total && !interrupt::requested() {
+                let chunks: Vec<Range<u128>> = (next..(next + window).min(chunks_total)).map(chunk_at).collect();
+                next += window;
+
+                let results: Vec<Batch> = chunks
+                    .into_par_iter()
+                    .map(|chunk| {
+                        let size = (chunk.end - chunk.start) as u64;
+                        // Untransformed forward chunks are matched in place;
+                        // only matches become Strings. Hashing matchers take
+                        // the whole chunk as one batch instead.
+                        if !reverse && transforms.is_empty() && !matcher.batches() {
+                            let mut product = Product::new(chars, length, chunk.start);
+                            let mut matches = Vec::new();
+                            for _ in 0..size {
+                                let Some(word) = product.next_str() else { break };
+                                if matcher.is_match(word) {
+                                    matches.push(word.to_string());
+                                }
+                            }
+                            return (size, matches);
+                        }
+                        let words: Box<dyn Iterator<Item = String>> = match reverse {
+                            true => Box::new(Keyspace::Product.candidates_rev(chars.to_vec(), length..=length, chunk)),
+                            false => Box::new(Product::new(chars, length, chunk.start).take(size as usize)),
+                        };
+                        let mut words: Vec<String> = words.map(|word| transform::apply_all(transforms, word)).collect();
+                        matcher.retain(&mut words);
+                        (size, words)
+                    })
+                    .collect();
+                for matches in results {
+                    if tx.send(matches).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        let mut searched = 0;
+        let mut found = 0;
+        // Dropping the receiver once --first is satisfied stops the generator.
+        'print: for (size, matches) in rx {
+            for word in &matches {
+                if out.full() {
+                    break 'print;
+                }
+                out.result(word, Some(matcher))?;
+                found += 1;
+            }
+            searched += size;
+        }
+        Ok((searched, found))
+    })
+}
@@ -0,0 +1,527 @@
+//! `--format parquet`: results written as a Parquet file on stdout.
+//!
+//! The file has one column per field — `value` and `length` always set,
+//! `score` and `matched` optional — and a row group for every
+//! [`GROUP_BYTES`] of buffered text, so memory stays bounded however many
+//! rows a run produces. Pages are uncompressed and PLAIN-encoded, the one
+//! layout every reader understands; the footer metadata is Thrift compact
+//! protocol, written by hand.
+
+use isaw_core::ngram::Model;
+use std::io::{self, Write};
+
+const MAGIC: &[u8] = b"PAR1";
+
+/// Buffered bytes that close a row group.
+const GROUP_BYTES: usize = 64 << 20;
+
+// Parquet enum values.
+const INT32: i32 = 1;
+const DOUBLE: i32 = 5;
+const BYTE_ARRAY: i32 = 6;
+const REQUIRED: i32 = 0;
+const OPTIONAL: i32 = 1;
+const UTF8: i32 = 0;
+const PLAIN: i32 = 0;
+const RLE: i32 = 3;
+const DATA_PAGE: i32 = 0;
+const UNCOMPRESSED: i32 = 0;
+
+struct Column {
+    name: &'static str,
+    kind: i32,
+    optional: bool,
+    text: bool,
+}
+
+const COLUMNS: [Column; 4] = [
+    Column { name: "value", kind: BYTE_ARRAY, optional: false, text: true },
+    Column { name: "length", kind: INT32, optional: false, text: false },
+    Column { name: "score", kind: DOUBLE, optional: true, text: false },
+    Column { name: "matched", kind: BYTE_ARRAY, optional: true, text: true },
+];
+
+/// One column's values in the current row group.
+#[derive(Default)]
+struct Values {
+    /// PLAIN-encoded non-null values.
+    plain: Vec<u8>,
+    /// Whether each row has a value, for optional columns.
+    present: Vec<bool>,
+}
+
+/// Where a written column chunk lives, for the footer.
+struct Chunk {
+    offset: u64,
+    size: u64,
+}
+
+struct Group {
+    rows: u64,
+    chunks: Vec<Chunk>,
+}
+
+pub struct Parquet {
+    model: Model,
+    columns: [Values; 4],
+    rows: u64,
+    /// Bytes written so far, where the next chunk starts.
+    offset: u64,
+    groups: Vec<Group>,
+}
+
+impl Parquet {
+    pub fn new() -> Self {
+        Parquet { model: Model::english(), columns: Default::default(), rows: 0, offset: 0, groups: Vec::new() }
+    }
+
+    /// Adds one result; `pattern` is what matched it, if anything did.
+    pub fn insert(&mut self, out: &mut dyn Write, value: &str, pattern: Option<&str>) -> io::Result<()> {
+        let [values, lengths, scores, matched] = &mut self.columns;
+        byte_array(&mut values.plain, value);
+        lengths.plain.extend_from_slice(&(value.chars().count() as i32).to_le_bytes());
+        let score = self.model.average(value);
+        scores.present.push(score.is_some());
+        if let Some(score) = score {
+            scores.plain.extend_from_slice(&score.to_le_bytes());
+        }
+        matched.present.push(pattern.is_some());
+        if let Some(pattern) = pattern {
+            byte_array(&mut matched.plain, pattern);
+        }
+        self.rows += 1;
+        if self.columns.iter().map(|column| column.plain.len()).sum::<usize>() >= GROUP_BYTES {
+            self.flush(out)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the last row group and the footer.
+    pub fn finish(mut self, out: &mut dyn Write) -> io::Result<()> {
+        if self.rows > 0 || self.offset == 0 {
+            self.flush(out)?;
+        }
+        let footer = self.footer();
+        out.write_all(&footer)?;
+        out.write_all(&(footer.len() as u32).to_le_bytes())?;
+        out.write_all(MAGIC)?;
+        out.flush()
+    }
+
+    /// Writes the buffered rows as a row group of one page per column.
+    fn flush(&mut self, out: &mut dyn Write) -> io::Result<()> {
+        if self.offset == 0 {
+            out.write_all(MAGIC)?;
+            self.offset = MAGIC.len() as u64;
+        }
+        if self.rows == 0 {
+            return Ok(());
+        }
+        let mut chunks = Vec::with_capacity(COLUMNS.len());
+        for (column, values) in COLUMNS.iter().zip(&mut self.columns) {
+            let mut data = Vec::new();
+            if column.optional {
+                let levels = definition_levels(&values.present);
+                data.extend_from_slice(&(levels.len() as u32).to_le_bytes());
+                data.extend_from_slice(&levels);
+            }
+            data.append(&mut values.plain);
+            values.present.clear();
+
+            let mut header = Thrift::new();
+            header.i32(1, DATA_PAGE);
+            header.i32(2, data.len() as i32);
+            header.i32(3, data.len() as i32);
+            header.begin(5);
+            header.i32(1, self.rows as i32);
+            header.i32(2, PLAIN);
+            header.i32(3, RLE);
+            header.i32(4, RLE);
+            header.end();
+            let header = header.finish();
+
+            out.write_all(&header)?;
+            out.write_all(&data)?;
+            let size = (header.len() + data.len()) as u64;
+            chunks.push(Chunk { offset: self.offset, size });
+            self.offset += size;
+        }
+        self.groups.push(Group { rows: self.rows, chunks });
+        self.rows = 0;
+        Ok(())
+    }
+
+    /// The `FileMetaData` struct.
+    fn footer(&self) -> Vec<u8> {
+        let mut meta = Thrift::new();
+        meta.i32(1, 1);
+        meta.list(2, Thrift::STRUCT, COLUMNS.len() + 1);
+        meta.element();
+        meta.string(4, "schema");
+        meta.i32(5, COLUMNS.len() as i32);
+        meta.end();
+        for column in &COLUMNS {
+            meta.element();
+            meta.i32(1, column.kind);
+            meta.i32(3, if column.optional { OPTIONAL } else { REQUIRED });
+            meta.string(4, column.name);
+            if column.text {
+                meta.i32(6, UTF8);
+                // LogicalType::STRING, an empty struct.
+                meta.begin(10);
+                meta.begin(1);
+                meta.end();
+                meta.end();
+            }
+            meta.end();
+        }
+        meta.i64(3, self.groups.iter().map(|group| group.rows).sum::<u64>() as i64);
+        meta.list(4, Thrift::STRUCT, self.groups.len());
+        for group in &self.groups {
+            meta.element();
+            meta.list(1, Thrift::STRUCT, group.chunks.len());
+            for (column, chunk) in COLUMNS.iter().zip(&group.chunks) {
+                meta.element();
+                meta.i64(2, chunk.offset as i64);
+                meta.begin(3);
+                meta.i32(1, column.kind);
+                meta.list(2, Thrift::I32, 2);
+                meta.i32_element(PLAIN);
+                meta.i32_element(RLE);
+                meta.list(3, Thrift::BINARY, 1);
+                meta.string_element(column.name);
+                meta.i32(4, UNCOMPRESSED);
+                meta.i64(5, group.rows as i64);
+                meta.i64(6, chunk.size as i64);
+                meta.i64(7, chunk.size as i64);
+                meta.i64(9, chunk.offset as i64);
+                meta.end();
+                meta.end();
+            }
+            let size = group.chunks.iter().map(|chunk| chunk.size).sum::<u64>() as i64;
+            meta.i64(2, size);
+            meta.i64(3, group.rows as i64);
+            meta.i64(5, group.chunks[0].offset as i64);
+            meta.i64(6, size);
+            meta.end();
+        }
+        meta.string(6, concat!("isaw version ", env!("CARGO_PKG_VERSION")));
+        meta.finish()
+    }
+}
+
+fn byte_array(plain: &mut Vec<u8>, text: &str) {
+    plain.extend_from_slice(&(text.len() as u32).to_le_bytes());
+    plain.extend_from_slice(text.as_bytes());
+}
+
+/// 1-bit definition levels in the RLE/bit-packing hybrid, as plain runs.
+fn definition_levels(present: &[bool]) -> Vec<u8> {
+    let mut levels = Vec::new();
+    for run in present.chunk_by(|a, b| a == b) {
+        varint(&mut levels, (run.len() as u64) << 1);
+        levels.push(run[0] as u8);
+    }
+    levels
+}
+
+fn varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+/// A Thrift compact protocol encoder for one top-level struct.
+struct Thrift {
+    buf: Vec<u8>,
+    /// The last field id written in each open struct.
+    last: Vec<i16>,
+}
+
+impl Thrift {
+    const I32: u8 = 5;
+    const I64: u8 = 6;
+    const BINARY: u8 = 8;
+    const LIST: u8 = 9;
+    const STRUCT: u8 = 12;
+
+    fn new() -> Self {
+        Thrift { buf: Vec::new(), last: vec![0] }
+    }
+
+    fn field(&mut self, id: i16, kind: u8) {
+        let last = self.last.last_mut().expect("an open struct");
+        match id - *last {
+            delta @ 1..=15 => self.buf.push((delta as u8) << 4 | kind),
+            _ => {
+                self.buf.push(kind);
+                varint(&mut self.buf, zigzag(id as i64));
+            }
+        }
+        *last = id;
+    }
+
+    fn i32(&mut self, id: i16, value: i32) {
+        self.field(id, Self::I32);
+        varint(&mut self.buf, zigzag(value as i64));
+    }
+
+    fn i64(&mut self, id: i16, value: i64) {
+        self.field(id, Self::I64);
+        varint(&mut self.buf, zigzag(value));
+    }
+
+    fn string(&mut self, id: i16, value: &str) {
+        self.field(id, Self::BINARY);
+        self.string_element(value);
+    }
+
+    fn begin(&mut self, id: i16) {
+        self.field(id, Self::STRUCT);
+        self.last.push(0);
+    }
+
+    fn end(&mut self) {
+        self.buf.push(0);
+        self.last.pop();
+    }
+
+    fn list(&mut self, id: i16, kind: u8, len: usize) {
+        self.field(id, Self::LIST);
+        if len < 15 {
+            self.buf.push((len as u8) << 4 | kind);
+        } else {
+            self.buf.push(0xf0 | kind);
+            varint(&mut self.buf, len as u64);
+        }
+    }
+
+    /// Opens a struct inside a list; close it with [`Thrift::end`].
+    fn element(&mut self) {
+        self.last.push(0);
+    }
+
+    fn i32_element(&mut self, value: i32) {
+        varint(&mut self.buf, zigzag(value as i64));
+    }
+
+    fn string_element(&mut self, value: &str) {
+        varint(&mut self.buf, value.len() as u64);
+        self.buf.extend_from_slice(value.as_bytes());
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.end();
+        self.buf
+    }
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A decoded Thrift compact value.
+    #[derive(Debug)]
+    enum Value {
+        Int(i64),
+        Binary(Vec<u8>),
+        List(Vec<Value>),
+        Struct(Vec<(i16, Value)>),
+    }
+
+    impl Value {
+        fn field(&self, id: i16) -> &Value {
+            let Value::Struct(fields) = self else { panic!("{:?} is not a struct", self) };
+            &fields.iter().find(|(field, _)| *field == id).unwrap_or_else(|| panic!("no field {} in {:?}", id, fields)).1
+        }
+
+        fn has(&self, id: i16) -> bool {
+            matches!(self, Value::Struct(fields) if fields.iter().any(|(field, _)| *field == id))
+        }
+
+        fn int(&self) -> i64 {
+            let Value::Int(value) = self else { panic!("{:?} is not an integer", self) };
+            *value
+        }
+
+        fn text(&self) -> &str {
+            let Value::Binary(bytes) = self else { panic!("{:?} is not binary", self) };
+            std::str::from_utf8(bytes).expect("UTF-8")
+        }
+
+        fn list(&self) -> &[Value] {
+            let Value::List(items) = self else { panic!("{:?} is not a list", self) };
+            items
+        }
+    }
+
+    fn read_varint(buf: &[u8], pos: &mut usize) -> u64 {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = buf[*pos];
+            *pos += 1;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte < 0x80 {
+                return value;
+            }
+            shift += 7;
+        }
+    }
+
+    fn read_int(buf: &[u8], pos: &mut usize) -> i64 {
+        let value = read_varint(buf, pos);
+        (value >> 1) as i64 ^ -((value & 1) as i64)
+    }
+
+    fn read_value(buf: &[u8], pos: &mut usize, kind: u8) -> Value {
+        match kind {
+            Thrift::I32 | Thrift::I64 => Value::Int(read_int(buf, pos)),
+            Thrift::BINARY => {
+                let len = read_varint(buf, pos) as usize;
+                *pos += len;
+                Value::Binary(buf[*pos - len..*pos].to_vec())
+            }
+            Thrift::LIST => {
+                let header = buf[*pos];
+                *pos += 1;
+                let len = match header >> 4 {
+                    15 => read_varint(buf, pos) as usize,
+                    len => len as usize,
+                };
+                Value::List((0..len).map(|_| read_value(buf, pos, header & 0x0f)).collect())
+            }
+            Thrift::STRUCT => read_struct(buf, pos),
+            kind => panic!("unexpected Thrift type {}", kind),
+        }
+    }
+
+    fn read_struct(buf: &[u8], pos: &mut usize) -> Value {
+        let mut fields = Vec::new();
+        let mut last = 0;
+        loop {
+            let header = buf[*pos];
+            *pos += 1;
+            if header == 0 {
+                return Value::Struct(fields);
+            }
+            let id = match header >> 4 {
+                0 => read_int(buf, pos) as i16,
+                delta => last + delta as i16,
+            };
+            last = id;
+            fields.push((id, read_value(buf, pos, header & 0x0f)));
+        }
+    }
+
+    /// Expands the 1-bit definition levels written by `definition_levels`.
+    fn read_levels(buf: &[u8], rows: usize) -> Vec<bool> {
+        let mut pos = 0;
+        let mut present = Vec::new();
+        while present.len() < rows {
+            let run = read_varint(buf, &mut pos);
+            assert_eq!(run & 1, 0, "only RLE runs are written");
+            present.extend(std::iter::repeat_n(buf[pos] == 1, (run >> 1) as usize));
+            pos += 1;
+        }
+        assert_eq!(pos, buf.len());
+        present
+    }
+
+    fn read_byte_arrays(mut buf: &[u8]) -> Vec<String> {
+        let mut texts = Vec::new();
+        while !buf.is_empty() {
+            let len = u32::from_le_bytes(buf[..4].try_into().unwrap()) as usize;
+            texts.push(String::from_utf8(buf[4..4 + len].to_vec()).unwrap());
+            buf = &buf[4 + len..];
+        }
+        texts
+    }
+
+    #[test]
+    fn file_reads_back() {
+        let rows = [("cat", Some("c")), ("xqzj", None), ("tea", Some("t")), ("ab", None)];
+        let mut parquet = Parquet::new();
+        let mut file = Vec::new();
+        for (value, pattern) in rows {
+            parquet.insert(&mut file, value, pattern).unwrap();
+        }
+        parquet.finish(&mut file).unwrap();
+
+        assert_eq!(&file[..4], MAGIC);
+        assert_eq!(&file[file.len() - 4..], MAGIC);
+        let footer_len = u32::from_le_bytes(file[file.len() - 8..file.len() - 4].try_into().unwrap()) as usize;
+        let footer = &file[file.len() - 8 - footer_len..file.len() - 8];
+        let mut pos = 0;
+        let meta = read_struct(footer, &mut pos);
+        assert_eq!(pos, footer.len());
+
+        let schema = meta.field(2).list();
+        assert_eq!(schema[0].field(5).int(), 4);
+        let names: Vec<&str> = schema[1..].iter().map(|element| element.field(4).text()).collect();
+        assert_eq!(names, ["value", "length", "score", "matched"]);
+        let kinds: Vec<i64> = schema[1..].iter().map(|element| element.field(1).int()).collect();
+        assert_eq!(kinds, [BYTE_ARRAY as i64, INT32 as i64, DOUBLE as i64, BYTE_ARRAY as i64]);
+        let repetition: Vec<i64> = schema[1..].iter().map(|element| element.field(3).int()).collect();
+        assert_eq!(repetition, [REQUIRED as i64, REQUIRED as i64, OPTIONAL as i64, OPTIONAL as i64]);
+        assert!(schema[1].has(6) && !schema[2].has(6) && schema[4].has(6));
+        assert_eq!(meta.field(3).int(), rows.len() as i64);
+
+        let groups = meta.field(4).list();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].field(3).int(), rows.len() as i64);
+        let mut pages = Vec::new();
+        for (chunk, column) in groups[0].field(1).list().iter().zip(&COLUMNS) {
+            let chunk = chunk.field(3);
+            assert_eq!(chunk.field(3).list()[0].text(), column.name);
+            assert_eq!(chunk.field(5).int(), rows.len() as i64);
+            let mut pos = chunk.field(9).int() as usize;
+            let header = read_struct(&file, &mut pos);
+            assert_eq!(header.field(1).int(), DATA_PAGE as i64);
+            assert_eq!(header.field(5).field(1).int(), rows.len() as i64);
+            let len = header.field(3).int() as usize;
+            assert_eq!(pos + len - chunk.field(9).int() as usize, chunk.field(6).int() as usize);
+            pages.push(&file[pos..pos + len]);
+        }
+
+        // Optional pages are definition levels, prefixed by their length,
+        // followed by the present values.
+        let optional = |page: &[u8]| {
+            let len = u32::from_le_bytes(page[..4].try_into().unwrap()) as usize;
+            (read_levels(&page[4..4 + len], rows.len()), page[4 + len..].to_vec())
+        };
+        assert_eq!(read_byte_arrays(pages[0]), rows.map(|(value, _)| value));
+        let lengths: Vec<i32> = pages[1].chunks_exact(4).map(|b| i32::from_le_bytes(b.try_into().unwrap())).collect();
+        assert_eq!(lengths, [3, 4, 3, 2]);
+
+        let model = Model::english();
+        let (present, plain) = optional(pages[2]);
+        let scores: Vec<f64> = plain.chunks_exact(8).map(|b| f64::from_le_bytes(b.try_into().unwrap())).collect();
+        let expected: Vec<Option<f64>> = rows.iter().map(|(value, _)| model.average(value)).collect();
+        assert!(expected.iter().any(Option::is_some));
+        assert_eq!(present, expected.iter().map(Option::is_some).collect::<Vec<_>>());
+        assert_eq!(scores, expected.into_iter().flatten().collect::<Vec<_>>());
+
+        let (present, plain) = optional(pages[3]);
+        assert_eq!(present, [true, false, true, false]);
+        assert_eq!(read_byte_arrays(&plain), ["c", "t"]);
+    }
+
+    #[test]
+    fn empty_file_has_schema_and_no_rows() {
+        let mut file = Vec::new();
+        Parquet::new().finish(&mut file).unwrap();
+        let footer_len = u32::from_le_bytes(file[file.len() - 8..file.len() - 4].try_into().unwrap()) as usize;
+        let meta = read_struct(&file[file.len() - 8 - footer_len..], &mut 0);
+        assert_eq!(&file[..4], MAGIC);
+        assert_eq!(meta.field(2).list().len(), 5);
+        assert_eq!(meta.field(3).int(), 0);
+        assert!(meta.field(4).list().is_empty());
+    }
+}
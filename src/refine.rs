@@ -0,0 +1,133 @@
+//! `isaw refine`: narrowing a saved result set from a prompt.
+//!
+//! Results already generated (say with `-q > results.txt`) are loaded once;
+//! every filter typed at the prompt re-filters the current set in place,
+//! without generating anything again, and `undo` steps back. `export` saves
+//! whatever is left. Commands are read a line at a time, so a file of them
+//! can be piped in as well.
+
+use crate::cli::RefineArgs;
+use crate::Result;
+use colored::*;
+use isaw_core::Matcher;
+use std::io::{BufRead, Write};
+
+/// Results shown after each filter.
+const PREVIEW: usize = 10;
+
+/// What a filter command keeps.
+type Filter = Box<dyn Fn(&str) -> bool>;
+
+const HELP: &str = "\
+  contains TEXT   keep results containing TEXT
+  excludes TEXT   drop results containing TEXT
+  length N[-M]    keep results N (to M) characters long
+  regex PATTERN   keep results matching PATTERN
+  show [N]        print the first N results (all without N)
+  undo            take back the last filter
+  reset           take back every filter
+  export FILE     write the current results to FILE
+  quit            leave";
+
+pub fn refine(out: &mut dyn Write, args: RefineArgs) -> Result<()> {
+    let text = std::fs::read_to_string(&args.results).map_err(|e| format!("Cannot read {}: {}", args.results.display(), e))?;
+    let all: Vec<&str> = text.lines().filter(|line| !line.is_empty()).collect();
+    // Each filter pushes the indices that survive it.
+    let mut history: Vec<(String, Vec<usize>)> = vec![(String::new(), (0..all.len()).collect())];
+
+    writeln!(out, "{}", format!("🔬 Refining {} results from {}", all.len(), args.results.display()).cyan().bold())?;
+    writeln!(out, "{}", "   Type filters to narrow them down; `help` lists the commands".dimmed())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        write!(out, "{} ", format!("[{}]>", history.last().map_or(0, |(_, kept)| kept.len())).green().bold())?;
+        out.flush()?;
+        let Some(line) = lines.next().transpose()? else { break };
+        let line = line.trim();
+        let (command, argument) = line.split_once(char::is_whitespace).map_or((line, ""), |(c, a)| (c, a.trim()));
+        let current = &history.last().expect("the full set").1;
+        let filter: Option<Filter> = match (command, argument) {
+            ("", _) => continue,
+            ("quit" | "exit" | "q", _) => break,
+            ("help" | "?", _) => {
+                writeln!(out, "{}", HELP.dimmed())?;
+                continue;
+            }
+            ("contains", text) if !text.is_empty() => {
+                let text = text.to_string();
+                Some(Box::new(move |word: &str| word.contains(text.as_str())))
+            }
+            ("excludes", text) if !text.is_empty() => {
+                let text = text.to_string();
+                Some(Box::new(move |word: &str| !word.contains(text.as_str())))
+            }
+            ("length", spec) if !spec.is_empty() => match crate::length_range(spec) {
+                Ok(range) => Some(Box::new(move |word: &str| range.as_ref().is_none_or(|range| range.contains(&word.chars().count())))),
+                Err(e) => {
+                    writeln!(out, "{}", format!("   {}", e).red())?;
+                    continue;
+                }
+            },
+            ("regex", pattern) if !pattern.is_empty() => match Matcher::regex(pattern) {
+                Ok(matcher) => Some(Box::new(move |word: &str| matcher.is_match(word))),
+                Err(e) => {
+                    writeln!(out, "{}", format!("   Invalid regex: {}", e).red())?;
+                    continue;
+                }
+            },
+            ("show", count) => {
+                let count = count.parse().unwrap_or(usize::MAX);
+                for &i in current.iter().take(count) {
+                    writeln!(out, "  {}", all[i])?;
+                }
+                continue;
+            }
+            ("undo", _) => {
+                match history.len() > 1 {
+                    true => {
+                        let (undone, _) = history.pop().expect("more than one step");
+                        writeln!(out, "{}", format!("   Undid '{}'", undone).dimmed())?;
+                    }
+                    false => writeln!(out, "{}", "   Nothing to undo".dimmed())?,
+                }
+                continue;
+            }
+            ("reset", _) => {
+                history.truncate(1);
+                continue;
+            }
+            ("export", path) if !path.is_empty() => {
+                let mut file = std::io::BufWriter::new(std::fs::File::create(path).map_err(|e| format!("Cannot write {}: {}", path, e))?);
+                for &i in current {
+                    writeln!(file, "{}", all[i])?;
+                }
+                file.flush().map_err(|e| format!("Cannot write {}: {}", path, e))?;
+                writeln!(out, "{}", format!("   Wrote {} result{} to {}", current.len(), if current.len() == 1 { "" } else { "s" }, path).dimmed())?;
+                continue;
+            }
+            _ => None,
+        };
+        let Some(filter) = filter else {
+            writeln!(out, "{}", format!("   Unknown command '{}'; `help` lists them", line).red())?;
+            continue;
+        };
+        let kept: Vec<usize> = current.iter().copied().filter(|&i| filter(all[i])).collect();
+        writeln!(out, "{}", format!("   {} of {} remain", kept.len(), current.len()).dimmed())?;
+        for &i in kept.iter().take(PREVIEW) {
+            writeln!(out, "  {}", all[i])?;
+        }
+        if kept.len() > PREVIEW {
+            writeln!(out, "{}", format!("   ... and {} more", kept.len() - PREVIEW).dimmed())?;
+        }
+        history.push((line.to_string(), kept));
+    }
+
+    let kept = history.last().map_or(0, |(_, kept)| kept.len());
+    writeln!(out)?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    writeln!(out, "{}", format!("✨ {} of {} results after {} filter{}", kept, all.len(), history.len() - 1, if history.len() == 2 { "" } else { "s" }).green().bold())?;
+    Ok(())
+}
+
@@ -0,0 +1,156 @@
+//! `--report` and `--letter-stats`: breakdowns of the results a run printed,
+//! written to stderr after it finishes like `--stats`.
+//!
+//! Results are tallied by length and by first letter, and the most
+//! English-looking ones are kept, scored by the average log-probability of
+//! their letters under English letter frequencies. Letter stats count how
+//! many results use each letter, to show which tiles matter most.
+
+use crate::stats::StatsFormat;
+use colored::*;
+use isaw_core::ngram::Model;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap};
+
+pub struct Report {
+    format: StatsFormat,
+    top: usize,
+    model: Model,
+    results: u64,
+    lengths: BTreeMap<usize, u64>,
+    initials: BTreeMap<char, u64>,
+    /// The best `top` results so far, worst on top for eviction.
+    best: BinaryHeap<Scored>,
+}
+
+/// A result ordered so that the heap's maximum is the lowest score.
+struct Scored(f64, String);
+
+impl PartialEq for Scored {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.total_cmp(&self.0).then_with(|| self.1.cmp(&other.1))
+    }
+}
+
+impl Report {
+    pub fn new(format: StatsFormat, top: usize) -> Self {
+        Report { format, top, model: Model::english(), results: 0, lengths: BTreeMap::new(), initials: BTreeMap::new(), best: BinaryHeap::new() }
+    }
+
+    pub fn record(&mut self, word: &str) {
+        self.results += 1;
+        let length = word.chars().count();
+        *self.lengths.entry(length).or_default() += 1;
+        if let Some(initial) = word.chars().next() {
+            *self.initials.entry(initial).or_default() += 1;
+        }
+        if self.top == 0 {
+            return;
+        }
+        let Some(score) = self.model.average(word) else { return };
+        if self.best.len() < self.top {
+            self.best.push(Scored(score, word.to_string()));
+        } else if self.best.peek().is_some_and(|worst| score > worst.0) {
+            self.best.pop();
+            self.best.push(Scored(score, word.to_string()));
+        }
+    }
+
+    pub fn print(&self) {
+        let mut best: Vec<&Scored> = self.best.iter().collect();
+        best.sort();
+        match self.format {
+            StatsFormat::Text => {
+                errln!("{}", format!("📋 Report: {} result{}", self.results, if self.results == 1 { "" } else { "s" }).cyan().bold());
+                let most = self.lengths.values().copied().max().unwrap_or(1);
+                errln!("{}", "By length".bold());
+                for (length, count) in &self.lengths {
+                    let bar = "█".repeat((count * 30).div_ceil(most) as usize);
+                    errln!("  {:>4}  {:<31}{}", length.to_string().yellow(), bar.blue(), count);
+                }
+                errln!("{}", "By first letter".bold());
+                let initials: Vec<String> = self.initials.iter().map(|(c, count)| format!("{} {}", c.to_string().yellow(), count)).collect();
+                for row in initials.chunks(8) {
+                    errln!("  {}", row.join("  "));
+                }
+                if !best.is_empty() {
+                    errln!("{}", "Most English-like".bold());
+                    for Scored(score, word) in best {
+                        errln!("  {:<20} {}", word, format!("{:.2}", score).dimmed());
+                    }
+                }
+            }
+            StatsFormat::Json => {
+                let lengths: Vec<String> = self.lengths.iter().map(|(length, count)| format!("\"{}\":{}", length, count)).collect();
+                let initials: Vec<String> = self.initials.iter().map(|(c, count)| format!("{}:{}", json_string(&c.to_string()), count)).collect();
+                let best: Vec<String> = best.iter().map(|Scored(score, word)| format!("{{\"word\":{},\"score\":{:.4}}}", json_string(word), score)).collect();
+                errln!(
+                    "{{\"results\":{},\"lengths\":{{{}}},\"initials\":{{{}}},\"top\":[{}]}}",
+                    self.results,
+                    lengths.join(","),
+                    initials.join(","),
+                    best.join(",")
+                );
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct LetterStats {
+    results: u64,
+    /// Per letter, the number of results containing it at least once.
+    letters: BTreeMap<char, u64>,
+}
+
+impl LetterStats {
+    pub fn record(&mut self, word: &str) {
+        self.results += 1;
+        let mut seen: Vec<char> = word.chars().filter(|c| !c.is_whitespace()).collect();
+        seen.sort_unstable();
+        seen.dedup();
+        for c in seen {
+            *self.letters.entry(c).or_default() += 1;
+        }
+    }
+
+    pub fn print(&self) {
+        let mut letters: Vec<(char, u64)> = self.letters.iter().map(|(&c, &count)| (c, count)).collect();
+        letters.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        errln!("{}", format!("🔡 Letters across {} result{}", self.results, if self.results == 1 { "" } else { "s" }).cyan().bold());
+        for (c, count) in letters {
+            let share = count as f64 / self.results.max(1) as f64;
+            let bar = "█".repeat((share * 30.0).ceil() as usize);
+            errln!("  {:<3}{:<31}{:>6.2}%  {}", c.to_string().yellow(), bar.blue(), share * 100.0, count.to_string().dimmed());
+        }
+    }
+}
+
+pub fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
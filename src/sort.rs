@@ -0,0 +1,92 @@
+//! `--sort`: results buffered in memory up to a budget, then spilled as
+//! sorted runs to temporary files and merged back together at the end.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Keeps temp files unique when the daemon runs several requests at once.
+static NEXT_FILE: AtomicUsize = AtomicUsize::new(0);
+
+/// A fresh path in the temp directory for spilling `kind` to.
+pub fn temp_path(kind: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("isaw-{}-{}-{}", kind, std::process::id(), NEXT_FILE.fetch_add(1, Ordering::Relaxed)))
+}
+
+pub struct ExternalSort {
+    budget: usize,
+    buffer: Vec<String>,
+    bytes: usize,
+    runs: Vec<PathBuf>,
+}
+
+impl ExternalSort {
+    /// Keeps roughly `budget` bytes of results in memory before spilling.
+    pub fn new(budget: usize) -> Self {
+        ExternalSort { budget, buffer: Vec::new(), bytes: 0, runs: Vec::new() }
+    }
+
+    pub fn push(&mut self, word: &str) -> io::Result<()> {
+        self.bytes += word.len() + std::mem::size_of::<String>();
+        self.buffer.push(word.to_string());
+        if self.bytes >= self.budget {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    fn spill(&mut self) -> io::Result<()> {
+        self.buffer.sort_unstable();
+        let path = temp_path("sort");
+        // Recorded first so Drop cleans up even if writing fails halfway.
+        self.runs.push(path.clone());
+        let mut file = BufWriter::new(File::create(&path)?);
+        for word in self.buffer.drain(..) {
+            writeln!(file, "{}", word)?;
+        }
+        file.flush()?;
+        self.bytes = 0;
+        Ok(())
+    }
+
+    /// Feeds every result to `write` in sorted order.
+    pub fn finish(mut self, mut write: impl FnMut(&str) -> io::Result<()>) -> io::Result<()> {
+        if self.runs.is_empty() {
+            self.buffer.sort_unstable();
+            return self.buffer.iter().try_for_each(|word| write(word));
+        }
+        if !self.buffer.is_empty() {
+            self.spill()?;
+        }
+
+        let mut readers = self
+            .runs
+            .iter()
+            .map(|path| Ok(BufReader::new(File::open(path)?).lines()))
+            .collect::<io::Result<Vec<_>>>()?;
+        let mut heap = BinaryHeap::new();
+        for (run, lines) in readers.iter_mut().enumerate() {
+            if let Some(word) = lines.next().transpose()? {
+                heap.push(Reverse((word, run)));
+            }
+        }
+        while let Some(Reverse((word, run))) = heap.pop() {
+            write(&word)?;
+            if let Some(next) = readers[run].next().transpose()? {
+                heap.push(Reverse((next, run)));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ExternalSort {
+    fn drop(&mut self) {
+        for path in &self.runs {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
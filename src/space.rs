@@ -0,0 +1,185 @@
+//! Projected output sizes: `count --bytes`, and the check that a run
+//! redirected to a file has room to finish.
+//!
+//! A run can't know how many results a search or filter will let through,
+//! so the check only covers runs printing every candidate, and only text
+//! written to stdout (`isaw words abcdefghij -q > all.txt`); other sinks
+//! grow by amounts that are hard to predict.
+
+use crate::cli::{RunOpts, SizeFormat};
+use crate::output::{si, OutputFormat};
+use crate::report::json_string;
+use crate::Result;
+use colored::*;
+use isaw_core::generate::Keyspace;
+use std::ops::{Range, RangeInclusive};
+use std::sync::OnceLock;
+
+/// Free bytes where stdout is written, when it is a regular file.
+static FREE: OnceLock<Option<u64>> = OnceLock::new();
+
+/// Notes the free space under stdout; runs answering a daemon's clients
+/// never call this, so their output, going elsewhere, isn't checked.
+pub fn watch_stdout() {
+    FREE.get_or_init(stdout_free);
+}
+
+#[cfg(unix)]
+fn stdout_free() -> Option<u64> {
+    use std::os::fd::AsFd;
+    let file = std::fs::File::from(std::io::stdout().as_fd().try_clone_to_owned().ok()?);
+    match file.metadata().ok()?.is_file() {
+        true => free(&file),
+        false => None,
+    }
+}
+
+/// Without file descriptors stdout can't be looked at as a file.
+#[cfg(not(unix))]
+fn stdout_free() -> Option<u64> {
+    None
+}
+
+#[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+mod sys {
+    use std::ffi::c_int;
+
+    /// `struct statvfs` as 64-bit Linux lays it out.
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct Statvfs {
+        pub f_bsize: u64,
+        pub f_frsize: u64,
+        pub f_blocks: u64,
+        pub f_bfree: u64,
+        pub f_bavail: u64,
+        pub f_files: u64,
+        pub f_ffree: u64,
+        pub f_favail: u64,
+        pub f_fsid: u64,
+        pub f_flag: u64,
+        pub f_namemax: u64,
+        pub spare: [c_int; 6],
+    }
+
+    unsafe extern "C" {
+        pub fn fstatvfs(fd: c_int, buf: *mut Statvfs) -> c_int;
+    }
+}
+
+/// Bytes an unprivileged writer may still add to `file`'s filesystem.
+#[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+fn free(file: &std::fs::File) -> Option<u64> {
+    use std::os::unix::io::AsRawFd;
+    let mut stats = sys::Statvfs::default();
+    match unsafe { sys::fstatvfs(file.as_raw_fd(), &mut stats) } {
+        0 => stats.f_bavail.checked_mul(stats.f_frsize),
+        _ => None,
+    }
+}
+
+/// Elsewhere there is nothing to check against.
+#[cfg(all(unix, not(all(target_os = "linux", target_pointer_width = "64"))))]
+fn free(_: &std::fs::File) -> Option<u64> {
+    None
+}
+
+/// Refuses a run whose results won't fit where stdout is redirected, or
+/// only warns with `--force`. `range` is the ranks to be generated, all
+/// of them when `None`; `filtered` runs may print fewer and are let be.
+pub fn check(run: &RunOpts, keyspace: Keyspace, chars: &[char], lengths: RangeInclusive<usize>, range: Option<&Range<u128>>, filtered: bool) -> Result<()> {
+    let Some(free) = FREE.get().copied().flatten() else { return Ok(()) };
+    let elsewhere = run.output_sqlite.is_some() || run.output_tcp.is_some() || run.output_udp.is_some() || run.format != OutputFormat::Text;
+    if filtered || elsewhere || run.filter_cmd.is_some() || run.dedup {
+        return Ok(());
+    }
+    let Some(needed) = projected(run, keyspace, chars, lengths, range) else { return Ok(()) };
+    if needed <= free as u128 {
+        return Ok(());
+    }
+    let message = format!("Results would take about {}B, but only {}B is free where they are written", si(needed as f64), si(free as f64));
+    match run.force {
+        true => {
+            errln!("{}", format!("⚠  {}; the output will be cut short", message).yellow());
+            Ok(())
+        }
+        false => Err(format!("{}; pass --force to start anyway", message).into()),
+    }
+}
+
+/// Bytes the run's results take as printed, `None` when too many to count.
+fn projected(run: &RunOpts, keyspace: Keyspace, chars: &[char], lengths: RangeInclusive<usize>, range: Option<&Range<u128>>) -> Option<u128> {
+    let letters: String = chars.iter().collect();
+    let mut size = Size::new(SizeFormat::Text, &letters, run.join.as_deref().unwrap_or(""));
+    if !run.quiet {
+        size.framing += "  ".len() as u128;
+    }
+    let n = chars.len();
+    let mut range = match range {
+        Some(range) => range.clone(),
+        None => 0..keyspace.total(n, lengths.clone())?,
+    };
+    if let Some(first) = run.first {
+        let first = first as u128;
+        range = match run.reverse {
+            true => range.end.saturating_sub(first).max(range.start)..range.end,
+            false => range.start..range.end.min(range.start.saturating_add(first)),
+        };
+    }
+    let (mut offset, mut bytes) = (0u128, 0u128);
+    for k in lengths {
+        let count = keyspace.size(n, k)?;
+        let overlap = range.end.min(offset + count).saturating_sub(range.start.max(offset));
+        bytes = bytes.checked_add(size.of(overlap, k)?)?;
+        offset = offset.checked_add(count)?;
+    }
+    Some(bytes)
+}
+
+/// What results of each length take up once written.
+pub struct Size {
+    /// Bytes of every letter summed, as written in the format.
+    letters: u128,
+    n: u128,
+    /// Bytes between two letters.
+    join: u128,
+    /// Bytes around a result: quotes, braces, the newline.
+    framing: u128,
+    /// Bytes of the line written once, before any result.
+    pub header: u128,
+}
+
+impl Size {
+    pub fn new(format: SizeFormat, letters: &str, join: &str) -> Self {
+        // Bytes of `s` inside a result; CSV doubles quotes, JSON escapes.
+        let escaped = |s: &str| match format {
+            SizeFormat::Text => s.len(),
+            SizeFormat::Jsonl => json_string(s).len() - 2,
+            SizeFormat::Csv => s.len() + s.matches('"').count(),
+        } as u128;
+        // Quoting one CSV field means quoting all of them, as an upper bound.
+        let quoted = format == SizeFormat::Csv && letters.chars().chain(join.chars()).any(|c| matches!(c, ',' | '"' | '\n' | '\r'));
+        let (framing, header) = match format {
+            SizeFormat::Text => (1, 0),
+            SizeFormat::Jsonl => (r#"{"value":""}"#.len() as u128 + 1, 0),
+            SizeFormat::Csv => (1 + if quoted { 2 } else { 0 }, "value\n".len() as u128),
+        };
+        let letters_bytes = letters.chars().map(|c| escaped(c.encode_utf8(&mut [0; 4]))).sum();
+        Size { letters: letters_bytes, n: letters.chars().count() as u128, join: escaped(join), framing, header }
+    }
+
+    /// Bytes taken by `count` results of length `k`. Each letter fills an
+    /// equal share of the positions across all arrangements or selections,
+    /// so the letters are counted at their average size, exactly unless
+    /// counting necklaces.
+    pub fn of(&self, count: u128, k: usize) -> Option<u128> {
+        let k = k as u128;
+        let letters = match self.n {
+            0 => 0,
+            n => count.checked_mul(k)?.checked_mul(self.letters)? / n,
+        };
+        let per_result = k.saturating_sub(1).checked_mul(self.join)?.checked_add(self.framing)?;
+        letters.checked_add(count.checked_mul(per_result)?)
+    }
+}
+
@@ -0,0 +1,94 @@
+//! `--output-sqlite`: results inserted straight into a SQLite database.
+//!
+//! Rows are fed as SQL to the `sqlite3` shell rather than through a linked
+//! library, committed every [`BATCH`] rows so large runs don't hold one huge
+//! transaction open. Each row records the result, its length in characters,
+//! the pattern that matched it and its English-likeness score.
+
+use crate::Result;
+use isaw_core::ngram::Model;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+/// Rows per transaction.
+const BATCH: usize = 10_000;
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS results (value TEXT NOT NULL, length INTEGER NOT NULL, matched_pattern TEXT, score REAL);";
+
+pub struct Sqlite {
+    child: Child,
+    input: BufWriter<ChildStdin>,
+    /// Rows inserted since the last commit.
+    pending: usize,
+    model: Model,
+}
+
+impl Sqlite {
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut child = Command::new("sqlite3")
+            .args(["-batch", "-bail"])
+            .arg(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::NotFound => "--output-sqlite needs the sqlite3 shell on the PATH".to_string(),
+                _ => format!("Cannot run sqlite3: {}", e),
+            })?;
+        let input = BufWriter::new(child.stdin.take().expect("piped stdin"));
+        let mut sqlite = Sqlite { child, input, pending: 0, model: Model::english() };
+        writeln!(sqlite.input, "{}\nBEGIN;", SCHEMA).map_err(|e| sqlite.failure(e))?;
+        Ok(sqlite)
+    }
+
+    /// Adds one result; `pattern` is what matched it, if anything did.
+    pub fn insert(&mut self, value: &str, pattern: Option<&str>) -> io::Result<()> {
+        let score = self.model.average(value).map_or("NULL".to_string(), |score| format!("{:.4}", score));
+        let written = writeln!(self.input, "INSERT INTO results VALUES ({}, {}, {}, {});", quote(value), value.chars().count(), pattern.map_or("NULL".to_string(), quote), score);
+        written.map_err(|e| self.failure(e))?;
+        self.pending += 1;
+        if self.pending == BATCH {
+            writeln!(self.input, "COMMIT;\nBEGIN;").map_err(|e| self.failure(e))?;
+            self.pending = 0;
+        }
+        Ok(())
+    }
+
+    /// Commits the last batch and waits for sqlite3 to finish writing.
+    pub fn finish(mut self) -> io::Result<()> {
+        writeln!(self.input, "COMMIT;").map_err(|e| self.failure(e))?;
+        self.input.flush().map_err(|e| self.failure(e))?;
+        let Sqlite { mut child, input, .. } = self;
+        drop(input);
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(io::Error::other(format!("sqlite3 failed: {}", stderr(&mut child))));
+        }
+        Ok(())
+    }
+
+    /// A write to sqlite3 failed, most likely because it exited on an
+    /// error; report that error rather than the broken pipe.
+    fn failure(&mut self, error: io::Error) -> io::Error {
+        if error.kind() != io::ErrorKind::BrokenPipe {
+            return error;
+        }
+        let _ = self.child.wait();
+        io::Error::other(format!("sqlite3 failed: {}", stderr(&mut self.child)))
+    }
+}
+
+fn stderr(child: &mut Child) -> String {
+    let mut message = String::new();
+    if let Some(stderr) = &mut child.stderr {
+        let _ = stderr.read_to_string(&mut message);
+    }
+    message.trim().to_string()
+}
+
+/// A SQL string literal.
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
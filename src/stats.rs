@@ -0,0 +1,74 @@
+//! `--stats`: timing, throughput and memory for a finished run, written to
+//! stderr so the results on stdout stay untouched.
+
+use crate::output::si;
+use clap::ValueEnum;
+use colored::*;
+use std::time::Instant;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum StatsFormat {
+    /// A short human-readable summary
+    Text,
+    /// One JSON object per run
+    Json,
+}
+
+pub struct Stats {
+    format: StatsFormat,
+    started: Instant,
+}
+
+impl Stats {
+    pub fn start(format: StatsFormat) -> Self {
+        Stats { format, started: Instant::now() }
+    }
+
+    /// Reports `generated` candidates, of which `matched` passed the filter
+    /// (`None` when nothing was filtered).
+    pub fn report(&self, generated: u64, matched: Option<u64>) {
+        let secs = self.started.elapsed().as_secs_f64();
+        let rate = generated as f64 / secs.max(1e-9);
+        let share = matched.map(|m| m as f64 / generated.max(1) as f64);
+        let peak = peak_rss();
+
+        match self.format {
+            StatsFormat::Text => {
+                errln!("{}", "📈 Stats".cyan().bold());
+                errln!("  Elapsed:     {:.3}s", secs);
+                errln!("  Candidates:  {} ({}/sec)", generated, si(rate));
+                if let (Some(matched), Some(share)) = (matched, share) {
+                    errln!("  Matched:     {} ({:.4}%)", matched, share * 100.0);
+                }
+                match peak {
+                    Some(bytes) => errln!("  Peak memory: {:.1} MiB", bytes as f64 / (1024.0 * 1024.0)),
+                    None => errln!("  Peak memory: unavailable"),
+                }
+            }
+            StatsFormat::Json => {
+                let json = |v: Option<String>| v.unwrap_or_else(|| "null".to_string());
+                errln!(
+                    "{{\"elapsed_secs\":{:.6},\"candidates\":{},\"candidates_per_sec\":{:.1},\"matched\":{},\"match_rate\":{},\"peak_rss_bytes\":{}}}",
+                    secs,
+                    generated,
+                    rate,
+                    json(matched.map(|m| m.to_string())),
+                    json(share.map(|s| format!("{:.6}", s))),
+                    json(peak.map(|b| b.to_string())),
+                );
+            }
+        }
+    }
+}
+
+/// Peak resident set size of this process, where the OS reports it.
+fn peak_rss() -> Option<u64> {
+    if cfg!(target_os = "linux") {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+        let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+        Some(kib * 1024)
+    } else {
+        None
+    }
+}
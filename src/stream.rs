@@ -0,0 +1,45 @@
+//! `--output-tcp` and `--output-udp`: result lines streamed to a remote
+//! collector as they are found, for headless boxes without shared storage.
+//!
+//! TCP sends each line as soon as it is printed; UDP sends one datagram
+//! per line and, being UDP, gives no guarantee any of them arrive.
+
+use crate::Result;
+use std::io::{self, LineWriter, Write};
+use std::net::{TcpStream, ToSocketAddrs, UdpSocket};
+
+pub enum Stream {
+    Tcp(LineWriter<TcpStream>),
+    Udp(UdpSocket),
+}
+
+impl Stream {
+    pub fn tcp(address: &str) -> Result<Self> {
+        let stream = TcpStream::connect(address).map_err(|e| format!("Cannot reach collector at {}: {}", address, e))?;
+        Ok(Stream::Tcp(LineWriter::new(stream)))
+    }
+
+    pub fn udp(address: &str) -> Result<Self> {
+        let fail = |e: io::Error| format!("Cannot reach collector at {}: {}", address, e);
+        let target = address.to_socket_addrs().map_err(fail)?.next().ok_or_else(|| format!("Cannot resolve collector address {}", address))?;
+        // Bind the same address family as the collector's.
+        let any = if target.is_ipv6() { "[::]:0" } else { "0.0.0.0:0" };
+        let socket = UdpSocket::bind(any).map_err(fail)?;
+        socket.connect(target).map_err(fail)?;
+        Ok(Stream::Udp(socket))
+    }
+
+    pub fn send(&mut self, line: &str) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => writeln!(stream, "{}", line),
+            Stream::Udp(socket) => socket.send(format!("{}\n", line).as_bytes()).map(drop),
+        }
+    }
+
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(mut stream) => stream.flush(),
+            Stream::Udp(_) => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! `--summary json`: one JSON object on stderr describing a finished run —
+//! what was asked for, what came out and whether a limit cut it short — so
+//! wrappers don't have to scrape the "✨" line.
+
+use crate::cli::RunOpts;
+use crate::report::json_string;
+use clap::ValueEnum;
+use std::time::Instant;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum SummaryFormat {
+    /// One JSON object per run
+    Json,
+}
+
+/// Why a run ended before its keyspace did.
+#[derive(Clone, Copy)]
+pub enum Stop {
+    /// `--first` (or `--fail-fast`) had its results.
+    First,
+    /// Ctrl-C.
+    Interrupted,
+}
+
+pub struct Summary {
+    started: Instant,
+    command: &'static str,
+    /// Name and JSON value of each parameter, in print order.
+    parameters: Vec<(&'static str, String)>,
+}
+
+impl Summary {
+    pub fn start(run: &RunOpts) -> Self {
+        let mut parameters = Vec::new();
+        let paths: Vec<String> = run.dictionary.iter().map(|path| path.display().to_string()).collect();
+        if !paths.is_empty() {
+            parameters.push(("dictionary", list(&paths)));
+        }
+        if !run.lang.is_empty() {
+            parameters.push(("lang", list(&run.lang)));
+        }
+        if let Some(slice) = run.slice {
+            parameters.push(("slice", json_string(&slice.to_string())));
+        }
+        if let Some(resume) = run.resume {
+            parameters.push(("resume", resume.to_string()));
+        }
+        if let Some(first) = run.first {
+            parameters.push(("first", first.to_string()));
+        }
+        for (name, set) in [("reverse", run.reverse), ("any_case", run.any_case), ("dedup", run.dedup), ("sort", run.sort)] {
+            if set {
+                parameters.push((name, "true".to_string()));
+            }
+        }
+        Summary { started: Instant::now(), command: "", parameters }
+    }
+
+    /// Names the subcommand and puts its own parameters ahead of the shared ones.
+    pub fn describe(&mut self, command: &'static str, parameters: Vec<(&'static str, String)>) {
+        self.command = command;
+        self.parameters.splice(0..0, parameters);
+    }
+
+    /// Prints the summary for `generated` candidates, `matched` of which
+    /// passed the filter when there was one, and `printed` results.
+    pub fn print(&self, generated: u64, matched: Option<u64>, printed: u64, stop: Option<Stop>, resume: Option<u128>) {
+        let parameters: Vec<String> = self.parameters.iter().map(|(name, value)| format!("{}:{}", json_string(name), value)).collect();
+        let stopped = match stop {
+            Some(Stop::First) => "\"first\"",
+            Some(Stop::Interrupted) => "\"interrupted\"",
+            None => "null",
+        };
+        errln!(
+            "{{\"command\":{},\"parameters\":{{{}}},\"candidates\":{},\"matched\":{},\"printed\":{},\"elapsed_secs\":{:.6},\"stopped\":{},\"resume\":{}}}",
+            json_string(self.command),
+            parameters.join(","),
+            generated,
+            matched.map_or("null".to_string(), |m| m.to_string()),
+            printed,
+            self.started.elapsed().as_secs_f64(),
+            stopped,
+            resume.map_or("null".to_string(), |r| r.to_string()),
+        );
+    }
+}
+
+/// A JSON array of strings.
+pub fn list(items: &[String]) -> String {
+    let items: Vec<String> = items.iter().map(|item| json_string(item)).collect();
+    format!("[{}]", items.join(","))
+}
@@ -0,0 +1,377 @@
+//! `isaw daemon --websocket`: queries streamed to web pages as they run.
+//!
+//! isaw has no server mode apart from the daemon, so this is where the
+//! WebSocket endpoint lives; `isaw serve` is an alias for `isaw daemon`.
+//!
+//! A page opens `ws://HOST:PORT/` and sends one text message holding a
+//! generating command, one argument per line (`words\nhello\n-m\n3`). The
+//! command runs with `--quiet` and each result is sent the moment it is
+//! printed; while the query runs a progress frame goes out every
+//! [`PROGRESS`], so a page can tell a slow query from a dead one. Frames
+//! are JSON text messages, the last one `done` or `error`:
+//!
+//! ```text
+//! {"type":"match","value":"hello"}
+//! {"type":"progress","matches":120,"elapsed":3.00}
+//! {"type":"done","matches":120,"elapsed":3.41}
+//! {"type":"error","message":"..."}
+//! ```
+//!
+//! Every connection gets a thread of its own. A page that closes its
+//! socket cancels the query with the next result sent.
+
+use crate::distributed::parse_job;
+use crate::cli::Cli;
+use crate::output::OutputFormat;
+use crate::report::json_string;
+use crate::{metrics, run, NotFound, Result};
+use colored::*;
+use isaw_core::{alphabet, sha1};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Joined to the client's key to prove the server speaks WebSocket (RFC 6455).
+const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// How often a running query reports progress.
+const PROGRESS: Duration = Duration::from_secs(1);
+
+/// Largest command message accepted.
+const MAX_MESSAGE: usize = 1 << 20;
+
+// Frame opcodes.
+const CONTINUATION: u8 = 0x0;
+const TEXT: u8 = 0x1;
+const CLOSE: u8 = 0x8;
+const PING: u8 = 0x9;
+const PONG: u8 = 0xa;
+
+/// What a streamed query reports, in order: matches and progress while it
+/// runs, then `Done` if it finishes.
+pub enum Event<'a> {
+    Match(&'a str),
+    Progress { matches: u64, elapsed: Duration },
+    Done { matches: u64, elapsed: Duration },
+}
+
+/// Passes one event on to the client.
+pub type Send<'a> = &'a (dyn Fn(Event) -> io::Result<()> + Sync);
+
+/// Accepts WebSocket connections forever, one query each.
+pub fn serve(out: &mut dyn Write, address: &str) -> Result<()> {
+    let listener = TcpListener::bind(address).map_err(|e| format!("Cannot listen on {}: {}", address, e))?;
+
+    writeln!(out, "{}", format!("🛰  isaw daemon serving WebSocket queries on ws://{}/", listener.local_addr()?).cyan().bold())?;
+    writeln!(out, "{}", "─".repeat(50).dimmed())?;
+    out.flush()?;
+    // Results are bare words; nothing reading them wants terminal colors.
+    colored::control::set_override(false);
+
+    for stream in listener.incoming().flatten() {
+        thread::spawn(move || {
+            // Pages going away mid-query is routine, not worth reporting.
+            if let Err(e) = handle(stream).or_else(|e| if e.kind() == io::ErrorKind::BrokenPipe { Ok(()) } else { Err(e) }) {
+                eprintln!("Connection failed: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+fn handle(mut stream: TcpStream) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let Some(key) = handshake(&mut reader)? else {
+        return stream.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Type: text/plain\r\nConnection: close\r\n\r\nExpected a WebSocket upgrade\n");
+    };
+    write!(stream, "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n", accept(&key))?;
+    let Some(message) = read_message(&mut reader, &mut stream)? else { return Ok(()) };
+
+    let socket = Mutex::new(stream);
+    let text = |text: &str| write_frame(&mut *socket.lock().unwrap_or_else(|e| e.into_inner()), TEXT, text.as_bytes());
+    let send = |event: Event| text(&json(event));
+    let job: Vec<String> = message.lines().map(str::to_string).collect();
+    let started = Instant::now();
+    let outcome = prepare(&job).and_then(|cli| query(cli, &send));
+    metrics::query(outcome.is_ok(), started.elapsed());
+    if let Err(e) = outcome {
+        text(&format!("{{\"type\":\"error\",\"message\":{}}}", json_string(&e.to_string())))?;
+    }
+    write_frame(&mut *socket.lock().unwrap_or_else(|e| e.into_inner()), CLOSE, &1000u16.to_be_bytes())
+}
+
+/// The frame for one event.
+fn json(event: Event) -> String {
+    let progress = |kind: &str, matches: u64, elapsed: Duration| format!("{{\"type\":\"{}\",\"matches\":{},\"elapsed\":{:.2}}}", kind, matches, elapsed.as_secs_f64());
+    match event {
+        Event::Match(value) => format!("{{\"type\":\"match\",\"value\":{}}}", json_string(value)),
+        Event::Progress { matches, elapsed } => progress("progress", matches, elapsed),
+        Event::Done { matches, elapsed } => progress("done", matches, elapsed),
+    }
+}
+
+/// Parses `job`, the arguments after `isaw`, as a generating command that
+/// can be streamed, with `--quiet` set.
+pub fn prepare(job: &[String]) -> Result<Cli> {
+    let mut cli = parse_job(job)?;
+    match cli.command.run_opts_mut() {
+        Some(run) if run.format == OutputFormat::Parquet => return Err("Parquet output can't be streamed".into()),
+        Some(run) => run.quiet = true,
+        None => return Err("Only generating subcommands can be streamed".into()),
+    }
+    Ok(cli)
+}
+
+/// Runs a [`prepare`]d command, streaming its results through `send`.
+/// `isaw daemon --grpc` serves the same queries.
+pub fn query(cli: Cli, send: Send) -> Result<()> {
+    let started = Instant::now();
+    let count = AtomicU64::new(0);
+    let mut matches = Matches { send, count: &count, line: Vec::new() };
+    let outcome = thread::scope(|scope| {
+        let (stop, stopped) = mpsc::channel::<()>();
+        let count = &count;
+        scope.spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(PROGRESS) {
+                if send(Event::Progress { matches: count.load(Ordering::Relaxed), elapsed: started.elapsed() }).is_err() {
+                    break;
+                }
+            }
+        });
+        let outcome = run(cli.command, &mut matches);
+        drop(stop);
+        outcome
+    });
+    match outcome {
+        // --fail-fast without a match is an answer, not a failure.
+        Err(e) if !e.is::<NotFound>() => return Err(e),
+        _ => {}
+    }
+    send(Event::Done { matches: count.load(Ordering::Relaxed), elapsed: started.elapsed() })?;
+    Ok(())
+}
+
+/// Sends every line the query prints as a match.
+struct Matches<'a> {
+    send: Send<'a>,
+    count: &'a AtomicU64,
+    /// The unfinished line.
+    line: Vec<u8>,
+}
+
+impl Write for Matches<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.line.extend_from_slice(buf);
+        while let Some(end) = self.line.iter().position(|&b| b == b'\n') {
+            let rest = self.line.split_off(end + 1);
+            let line = std::mem::replace(&mut self.line, rest);
+            let value = String::from_utf8_lossy(&line[..end]);
+            (self.send)(Event::Match(&value))?;
+            self.count.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Reads the HTTP upgrade request; the client's `Sec-WebSocket-Key`, or
+/// `None` when it isn't a WebSocket handshake.
+fn handshake(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut upgrade = false;
+    let mut key = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed during the handshake"));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            return Ok(key.filter(|_| upgrade));
+        }
+        let Some((name, value)) = line.split_once(':') else { continue };
+        match name.trim().to_ascii_lowercase().as_str() {
+            "upgrade" => upgrade = value.trim().eq_ignore_ascii_case("websocket"),
+            "sec-websocket-key" => key = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+}
+
+/// The `Sec-WebSocket-Accept` answer to a client's key.
+fn accept(key: &str) -> String {
+    base64(&sha1::digest(format!("{}{}", key, GUID).as_bytes()))
+}
+
+/// The page's first text message, answering pings on the way; `None` if
+/// it closes the socket first.
+fn read_message(reader: &mut impl Read, stream: &mut impl Write) -> io::Result<Option<String>> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+    let mut message = Vec::new();
+    loop {
+        let mut head = [0u8; 2];
+        reader.read_exact(&mut head)?;
+        let (fin, opcode, masked) = (head[0] & 0x80 != 0, head[0] & 0x0f, head[1] & 0x80 != 0);
+        let len = match head[1] & 0x7f {
+            126 => {
+                let mut len = [0u8; 2];
+                reader.read_exact(&mut len)?;
+                u16::from_be_bytes(len) as u64
+            }
+            127 => {
+                let mut len = [0u8; 8];
+                reader.read_exact(&mut len)?;
+                u64::from_be_bytes(len)
+            }
+            len => len as u64,
+        };
+        if message.len() as u64 + len > MAX_MESSAGE as u64 {
+            return Err(invalid("message too large"));
+        }
+        // Clients always mask; unmasked frames XOR with zeros.
+        let mut mask = [0u8; 4];
+        if masked {
+            reader.read_exact(&mut mask)?;
+        }
+        let mut payload = vec![0u8; len as usize];
+        reader.read_exact(&mut payload)?;
+        for (i, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[i % 4];
+        }
+        match opcode {
+            CLOSE => return Ok(None),
+            PING => write_frame(stream, PONG, &payload)?,
+            PONG => {}
+            TEXT | CONTINUATION => {
+                message.extend_from_slice(&payload);
+                if fin {
+                    return String::from_utf8(message).map(Some).map_err(|_| invalid("message is not UTF-8"));
+                }
+            }
+            _ => return Err(invalid("expected a text message")),
+        }
+    }
+}
+
+/// Writes one unfragmented, unmasked frame, as servers send them.
+fn write_frame(stream: &mut impl Write, opcode: u8, payload: &[u8]) -> io::Result<()> {
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(0x80 | opcode);
+    match payload.len() {
+        len @ 0..=125 => frame.push(len as u8),
+        len @ 126..=0xffff => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    stream.write_all(&frame)
+}
+
+/// Standard padded base64.
+fn base64(bytes: &[u8]) -> String {
+    let digits = alphabet::preset("base64").expect("a built-in alphabet").as_bytes();
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &byte)| n | (byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            encoded.push(match i <= chunk.len() {
+                true => digits[(n >> (18 - 6 * i) & 63) as usize] as char,
+                false => '=',
+            });
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// A client frame: always masked, as RFC 6455 requires.
+    fn client_frame(fin: bool, opcode: u8, payload: &[u8]) -> Vec<u8> {
+        let mask = [0x37, 0xfa, 0x21, 0x3d];
+        let mut frame = vec![(fin as u8) << 7 | opcode];
+        match payload.len() {
+            len @ 0..=125 => frame.push(0x80 | len as u8),
+            len => {
+                frame.push(0x80 | 126);
+                frame.extend_from_slice(&(len as u16).to_be_bytes());
+            }
+        }
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(i, byte)| byte ^ mask[i % 4]));
+        frame
+    }
+
+    #[test]
+    fn handshake_answers_the_rfc_example() {
+        let request = "GET /chat HTTP/1.1\r\nHost: server.example.com\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n";
+        let key = handshake(&mut Cursor::new(request)).unwrap().expect("an upgrade");
+        assert_eq!(key, "dGhlIHNhbXBsZSBub25jZQ==");
+        assert_eq!(accept(&key), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    #[test]
+    fn handshake_rejects_plain_http() {
+        let request = "GET / HTTP/1.1\r\nHost: localhost\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n";
+        assert_eq!(handshake(&mut Cursor::new(request)).unwrap(), None);
+        assert!(handshake(&mut Cursor::new("GET / HTTP/1.1\r\n")).is_err());
+    }
+
+    #[test]
+    fn reads_masked_fragmented_messages_and_answers_pings() {
+        let long = "x".repeat(300);
+        let mut input = client_frame(false, TEXT, b"words\n");
+        input.extend(client_frame(true, PING, b"hi"));
+        input.extend(client_frame(true, CONTINUATION, long.as_bytes()));
+        let mut replies = Vec::new();
+        let message = read_message(&mut Cursor::new(input), &mut replies).unwrap();
+        assert_eq!(message, Some(format!("words\n{}", long)));
+        assert_eq!(replies, [0x80 | PONG, 2, b'h', b'i']);
+
+        let mut replies = Vec::new();
+        assert_eq!(read_message(&mut Cursor::new(client_frame(true, CLOSE, &[])), &mut replies).unwrap(), None);
+        assert!(read_message(&mut Cursor::new(client_frame(true, 0x2, b"binary")), &mut replies).is_err());
+    }
+
+    #[test]
+    fn writes_server_frames_unmasked() {
+        let mut frame = Vec::new();
+        write_frame(&mut frame, TEXT, b"Hello").unwrap();
+        assert_eq!(frame, [0x81, 0x05, b'H', b'e', b'l', b'l', b'o']);
+
+        let payload = vec![b'a'; 256];
+        let mut frame = Vec::new();
+        write_frame(&mut frame, TEXT, &payload).unwrap();
+        assert_eq!(frame[..4], [0x81, 126, 0x01, 0x00]);
+        assert_eq!(frame[4..], payload[..]);
+
+        let payload = vec![b'a'; 70_000];
+        let mut frame = Vec::new();
+        write_frame(&mut frame, TEXT, &payload).unwrap();
+        assert_eq!(frame[..10], [0x81, 127, 0, 0, 0, 0, 0, 0x01, 0x11, 0x70]);
+        assert_eq!(frame.len(), 10 + payload.len());
+    }
+
+    #[test]
+    fn base64_pads() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+}
@@ -0,0 +1,241 @@
+//! Loading `--dictionary` files.
+//!
+//! Files are memory-mapped rather than read, so even wordlists of hundreds
+//! of MB open instantly and only the pages lookups touch become resident.
+//! Opened dictionaries are cached per process, which lets a long-running
+//! `isaw daemon` index each file once instead of on every request; so are
+//! the last few tries built from them, for repeated queries over the same
+//! letters or grid.
+//!
+//! `--lang` picks the lists managed by `isaw dict` under that name, plus any
+//! configured in `$XDG_CONFIG_HOME/isaw/dictionaries` (or
+//! `~/.config/isaw/dictionaries`), one `<lang> <path>` pair per line.
+
+use crate::{dict, log, Result};
+use isaw_core::dictionary::Dictionary;
+use isaw_core::trie::Trie;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Size and modification time, to notice when a cached file changed.
+type Key = (u64, Option<SystemTime>);
+
+type Cache = HashMap<PathBuf, (Key, Arc<Dictionary>)>;
+
+static CACHE: Mutex<Option<Cache>> = Mutex::new(None);
+
+/// Tries kept for reuse; each can hold a whole wordlist.
+const TRIES: usize = 4;
+
+/// Which dictionaries a trie was built from and the filter its words passed.
+type TrieKey = (Vec<Arc<Dictionary>>, String);
+
+/// Most recently used last.
+static TRIE_CACHE: Mutex<Vec<(TrieKey, Arc<Trie>)>> = Mutex::new(Vec::new());
+
+/// The `--dictionary` files and `--lang` wordlists, each with the name
+/// results are tagged with: the language, or the file name without extension.
+pub fn resolve(paths: &[PathBuf], langs: &[String]) -> Result<Vec<(String, Arc<Dictionary>)>> {
+    let mut dictionaries = Vec::new();
+    for path in paths {
+        // A bare name that isn't a file refers to a managed list.
+        if let Some(name) = path.to_str().filter(|_| !path.exists())
+            && let Some(managed) = dict::managed(name)
+        {
+            dictionaries.push((name.to_string(), open_managed(name, &managed)?));
+            continue;
+        }
+        let name = path.file_stem().unwrap_or(path.as_os_str()).to_string_lossy().into_owned();
+        dictionaries.push((name, open(path)?));
+    }
+    if !langs.is_empty() {
+        let configured = languages()?;
+        for lang in langs {
+            let paths: Vec<PathBuf> = configured.iter().filter(|(l, _)| l == lang).map(|(_, path)| path.clone()).collect();
+            let managed = dict::managed(lang);
+            if paths.is_empty() && managed.is_none() {
+                return Err(format!("No wordlist configured for --lang {} (run `isaw dict add {} FILE` or list it in {})", lang, lang, config_path().display()).into());
+            }
+            for path in &paths {
+                dictionaries.push((lang.clone(), open(path)?));
+            }
+            if let Some(managed) = managed {
+                dictionaries.push((lang.clone(), open_managed(lang, &managed)?));
+            }
+        }
+    }
+    Ok(dictionaries)
+}
+
+fn config_path() -> PathBuf {
+    let base = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME").unwrap_or_default()).join(".config"),
+    };
+    base.join("isaw").join("dictionaries")
+}
+
+/// The `(lang, path)` pairs from the config file; none if it doesn't exist.
+fn languages() -> Result<Vec<(String, PathBuf)>> {
+    let path = config_path();
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Cannot read {}: {}", path.display(), e).into()),
+    };
+    let mut pairs = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (lang, file) = line.split_once(char::is_whitespace).ok_or_else(|| format!("{}:{}: expected `<lang> <path>`", path.display(), n + 1))?;
+        pairs.push((lang.to_string(), PathBuf::from(file.trim())));
+    }
+    Ok(pairs)
+}
+
+/// Opens `path`, reusing the cached copy while the file is unchanged.
+pub fn open(path: &Path) -> Result<Arc<Dictionary>> {
+    open_checked(path, |_| Ok(()))
+}
+
+/// Opens the `isaw dict` list `name`, refusing it if it no longer hashes
+/// to what the index recorded. Checked once per change, like the cache.
+fn open_managed(name: &str, path: &Path) -> Result<Arc<Dictionary>> {
+    let expected = dict::recorded_sha256(name)?;
+    open_checked(path, |bytes| expected.as_deref().map_or(Ok(()), |expected| dict::check(name, bytes, expected)))
+}
+
+/// [`open`], running `check` on the bytes whenever they are freshly mapped.
+fn open_checked(path: &Path, check: impl FnOnce(&[u8]) -> Result<()>) -> Result<Arc<Dictionary>> {
+    let fail = |e: std::io::Error| format!("Cannot open dictionary {}: {}", path.display(), e);
+    let file = File::open(path).map_err(fail)?;
+    let meta = file.metadata().map_err(fail)?;
+    let key = (meta.len(), meta.modified().ok());
+
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    let cache = cache.get_or_insert_with(HashMap::new);
+    if let Some((cached, dictionary)) = cache.get(path)
+        && *cached == key
+    {
+        log::event(log::Level::Trace, "dictionary cached", &[("path", &path.display())]);
+        return Ok(Arc::clone(dictionary));
+    }
+
+    log::event(log::Level::Trace, "mapping dictionary", &[("path", &path.display()), ("bytes", &meta.len())]);
+    let bytes = map(&file, meta.len()).map_err(fail)?;
+    check(bytes.as_ref())?;
+    let dictionary = Arc::new(Dictionary::new(bytes).map_err(|e| format!("Cannot open dictionary {}: {}", path.display(), e))?);
+    cache.insert(path.to_path_buf(), (key, Arc::clone(&dictionary)));
+    Ok(dictionary)
+}
+
+/// The trie of the words in `dictionaries` that pass `keep`, reused while
+/// the dictionaries are unchanged and `filter` (which must describe `keep`
+/// completely) is the same.
+pub fn trie(dictionaries: &[(String, Arc<Dictionary>)], filter: String, keep: impl Fn(&str) -> bool) -> Arc<Trie> {
+    let key: TrieKey = (dictionaries.iter().map(|(_, dictionary)| Arc::clone(dictionary)).collect(), filter);
+    let same = |other: &TrieKey| other.1 == key.1 && other.0.len() == key.0.len() && other.0.iter().zip(&key.0).all(|(a, b)| Arc::ptr_eq(a, b));
+    let mut cache = TRIE_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(at) = cache.iter().position(|(other, _)| same(other)) {
+        log::event(log::Level::Trace, "trie cached", &[("filter", &key.1)]);
+        let entry = cache.remove(at);
+        let trie = Arc::clone(&entry.1);
+        cache.push(entry);
+        return trie;
+    }
+    let trie = log::timed("trie build", || {
+        let mut trie = Trie::default();
+        for (_, dictionary) in dictionaries {
+            for word in dictionary.words() {
+                if keep(word) {
+                    trie.insert(word);
+                }
+            }
+        }
+        Arc::new(trie)
+    });
+    if cache.len() == TRIES {
+        cache.remove(0);
+    }
+    cache.push((key, Arc::clone(&trie)));
+    trie
+}
+
+#[cfg(unix)]
+fn map(file: &File, len: u64) -> std::io::Result<Mmap> {
+    Mmap::new(file, len as usize)
+}
+
+#[cfg(not(unix))]
+fn map(file: &File, len: u64) -> std::io::Result<Vec<u8>> {
+    use std::io::Read;
+    let mut bytes = Vec::with_capacity(len as usize);
+    (&*file).read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// A read-only private mapping of a whole file.
+#[cfg(unix)]
+struct Mmap {
+    ptr: *mut std::ffi::c_void,
+    len: usize,
+}
+
+// The mapping is read-only and owned exclusively by this value.
+#[cfg(unix)]
+unsafe impl Send for Mmap {}
+#[cfg(unix)]
+unsafe impl Sync for Mmap {}
+
+#[cfg(unix)]
+mod sys {
+    use std::ffi::{c_int, c_void};
+
+    pub const PROT_READ: c_int = 1;
+    pub const MAP_PRIVATE: c_int = 2;
+
+    unsafe extern "C" {
+        pub fn mmap(addr: *mut c_void, len: usize, prot: c_int, flags: c_int, fd: c_int, offset: i64) -> *mut c_void;
+        pub fn munmap(addr: *mut c_void, len: usize) -> c_int;
+    }
+}
+
+#[cfg(unix)]
+impl Mmap {
+    fn new(file: &File, len: usize) -> std::io::Result<Self> {
+        use std::os::unix::io::AsRawFd;
+        if len == 0 {
+            // Zero-length mappings are an error; nothing needs mapping anyway.
+            return Ok(Mmap { ptr: std::ptr::null_mut(), len });
+        }
+        let ptr = unsafe { sys::mmap(std::ptr::null_mut(), len, sys::PROT_READ, sys::MAP_PRIVATE, file.as_raw_fd(), 0) };
+        if ptr as isize == -1 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(Mmap { ptr, len })
+    }
+}
+
+#[cfg(unix)]
+impl AsRef<[u8]> for Mmap {
+    fn as_ref(&self) -> &[u8] {
+        if self.len == 0 {
+            return &[];
+        }
+        unsafe { std::slice::from_raw_parts(self.ptr as *const u8, self.len) }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        if self.len != 0 {
+            unsafe { sys::munmap(self.ptr, self.len) };
+        }
+    }
+}
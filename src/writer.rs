@@ -0,0 +1,91 @@
+//! Output on a thread of its own, so generating and matching never wait on
+//! a slow terminal, pipe or disk.
+//!
+//! Writes are buffered in a [`Pipe`] and handed to the writer thread through
+//! a bounded channel. While the thread keeps up every finished line goes
+//! over at once, so sparse results show up immediately; once the channel is
+//! full, lines pile up into bigger chunks instead, and past [`MAX_CHUNK`]
+//! the producer blocks until the sink catches up.
+
+use crate::Result;
+use std::io::{self, Write};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+
+/// Chunks in flight between the producer and the writer thread.
+const CAPACITY: usize = 64;
+
+/// Bytes a chunk may grow to before the producer waits for the sink.
+const MAX_CHUNK: usize = 1 << 20;
+
+enum Chunk {
+    Data(Vec<u8>),
+    Flush,
+}
+
+/// The producer's end: a buffered writer feeding the channel.
+struct Pipe {
+    tx: SyncSender<Chunk>,
+    buffer: Vec<u8>,
+}
+
+impl Pipe {
+    fn send(&mut self, chunk: Chunk) -> io::Result<()> {
+        self.tx.send(chunk).map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "output thread stopped"))
+    }
+}
+
+impl Write for Pipe {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= MAX_CHUNK {
+            let chunk = std::mem::take(&mut self.buffer);
+            self.send(Chunk::Data(chunk))?;
+        } else if self.buffer.ends_with(b"\n") {
+            match self.tx.try_send(Chunk::Data(std::mem::take(&mut self.buffer))) {
+                Ok(()) => {}
+                // The writer is behind: keep collecting into this chunk.
+                Err(TrySendError::Full(Chunk::Data(chunk))) => self.buffer = chunk,
+                Err(_) => return Err(io::Error::new(io::ErrorKind::BrokenPipe, "output thread stopped")),
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.buffer.is_empty() {
+            let chunk = std::mem::take(&mut self.buffer);
+            self.send(Chunk::Data(chunk))?;
+        }
+        self.send(Chunk::Flush)
+    }
+}
+
+/// Runs `body` with a writer whose output a separate thread copies to
+/// `sink`. A failure of the sink itself (a closed pipe, a full disk) is
+/// reported in place of whatever `body` made of it.
+pub fn threaded<T>(sink: impl Write + Send, body: impl FnOnce(&mut dyn Write) -> Result<T>) -> Result<T> {
+    let (tx, rx) = mpsc::sync_channel(CAPACITY);
+    std::thread::scope(|scope| {
+        let writer = scope.spawn(move || -> io::Result<()> {
+            let mut sink = sink;
+            for chunk in rx {
+                match chunk {
+                    Chunk::Data(bytes) => sink.write_all(&bytes)?,
+                    Chunk::Flush => sink.flush()?,
+                }
+            }
+            sink.flush()
+        });
+        let mut pipe = Pipe { tx, buffer: Vec::new() };
+        let result = body(&mut pipe).and_then(|value| {
+            pipe.flush()?;
+            Ok(value)
+        });
+        drop(pipe);
+        match writer.join() {
+            Ok(Err(e)) => Err(e.into()),
+            Ok(Ok(())) => result,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    })
+}
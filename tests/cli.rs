@@ -0,0 +1,549 @@
+//! Output and exit codes scripts rely on; the codes are listed in the README.
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn isaw(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_isaw")).args(args).output().expect("isaw runs")
+}
+
+fn code(args: &[&str]) -> i32 {
+    isaw(args).status.code().expect("isaw exits normally")
+}
+
+/// The lines a successful run prints.
+fn lines(args: &[&str]) -> Vec<String> {
+    let output = isaw(args);
+    assert!(output.status.success(), "isaw {:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).lines().map(str::to_string).collect()
+}
+
+/// An empty directory of the test's own.
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("isaw-test-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("scratch directory");
+    dir
+}
+
+#[test]
+fn fail_fast_prints_the_first_match() {
+    let output = isaw(&["permutations", "abc", "-s", "ab", "--fail-fast", "-q"]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ab\n");
+}
+
+#[test]
+fn fail_fast_without_a_match_exits_3() {
+    let output = isaw(&["permutations", "abc", "-s", "zzz", "--fail-fast"]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn quiet_output_is_bare_words() {
+    let output = isaw(&["permutations", "abc", "-q"]);
+    assert!(output.status.success());
+    let words: Vec<String> = String::from_utf8_lossy(&output.stdout).lines().map(str::to_string).collect();
+    assert_eq!(words, ["a", "b", "c", "ab", "ac", "ba", "bc", "ca", "cb", "abc", "acb", "bac", "bca", "cab", "cba"]);
+}
+
+#[test]
+fn each_class_of_invalid_input_has_its_own_exit_code() {
+    assert_eq!(code(&["frobnicate"]), 2);
+    assert_eq!(code(&["permutations", "abc", "--slice", "0/3"]), 2);
+
+    assert_eq!(code(&["count", "abc", "-m", "3", "-x", "2"]), 4);
+    assert_eq!(code(&["permutations", "abc", "-m", "4"]), 4);
+    assert_eq!(code(&["search", "-n", "0", "a"]), 4);
+
+    assert_eq!(code(&["permutations", ""]), 5);
+    assert_eq!(code(&["search", "a", "-l", "", "-n", "2"]), 5);
+
+    assert_eq!(code(&["search", "(", "--regex", "-n", "2"]), 6);
+    assert_eq!(code(&["words", "abc", "--hash", "beef"]), 6);
+
+    assert_eq!(code(&["count", "abcdefghijklmnopqrstuvwxyz0123456789"]), 7);
+    assert_eq!(code(&["search", "a", "-l", "abc", "-n", "30000"]), 7);
+}
+
+#[test]
+fn rejected_runs_print_no_banner() {
+    let output = isaw(&["search", "-n", "0", "a"]);
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Length 0"));
+}
+
+#[test]
+fn rate_must_be_finite_and_not_absurdly_slow() {
+    for rate in ["1e-20", "0", "-1", "inf", "NaN", "fast"] {
+        let output = isaw(&["permutations", "ab", "-q", &format!("--rate={}", rate)]);
+        assert_eq!(output.status.code(), Some(2), "--rate {}", rate);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("--rate"), "--rate {}", rate);
+    }
+    let output = isaw(&["permutations", "ab", "-q", "--rate", "1000"]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).lines().count(), 4);
+}
+
+#[test]
+fn debruijn_takes_a_literal_alphabet() {
+    let output = isaw(&["debruijn", "--alphabet", "01", "--length", "3", "-q"]);
+    assert!(output.status.success());
+    let sequence = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    assert_eq!(sequence, "0001011100");
+    // B(2, 3): all eight binary triples, each exactly once.
+    let mut windows: Vec<&str> = (0..=sequence.len() - 3).map(|i| &sequence[i..i + 3]).collect();
+    windows.sort();
+    windows.dedup();
+    assert_eq!(windows.len(), 8);
+    assert_eq!(code(&["debruijn", "--alphabet", "digits", "-n", "2", "-q"]), 0);
+}
+
+#[test]
+fn debruijn_rejects_unusable_alphabets() {
+    assert_eq!(code(&["debruijn", "--alphabet", "001", "-n", "3"]), 5);
+    assert_eq!(code(&["debruijn", "-l", "01", "-n", "0"]), 4);
+}
+
+#[test]
+fn necklaces_of_repeated_letters_are_listed_and_counted_once() {
+    for symmetry in ["rotation", "rotation+reflection"] {
+        let output = isaw(&["permutations", "aabb", "-m", "4", "--distinct-under", symmetry, "-q"]);
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "aabb\nabab\n");
+        let output = isaw(&["count", "aabb", "-m", "4", "--distinct-under", symmetry]);
+        assert!(String::from_utf8_lossy(&output.stdout).contains("Total: 2"));
+    }
+    assert_eq!(code(&["combinations", "aabb", "-l", "2", "--distinct-under", "rotation"]), 5);
+}
+
+#[test]
+fn caesar_reads_short_text_with_the_built_in_quadgrams() {
+    let output = isaw(&["caesar", "wkh txlfn eurzq ira"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Most likely: ROT 3 → the quick brown fox"));
+}
+
+#[test]
+fn subst_recovers_a_known_plaintext() {
+    let plaintext = "it was the best of times it was the worst of times it was the age of wisdom it was the age of foolishness it was the epoch of belief it was the epoch of incredulity it was the season of light it was the season of darkness it was the spring of hope it was the winter of despair we had everything before us we had nothing before us we were all going direct to heaven we were all going direct the other way";
+    // Enciphered with the key qwertyuiopasdfghjklzxcvbnm.
+    let ciphertext = "oz vql zit wtlz gy zodtl oz vql zit vgklz gy zodtl oz vql zit qut gy volrgd oz vql zit qut gy yggsoliftll oz vql zit thgei gy wtsoty oz vql zit thgei gy ofektrxsozn oz vql zit ltqlgf gy souiz oz vql zit ltqlgf gy rqkaftll oz vql zit lhkofu gy ight oz vql zit vofztk gy rtlhqok vt iqr tctknziofu wtygkt xl vt iqr fgziofu wtygkt xl vt vtkt qss ugofu roktez zg itqctf vt vtkt qss ugofu roktez zit gzitk vqn";
+    let output = isaw(&["subst", ciphertext, "--seed", "1", "--restarts", "20", "--top", "1"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains(&format!("1. {}", plaintext)));
+}
+
+#[test]
+fn vigenere_finds_a_known_key() {
+    // 97 letters of Orwell under the key KEY.
+    let ciphertext = "sx ukw y lvgqlr msjn hyi ml ktpsp yxh rri avsauw uovc cxpsogxk rrmpdicx agxwryr qwmrr lgc gfsr ledxvib srry lgc fpoeqd";
+    let output = isaw(&["vigenere", ciphertext, "-n", "3"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Most likely key: KEY"));
+    assert!(stdout.contains("KEY  it was a bright cold day in april and the clocks were striking thirteen winston smith his chin nuzzled into his breast"));
+}
+
+#[cfg(unix)]
+#[test]
+fn daemon_answers_in_the_clients_directory_with_its_diagnostics() {
+    let dir = scratch("daemon");
+    std::fs::write(dir.join("w.txt"), "cat\nact\ndog\n").unwrap();
+    let socket = dir.join("isaw.sock");
+    let mut daemon = Command::new(env!("CARGO_BIN_EXE_isaw"))
+        .args(["daemon", "--socket"])
+        .arg(&socket)
+        .current_dir("/")
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .expect("daemon starts");
+    for _ in 0..100 {
+        if socket.exists() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    let output = Command::new(env!("CARGO_BIN_EXE_isaw"))
+        .arg(format!("--connect={}", socket.display()))
+        .args(["words", "tca", "-d", "w.txt", "--stats", "-q"])
+        .current_dir(&dir)
+        .output()
+        .expect("client runs");
+    daemon.kill().unwrap();
+    daemon.wait().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let mut words: Vec<String> = String::from_utf8_lossy(&output.stdout).lines().map(str::to_string).collect();
+    words.sort();
+    assert_eq!(words, ["act", "cat"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Stats"));
+}
+
+#[test]
+fn managed_dictionaries_are_refused_once_changed_on_disk() {
+    let dir = scratch("dict-verify");
+    std::fs::write(dir.join("w.txt"), "cat\nact\ndog\n").unwrap();
+    let run = |args: &[&str]| Command::new(env!("CARGO_BIN_EXE_isaw")).args(args).env("XDG_DATA_HOME", &dir).current_dir(&dir).output().expect("isaw runs");
+    assert!(run(&["dict", "add", "pets", "w.txt"]).status.success());
+    assert!(run(&["dict", "verify"]).status.success());
+    assert!(run(&["words", "tca", "-d", "pets", "-q"]).status.success());
+
+    std::fs::write(dir.join("isaw/dictionaries/pets.txt"), "cat\nact\ndog\nrat\n").unwrap();
+    let output = run(&["words", "tca", "-d", "pets", "-q"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("changed on disk"));
+    assert!(!run(&["dict", "verify", "pets"]).status.success());
+
+    assert!(run(&["dict", "add", "pets", "w.txt"]).status.success());
+    assert!(run(&["words", "tca", "-d", "pets", "-q"]).status.success());
+}
+
+#[test]
+fn complete_folds_case_on_letters_not_bytes() {
+    let dir = scratch("complete");
+    std::fs::write(dir.join("w.txt"), "Éclair\néclat\necho\nover\n").unwrap();
+    let words = dir.join("w.txt");
+    let output = isaw(&["complete", "ÉC", "-d", words.to_str().unwrap(), "-q"]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "éclat\néclair\n");
+    let output = isaw(&["complete", "É", "-d", words.to_str().unwrap()]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn suggest_ranks_equally_close_words_by_built_in_counts() {
+    let dir = scratch("suggest");
+    std::fs::write(dir.join("w.txt"), "tye\nthe\n").unwrap();
+    std::fs::write(dir.join("counts.txt"), "tye 9\n").unwrap();
+    let words = dir.join("w.txt");
+    let output = isaw(&["suggest", "tge", "-d", words.to_str().unwrap(), "-q"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "the\ntye\n");
+    let counts = dir.join("counts.txt");
+    let output = isaw(&["suggest", "tge", "-d", words.to_str().unwrap(), "--frequencies", counts.to_str().unwrap(), "-q"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "tye\nthe\n");
+}
+
+#[test]
+fn gray_order_changes_one_letter_per_combination() {
+    let combinations = lines(&["combinations", "abcdef", "-m", "3", "-x", "3", "--order", "gray", "-q"]);
+    assert_eq!(combinations.len(), 20);
+    let mut sorted: Vec<String> = combinations.iter().map(|c| c.chars().collect::<std::collections::BTreeSet<char>>().into_iter().collect()).collect();
+    sorted.sort();
+    sorted.dedup();
+    assert_eq!(sorted.len(), 20);
+    for pair in combinations.windows(2) {
+        let shared = pair[0].chars().filter(|&c| pair[1].contains(c)).count();
+        assert_eq!(shared, 2, "{} -> {}", pair[0], pair[1]);
+    }
+}
+
+#[test]
+fn sjt_and_heap_orders_swap_one_pair_per_permutation() {
+    let lex = lines(&["permutations", "abcd", "-m", "4", "--order", "lex", "-q"]);
+    let mut sorted = lex.clone();
+    sorted.sort();
+    assert_eq!(lex, sorted);
+    assert_eq!(lex.len(), 24);
+    for (order, adjacent) in [("sjt", true), ("heap", false)] {
+        let permutations = lines(&["permutations", "abcd", "-m", "4", "--order", order, "-q"]);
+        let mut all = permutations.clone();
+        all.sort();
+        assert_eq!(all, lex, "{} covers every permutation once", order);
+        for pair in permutations.windows(2) {
+            let moved: Vec<usize> = pair[0].chars().zip(pair[1].chars()).enumerate().filter(|(_, (a, b))| a != b).map(|(i, _)| i).collect();
+            assert_eq!(moved.len(), 2, "{}: {} -> {}", order, pair[0], pair[1]);
+            assert!(!adjacent || moved[1] == moved[0] + 1, "{}: {} -> {}", order, pair[0], pair[1]);
+        }
+    }
+}
+
+#[test]
+fn perm_inverse_cycles_and_order_agree() {
+    let word = "cabed";
+    let inverse = lines(&["perm", "inverse", word]);
+    assert_eq!(inverse, ["bcaed"]);
+    assert_eq!(lines(&["perm", "compose", word, &inverse[0]]), ["abcde"]);
+    assert_eq!(lines(&["perm", "cycles", "(a c b)(d e)"]), [word]);
+
+    let info = lines(&["perm", "info", word]).join("\n");
+    assert!(info.contains("(a c b)(d e)"), "{}", info);
+    assert!(info.contains("order    6") && info.contains("odd"), "{}", info);
+    // Six compositions, and no fewer, bring it back to the identity.
+    let mut power = word.to_string();
+    for steps in 2..=6 {
+        power = lines(&["perm", "compose", &power, word]).remove(0);
+        assert_eq!(power == "abcde", steps == 6, "{} after {}", power, steps);
+    }
+}
+
+#[test]
+fn fill_completes_a_grid_around_prefilled_letters_and_blocks() {
+    let dir = scratch("fill");
+    std::fs::write(dir.join("grid.txt"), "b.#\n..#\n###\n").unwrap();
+    std::fs::write(dir.join("w.txt"), "be\nto\nbt\neo\nba\nax\n").unwrap();
+    let grid = dir.join("grid.txt");
+    let words = dir.join("w.txt");
+    let fill = lines(&["fill", grid.to_str().unwrap(), "-d", words.to_str().unwrap(), "--limit", "5"]).join("\n");
+    // Only the four two-letter words fit, across one way or down the other.
+    assert!(fill.contains("B E #\n  T O #") && fill.contains("B T #\n  E O #"), "{}", fill);
+    assert!(fill.contains("2 fills"), "{}", fill);
+
+    // A symmetric square would need each word twice, across and down.
+    std::fs::write(dir.join("square.txt"), "..\n..\n").unwrap();
+    std::fs::write(dir.join("w.txt"), "ab\nba\n").unwrap();
+    let square = dir.join("square.txt");
+    let fill = lines(&["fill", square.to_str().unwrap(), "-d", words.to_str().unwrap()]).join("\n");
+    assert!(fill.contains("No fill exists"), "{}", fill);
+}
+
+#[test]
+fn wordsearch_finds_words_in_every_direction() {
+    let dir = scratch("wordsearch");
+    std::fs::write(dir.join("grid.txt"), "cat\naox\ntxd\n").unwrap();
+    std::fs::write(dir.join("w.txt"), "cat\ncod\ndoc\ntac\nzzz\n").unwrap();
+    let grid = dir.join("grid.txt");
+    let words = dir.join("w.txt");
+    let mut found: Vec<String> = lines(&["wordsearch", "--grid", grid.to_str().unwrap(), "-d", words.to_str().unwrap()]).into_iter().filter(|line| line.contains("row")).map(|line| line.split_whitespace().collect::<Vec<_>>().join(" ")).collect();
+    found.sort();
+    assert_eq!(found, [
+        "cat row 1, column 1, E",
+        "cat row 1, column 1, S",
+        "cod row 1, column 1, SE",
+        "doc row 3, column 3, NW",
+        "tac row 1, column 3, W",
+        "tac row 3, column 1, N",
+    ]);
+}
+
+#[test]
+fn cryptogram_decodes_by_word_shape_and_ranks_the_likeliest_first() {
+    let dir = scratch("cryptogram");
+    std::fs::write(dir.join("w.txt"), "the\ncat\nsat\non\nmat\nhat\ndog\na\n").unwrap();
+    let words = dir.join("w.txt");
+    let solutions = lines(&["cryptogram", "XUM EQX GQX LK XUM RQX", "-d", words.to_str().unwrap()]);
+    let ranked: Vec<&String> = solutions.iter().filter(|line| line.contains("6/6 words")).collect();
+    assert!(ranked[0].contains("1. THE CAT SAT ON THE MAT"), "{:?}", solutions);
+    // Every reading keeps the repeated XUM as one word, so only the three
+    // -AT words can trade places: 3! keys.
+    assert!(ranked.iter().all(|line| line.contains(" THE ") && line.contains(" ON THE ")), "{:?}", solutions);
+    assert!(solutions.iter().any(|line| line.contains("6 distinct solutions")), "{:?}", solutions);
+
+    let none = lines(&["cryptogram", "ABCD", "-d", words.to_str().unwrap()]).join("\n");
+    assert!(none.contains("No key turns every word into a wordlist word"), "{}", none);
+}
+
+#[test]
+fn palindromes_are_built_directly_and_found_in_the_dictionary() {
+    let mut built = lines(&["permutations", "aabbc", "-m", "1", "--palindromes-only", "-q"]);
+    let mut filtered: Vec<String> = lines(&["permutations", "aabbc", "-m", "1", "-q"]).into_iter().filter(|word| word.chars().eq(word.chars().rev())).collect();
+    built.sort();
+    // Filtering sees each arrangement of the repeated letters again.
+    filtered.sort();
+    filtered.dedup();
+    assert_eq!(built, filtered);
+    assert!(built.contains(&"abcba".to_string()));
+
+    let dir = scratch("palindrome");
+    std::fs::write(dir.join("w.txt"), "level\nradar\nrace\ncivic\nnoon\nlevels\nere\na\n").unwrap();
+    let words = dir.join("w.txt");
+    // radar needs a second r, levels isn't a palindrome.
+    let found: Vec<String> = lines(&["palindrome", "levelra", "-d", words.to_str().unwrap()]).into_iter().map(|line| line.trim().to_string()).collect();
+    assert!(found.contains(&"level".to_string()) && found.contains(&"ere".to_string()), "{:?}", found);
+    assert!(!found.iter().any(|line| line == "radar" || line == "levels" || line == "race"), "{:?}", found);
+}
+
+#[test]
+fn ladder_finds_a_shortest_chain_of_one_letter_changes() {
+    let dir = scratch("ladder");
+    std::fs::write(dir.join("w.txt"), "cold\ncord\ncard\nward\nwarm\nworm\nword\ncore\nwore\nwire\n").unwrap();
+    let words = dir.join("w.txt");
+    let list = std::fs::read_to_string(&words).unwrap();
+    let rungs: Vec<String> = lines(&["ladder", "cold", "warm", "-d", words.to_str().unwrap()])
+        .into_iter()
+        .filter_map(|line| line.trim().split_once(". ").map(|(_, word)| word.to_string()))
+        .collect();
+    // cold and warm differ in every letter, so four changes is the least.
+    assert_eq!(rungs.len(), 5, "{:?}", rungs);
+    assert_eq!((rungs[0].as_str(), rungs[4].as_str()), ("cold", "warm"));
+    for pair in rungs.windows(2) {
+        assert_eq!(pair[0].chars().zip(pair[1].chars()).filter(|(a, b)| a != b).count(), 1, "{:?}", pair);
+        assert!(list.lines().any(|word| word == pair[1]));
+    }
+    assert!(!isaw(&["ladder", "cold", "xyzw", "-d", words.to_str().unwrap()]).status.success());
+}
+
+#[test]
+fn split_uses_every_letter_in_exactly_k_words() {
+    let dir = scratch("split");
+    std::fs::write(dir.join("w.txt"), "cat\ndog\ngod\nact\ndo\ncog\ntad\n").unwrap();
+    let words = dir.join("w.txt");
+    let splits: Vec<String> = lines(&["split", "catdog", "-k", "2", "-d", words.to_str().unwrap()]).into_iter().filter(|line| line.contains(" + ")).map(|line| line.trim().to_string()).collect();
+    assert_eq!(splits, ["act + dog", "act + god", "cat + dog", "cat + god", "cog + tad"]);
+    for split in &splits {
+        let mut letters: Vec<char> = split.replace(" + ", "").chars().collect();
+        letters.sort();
+        assert_eq!(letters, ['a', 'c', 'd', 'g', 'o', 't']);
+    }
+    // `do` leaves four letters that no two words make.
+    let three = lines(&["split", "catdog", "-k", "3", "-d", words.to_str().unwrap()]);
+    assert!(!three.iter().any(|line| line.contains(" + ")), "{:?}", three);
+}
+
+#[test]
+fn typos_follow_the_keyboard_layout_and_dictionary() {
+    let typos = lines(&["typos", "cat", "-q"]);
+    for expected in ["at", "ca", "ccat", "catt", "act", "cta", "xat", "vat", "dat", "fat"] {
+        assert!(typos.contains(&expected.to_string()), "{} missing from {:?}", expected, typos);
+    }
+    let mut distinct = typos.clone();
+    distinct.sort();
+    distinct.dedup();
+    assert_eq!(distinct.len(), typos.len());
+    assert!(!typos.contains(&"cat".to_string()) && !typos.contains(&"cpt".to_string()));
+
+    // On AZERTY the a sits under z, next to q.
+    let middle: Vec<String> = lines(&["typos", "cat", "--layout", "azerty", "-q"]).into_iter().filter(|typo| typo.len() == 3 && typo.starts_with('c') && typo.ends_with('t')).collect();
+    assert_eq!(middle, ["czt", "cqt"]);
+
+    let dir = scratch("typos");
+    std::fs::write(dir.join("w.txt"), "act\nbat\nfat\ncat\n").unwrap();
+    let words = dir.join("w.txt");
+    assert_eq!(lines(&["typos", "cat", "-d", words.to_str().unwrap(), "-q"]), ["act", "fat"]);
+}
+
+#[test]
+fn morse_round_trips_and_lists_every_unspaced_reading() {
+    assert_eq!(lines(&["morse", "hi there"]), [".... .. / - .... . .-. ."]);
+    assert_eq!(lines(&["morse", ".... .. / - .... . .-. ."]), ["hi there"]);
+    assert!(!isaw(&["morse", "#"]).status.success());
+
+    let readings = lines(&["morse", "...---...", "-q"]);
+    assert!(readings.contains(&"sos".to_string()) && readings.contains(&"eeagee".to_string()));
+    let mut distinct = readings.clone();
+    distinct.sort();
+    distinct.dedup();
+    assert_eq!(distinct.len(), readings.len());
+    // Every reading spells the same dots and dashes once the gaps go.
+    for reading in readings.iter().step_by(17) {
+        assert_eq!(lines(&["morse", reading])[0].replace(' ', ""), "...---...", "{}", reading);
+    }
+
+    let dir = scratch("morse");
+    std::fs::write(dir.join("w.txt"), "sos\nsets\ndog\n").unwrap();
+    let words = dir.join("w.txt");
+    assert_eq!(lines(&["morse", "...---...", "-d", words.to_str().unwrap(), "-q"]), ["sos"]);
+}
+
+#[test]
+fn output_sqlite_inserts_every_result_with_its_columns() {
+    if Command::new("sqlite3").arg("-version").output().is_err() {
+        return;
+    }
+    let dir = scratch("sqlite");
+    let db = dir.join("r.db");
+    let query = |sql: &str| String::from_utf8(Command::new("sqlite3").arg(&db).arg(sql).output().expect("sqlite3 runs").stdout).unwrap();
+
+    assert!(isaw(&["search", "a", "--letters", "a'b", "-n", "2", "--output-sqlite", db.to_str().unwrap()]).status.success());
+    assert_eq!(query("SELECT value, length, matched_pattern FROM results ORDER BY value"), "'a|2|a\na'|2|a\naa|2|a\nab|2|a\nba|2|a\n");
+    assert_eq!(query("SELECT count(*) FROM results WHERE score < 0"), "5\n");
+
+    // Far more rows than one transaction holds, appended to the same table.
+    assert!(isaw(&["search", "a", "-n", "4", "--output-sqlite", db.to_str().unwrap()]).status.success());
+    assert_eq!(query("SELECT count(*) FROM results"), format!("{}\n", 5 + 26 * 26 * 26 * 26 - 25 * 25 * 25 * 25));
+}
+
+#[test]
+fn output_tcp_and_udp_stream_result_lines_to_a_collector() {
+    use std::io::Read;
+
+    let collector = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = collector.local_addr().unwrap().to_string();
+    let received = std::thread::spawn(move || {
+        let mut text = String::new();
+        collector.accept().unwrap().0.read_to_string(&mut text).unwrap();
+        text
+    });
+    let output = isaw(&["permutations", "abc", "-m", "3", "--output-tcp", &address]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(received.join().unwrap(), "abc\nacb\nbac\nbca\ncab\ncba\n");
+
+    let collector = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    collector.set_read_timeout(Some(std::time::Duration::from_secs(10))).unwrap();
+    let address = collector.local_addr().unwrap().to_string();
+    assert!(isaw(&["permutations", "ab", "-m", "2", "--output-udp", &address]).status.success());
+    // Loopback doesn't drop datagrams this small.
+    let mut datagrams = Vec::new();
+    for _ in 0..2 {
+        let mut buf = [0; 64];
+        let len = collector.recv(&mut buf).unwrap();
+        datagrams.push(String::from_utf8_lossy(&buf[..len]).into_owned());
+    }
+    assert_eq!(datagrams, ["ab\n", "ba\n"]);
+
+    assert!(!isaw(&["permutations", "ab", "--output-tcp", "127.0.0.1:1"]).status.success());
+}
+
+#[test]
+fn dedup_keeps_first_occurrences_across_lengths() {
+    let all = lines(&["permutations", "aab", "-m", "1", "-q"]);
+    let mut firsts = Vec::new();
+    for word in &all {
+        if !firsts.contains(word) {
+            firsts.push(word.clone());
+        }
+    }
+    assert_eq!(lines(&["permutations", "aab", "-m", "1", "--dedup", "-q"]), firsts);
+
+    // 65,201 distinct results overflow 1 MiB, so the rest of the run is
+    // remembered by a Bloom filter: never a repeat, rarely a lost result.
+    let folded = lines(&["permutations", "aabbccdde", "-m", "1", "--dedup", "--dedup-memory", "1", "-q"]);
+    let mut distinct = folded.clone();
+    distinct.sort();
+    distinct.dedup();
+    assert_eq!(distinct.len(), folded.len());
+    assert!(folded.len() > 65_201 * 99 / 100 && folded.len() <= 65_201, "{}", folded.len());
+}
+
+#[test]
+fn sort_spills_past_its_budget_and_cleans_up() {
+    let dir = scratch("sort");
+    let run = |args: &[&str]| Command::new(env!("CARGO_BIN_EXE_isaw")).args(args).env("TMPDIR", &dir).output().expect("isaw runs");
+    let mut expected = lines(&["permutations", "abcdefgh", "-m", "1", "--order", "heap", "-q"]);
+    expected.sort();
+    // About 3 MiB of results against a 1 MiB budget: several sorted runs
+    // on disk, merged back together.
+    let output = run(&["permutations", "abcdefgh", "-m", "1", "--order", "heap", "--sort", "--sort-memory", "1", "-q"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let sorted: Vec<&str> = std::str::from_utf8(&output.stdout).unwrap().lines().collect();
+    assert_eq!(sorted, expected);
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0, "temp files left behind");
+
+    // It did need the disk: without a temp directory the same run fails.
+    let missing = Command::new(env!("CARGO_BIN_EXE_isaw"))
+        .args(["permutations", "abcdefgh", "-m", "1", "--order", "heap", "--sort", "--sort-memory", "1", "-q"])
+        .env("TMPDIR", dir.join("missing"))
+        .output()
+        .expect("isaw runs");
+    assert!(!missing.status.success());
+}
+
+#[test]
+fn max_memory_spills_dedup_and_sort_and_stays_exact() {
+    let dir = scratch("max-memory");
+    let job = ["permutations", "aabbccdde", "-m", "1", "--dedup", "--sort", "--max-memory", "2M", "-q"];
+    let mut expected = lines(&["permutations", "aabbccdde", "-m", "1", "-q"]);
+    expected.sort();
+    expected.dedup();
+
+    // Unlike --dedup-memory, a full set goes to disk rather than into a
+    // Bloom filter, so nothing is lost.
+    let output = Command::new(env!("CARGO_BIN_EXE_isaw")).args(job).env("TMPDIR", &dir).output().expect("isaw runs");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let results: Vec<&str> = std::str::from_utf8(&output.stdout).unwrap().lines().collect();
+    assert_eq!(results, expected);
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0, "temp files left behind");
+
+    let missing = Command::new(env!("CARGO_BIN_EXE_isaw")).args(job).env("TMPDIR", dir.join("missing")).output().expect("isaw runs");
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("Cannot use temp files for --dedup"));
+    assert_eq!(code(&["permutations", "abc", "--max-memory", "2X"]), 2);
+}